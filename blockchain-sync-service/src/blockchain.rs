@@ -6,12 +6,50 @@ use web3::types::{
     TransactionRequest, Bytes, BlockNumber as Web3BlockNumber
 };
 use web3::Web3;
+use web3::Transport;
 use std::collections::HashMap;
 use chrono::{DateTime, Utc};
+use serde_json::json;
 
-use crate::error_handler::{ErrorHandler, CircuitBreaker, HealthMonitor};
+use crate::error_handler::{ErrorHandler, CircuitBreaker, CircuitBreakerState, HealthMonitor};
+use crate::header_chain::HeaderChain;
 use crate::metrics::BlockchainMetrics;
+use crate::models::AccountProof;
+use crate::mpt_proof::{self, VerifiedAccount};
+use crate::rpc_pool::RpcPool;
 use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// How a multi-endpoint [`BlockchainClient`] reconciles reads across
+/// backends.
+#[derive(Debug, Clone)]
+pub enum QuorumPolicy {
+    /// Try endpoints in priority order, only advancing to the next one on
+    /// error or an open circuit breaker.
+    First,
+    /// Require at least `threshold` endpoints to return an identical
+    /// (serialized) result before accepting it.
+    Majority { threshold: usize },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum QuorumError {
+    #[error("no configured endpoints responded successfully")]
+    AllEndpointsFailed,
+    #[error("endpoints disagreed on result: {0:?}")]
+    QuorumDisagreement(Vec<(String, String)>),
+}
+
+/// A single upstream endpoint participating in a quorum/failover read, each
+/// with its own isolated failure-handling state so one bad provider can't
+/// trip the whole client.
+struct QuorumEndpoint {
+    url: String,
+    weight: usize,
+    web3: Web3<Http>,
+    circuit_breaker: tokio::sync::Mutex<CircuitBreaker>,
+    health_monitor: tokio::sync::Mutex<HealthMonitor>,
+}
 
 #[derive(Clone)]
 pub struct BlockchainClient {
@@ -21,6 +59,105 @@ pub struct BlockchainClient {
     health_monitor: HealthMonitor,
     metrics: Arc<BlockchainMetrics>,
     rpc_url: String,
+    quorum: Option<Arc<QuorumTransport>>,
+    header_chain: Option<Arc<Mutex<HeaderChain>>>,
+    pool: Option<PooledTransport>,
+}
+
+/// Backs [`BlockchainClient::new_pooled`]: a `web3` transport per configured
+/// URL alongside the [`RpcPool`] that picks and ejects among them, kept
+/// separate from `quorum` since the pool optimizes for lowest latency rather
+/// than agreement across backends.
+#[derive(Clone)]
+struct PooledTransport {
+    pool: Arc<RpcPool>,
+    transports: Arc<HashMap<String, Web3<Http>>>,
+}
+
+struct QuorumTransport {
+    endpoints: Vec<QuorumEndpoint>,
+    policy: QuorumPolicy,
+    metrics: Arc<BlockchainMetrics>,
+}
+
+impl QuorumTransport {
+    /// Dispatches `call` to every endpoint (for `Majority`) or in priority
+    /// order (for `First`), returning a result once the configured policy is
+    /// satisfied.
+    async fn read<T, F, Fut>(&self, method: &str, call: F) -> Result<T>
+    where
+        T: serde::Serialize + Clone + Send + 'static,
+        F: Fn(Web3<Http>) -> Fut + Send + Sync,
+        Fut: std::future::Future<Output = Result<T>> + Send,
+    {
+        match &self.policy {
+            QuorumPolicy::First => {
+                for endpoint in &self.endpoints {
+                    {
+                        let breaker = endpoint.circuit_breaker.lock().await;
+                        if *breaker.state() == CircuitBreakerState::Open {
+                            continue;
+                        }
+                    }
+                    match call(endpoint.web3.clone()).await {
+                        Ok(value) => {
+                            endpoint.health_monitor.lock().await.record_success();
+                            return Ok(value);
+                        }
+                        Err(e) => {
+                            log::warn!("quorum endpoint {} failed for {}: {}", endpoint.url, method, e);
+                            endpoint.health_monitor.lock().await.record_error();
+                            endpoint.circuit_breaker.lock().await.record_external_failure();
+                        }
+                    }
+                }
+                Err(QuorumError::AllEndpointsFailed.into())
+            }
+            QuorumPolicy::Majority { threshold } => {
+                let mut responses: Vec<(String, String, T)> = Vec::new();
+                for endpoint in &self.endpoints {
+                    match call(endpoint.web3.clone()).await {
+                        Ok(value) => {
+                            let serialized = serde_json::to_string(&value).unwrap_or_default();
+                            endpoint.health_monitor.lock().await.record_success();
+                            for _ in 0..endpoint.weight {
+                                responses.push((endpoint.url.clone(), serialized.clone(), value.clone()));
+                            }
+                        }
+                        Err(e) => {
+                            log::warn!("quorum endpoint {} failed for {}: {}", endpoint.url, method, e);
+                            endpoint.health_monitor.lock().await.record_error();
+                        }
+                    }
+                }
+
+                let mut tally: HashMap<String, usize> = HashMap::new();
+                for (_, serialized, _) in &responses {
+                    *tally.entry(serialized.clone()).or_insert(0) += 1;
+                }
+
+                if let Some((winner, count)) = tally.into_iter().max_by_key(|(_, count)| *count) {
+                    if count >= *threshold {
+                        let value = responses
+                            .into_iter()
+                            .find(|(_, serialized, _)| serialized == &winner)
+                            .map(|(_, _, value)| value)
+                            .unwrap();
+                        return Ok(value);
+                    }
+                }
+
+                let divergence: Vec<(String, String)> = responses
+                    .into_iter()
+                    .map(|(url, serialized, _)| (url, serialized))
+                    .collect();
+                for (url, _) in &divergence {
+                    self.metrics.record_quorum_divergence(url);
+                }
+                Err(QuorumError::QuorumDisagreement(divergence).into())
+            }
+        }
+    }
 }
 
 impl BlockchainClient {
@@ -40,16 +177,191 @@ impl BlockchainClient {
         
         let health_monitor = HealthMonitor::new();
         
-        Ok(Self { 
+        Ok(Self {
             web3,
             error_handler,
             circuit_breaker,
             health_monitor,
             metrics,
             rpc_url: rpc_url.to_string(),
+            quorum: None,
+            header_chain: None,
+            pool: None,
+        })
+    }
+
+    /// Enables weak-subjectivity header verification: every header returned
+    /// by [`BlockchainClient::get_block_header`] from now on is checked
+    /// against the chain seeded from `(checkpoint_number, checkpoint_hash)`
+    /// instead of being trusted blindly.
+    pub fn with_checkpoint(mut self, checkpoint_number: u64, checkpoint_hash: H256) -> Self {
+        self.header_chain = Some(Arc::new(Mutex::new(HeaderChain::new(
+            checkpoint_number,
+            checkpoint_hash,
+        ))));
+        self
+    }
+
+    /// Verifies `header` against the configured checkpoint chain, if any.
+    /// No-op (always succeeds) when no checkpoint was configured.
+    pub async fn verify_header(&self, header: &BlockHeader) -> Result<()> {
+        if let Some(header_chain) = &self.header_chain {
+            header_chain.lock().await.verify_header(header)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the CHT root for `section` if the header chain has
+    /// materialized one, i.e. enough headers have been observed in that
+    /// 2048-block range since the checkpoint.
+    pub async fn get_cht_root(&self, section: u64) -> Option<H256> {
+        match &self.header_chain {
+            Some(header_chain) => header_chain.lock().await.get_cht_root(section),
+            None => None,
+        }
+    }
+
+    /// Builds a client backed by several upstream endpoints instead of one.
+    /// Reads are dispatched per `policy`: `QuorumPolicy::First` fails over to
+    /// the next healthy endpoint, while `QuorumPolicy::Majority` requires a
+    /// weighted quorum of endpoints to agree before returning a result.
+    pub async fn new_quorum(
+        endpoints: Vec<(String, usize)>,
+        policy: QuorumPolicy,
+        metrics: Arc<BlockchainMetrics>,
+    ) -> Result<Self> {
+        if endpoints.is_empty() {
+            return Err(anyhow::anyhow!("new_quorum requires at least one endpoint"));
+        }
+
+        let mut quorum_endpoints = Vec::with_capacity(endpoints.len());
+        for (url, weight) in &endpoints {
+            let transport = Http::new(url)?;
+            quorum_endpoints.push(QuorumEndpoint {
+                url: url.clone(),
+                weight: *weight,
+                web3: Web3::new(transport),
+                circuit_breaker: tokio::sync::Mutex::new(CircuitBreaker::new(
+                    5,
+                    std::time::Duration::from_secs(60),
+                )),
+                health_monitor: tokio::sync::Mutex::new(HealthMonitor::new()),
+            });
+        }
+
+        let primary_url = endpoints[0].0.clone();
+        let primary_transport = Http::new(&primary_url)?;
+
+        let error_handler = ErrorHandler::new()
+            .with_retries(3)
+            .with_base_delay(std::time::Duration::from_secs(1))
+            .with_max_delay(std::time::Duration::from_secs(30));
+
+        Ok(Self {
+            web3: Web3::new(primary_transport),
+            error_handler,
+            circuit_breaker: CircuitBreaker::new(5, std::time::Duration::from_secs(60)),
+            health_monitor: HealthMonitor::new(),
+            metrics: metrics.clone(),
+            rpc_url: primary_url,
+            quorum: Some(Arc::new(QuorumTransport {
+                endpoints: quorum_endpoints,
+                policy,
+                metrics,
+            })),
+            header_chain: None,
+            pool: None,
         })
     }
 
+    /// Builds a client backed by a [`RpcPool`] of upstream endpoints: each
+    /// pooled call picks the lowest-latency healthy endpoint, records its
+    /// outcome back into the pool, and falls through to the next-best
+    /// endpoint on error instead of failing immediately.
+    pub async fn new_pooled(urls: Vec<String>, metrics: Arc<BlockchainMetrics>) -> Result<Self> {
+        if urls.is_empty() {
+            return Err(anyhow::anyhow!("new_pooled requires at least one endpoint"));
+        }
+
+        let mut transports = HashMap::with_capacity(urls.len());
+        for url in &urls {
+            transports.insert(url.clone(), Web3::new(Http::new(url)?));
+        }
+
+        let primary_url = urls[0].clone();
+        let primary_transport = transports.get(&primary_url).unwrap().clone();
+
+        let error_handler = ErrorHandler::new()
+            .with_retries(3)
+            .with_base_delay(std::time::Duration::from_secs(1))
+            .with_max_delay(std::time::Duration::from_secs(30));
+
+        Ok(Self {
+            web3: primary_transport,
+            error_handler,
+            circuit_breaker: CircuitBreaker::new(5, std::time::Duration::from_secs(60)),
+            health_monitor: HealthMonitor::new(),
+            metrics: metrics.clone(),
+            rpc_url: primary_url,
+            quorum: None,
+            header_chain: None,
+            pool: Some(PooledTransport {
+                pool: Arc::new(RpcPool::new(urls, metrics)),
+                transports: Arc::new(transports),
+            }),
+        })
+    }
+
+    /// Like [`BlockchainClient::get_latest_block_number`], but routed
+    /// through the configured [`RpcPool`]: picks the lowest-latency healthy
+    /// endpoint, falls through to the next-best one on error, and records
+    /// per-endpoint success/failure back into the pool. Falls back to the
+    /// single-endpoint path when the client wasn't built with
+    /// [`BlockchainClient::new_pooled`].
+    pub async fn get_latest_block_number_pooled(&self) -> Result<u64> {
+        let Some(pooled) = &self.pool else {
+            return self.get_latest_block_number().await;
+        };
+
+        let mut tried = Vec::new();
+        loop {
+            let Some(url) = pooled.pool.select_excluding(&tried) else {
+                return Err(anyhow::anyhow!("rpc_pool: no healthy endpoint available"));
+            };
+
+            let web3 = pooled.transports.get(&url).expect("selected url must be in transports").clone();
+            let start = std::time::Instant::now();
+            match web3.eth().block_number().await {
+                Ok(block_number) => {
+                    pooled.pool.record_success(&url, start.elapsed());
+                    return Ok(block_number.as_u64());
+                }
+                Err(e) => {
+                    log::warn!("rpc_pool endpoint {} failed for eth_blockNumber: {}", url, e);
+                    pooled.pool.record_failure(&url);
+                    tried.push(url);
+                }
+            }
+        }
+    }
+
+    /// Like [`BlockchainClient::get_latest_block_number`], but dispatched
+    /// across the configured quorum endpoints instead of the single `web3`
+    /// transport. Falls back to the single-endpoint path when the client
+    /// wasn't built with [`BlockchainClient::new_quorum`].
+    pub async fn get_latest_block_number_quorum(&self) -> Result<u64> {
+        match &self.quorum {
+            Some(quorum) => {
+                quorum
+                    .read("eth_blockNumber", |web3| async move {
+                        Ok(web3.eth().block_number().await?.as_u64())
+                    })
+                    .await
+            }
+            None => self.get_latest_block_number().await,
+        }
+    }
+
     pub async fn get_latest_block_number(&self) -> Result<u64> {
         let start_time = std::time::Instant::now();
         let operation = || {
@@ -115,12 +427,22 @@ impl BlockchainClient {
             })
         };
 
-        self.error_handler.execute_with_retry_and_error_tracking(
+        let header = self.error_handler.execute_with_retry_and_error_tracking(
             operation,
             crate::error_handler::ErrorType::BlockNotFound,
             Some(block_number),
             None,
-        ).await
+        ).await?;
+
+        if let Err(e) = self.verify_header(&header).await {
+            return Err(anyhow::anyhow!(
+                "header verification failed for block {}: {}",
+                block_number,
+                e
+            ));
+        }
+
+        Ok(header)
     }
 
     pub async fn get_transaction_receipt(&self, tx_hash: &H256) -> Result<Option<TransactionReceipt>> {
@@ -253,6 +575,86 @@ impl BlockchainClient {
         ).await
     }
 
+    pub async fn get_fee_history(
+        &self,
+        block_count: u64,
+        newest_block: BlockNumber,
+        reward_percentiles: Vec<f64>,
+    ) -> Result<web3::types::FeeHistory> {
+        let operation = || {
+            let web3 = self.web3.clone();
+            let reward_percentiles = reward_percentiles.clone();
+            Box::pin(async move {
+                let history = web3
+                    .eth()
+                    .fee_history(block_count.into(), newest_block, Some(reward_percentiles))
+                    .await?;
+                Ok(history)
+            })
+        };
+
+        self.error_handler.execute_with_retry_and_error_tracking(
+            operation,
+            crate::error_handler::ErrorType::RpcConnection,
+            None,
+            None,
+        ).await
+    }
+
+    /// Estimates EIP-1559 fee parameters from the last ~20 blocks of fee
+    /// history: `max_priority_fee_per_gas` is the median of the 50th
+    /// percentile priority-fee rewards, and `max_fee_per_gas` doubles the
+    /// pending block's base fee to tolerate a couple of full blocks before
+    /// it's re-estimated. Falls back to `get_gas_price` on pre-London chains
+    /// or when the node returns no reward data.
+    pub async fn estimate_eip1559_fees(&self) -> Result<(U256, U256)> {
+        let start_time = std::time::Instant::now();
+
+        let result = async {
+            let history = self
+                .get_fee_history(20, BlockNumber::Pending, vec![10.0, 50.0, 90.0])
+                .await?;
+
+            let base_fee = history
+                .base_fee_per_gas
+                .last()
+                .copied()
+                .ok_or_else(|| anyhow::anyhow!("fee history returned no base_fee_per_gas"))?;
+
+            let rewards = history
+                .reward
+                .ok_or_else(|| anyhow::anyhow!("fee history returned no reward data (pre-London chain?)"))?;
+
+            let mut median_rewards: Vec<U256> = rewards
+                .iter()
+                .filter_map(|block_rewards| block_rewards.get(1).copied()) // index 1 => 50th percentile
+                .collect();
+
+            if median_rewards.is_empty() {
+                return Err(anyhow::anyhow!("no priority-fee reward samples available"));
+            }
+
+            median_rewards.sort();
+            let max_priority_fee_per_gas = median_rewards[median_rewards.len() / 2];
+            let max_fee_per_gas = base_fee * 2 + max_priority_fee_per_gas;
+
+            Ok((max_fee_per_gas, max_priority_fee_per_gas))
+        }
+        .await;
+
+        let duration = start_time.elapsed().as_secs_f64();
+        self.metrics.record_fee_estimation_duration(duration);
+
+        match result {
+            Ok(fees) => Ok(fees),
+            Err(e) => {
+                log::warn!("EIP-1559 fee estimation failed, falling back to legacy gas price: {}", e);
+                let gas_price = self.get_gas_price().await?;
+                Ok((gas_price, U256::zero()))
+            }
+        }
+    }
+
     pub async fn get_block_gas_limit(&self, block_number: u64) -> Result<U256> {
         let operation = || {
             let web3 = self.web3.clone();
@@ -303,6 +705,140 @@ impl BlockchainClient {
         ).await
     }
 
+    /// Calls `debug_traceTransaction` with the given tracer config (e.g.
+    /// `{"tracer": "callTracer"}`), returning the raw structured trace. Nodes
+    /// without the `debug` namespace fail fast via `ErrorType::TracingUnsupported`
+    /// instead of burning all retry attempts.
+    pub async fn debug_trace_transaction(
+        &self,
+        tx_hash: &H256,
+        opts: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        self.call_trace_method("debug_traceTransaction", vec![json!(format!("{:?}", tx_hash)), opts])
+            .await
+    }
+
+    /// `trace_transaction` flat-trace RPC -- internal calls and value
+    /// transfers for a single transaction.
+    pub async fn trace_transaction(&self, tx_hash: &H256) -> Result<serde_json::Value> {
+        self.call_trace_method("trace_transaction", vec![json!(format!("{:?}", tx_hash))])
+            .await
+    }
+
+    /// `trace_block` flat-trace RPC -- internal calls for every transaction
+    /// in a block.
+    pub async fn trace_block(&self, block_number: u64) -> Result<serde_json::Value> {
+        self.call_trace_method(
+            "trace_block",
+            vec![json!(format!("0x{:x}", block_number))],
+        )
+        .await
+    }
+
+    /// `trace_filter` RPC -- internal calls across a block range, optionally
+    /// restricted to a set of `from`/`to` addresses.
+    pub async fn trace_filter(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        from_addresses: Vec<H160>,
+        to_addresses: Vec<H160>,
+    ) -> Result<serde_json::Value> {
+        let filter = json!({
+            "fromBlock": format!("0x{:x}", from_block),
+            "toBlock": format!("0x{:x}", to_block),
+            "fromAddress": from_addresses,
+            "toAddress": to_addresses,
+        });
+        self.call_trace_method("trace_filter", vec![filter]).await
+    }
+
+    async fn call_trace_method(
+        &self,
+        method: &'static str,
+        params: Vec<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        let operation = || {
+            let web3 = self.web3.clone();
+            let params = params.clone();
+            Box::pin(async move {
+                web3.transport()
+                    .execute(method, params)
+                    .await
+                    .map_err(anyhow::Error::from)
+            })
+        };
+
+        self.error_handler.execute_with_retry_and_error_tracking(
+            operation,
+            crate::error_handler::ErrorType::TracingUnsupported,
+            None,
+            None,
+        ).await
+    }
+
+    /// Calls `eth_getProof`, returning the EIP-1186 account proof plus
+    /// per-slot storage proofs for `storage_keys`.
+    pub async fn get_proof(
+        &self,
+        address: &str,
+        storage_keys: Vec<H256>,
+        block_number: Option<u64>,
+    ) -> Result<AccountProof> {
+        let block_param = match block_number {
+            Some(n) => format!("0x{:x}", n),
+            None => "latest".to_string(),
+        };
+        let keys: Vec<String> = storage_keys.iter().map(|k| format!("{:?}", k)).collect();
+
+        let raw = self
+            .call_trace_method(
+                "eth_getProof",
+                vec![json!(address), json!(keys), json!(block_param)],
+            )
+            .await?;
+
+        serde_json::from_value(raw).map_err(|e| anyhow::anyhow!("failed to decode eth_getProof response: {}", e))
+    }
+
+    /// Verifies an `eth_getProof` account proof against a trusted
+    /// `state_root` (e.g. from a [`BlockchainClient::get_block_header`] that
+    /// has already passed [`BlockchainClient::verify_header`]), walking the
+    /// RLP-encoded Merkle-Patricia proof nodes down to the account leaf.
+    /// Returns an error on any mismatch, which `ErrorHandler::classify_error`
+    /// classifies as `ErrorType::ProofVerificationFailed`.
+    pub fn verify_account_proof(
+        &self,
+        state_root: H256,
+        address: &str,
+        proof: &AccountProof,
+    ) -> Result<VerifiedAccount> {
+        let address: Address = address.parse()?;
+        let account_proof: Vec<Bytes> = proof
+            .account_proof
+            .iter()
+            .map(|node| hex_to_bytes(node))
+            .collect::<Result<_>>()?;
+
+        let leaf = mpt_proof::verify_proof(state_root, address.as_bytes(), &account_proof)
+            .map_err(|e| anyhow::anyhow!("account proof verification failed: {}", e))?;
+        let account = mpt_proof::decode_account(&leaf)
+            .map_err(|e| anyhow::anyhow!("failed to decode verified account leaf: {}", e))?;
+
+        for entry in &proof.storage_proof {
+            let slot: H256 = entry.key.parse()?;
+            let storage_proof: Vec<Bytes> = entry
+                .proof
+                .iter()
+                .map(|node| hex_to_bytes(node))
+                .collect::<Result<_>>()?;
+            mpt_proof::verify_proof(account.storage_hash, slot.as_bytes(), &storage_proof)
+                .map_err(|e| anyhow::anyhow!("storage proof verification failed for slot {}: {}", entry.key, e))?;
+        }
+
+        Ok(account)
+    }
+
     pub async fn get_sync_status(&self) -> Result<SyncState> {
         let operation = || {
             let web3 = self.web3.clone();
@@ -492,3 +1028,8 @@ impl BlockchainClient {
         self.health_monitor.record_error();
     }
 }
+
+fn hex_to_bytes(s: &str) -> Result<Bytes> {
+    let trimmed = s.strip_prefix("0x").unwrap_or(s);
+    Ok(Bytes(hex::decode(trimmed)?))
+}
@@ -1,9 +1,13 @@
 use anyhow::Result;
 use chrono::Utc;
+use rand::Rng;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::time::sleep;
 use uuid::Uuid;
 
+use crate::metrics::BlockchainMetrics;
 use crate::models::ProcessingError;
 
 #[derive(Debug, Clone)]
@@ -17,6 +21,12 @@ pub enum ErrorType {
     ParsingError,
     RateLimit,
     NetworkError,
+    HeaderVerificationFailed,
+    TracingUnsupported,
+    ProofVerificationFailed,
+    /// A single attempt was aborted by `tokio::time::timeout` (per-attempt
+    /// timeout or total-operation deadline) before it could complete.
+    TimeoutError,
     Unknown,
 }
 
@@ -32,25 +42,312 @@ impl std::fmt::Display for ErrorType {
             ErrorType::ParsingError => write!(f, "PARSING_ERROR"),
             ErrorType::RateLimit => write!(f, "RATE_LIMIT"),
             ErrorType::NetworkError => write!(f, "NETWORK_ERROR"),
+            ErrorType::HeaderVerificationFailed => write!(f, "HEADER_VERIFICATION_FAILED"),
+            ErrorType::TracingUnsupported => write!(f, "TRACING_UNSUPPORTED"),
+            ErrorType::ProofVerificationFailed => write!(f, "PROOF_VERIFICATION_FAILED"),
+            ErrorType::TimeoutError => write!(f, "TIMEOUT_ERROR"),
             ErrorType::Unknown => write!(f, "UNKNOWN"),
         }
     }
 }
 
+/// Decoded JSON-RPC error, distinguishing conditions that are worth retrying
+/// (rate limiting, transient resource exhaustion) from ones that aren't
+/// (method not found, a reverted call) so callers don't burn all their
+/// retries on a request that will never succeed.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum TransportError {
+    #[error("method not found: {0}")]
+    MethodNotFound(String),
+    #[error("rate limited by upstream RPC")]
+    RateLimited,
+    #[error("execution reverted: {data:?}")]
+    ExecutionReverted { data: Option<String> },
+    #[error("upstream resource temporarily unavailable")]
+    ResourceUnavailable,
+    #[error("JSON-RPC error {code}: {message}")]
+    Custom { code: i64, message: String },
+}
+
+impl TransportError {
+    /// `true` for errors where retrying the same request against the same
+    /// endpoint cannot succeed (the method will still be missing, the call
+    /// will still revert).
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            TransportError::MethodNotFound(_) | TransportError::ExecutionReverted { .. }
+        )
+    }
+
+    /// Parses a `web3::Error::Rpc` JSON-RPC error object (code, message,
+    /// optional `data`) into a typed variant. Falls back to `Custom` for
+    /// codes this crate doesn't special-case.
+    pub fn from_rpc_error(error: &web3::error::Error) -> Option<Self> {
+        if let web3::error::Error::Rpc(rpc_error) = error {
+            let code = rpc_error.code.code();
+            let message = rpc_error.message.clone();
+            let data = rpc_error.data.as_ref().map(|d| d.to_string());
+
+            return Some(match code {
+                -32601 => TransportError::MethodNotFound(message),
+                -32005 | -32029 => TransportError::RateLimited,
+                -32000 if message.to_lowercase().contains("revert") => {
+                    TransportError::ExecutionReverted { data }
+                }
+                -32603 => TransportError::ResourceUnavailable,
+                _ => TransportError::Custom { code, message },
+            });
+        }
+        None
+    }
+
+    pub fn from_anyhow(error: &anyhow::Error) -> Option<Self> {
+        error
+            .downcast_ref::<web3::error::Error>()
+            .and_then(Self::from_rpc_error)
+    }
+}
+
+/// Returned (wrapped in `anyhow::Error`) once `execute_with_retry_and_error_tracking`
+/// and its `_with_backoff`/`_with_timeout` siblings give up — either a
+/// non-retryable `TransportError` or the retry budget running out. Bundles
+/// the `error_type` category a caller can match on for control flow with the
+/// failing operation's name, how many attempts were made, and the original
+/// error so logging (`{:#}` below walks the whole anyhow chain) doesn't lose
+/// anything a flattened `.to_string()` would have dropped. Stays wrapped in
+/// `anyhow::Error` rather than becoming these methods' own `Result<T, E>` so
+/// the existing call sites across this crate don't need their signatures
+/// touched — match via `err.downcast_ref::<RetryExhaustedError>()`.
+#[derive(Debug, thiserror::Error)]
+#[error("{operation} failed as {error_type} after {attempts} attempt(s): {source:#}")]
+pub struct RetryExhaustedError {
+    pub error_type: ErrorType,
+    pub operation: String,
+    pub attempts: u32,
+    pub source: anyhow::Error,
+}
+
+/// Best-effort operation label for `RetryExhaustedError` when a call site
+/// doesn't pass a name of its own: `execute_with_retry_and_error_tracking`
+/// has no dedicated "operation name" parameter today (adding one would mean
+/// touching every one of this crate's call sites), so this folds in
+/// whatever identifying context it already receives — `block_number`/
+/// `transaction_hash` — alongside the `error_type` category.
+fn operation_label(error_type: &ErrorType, block_number: Option<u64>, transaction_hash: &Option<String>) -> String {
+    match (block_number, transaction_hash) {
+        (Some(block), Some(tx)) => format!("{} (block {}, tx {})", error_type, block, tx),
+        (Some(block), None) => format!("{} (block {})", error_type, block),
+        (None, Some(tx)) => format!("{} (tx {})", error_type, tx),
+        (None, None) => error_type.to_string(),
+    }
+}
+
+/// How many times to retry an operation classified as a given `ErrorType`,
+/// and the jittered backoff bounds to use while doing so.
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryConfig {
+    pub fn new(max_retries: u32, base_delay: Duration, max_delay: Duration) -> Self {
+        Self { max_retries, base_delay, max_delay }
+    }
+}
+
+/// How the delay between retry attempts grows. `ExponentialWithJitter`
+/// applies *full* jitter (uniform over `[0, delay]`) rather than the
+/// decorrelated jitter `execute_with_retry`'s original backoff used —
+/// desynchronizing concurrent callers across the whole range, rather than a
+/// narrower trending-upward one, so they don't retry in synchronized waves.
+#[derive(Debug, Clone)]
+pub enum BackoffStrategy {
+    Fixed(Duration),
+    Linear { base: Duration, increment: Duration, max: Duration },
+    ExponentialWithJitter { base: Duration, max: Duration, multiplier: f64 },
+}
+
+impl BackoffStrategy {
+    fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        match self {
+            BackoffStrategy::Fixed(delay) => *delay,
+            BackoffStrategy::Linear { base, increment, max } => {
+                base.saturating_add(*increment * attempt).min(*max)
+            }
+            BackoffStrategy::ExponentialWithJitter { base, max, multiplier } => {
+                let scaled_ms = base.as_millis() as f64 * multiplier.powi(attempt as i32);
+                let capped_ms = (scaled_ms.min(max.as_millis() as f64) as u64).max(1);
+                let jittered_ms = rand::thread_rng().gen_range(0..=capped_ms);
+                Duration::from_millis(jittered_ms)
+            }
+        }
+    }
+}
+
+impl Default for BackoffStrategy {
+    fn default() -> Self {
+        BackoffStrategy::ExponentialWithJitter {
+            base: Duration::from_secs(1),
+            max: Duration::from_secs(60),
+            multiplier: 2.0,
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct ErrorHandler {
     max_retries: u32,
     base_delay: Duration,
     max_delay: Duration,
-    backoff_multiplier: f64,
+    /// Per-`ErrorType` overrides (keyed by `ErrorType::to_string()`), e.g.
+    /// more patient retries for `RateLimit`, near-zero retries for errors
+    /// that can't recover. Falls back to `max_retries`/`base_delay`/
+    /// `max_delay` above when a type isn't registered.
+    retry_policies: HashMap<String, RetryConfig>,
+    /// Per-`ErrorType` backoff strategy overrides, same lookup convention as
+    /// `retry_policies`. Falls back to `ExponentialWithJitter` bounded by the
+    /// type's `RetryConfig` delays when a type isn't registered and no
+    /// per-call override is given.
+    backoff_policies: HashMap<String, BackoffStrategy>,
+    /// Thresholds for the per-`ErrorType` circuit breaker layered on top of
+    /// retries in `execute_with_retry_and_error_tracking`.
+    circuit_config: CircuitBreakerConfig,
+    /// One circuit per `ErrorType` (keyed by `ErrorType::to_string()`), so a
+    /// persistently-down database doesn't trip the breaker guarding RPC
+    /// calls and vice versa. `Arc` so `ErrorHandler` stays cheaply cloneable
+    /// (mirroring `metrics: Arc<BlockchainMetrics>` elsewhere in this crate)
+    /// while every clone shares the same live circuit state.
+    circuits: Arc<Mutex<HashMap<String, PerTypeCircuit>>>,
+    /// Set via `with_metrics` so `execute_with_retry_and_error_tracking_with_fallback`
+    /// can record `BlockchainMetrics::record_fallback_taken`. `None` leaves
+    /// fallback-taking silent except for the `log::warn!` it always emits.
+    metrics: Option<Arc<BlockchainMetrics>>,
+    /// Per-`ErrorType` invocation/retry/latency counters (keyed by
+    /// `ErrorType::to_string()`, same convention as `circuits`), read back
+    /// via `metrics_snapshot()`. Decoupled from `prometheus`/`BlockchainMetrics`
+    /// on purpose — this struct has no Prometheus dependency of its own, so a
+    /// caller can publish the snapshot however its own metrics registry wants.
+    op_metrics: Arc<Mutex<HashMap<String, ErrorTypeMetrics>>>,
+}
+
+/// Aggregate counters for one `ErrorType`, returned by
+/// `ErrorHandler::metrics_snapshot`. `latency_ms_sum`/`latency_count` stand
+/// in for a histogram: a caller that wants buckets can still derive a mean,
+/// and one that just wants "is this getting slower" can track the ratio over
+/// time without this crate picking bucket boundaries on its behalf.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct ErrorTypeMetrics {
+    pub total_invocations: u64,
+    pub retries_performed: u64,
+    pub successes_after_retry: u64,
+    pub final_failures: u64,
+    pub fallbacks_taken: u64,
+    pub circuit_trips: u64,
+    pub latency_ms_sum: u64,
+    pub latency_count: u64,
+}
+
+/// Returned by `execute_with_retry_and_error_tracking_with_fallback`:
+/// `degraded` is `true` when `value` came from the fallback rather than a
+/// fresh successful call, so callers (e.g. a DeFi price/balance endpoint)
+/// can surface that to their own clients instead of passing off stale data
+/// as current.
+#[derive(Debug, Clone)]
+pub struct FallbackResult<T> {
+    pub value: T,
+    pub degraded: bool,
+}
+
+/// Thresholds for the sliding-window circuit breaker in `ErrorHandler`,
+/// modeled on MicroProfile Fault Tolerance's `@CircuitBreaker`: trip to
+/// `Open` after `failure_threshold` failures inside `window`, stay `Open`
+/// for `cooldown`, then allow `half_open_trial_count` trial calls through
+/// before deciding whether to close or re-open.
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    pub failure_threshold: u32,
+    pub window: Duration,
+    pub cooldown: Duration,
+    pub half_open_trial_count: u32,
+}
+
+impl CircuitBreakerConfig {
+    pub fn new(failure_threshold: u32, window: Duration, cooldown: Duration, half_open_trial_count: u32) -> Self {
+        Self { failure_threshold, window, cooldown, half_open_trial_count }
+    }
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            window: Duration::from_secs(60),
+            cooldown: Duration::from_secs(30),
+            half_open_trial_count: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PerTypeCircuitState {
+    Closed,
+    Open { opened_at: std::time::Instant },
+    HalfOpen { trials_used: u32 },
+}
+
+struct PerTypeCircuit {
+    state: PerTypeCircuitState,
+    /// Timestamps of failures inside the current `window`, pruned on every
+    /// failure so `failures.len()` is always the rolling count.
+    failures: VecDeque<std::time::Instant>,
+}
+
+impl PerTypeCircuit {
+    fn new() -> Self {
+        Self { state: PerTypeCircuitState::Closed, failures: VecDeque::new() }
+    }
 }
 
 impl ErrorHandler {
     pub fn new() -> Self {
+        let mut retry_policies = HashMap::new();
+
+        // Rate limiting recovers, but needs room to breathe: more retries
+        // and a longer base delay than the default.
+        retry_policies.insert(
+            ErrorType::RateLimit.to_string(),
+            RetryConfig::new(8, Duration::from_secs(3), Duration::from_secs(120)),
+        );
+        // A network blip is usually gone within a couple of seconds; retry
+        // fast and often rather than backing off slowly.
+        retry_policies.insert(
+            ErrorType::NetworkError.to_string(),
+            RetryConfig::new(8, Duration::from_millis(200), Duration::from_secs(10)),
+        );
+        // These never recover by retrying the same request: the block/tx
+        // still won't exist, the payload still won't parse.
+        retry_policies.insert(
+            ErrorType::BlockNotFound.to_string(),
+            RetryConfig::new(0, Duration::from_millis(100), Duration::from_millis(100)),
+        );
+        retry_policies.insert(
+            ErrorType::ParsingError.to_string(),
+            RetryConfig::new(0, Duration::from_millis(100), Duration::from_millis(100)),
+        );
+
         Self {
             max_retries: 5,
             base_delay: Duration::from_secs(1),
             max_delay: Duration::from_secs(60),
-            backoff_multiplier: 2.0,
+            retry_policies,
+            backoff_policies: HashMap::new(),
+            circuit_config: CircuitBreakerConfig::default(),
+            circuits: Arc::new(Mutex::new(HashMap::new())),
+            metrics: None,
+            op_metrics: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -69,81 +366,474 @@ impl ErrorHandler {
         self
     }
 
+    /// Registers (or overrides) the retry policy used for operations
+    /// classified as `error_type`.
+    pub fn with_retry_policy(mut self, error_type: ErrorType, config: RetryConfig) -> Self {
+        self.retry_policies.insert(error_type.to_string(), config);
+        self
+    }
+
+    /// Overrides the thresholds used by the per-`ErrorType` circuit breaker.
+    pub fn with_circuit_breaker_config(mut self, config: CircuitBreakerConfig) -> Self {
+        self.circuit_config = config;
+        self
+    }
+
+    /// Registers (or overrides) the backoff strategy used for operations
+    /// classified as `error_type`.
+    pub fn with_backoff_policy(mut self, error_type: ErrorType, strategy: BackoffStrategy) -> Self {
+        self.backoff_policies.insert(error_type.to_string(), strategy);
+        self
+    }
+
+    /// Lets `execute_with_retry_and_error_tracking_with_fallback` record
+    /// `BlockchainMetrics::record_fallback_taken` whenever it returns a
+    /// degraded value.
+    pub fn with_metrics(mut self, metrics: Arc<BlockchainMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Resolves the policy for `error_type`, falling back to this handler's
+    /// default `max_retries`/`base_delay`/`max_delay` when no override is
+    /// registered.
+    fn retry_config_for(&self, error_type: &ErrorType) -> RetryConfig {
+        self.retry_policies
+            .get(&error_type.to_string())
+            .cloned()
+            .unwrap_or_else(|| RetryConfig::new(self.max_retries, self.base_delay, self.max_delay))
+    }
+
+    /// Resolves the backoff strategy for `error_type`: an explicit per-call
+    /// `override_strategy` wins, then a registered per-type policy, then
+    /// `ExponentialWithJitter` (multiplier 2) between the `base_delay` and
+    /// `max_delay` of `retry_config_for(error_type)`.
+    fn backoff_strategy_for(&self, error_type: &ErrorType, override_strategy: Option<&BackoffStrategy>) -> BackoffStrategy {
+        override_strategy
+            .cloned()
+            .or_else(|| self.backoff_policies.get(&error_type.to_string()).cloned())
+            .unwrap_or_else(|| {
+                let config = self.retry_config_for(error_type);
+                BackoffStrategy::ExponentialWithJitter {
+                    base: config.base_delay,
+                    max: config.max_delay,
+                    multiplier: 2.0,
+                }
+            })
+    }
+
     pub async fn execute_with_retry<F, T>(&self, operation: F) -> Result<T>
     where
         F: Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T>> + Send>> + Send + Sync,
     {
         let mut last_error = None;
-        
+        let mut prev_delay = self.base_delay;
+
         for attempt in 0..=self.max_retries {
             match operation().await {
                 Ok(result) => return Ok(result),
                 Err(e) => {
                     last_error = Some(e);
-                    
+
                     if attempt < self.max_retries {
-                        let delay = self.calculate_delay(attempt);
-                        log::warn!("Operation failed (attempt {}/{}), retrying in {:?}: {}", 
+                        let delay = Self::decorrelated_jitter_delay(self.base_delay, self.max_delay, prev_delay);
+                        prev_delay = delay;
+                        log::warn!("Operation failed (attempt {}/{}), retrying in {:?}: {}",
                             attempt + 1, self.max_retries + 1, delay, last_error.as_ref().unwrap());
                         sleep(delay).await;
                     }
                 }
             }
         }
-        
+
         Err(last_error.unwrap())
     }
 
+    /// Checks whether a call classified as `error_type` may proceed at all,
+    /// per this handler's `CircuitBreakerConfig`. Transitions `Open` ->
+    /// `HalfOpen` once `cooldown` has elapsed, and meters out at most
+    /// `half_open_trial_count` trial calls while `HalfOpen`. Returns
+    /// `Err(CircuitOpen)` otherwise, short-circuiting before `operation` is
+    /// ever invoked.
+    fn check_circuit(&self, error_type: &ErrorType) -> Result<()> {
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits.entry(error_type.to_string()).or_insert_with(PerTypeCircuit::new);
+
+        match &mut circuit.state {
+            PerTypeCircuitState::Closed => Ok(()),
+            PerTypeCircuitState::Open { opened_at } => {
+                if opened_at.elapsed() >= self.circuit_config.cooldown {
+                    circuit.state = PerTypeCircuitState::HalfOpen { trials_used: 1 };
+                    log::info!("Circuit breaker for {} transitioning to HalfOpen", error_type);
+                    Ok(())
+                } else {
+                    Err(anyhow::anyhow!("CircuitOpen: {} circuit is open", error_type))
+                }
+            }
+            PerTypeCircuitState::HalfOpen { trials_used } => {
+                if *trials_used < self.circuit_config.half_open_trial_count {
+                    *trials_used += 1;
+                    Ok(())
+                } else {
+                    Err(anyhow::anyhow!("CircuitOpen: {} circuit half-open trial budget exhausted", error_type))
+                }
+            }
+        }
+    }
+
+    /// Feeds the outcome of one `execute_with_retry_and_error_tracking` call
+    /// (after all its retries) into `error_type`'s circuit: a success closes
+    /// the circuit and clears its failure window; a failure while `Closed`
+    /// or `Open` records into the rolling window and trips to `Open` once
+    /// `failure_threshold` is reached within `window`; a failure while
+    /// `HalfOpen` re-opens immediately, resetting the cooldown.
+    fn record_circuit_outcome(&self, error_type: &ErrorType, success: bool) {
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits.entry(error_type.to_string()).or_insert_with(PerTypeCircuit::new);
+
+        if success {
+            circuit.failures.clear();
+            circuit.state = PerTypeCircuitState::Closed;
+            return;
+        }
+
+        if matches!(circuit.state, PerTypeCircuitState::HalfOpen { .. }) {
+            circuit.failures.clear();
+            circuit.state = PerTypeCircuitState::Open { opened_at: std::time::Instant::now() };
+            log::warn!("Circuit breaker for {} re-opened after a failed HalfOpen trial", error_type);
+            return;
+        }
+
+        let now = std::time::Instant::now();
+        circuit.failures.push_back(now);
+        while let Some(&front) = circuit.failures.front() {
+            if now.duration_since(front) > self.circuit_config.window {
+                circuit.failures.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if circuit.failures.len() as u32 >= self.circuit_config.failure_threshold {
+            circuit.state = PerTypeCircuitState::Open { opened_at: now };
+            log::warn!(
+                "Circuit breaker for {} opened after {} failures within {:?}",
+                error_type, circuit.failures.len(), self.circuit_config.window
+            );
+            self.op_metrics.lock().unwrap().entry(error_type.to_string()).or_default().circuit_trips += 1;
+        }
+    }
+
+    /// Returns a snapshot of the invocation/retry/latency counters collected
+    /// so far, keyed by `ErrorType::to_string()`, for a caller to publish to
+    /// Prometheus (or log, or assert on in a test) without holding this
+    /// handler's internal lock.
+    pub fn metrics_snapshot(&self) -> HashMap<String, ErrorTypeMetrics> {
+        self.op_metrics.lock().unwrap().clone()
+    }
+
+    fn record_invocation_started(&self, error_type: &ErrorType) {
+        self.op_metrics.lock().unwrap().entry(error_type.to_string()).or_default().total_invocations += 1;
+    }
+
+    fn record_retry_performed(&self, error_type: &ErrorType) {
+        self.op_metrics.lock().unwrap().entry(error_type.to_string()).or_default().retries_performed += 1;
+    }
+
+    /// Feeds one call's terminal outcome (after all retries) into
+    /// `error_type`'s counters: `attempt > 0` on success means it only
+    /// succeeded after at least one retry.
+    fn record_invocation_outcome(&self, error_type: &ErrorType, succeeded: bool, attempt: u32, elapsed: Duration) {
+        let mut metrics = self.op_metrics.lock().unwrap();
+        let entry = metrics.entry(error_type.to_string()).or_default();
+        if succeeded {
+            if attempt > 0 {
+                entry.successes_after_retry += 1;
+            }
+        } else {
+            entry.final_failures += 1;
+        }
+        entry.latency_ms_sum += elapsed.as_millis() as u64;
+        entry.latency_count += 1;
+    }
+
+    fn record_fallback_taken_metric(&self, error_type: &ErrorType) {
+        self.op_metrics.lock().unwrap().entry(error_type.to_string()).or_default().fallbacks_taken += 1;
+    }
+
     pub async fn execute_with_retry_and_error_tracking<F, T>(
-        &self, 
+        &self,
+        operation: F,
+        error_type: ErrorType,
+        block_number: Option<u64>,
+        transaction_hash: Option<String>,
+    ) -> Result<T>
+    where
+        F: Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T>> + Send>> + Send + Sync,
+    {
+        self.execute_with_retry_and_error_tracking_with_backoff(
+            operation, error_type, block_number, transaction_hash, None,
+        ).await
+    }
+
+    /// Same as [`ErrorHandler::execute_with_retry_and_error_tracking`], but
+    /// `backoff_override` lets a single call pick a `BackoffStrategy`
+    /// without registering a handler-wide `with_backoff_policy` override —
+    /// useful for an operation that's an outlier within its `ErrorType`
+    /// (e.g. a bulk write that wants `Fixed` spacing while everything else
+    /// classified `DatabaseError` uses the default exponential backoff).
+    pub async fn execute_with_retry_and_error_tracking_with_backoff<F, T>(
+        &self,
         operation: F,
         error_type: ErrorType,
         block_number: Option<u64>,
         transaction_hash: Option<String>,
+        backoff_override: Option<BackoffStrategy>,
     ) -> Result<T>
     where
         F: Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T>> + Send>> + Send + Sync,
     {
+        self.check_circuit(&error_type)?;
+        self.record_invocation_started(&error_type);
+        let started_at = std::time::Instant::now();
+
+        let config = self.retry_config_for(&error_type);
+        let backoff = self.backoff_strategy_for(&error_type, backoff_override.as_ref());
         let mut last_error = None;
         let error_id = Uuid::new_v4().to_string();
-        
-        for attempt in 0..=self.max_retries {
+
+        for attempt in 0..=config.max_retries {
             match operation().await {
-                Ok(result) => return Ok(result),
+                Ok(result) => {
+                    self.record_circuit_outcome(&error_type, true);
+                    self.record_invocation_outcome(&error_type, true, attempt, started_at.elapsed());
+                    return Ok(result);
+                }
                 Err(e) => {
                     last_error = Some(e.clone());
-                    
+
                     // Log the error
                     let processing_error = ProcessingError {
                         error_id: error_id.clone(),
                         error_type: error_type.to_string(),
                         block_number,
                         transaction_hash: transaction_hash.clone(),
-                        error_message: e.to_string(),
+                        error_message: format!("{:#}", e),
                         retry_count: attempt,
                         last_retry: Utc::now(),
                         created_at: Utc::now(),
                     };
-                    
+
                     log::error!("Processing error: {:?}", processing_error);
-                    
-                    if attempt < self.max_retries {
-                        let delay = self.calculate_delay(attempt);
-                        log::warn!("Operation failed (attempt {}/{}), retrying in {:?}: {}", 
-                            attempt + 1, self.max_retries + 1, delay, e);
+
+                    if let Some(transport_error) = TransportError::from_anyhow(&e) {
+                        if transport_error.is_terminal() {
+                            log::warn!("Non-retryable transport error ({}), giving up after attempt {}", transport_error, attempt + 1);
+                            self.record_circuit_outcome(&error_type, false);
+                            self.record_invocation_outcome(&error_type, false, attempt, started_at.elapsed());
+                            return Err(anyhow::Error::new(RetryExhaustedError {
+                                operation: operation_label(&error_type, block_number, &transaction_hash),
+                                attempts: attempt + 1,
+                                error_type,
+                                source: last_error.unwrap(),
+                            }));
+                        }
+                    }
+
+                    if attempt < config.max_retries {
+                        let delay = backoff.delay_for_attempt(attempt);
+                        log::warn!("Operation failed (attempt {}/{}), retrying in {:?}: {}",
+                            attempt + 1, config.max_retries + 1, delay, e);
+                        self.record_retry_performed(&error_type);
                         sleep(delay).await;
                     }
                 }
             }
         }
-        
-        Err(last_error.unwrap())
+
+        self.record_circuit_outcome(&error_type, false);
+        self.record_invocation_outcome(&error_type, false, config.max_retries, started_at.elapsed());
+        let attempts = config.max_retries + 1;
+        Err(anyhow::Error::new(RetryExhaustedError {
+            operation: operation_label(&error_type, block_number, &transaction_hash),
+            attempts,
+            error_type,
+            source: last_error.unwrap(),
+        }))
+    }
+
+    /// Same as [`ErrorHandler::execute_with_retry_and_error_tracking`], but
+    /// bounds each attempt with `per_attempt_timeout` and/or the whole call
+    /// (all attempts plus backoff) with `total_deadline`. Either bound may be
+    /// `None` to leave that dimension unbounded. An attempt that elapses its
+    /// timeout is classified `ErrorType::TimeoutError` for logging, but still
+    /// counts against `error_type`'s retry budget and circuit breaker — a
+    /// database that's merely slow shouldn't get a separate, never-configured
+    /// circuit from one that's down. Once `total_deadline` has passed, no new
+    /// attempt is started (including the very first, if the deadline is
+    /// absurdly short) and the last observed error is returned.
+    pub async fn execute_with_retry_and_error_tracking_with_timeout<F, T>(
+        &self,
+        operation: F,
+        error_type: ErrorType,
+        block_number: Option<u64>,
+        transaction_hash: Option<String>,
+        per_attempt_timeout: Option<Duration>,
+        total_deadline: Option<Duration>,
+    ) -> Result<T>
+    where
+        F: Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T>> + Send>> + Send + Sync,
+    {
+        self.check_circuit(&error_type)?;
+        self.record_invocation_started(&error_type);
+        let started_at = std::time::Instant::now();
+
+        let config = self.retry_config_for(&error_type);
+        let backoff = self.backoff_strategy_for(&error_type, None);
+        let mut last_error = None;
+        let error_id = Uuid::new_v4().to_string();
+        let deadline = total_deadline.map(|d| std::time::Instant::now() + d);
+        let mut attempts_made: u32 = 0;
+
+        for attempt in 0..=config.max_retries {
+            let remaining = deadline.map(|d| d.saturating_duration_since(std::time::Instant::now()));
+            if let Some(remaining) = remaining {
+                if remaining.is_zero() {
+                    log::warn!("Aborting {} before attempt {}: total deadline exceeded", error_type, attempt + 1);
+                    break;
+                }
+            }
+            attempts_made += 1;
+
+            let attempt_timeout = match (per_attempt_timeout, remaining) {
+                (Some(t), Some(r)) => Some(t.min(r)),
+                (Some(t), None) => Some(t),
+                (None, Some(r)) => Some(r),
+                (None, None) => None,
+            };
+
+            let attempt_result = match attempt_timeout {
+                Some(duration) => match tokio::time::timeout(duration, operation()).await {
+                    Ok(result) => result,
+                    Err(_) => Err(anyhow::anyhow!("{} timed out after {:?}", ErrorType::TimeoutError, duration)),
+                },
+                None => operation().await,
+            };
+
+            match attempt_result {
+                Ok(result) => {
+                    self.record_circuit_outcome(&error_type, true);
+                    self.record_invocation_outcome(&error_type, true, attempt, started_at.elapsed());
+                    return Ok(result);
+                }
+                Err(e) => {
+                    let is_timeout = e.downcast_ref::<tokio::time::error::Elapsed>().is_some()
+                        || e.to_string().contains(&ErrorType::TimeoutError.to_string());
+                    last_error = Some(e.clone());
+
+                    let processing_error = ProcessingError {
+                        error_id: error_id.clone(),
+                        error_type: if is_timeout { ErrorType::TimeoutError.to_string() } else { error_type.to_string() },
+                        block_number,
+                        transaction_hash: transaction_hash.clone(),
+                        error_message: format!("{:#}", e),
+                        retry_count: attempt,
+                        last_retry: Utc::now(),
+                        created_at: Utc::now(),
+                    };
+
+                    log::error!("Processing error: {:?}", processing_error);
+
+                    if !is_timeout {
+                        if let Some(transport_error) = TransportError::from_anyhow(&e) {
+                            if transport_error.is_terminal() {
+                                log::warn!("Non-retryable transport error ({}), giving up after attempt {}", transport_error, attempt + 1);
+                                self.record_circuit_outcome(&error_type, false);
+                                self.record_invocation_outcome(&error_type, false, attempt, started_at.elapsed());
+                                return Err(anyhow::Error::new(RetryExhaustedError {
+                                    operation: operation_label(&error_type, block_number, &transaction_hash),
+                                    attempts: attempt + 1,
+                                    error_type,
+                                    source: last_error.unwrap(),
+                                }));
+                            }
+                        }
+                    }
+
+                    if attempt < config.max_retries {
+                        if let Some(deadline) = deadline {
+                            if std::time::Instant::now() >= deadline {
+                                log::warn!("Not retrying {}: total deadline exceeded after attempt {}", error_type, attempt + 1);
+                                break;
+                            }
+                        }
+                        let delay = backoff.delay_for_attempt(attempt);
+                        log::warn!("Operation failed (attempt {}/{}), retrying in {:?}: {}",
+                            attempt + 1, config.max_retries + 1, delay, e);
+                        self.record_retry_performed(&error_type);
+                        sleep(delay).await;
+                    }
+                }
+            }
+        }
+
+        self.record_circuit_outcome(&error_type, false);
+        self.record_invocation_outcome(&error_type, false, attempts_made.saturating_sub(1), started_at.elapsed());
+        Err(anyhow::Error::new(RetryExhaustedError {
+            operation: operation_label(&error_type, block_number, &transaction_hash),
+            attempts: attempts_made,
+            error_type,
+            source: last_error.unwrap_or_else(|| anyhow::anyhow!("operation exceeded total deadline before completing any attempt")),
+        }))
+    }
+
+    /// Same as [`ErrorHandler::execute_with_retry_and_error_tracking`], but
+    /// instead of propagating the final error (retries exhausted, or the
+    /// circuit open), calls `fallback` once and returns its value wrapped in
+    /// a [`FallbackResult`] with `degraded: true`. Lets a DeFi price/balance
+    /// query return a stale-but-usable cached value instead of failing the
+    /// whole request. Records `BlockchainMetrics::record_fallback_taken`
+    /// (if `with_metrics` was used) whenever the fallback is taken. If
+    /// `fallback` itself fails, the original retry error is returned.
+    pub async fn execute_with_retry_and_error_tracking_with_fallback<F, T>(
+        &self,
+        operation: F,
+        error_type: ErrorType,
+        block_number: Option<u64>,
+        transaction_hash: Option<String>,
+        fallback: impl FnOnce() -> Result<T>,
+    ) -> Result<FallbackResult<T>>
+    where
+        F: Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T>> + Send>> + Send + Sync,
+    {
+        match self.execute_with_retry_and_error_tracking(operation, error_type.clone(), block_number, transaction_hash).await {
+            Ok(value) => Ok(FallbackResult { value, degraded: false }),
+            Err(e) => match fallback() {
+                Ok(value) => {
+                    log::warn!("Returning degraded fallback value for {} after exhausted retries: {}", error_type, e);
+                    self.record_fallback_taken_metric(&error_type);
+                    if let Some(metrics) = &self.metrics {
+                        metrics.record_fallback_taken(&error_type.to_string());
+                    }
+                    Ok(FallbackResult { value, degraded: true })
+                }
+                Err(fallback_err) => {
+                    log::error!("Fallback for {} also failed ({}); returning original error", error_type, fallback_err);
+                    Err(e)
+                }
+            },
+        }
     }
 
-    fn calculate_delay(&self, attempt: u32) -> Duration {
-        let delay_ms = self.base_delay.as_millis() as f64 * self.backoff_multiplier.powi(attempt as i32);
-        let delay_ms = delay_ms.min(self.max_delay.as_millis() as f64);
-        Duration::from_millis(delay_ms as u64)
+    /// Decorrelated-jitter backoff: `sleep = min(max_delay,
+    /// random_uniform(base_delay, prev * 3))`. Spreads retries across a wide
+    /// `[base_delay, 3*prev]` range (rather than every worker backing off in
+    /// lockstep) while still trending upward and capping at `max_delay`.
+    fn decorrelated_jitter_delay(base_delay: Duration, max_delay: Duration, prev: Duration) -> Duration {
+        let base_ms = base_delay.as_millis().max(1) as u64;
+        let upper_ms = (prev.as_millis() as u64).saturating_mul(3).max(base_ms);
+        let jittered_ms = rand::thread_rng().gen_range(base_ms..=upper_ms);
+        Duration::from_millis(jittered_ms.min(max_delay.as_millis() as u64))
     }
 
     pub fn is_retryable_error(&self, error: &anyhow::Error) -> bool {
@@ -181,6 +871,12 @@ impl ErrorHandler {
             ErrorType::ParsingError
         } else if error_msg.contains("network") {
             ErrorType::NetworkError
+        } else if error_msg.contains("parent_hash") || error_msg.contains("header verification") {
+            ErrorType::HeaderVerificationFailed
+        } else if error_msg.contains("proof verification") {
+            ErrorType::ProofVerificationFailed
+        } else if error_msg.contains("method not found") || error_msg.contains("the method") && error_msg.contains("not available") {
+            ErrorType::TracingUnsupported
         } else {
             ErrorType::Unknown
         }
@@ -217,26 +913,7 @@ impl CircuitBreaker {
     where
         F: Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T>> + Send>> + Send + Sync,
     {
-        match self.state {
-            CircuitBreakerState::Open => {
-                if let Some(last_failure) = self.last_failure_time {
-                    if last_failure.elapsed() >= self.recovery_timeout {
-                        self.state = CircuitBreakerState::HalfOpen;
-                        log::info!("Circuit breaker transitioning to HalfOpen state");
-                    } else {
-                        return Err(anyhow::anyhow!("Circuit breaker is open"));
-                    }
-                } else {
-                    return Err(anyhow::anyhow!("Circuit breaker is open"));
-                }
-            }
-            CircuitBreakerState::HalfOpen => {
-                // Allow one request to test if service has recovered
-            }
-            CircuitBreakerState::Closed => {
-                // Normal operation
-            }
-        }
+        self.check_and_transition()?;
 
         match operation().await {
             Ok(result) => {
@@ -273,6 +950,46 @@ impl CircuitBreaker {
     pub fn failure_count(&self) -> u32 {
         self.failure_count
     }
+
+    /// Records a failure observed outside of [`CircuitBreaker::execute`],
+    /// e.g. when a caller dispatches the operation itself (as a multi-endpoint
+    /// quorum transport does) and only wants this breaker's bookkeeping.
+    pub fn record_external_failure(&mut self) {
+        self.on_failure();
+    }
+
+    /// Counterpart to [`CircuitBreaker::record_external_failure`] for
+    /// successful external calls.
+    pub fn record_external_success(&mut self) {
+        self.on_success();
+    }
+
+    /// Checks whether a call is currently allowed, transitioning `Open` to
+    /// `HalfOpen` once `recovery_timeout` has elapsed. Returns `Ok(true)`
+    /// when the caller must hold the single-probe gate (state is, or just
+    /// became, `HalfOpen`), `Ok(false)` for ordinary `Closed` operation, and
+    /// `Err` while the circuit is still open. Shared by [`CircuitBreaker::execute`]
+    /// and [`ResilientExecutor::run`], which need the same gating logic but
+    /// drive the actual call differently.
+    pub fn check_and_transition(&mut self) -> Result<bool> {
+        match self.state {
+            CircuitBreakerState::Open => {
+                if let Some(last_failure) = self.last_failure_time {
+                    if last_failure.elapsed() >= self.recovery_timeout {
+                        self.state = CircuitBreakerState::HalfOpen;
+                        log::info!("Circuit breaker transitioning to HalfOpen state");
+                        Ok(true)
+                    } else {
+                        Err(anyhow::anyhow!("Circuit breaker is open"))
+                    }
+                } else {
+                    Err(anyhow::anyhow!("Circuit breaker is open"))
+                }
+            }
+            CircuitBreakerState::HalfOpen => Ok(true),
+            CircuitBreakerState::Closed => Ok(false),
+        }
+    }
 }
 
 pub struct HealthMonitor {
@@ -324,3 +1041,104 @@ impl HealthMonitor {
         (self.success_count, self.error_count, self.error_rate(), self.uptime())
     }
 }
+
+/// Combined `/health`/`/metrics` view over a [`ResilientExecutor`]: the
+/// circuit breaker's state and failure count alongside the health monitor's
+/// success/error counters.
+#[derive(Debug, Clone)]
+pub struct ResilientExecutorStats {
+    pub circuit_state: CircuitBreakerState,
+    pub circuit_failure_count: u32,
+    pub success_count: u64,
+    pub error_count: u64,
+    pub error_rate: f64,
+    pub uptime: Duration,
+}
+
+/// Fuses [`ErrorHandler`], [`CircuitBreaker`], and [`HealthMonitor`] behind
+/// one `run` call, so a caller no longer has to remember to feed an
+/// operation's outcome into all three by hand. `CircuitBreaker` and
+/// `HealthMonitor` live behind `tokio::sync::Mutex`, matching how
+/// `QuorumTransport`'s per-endpoint breakers are shared across concurrent
+/// callers in `blockchain.rs`.
+pub struct ResilientExecutor {
+    error_handler: ErrorHandler,
+    circuit_breaker: tokio::sync::Mutex<CircuitBreaker>,
+    health_monitor: tokio::sync::Mutex<HealthMonitor>,
+    /// Lets exactly one call through while the circuit breaker is
+    /// `HalfOpen`, so a burst of concurrent callers can't all slip through
+    /// as "the" recovery probe and re-open the breaker on the first sign of
+    /// trouble.
+    half_open_gate: tokio::sync::Semaphore,
+}
+
+impl ResilientExecutor {
+    pub fn new(error_handler: ErrorHandler, circuit_breaker: CircuitBreaker, health_monitor: HealthMonitor) -> Self {
+        Self {
+            error_handler,
+            circuit_breaker: tokio::sync::Mutex::new(circuit_breaker),
+            health_monitor: tokio::sync::Mutex::new(health_monitor),
+            half_open_gate: tokio::sync::Semaphore::new(1),
+        }
+    }
+
+    /// Runs `operation`, retrying per `error_type`'s policy as
+    /// [`ErrorHandler::execute_with_retry_and_error_tracking`] would, but
+    /// first fails fast if the circuit is `Open`, gates `HalfOpen` recovery
+    /// probes to one at a time, and feeds the outcome into both the circuit
+    /// breaker and the health monitor. Only retryable failures (per
+    /// [`ErrorHandler::is_retryable_error`]) count against the breaker, so a
+    /// deterministic error like `ParsingError` doesn't trip it open.
+    pub async fn run<F, T>(
+        &self,
+        error_type: ErrorType,
+        block_number: Option<u64>,
+        transaction_hash: Option<String>,
+        operation: F,
+    ) -> Result<T>
+    where
+        F: Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T>> + Send>> + Send + Sync,
+    {
+        let is_half_open_probe = self.circuit_breaker.lock().await.check_and_transition()?;
+
+        let _permit = if is_half_open_probe {
+            Some(self.half_open_gate.acquire().await.map_err(|_| anyhow::anyhow!("half-open probe gate closed"))?)
+        } else {
+            None
+        };
+
+        let result = self
+            .error_handler
+            .execute_with_retry_and_error_tracking(operation, error_type, block_number, transaction_hash)
+            .await;
+
+        match &result {
+            Ok(_) => {
+                self.circuit_breaker.lock().await.record_external_success();
+                self.health_monitor.lock().await.record_success();
+            }
+            Err(e) => {
+                self.health_monitor.lock().await.record_error();
+                if self.error_handler.is_retryable_error(e) {
+                    self.circuit_breaker.lock().await.record_external_failure();
+                }
+            }
+        }
+
+        result
+    }
+
+    pub async fn stats(&self) -> ResilientExecutorStats {
+        let breaker = self.circuit_breaker.lock().await;
+        let health = self.health_monitor.lock().await;
+        let (success_count, error_count, error_rate, uptime) = health.get_stats();
+        ResilientExecutorStats {
+            circuit_state: breaker.state().clone(),
+            circuit_failure_count: breaker.failure_count(),
+            success_count,
+            error_count,
+            error_rate,
+            uptime,
+        }
+    }
+}
@@ -3,17 +3,70 @@ use chrono::Utc;
 use ethabi::{Contract, Event, Function, ParamType, Token};
 use hex;
 use rust_decimal::Decimal;
+use serde_json::json;
 use sha3::{Digest, Keccak256};
-use std::collections::HashMap;
-use web3::types::{Block, Transaction, TransactionReceipt, Log, H160, H256, U256, U64};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use web3::transports::Http;
+use web3::types::{
+    Block, Bytes, CallRequest, Log, Transaction, TransactionReceipt as Web3TransactionReceipt,
+    H160, H256, U256, U64,
+};
 use web3::Web3;
 
+use crate::log_decoder::LogDecoder;
 use crate::models::*;
 
+/// Number of recently-processed blocks `EthereumProcessor::observe_block`
+/// keeps around to resolve the common ancestor of a reorg. A reorg deeper
+/// than this can still be detected (the parent-hash mismatch is still
+/// caught) but its ancestor cannot be pinpointed from the window alone.
+const REORG_WINDOW_SIZE: usize = 128;
+
+/// One link in the chain of already-processed blocks, used to detect when a
+/// newly-seen block's `parent_hash` no longer lines up with the tip.
+#[derive(Debug, Clone)]
+struct BlockLink {
+    number: u64,
+    hash: H256,
+    parent_hash: H256,
+}
+
 pub struct EthereumProcessor {
     known_contracts: HashMap<String, Contract>,
     token_contracts: HashMap<String, TokenContractInfo>,
     defi_protocols: HashMap<String, DeFiProtocolInfo>,
+    reorg_window: Mutex<VecDeque<BlockLink>>,
+    log_decoder: LogDecoder,
+}
+
+/// The three transaction envelope types this processor understands. Each
+/// variant carries which fields are meaningful for it: legacy transactions
+/// never have an access list or 1559 fee fields, 2930 adds the access list,
+/// and 1559 adds the fee cap/tip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvelopeType {
+    Legacy,
+    Eip2930,
+    Eip1559,
+}
+
+impl EnvelopeType {
+    fn from_byte(transaction_type: u8) -> Self {
+        match transaction_type {
+            1 => EnvelopeType::Eip2930,
+            2 => EnvelopeType::Eip1559,
+            _ => EnvelopeType::Legacy,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            EnvelopeType::Legacy => "legacy",
+            EnvelopeType::Eip2930 => "eip2930",
+            EnvelopeType::Eip1559 => "eip1559",
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -38,8 +91,10 @@ impl EthereumProcessor {
             known_contracts: HashMap::new(),
             token_contracts: HashMap::new(),
             defi_protocols: HashMap::new(),
+            reorg_window: Mutex::new(VecDeque::new()),
+            log_decoder: LogDecoder::new(),
         };
-        
+
         // Initialize with known token contracts and DeFi protocols
         processor.initialize_known_contracts();
         processor
@@ -49,14 +104,20 @@ impl EthereumProcessor {
         // ERC-20 Transfer event signature
         let transfer_signature = "Transfer(address,address,uint256)";
         let transfer_hash = format!("0x{:x}", Keccak256::digest(transfer_signature.as_bytes()));
-        
+
         // ERC-721 Transfer event signature
         let transfer_721_signature = "Transfer(address,address,uint256)";
-        let transfer_721_hash = format!("0x{:x}", Keccak256::digest(transfer_721_signature.as_bytes()));
-        
+        let transfer_721_hash = format!(
+            "0x{:x}",
+            Keccak256::digest(transfer_721_signature.as_bytes())
+        );
+
         // ERC-1155 TransferSingle event signature
         let transfer_single_signature = "TransferSingle(address,address,address,uint256,uint256)";
-        let transfer_single_hash = format!("0x{:x}", Keccak256::digest(transfer_single_signature.as_bytes()));
+        let transfer_single_hash = format!(
+            "0x{:x}",
+            Keccak256::digest(transfer_single_signature.as_bytes())
+        );
 
         // Initialize DeFi protocols
         let uniswap_v2 = DeFiProtocolInfo {
@@ -66,9 +127,21 @@ impl EthereumProcessor {
                 "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f".to_string(), // Factory
             ],
             event_signatures: HashMap::from([
-                ("0xd78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822".to_string(), "Swap".to_string()),
-                ("0x4c209b5fc8ad50758f13e2e1088ba56a560dff690a1c6fef26394f4c03821c4f".to_string(), "Mint".to_string()),
-                ("0xcc16f5dbb4873280815c1ee09dbd06736cffcc184412cf7a71a0fdb75d397ca5".to_string(), "Burn".to_string()),
+                (
+                    "0xd78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822"
+                        .to_string(),
+                    "Swap".to_string(),
+                ),
+                (
+                    "0x4c209b5fc8ad50758f13e2e1088ba56a560dff690a1c6fef26394f4c03821c4f"
+                        .to_string(),
+                    "Mint".to_string(),
+                ),
+                (
+                    "0xcc16f5dbb4873280815c1ee09dbd06736cffcc184412cf7a71a0fdb75d397ca5"
+                        .to_string(),
+                    "Burn".to_string(),
+                ),
             ]),
         };
 
@@ -79,20 +152,37 @@ impl EthereumProcessor {
                 "0x1F98431c8aD98523631AE4a59f267346ea31F984".to_string(), // Factory
             ],
             event_signatures: HashMap::from([
-                ("0xc42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67".to_string(), "Swap".to_string()),
-                ("0x7a53080ba414158be7ec69b987b5fb7d07dee101fe85488f0853ae16239d0bde".to_string(), "Mint".to_string()),
-                ("0x0c396cd989a39f4459b5fa1aed6a9a8dcdbc45908acfd67e028cd568da98982c".to_string(), "Burn".to_string()),
+                (
+                    "0xc42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67"
+                        .to_string(),
+                    "Swap".to_string(),
+                ),
+                (
+                    "0x7a53080ba414158be7ec69b987b5fb7d07dee101fe85488f0853ae16239d0bde"
+                        .to_string(),
+                    "Mint".to_string(),
+                ),
+                (
+                    "0x0c396cd989a39f4459b5fa1aed6a9a8dcdbc45908acfd67e028cd568da98982c"
+                        .to_string(),
+                    "Burn".to_string(),
+                ),
             ]),
         };
 
-        self.defi_protocols.insert("uniswap_v2".to_string(), uniswap_v2);
-        self.defi_protocols.insert("uniswap_v3".to_string(), uniswap_v3);
+        self.defi_protocols
+            .insert("uniswap_v2".to_string(), uniswap_v2);
+        self.defi_protocols
+            .insert("uniswap_v3".to_string(), uniswap_v3);
     }
 
     pub fn process_block(&self, block: &Block<Transaction>) -> Result<BlockEvent> {
         let wei_to_eth = Decimal::new(1, 18); // 1 ETH = 10^18 wei
-        
+
         Ok(BlockEvent {
+            total_burnt_wei: "0".to_string(),
+            total_tips_wei: "0".to_string(),
+            average_effective_gas_price: "0".to_string(),
             block_number: block.number.unwrap().as_u64(),
             block_hash: format!("{:?}", block.hash.unwrap()),
             parent_hash: format!("{:?}", block.parent_hash),
@@ -102,7 +192,10 @@ impl EthereumProcessor {
             gas_limit: block.gas_limit.as_u64(),
             base_fee_per_gas: block.base_fee_per_gas.map(|fee| fee.to_string()),
             difficulty: block.difficulty.to_string(),
-            total_difficulty: block.total_difficulty.map(|d| d.to_string()).unwrap_or_default(),
+            total_difficulty: block
+                .total_difficulty
+                .map(|d| d.to_string())
+                .unwrap_or_default(),
             size: block.size.map(|s| s.as_u64()).unwrap_or(0),
             miner: format!("{:?}", block.author.unwrap_or_default()),
             extra_data: format!("{:?}", block.extra_data.0),
@@ -114,30 +207,191 @@ impl EthereumProcessor {
             transactions_root: format!("{:?}", block.transactions_root),
             receipts_root: format!("{:?}", block.receipts_root),
             withdrawals: block.withdrawals.as_ref().map(|withdrawals| {
-                withdrawals.iter().map(|w| Withdrawal {
-                    index: w.index.as_u64(),
-                    validator_index: w.validator_index.as_u64(),
-                    address: format!("{:?}", w.address),
-                    amount: w.amount.to_string(),
-                }).collect()
+                withdrawals
+                    .iter()
+                    .map(|w| Withdrawal {
+                        index: w.index.as_u64(),
+                        validator_index: w.validator_index.as_u64(),
+                        address: format!("{:?}", w.address),
+                        amount: w.amount.to_string(),
+                    })
+                    .collect()
             }),
             created_at: Utc::now(),
         })
     }
 
-    pub fn process_transaction(&self, tx: &Transaction, block: &Block<Transaction>) -> Result<TransactionEvent> {
+    /// Tracks `block` against the window of already-processed blocks and
+    /// reports a [`ReorgEvent`] if its `parent_hash` no longer matches the
+    /// tip of that window, i.e. the chain this processor was following has
+    /// been orphaned by a reorg. Should be called once per block, in the
+    /// order blocks are processed (typically right alongside
+    /// `process_block`).
+    ///
+    /// Walks the window backwards from the current tip looking for a block
+    /// whose hash equals the new block's `parent_hash`; everything above
+    /// that point is reported as orphaned. If no match is found within the
+    /// window, the reorg is deeper than `REORG_WINDOW_SIZE` and the
+    /// ancestor is reported as unknown (`common_ancestor_number: None`)
+    /// rather than guessed at.
+    pub fn observe_block(&self, block: &Block<Transaction>) -> Option<ReorgEvent> {
+        let number = block.number?.as_u64();
+        let hash = block.hash?;
+        let parent_hash = block.parent_hash;
+
+        let mut window = self.reorg_window.lock().unwrap();
+
+        let reorg_event = match window.back() {
+            Some(tip) if tip.hash != parent_hash => {
+                let (ancestor, orphaned) =
+                    Self::unwind_to_common_ancestor(&mut window, parent_hash);
+                if orphaned.is_empty() {
+                    None
+                } else {
+                    Some(ReorgEvent {
+                        detected_at_block: number,
+                        common_ancestor_number: ancestor.as_ref().map(|b| b.number),
+                        common_ancestor_hash: ancestor.as_ref().map(|b| format!("{:?}", b.hash)),
+                        orphaned_blocks: orphaned
+                            .into_iter()
+                            .rev()
+                            .map(|b| OrphanedBlock {
+                                block_number: b.number,
+                                block_hash: format!("{:?}", b.hash),
+                            })
+                            .collect(),
+                        created_at: Utc::now(),
+                    })
+                }
+            }
+            _ => None,
+        };
+
+        window.push_back(BlockLink {
+            number,
+            hash,
+            parent_hash,
+        });
+        while window.len() > REORG_WINDOW_SIZE {
+            window.pop_front();
+        }
+
+        reorg_event
+    }
+
+    /// Pops blocks off the back of `window` until the tip's hash matches
+    /// `target_parent_hash` or the window is exhausted. Returns the matching
+    /// ancestor link (if found) and the orphaned blocks in pop order (newest
+    /// first).
+    fn unwind_to_common_ancestor(
+        window: &mut VecDeque<BlockLink>,
+        target_parent_hash: H256,
+    ) -> (Option<BlockLink>, Vec<BlockLink>) {
+        let mut orphaned = Vec::new();
+        while let Some(tip) = window.back() {
+            if tip.hash == target_parent_hash {
+                return (Some(tip.clone()), orphaned);
+            }
+            orphaned.push(window.pop_back().unwrap());
+        }
+        (None, orphaned)
+    }
+
+    /// Computes the effective priority fee per gas paid to the block
+    /// producer: `min(max_priority_fee_per_gas, max_fee_per_gas - base_fee)`
+    /// for type-2 transactions, or `gas_price - base_fee` for legacy/2930
+    /// transactions. Returns `None` on pre-London blocks (`base_fee` is
+    /// `None`), where there is no fee to burn and the full gas price is the
+    /// effective price.
+    fn compute_priority_fee_per_gas(tx: &Transaction, base_fee: Option<U256>) -> Option<U256> {
+        let base_fee = base_fee?;
+
+        let priority_fee = if let (Some(max_fee), Some(max_priority_fee)) =
+            (tx.max_fee_per_gas, tx.max_priority_fee_per_gas)
+        {
+            let headroom = max_fee.saturating_sub(base_fee);
+            max_priority_fee.min(headroom)
+        } else {
+            let gas_price = tx.gas_price.unwrap_or_default();
+            gas_price.saturating_sub(base_fee)
+        };
+
+        Some(priority_fee)
+    }
+
+    /// Aggregates per-transaction fee economics (burnt fee, miner tip,
+    /// average effective gas price) across a block's processed receipts and
+    /// writes the rollups onto `block_event`. Pre-London blocks (no
+    /// `base_fee_per_gas`) leave `total_burnt_wei` at zero.
+    pub fn aggregate_block_fees(
+        &self,
+        block_event: &mut BlockEvent,
+        receipts: &[TransactionReceipt],
+    ) {
+        let mut total_burnt = U256::zero();
+        let mut total_tips = U256::zero();
+        let mut total_effective_price = U256::zero();
+
+        for receipt in receipts {
+            total_burnt = total_burnt.saturating_add(parse_u256(&receipt.burnt_wei));
+            total_tips = total_tips.saturating_add(parse_u256(&receipt.miner_tip_wei));
+            total_effective_price =
+                total_effective_price.saturating_add(parse_u256(&receipt.effective_gas_price));
+        }
+
+        block_event.total_burnt_wei = total_burnt.to_string();
+        block_event.total_tips_wei = total_tips.to_string();
+        block_event.average_effective_gas_price = if receipts.is_empty() {
+            "0".to_string()
+        } else {
+            (total_effective_price / U256::from(receipts.len() as u64)).to_string()
+        };
+    }
+
+    pub fn process_transaction(
+        &self,
+        tx: &Transaction,
+        block: &Block<Transaction>,
+    ) -> Result<TransactionEvent> {
         let wei_to_eth = Decimal::new(1, 18);
         let value_eth = Decimal::from_str_exact(&tx.value.to_string())? / wei_to_eth;
-        
+
         let is_contract_creation = tx.to.is_none();
         let is_contract_interaction = !tx.input.0.is_empty() && tx.to.is_some();
-        
-        let access_list = tx.access_list.as_ref().map(|list| {
-            list.iter().map(|entry| AccessListEntry {
-                address: format!("{:?}", entry.address),
-                storage_keys: entry.storage_keys.iter().map(|key| format!("{:?}", key)).collect(),
-            }).collect()
-        });
+
+        let envelope =
+            EnvelopeType::from_byte(tx.transaction_type.unwrap_or_default().as_u64() as u8);
+
+        // Only 2930/1559 envelopes carry an access list; a legacy tx never does.
+        let access_list = if envelope == EnvelopeType::Legacy {
+            None
+        } else {
+            tx.access_list.as_ref().map(|list| {
+                list.iter()
+                    .map(|entry| AccessListEntry {
+                        address: format!("{:?}", entry.address),
+                        storage_keys: entry
+                            .storage_keys
+                            .iter()
+                            .map(|key| format!("{:?}", key))
+                            .collect(),
+                    })
+                    .collect()
+            })
+        };
+
+        // Only 1559 envelopes carry fee-cap/tip fields; legacy and 2930 use `gas_price`.
+        let (max_fee_per_gas, max_priority_fee_per_gas) = if envelope == EnvelopeType::Eip1559 {
+            (
+                tx.max_fee_per_gas.map(|fee| fee.to_string()),
+                tx.max_priority_fee_per_gas.map(|fee| fee.to_string()),
+            )
+        } else {
+            (None, None)
+        };
+
+        let priority_fee_per_gas = Self::compute_priority_fee_per_gas(tx, block.base_fee_per_gas)
+            .map(|fee| fee.to_string());
 
         Ok(TransactionEvent {
             hash: format!("{:?}", tx.hash),
@@ -148,73 +402,150 @@ impl EthereumProcessor {
             to: tx.to.map(|addr| format!("{:?}", addr)),
             value: tx.value.to_string(),
             value_eth,
+            // For 1559 txs, `tx.gas_price` as returned by the node for a mined
+            // transaction already reflects the effective price actually paid;
+            // the receipt's `effective_gas_price` remains the source of truth
+            // and is recorded on the `TransactionReceipt` built by
+            // `process_transaction_receipt`; this field is not updated from it.
             gas_price: tx.gas_price.unwrap_or_default().to_string(),
             gas_limit: tx.gas.to_string(),
             gas_used: None, // Will be filled from receipt
-            max_fee_per_gas: tx.max_fee_per_gas.map(|fee| fee.to_string()),
-            max_priority_fee_per_gas: tx.max_priority_fee_per_gas.map(|fee| fee.to_string()),
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
             nonce: tx.nonce.as_u64(),
             input_data: format!("{:?}", tx.input.0),
             input_data_length: tx.input.0.len(),
             is_contract_creation,
             is_contract_interaction,
             transaction_type: tx.transaction_type.unwrap_or(0).as_u64() as u8,
+            transaction_type_name: envelope.name().to_string(),
             access_list,
             chain_id: tx.chain_id.map(|id| id.as_u64()),
             v: format!("{:?}", tx.v),
             r: format!("{:?}", tx.r),
             s: format!("{:?}", tx.s),
             timestamp: block.timestamp.as_u64(),
+            priority_fee_per_gas,
             created_at: Utc::now(),
         })
     }
 
-    pub fn process_transaction_receipt(&self, receipt: &TransactionReceipt, block: &Block<Transaction>) -> Result<TransactionReceipt> {
-        let processed_logs: Result<Vec<ContractEvent>> = receipt.logs.iter()
-            .map(|log| self.process_log(log, block))
-            .collect();
+    /// Also returns every token transfer/DeFi event detected across the
+    /// receipt's logs, fully populated (`transaction_hash`/`block_number`/
+    /// `timestamp` filled in here, per `LogDecoder`'s "filled in by the
+    /// caller" contract) so the caller can store/publish them the same way
+    /// `reorg_handler.rs` does for their rollback.
+    pub fn process_transaction_receipt(
+        &self,
+        receipt: &Web3TransactionReceipt,
+        block: &Block<Transaction>,
+        tx: &Transaction,
+    ) -> Result<(TransactionReceipt, Vec<TokenTransferEvent>, Vec<DeFiEvent>)> {
+        let mut token_transfers = Vec::new();
+        let mut defi_events = Vec::new();
+        let mut processed_logs = Vec::with_capacity(receipt.logs.len());
+        for log in &receipt.logs {
+            let (event, transfer, defi_event) = self.process_log(log, block)?;
+            processed_logs.push(event);
+            token_transfers.extend(transfer);
+            defi_events.extend(defi_event);
+        }
 
-        Ok(TransactionReceipt {
-            transaction_hash: format!("{:?}", receipt.transaction_hash),
-            block_number: block.number.unwrap().as_u64(),
-            block_hash: format!("{:?}", block.hash.unwrap()),
-            transaction_index: receipt.transaction_index.as_u64(),
-            from: format!("{:?}", receipt.from),
-            to: receipt.to.map(|addr| format!("{:?}", addr)),
-            gas_used: receipt.gas_used.to_string(),
-            effective_gas_price: receipt.effective_gas_price.to_string(),
-            contract_address: receipt.contract_address.map(|addr| format!("{:?}", addr)),
-            logs: processed_logs?,
-            logs_bloom: format!("{:?}", receipt.logs_bloom),
-            status: receipt.status.map(|s| s.as_u64()),
-            root: receipt.root.map(|r| format!("{:?}", r)),
-            cumulative_gas_used: receipt.cumulative_gas_used.to_string(),
-            created_at: Utc::now(),
-        })
+        let (burnt_wei, miner_tip_wei) =
+            match Self::compute_priority_fee_per_gas(tx, block.base_fee_per_gas) {
+                Some(priority_fee_per_gas) => {
+                    let base_fee = block.base_fee_per_gas.unwrap_or_default();
+                    let burnt = base_fee.saturating_mul(receipt.gas_used);
+                    let tip = priority_fee_per_gas.saturating_mul(receipt.gas_used);
+                    (burnt.to_string(), tip.to_string())
+                }
+                // Pre-London block: nothing is burnt, the whole gas price goes to the miner.
+                None => (
+                    "0".to_string(),
+                    receipt
+                        .effective_gas_price
+                        .saturating_mul(receipt.gas_used)
+                        .to_string(),
+                ),
+            };
+
+        let envelope =
+            EnvelopeType::from_byte(tx.transaction_type.unwrap_or_default().as_u64() as u8);
+
+        Ok((
+            TransactionReceipt {
+                transaction_hash: format!("{:?}", receipt.transaction_hash),
+                block_number: block.number.unwrap().as_u64(),
+                block_hash: format!("{:?}", block.hash.unwrap()),
+                transaction_index: receipt.transaction_index.as_u64(),
+                from: format!("{:?}", receipt.from),
+                to: receipt.to.map(|addr| format!("{:?}", addr)),
+                gas_used: receipt.gas_used.to_string(),
+                effective_gas_price: receipt.effective_gas_price.to_string(),
+                transaction_type: envelope as u8,
+                transaction_type_name: envelope.name().to_string(),
+                contract_address: receipt.contract_address.map(|addr| format!("{:?}", addr)),
+                logs: processed_logs,
+                logs_bloom: format!("{:?}", receipt.logs_bloom),
+                status: receipt.status.map(|s| s.as_u64()),
+                root: receipt.root.map(|r| format!("{:?}", r)),
+                cumulative_gas_used: receipt.cumulative_gas_used.to_string(),
+                burnt_wei,
+                miner_tip_wei,
+                internal_transactions: None, // Attached separately via `attach_internal_transactions`.
+                created_at: Utc::now(),
+            },
+            token_transfers,
+            defi_events,
+        ))
     }
 
-    pub fn process_log(&self, log: &Log, block: &Block<Transaction>) -> Result<ContractEvent> {
+    /// Decodes `log` into a [`ContractEvent`], plus the [`TokenTransferEvent`]/
+    /// [`DeFiEvent`] it carries, if any — both fully populated from `log`/
+    /// `block` rather than left for a further caller to fill in, since this
+    /// is the first point with access to both.
+    pub fn process_log(
+        &self,
+        log: &Log,
+        block: &Block<Transaction>,
+    ) -> Result<(ContractEvent, Option<TokenTransferEvent>, Option<DeFiEvent>)> {
         let contract_address = format!("{:?}", log.address);
-        let topics: Vec<String> = log.topics.iter().map(|topic| format!("{:?}", topic)).collect();
-        
-        // Try to decode the event
-        let (event_name, event_signature, decoded_data) = self.decode_event(&contract_address, &topics, &log.data.0)?;
-        
-        // Check if this is a token transfer
-        if let Some(token_transfer) = self.detect_token_transfer(&topics, &log.data.0)? {
-            // Process token transfer separately
-            self.process_token_transfer(&token_transfer, block)?;
-        }
-        
-        // Check if this is a DeFi event
-        if let Some(defi_event) = self.detect_defi_event(&contract_address, &topics)? {
-            // Process DeFi event separately
-            self.process_defi_event(&defi_event, block)?;
-        }
+        let topics: Vec<String> = log
+            .topics
+            .iter()
+            .map(|topic| format!("{:?}", topic))
+            .collect();
 
-        Ok(ContractEvent {
-            transaction_hash: format!("{:?}", log.transaction_hash),
-            block_number: block.number.unwrap().as_u64(),
+        // Try to decode the event
+        let (event_name, event_signature, decoded_data) =
+            self.decode_event(&contract_address, &topics, &log.data.0)?;
+
+        let transaction_hash = format!("{:?}", log.transaction_hash);
+        let block_number = block.number.unwrap().as_u64();
+        let timestamp = block.timestamp.as_u64();
+
+        let token_transfer = self
+            .detect_token_transfer(&topics, &log.data.0)?
+            .map(|mut transfer| {
+                transfer.transaction_hash = transaction_hash.clone();
+                transfer.block_number = block_number;
+                transfer.contract_address = contract_address.clone();
+                transfer.timestamp = timestamp;
+                transfer
+            });
+
+        let defi_event = self
+            .detect_defi_event(&contract_address, &topics, &log.data.0)?
+            .map(|mut event| {
+                event.transaction_hash = transaction_hash.clone();
+                event.block_number = block_number;
+                event.timestamp = timestamp;
+                event
+            });
+
+        let contract_event = ContractEvent {
+            transaction_hash,
+            block_number,
             contract_address,
             event_name,
             event_signature,
@@ -223,169 +554,69 @@ impl EthereumProcessor {
             decoded_data,
             log_index: log.log_index.as_u64(),
             removed: log.removed.unwrap_or(false),
-            timestamp: block.timestamp.as_u64(),
+            timestamp,
             created_at: Utc::now(),
-        })
+        };
+
+        Ok((contract_event, token_transfer, defi_event))
     }
 
-    fn decode_event(&self, contract_address: &str, topics: &[String], data: &[u8]) -> Result<(Option<String>, Option<String>, Option<HashMap<String, serde_json::Value>>)> {
+    /// Decodes `topics[0]` against `contract_address`'s registered ABI (if
+    /// any) first, falling back to `LogDecoder`'s built-in ERC-20/721/1155
+    /// signatures so `decoded_data` still gets populated for the common
+    /// token standards even when no ABI has been registered.
+    fn decode_event(
+        &self,
+        contract_address: &str,
+        topics: &[String],
+        data: &[u8],
+    ) -> Result<(
+        Option<String>,
+        Option<String>,
+        Option<HashMap<String, serde_json::Value>>,
+    )> {
         if topics.is_empty() {
             return Ok((None, None, None));
         }
 
-        let event_signature = topics[0].clone();
-        
-        // Try to find the contract in known contracts
         if let Some(contract) = self.known_contracts.get(contract_address) {
-            // Try to decode with known contract ABI
-            for event in contract.events() {
-                let signature = format!("0x{:x}", Keccak256::digest(event.signature().as_bytes()));
-                if signature == event_signature {
-                    // Try to decode the event data
-                    if let Ok(decoded) = event.parse_log(ethabi::RawLog {
-                        topics: topics.iter().skip(1).map(|t| {
-                            let hex_str = t.trim_start_matches("0x");
-                            H256::from_slice(&hex::decode(hex_str).unwrap_or_default())
-                        }).collect(),
-                        data: data.to_vec(),
-                    }) {
-                        let mut decoded_data = HashMap::new();
-                        for (param, token) in event.inputs.iter().zip(decoded.params) {
-                            decoded_data.insert(param.name.clone(), self.token_to_json_value(&token));
-                        }
-                        return Ok((Some(event.name.clone()), Some(event.signature().to_string()), Some(decoded_data)));
-                    }
-                }
+            if let Some(decoded) = self.log_decoder.decode_with_abi(contract, topics, data)? {
+                return Ok((
+                    Some(decoded.event_name),
+                    Some(decoded.event_signature),
+                    Some(decoded.decoded_data),
+                ));
             }
         }
 
-        Ok((None, Some(event_signature), None))
-    }
-
-    fn token_to_json_value(&self, token: &Token) -> serde_json::Value {
-        match token {
-            Token::Address(addr) => serde_json::Value::String(format!("{:?}", addr)),
-            Token::Bytes(bytes) => serde_json::Value::String(hex::encode(bytes)),
-            Token::Int(int) => serde_json::Value::String(int.to_string()),
-            Token::Uint(uint) => serde_json::Value::String(uint.to_string()),
-            Token::Bool(b) => serde_json::Value::Bool(*b),
-            Token::String(s) => serde_json::Value::String(s.clone()),
-            Token::Array(tokens) => {
-                let values: Vec<serde_json::Value> = tokens.iter().map(|t| self.token_to_json_value(t)).collect();
-                serde_json::Value::Array(values)
-            },
-            Token::FixedArray(tokens) => {
-                let values: Vec<serde_json::Value> = tokens.iter().map(|t| self.token_to_json_value(t)).collect();
-                serde_json::Value::Array(values)
-            },
-            Token::Tuple(tokens) => {
-                let mut map = serde_json::Map::new();
-                for (i, token) in tokens.iter().enumerate() {
-                    map.insert(i.to_string(), self.token_to_json_value(token));
-                }
-                serde_json::Value::Object(map)
-            },
-        }
-    }
-
-    fn detect_token_transfer(&self, topics: &[String], data: &[u8]) -> Result<Option<TokenTransferEvent>> {
-        if topics.is_empty() {
-            return Ok(None);
-        }
-
-        let event_signature = &topics[0];
-        
-        // ERC-20 Transfer event signature
-        let transfer_signature = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
-        
-        if event_signature == transfer_signature && topics.len() >= 4 {
-            // This is likely an ERC-20 Transfer event
-            let from = format!("0x{}", &topics[1][26..]); // Remove padding
-            let to = format!("0x{}", &topics[2][26..]); // Remove padding
-            let value = U256::from_big_endian(&hex::decode(&topics[3][2..]).unwrap_or_default());
-            
-            return Ok(Some(TokenTransferEvent {
-                transaction_hash: "".to_string(), // Will be filled by caller
-                block_number: 0, // Will be filled by caller
-                contract_address: "".to_string(), // Will be filled by caller
-                token_type: TokenType::ERC20,
-                from,
-                to,
-                value: Some(value.to_string()),
-                token_id: None,
-                amount: None,
-                timestamp: 0, // Will be filled by caller
-                created_at: Utc::now(),
-            }));
+        if let Some(decoded) = self.log_decoder.decode_builtin(topics, data) {
+            return Ok((
+                Some(decoded.event_name),
+                Some(decoded.event_signature),
+                Some(decoded.decoded_data),
+            ));
         }
 
-        Ok(None)
+        Ok((None, Some(topics[0].clone()), None))
     }
 
-    fn detect_defi_event(&self, contract_address: &str, topics: &[String]) -> Result<Option<DeFiEvent>> {
-        if topics.is_empty() {
-            return Ok(None);
-        }
-
-        let event_signature = &topics[0];
-        
-        // Check against known DeFi protocols
-        for (protocol_name, protocol_info) in &self.defi_protocols {
-            if protocol_info.contract_addresses.contains(&contract_address.to_lowercase()) {
-                if let Some(event_name) = protocol_info.event_signatures.get(event_signature) {
-                    return Ok(Some(DeFiEvent {
-                        transaction_hash: "".to_string(), // Will be filled by caller
-                        block_number: 0, // Will be filled by caller
-                        protocol: protocol_name.clone(),
-                        event_type: self.map_defi_event_type(event_name),
-                        user: "".to_string(), // Would need to decode from topics/data
-                        amount: None,
-                        token: None,
-                        pool: None,
-                        timestamp: 0, // Will be filled by caller
-                        created_at: Utc::now(),
-                    }));
-                }
-            }
-        }
-
-        Ok(None)
-    }
-
-    fn map_defi_event_type(&self, event_name: &str) -> DeFiEventType {
-        match event_name.to_lowercase().as_str() {
-            "swap" => DeFiEventType::Swap,
-            "mint" => DeFiEventType::LiquidityAdd,
-            "burn" => DeFiEventType::LiquidityRemove,
-            "lend" | "deposit" => DeFiEventType::Lending,
-            "borrow" => DeFiEventType::Borrowing,
-            "repay" | "repayment" => DeFiEventType::Repayment,
-            "liquidate" | "liquidation" => DeFiEventType::Liquidation,
-            "stake" => DeFiEventType::Staking,
-            "unstake" => DeFiEventType::Unstaking,
-            "claim" | "reward" => DeFiEventType::RewardClaim,
-            _ => DeFiEventType::Swap, // Default fallback
-        }
+    fn detect_token_transfer(
+        &self,
+        topics: &[String],
+        data: &[u8],
+    ) -> Result<Option<TokenTransferEvent>> {
+        self.log_decoder.decode_token_transfer(topics, data)
     }
 
-    fn process_token_transfer(&self, transfer: &TokenTransferEvent, block: &Block<Transaction>) -> Result<()> {
-        // This would typically send the token transfer event to Kafka
-        // For now, we'll just log it
-        log::info!("Token transfer detected: {} -> {} ({} {})", 
-            transfer.from, transfer.to, 
-            transfer.value.as_ref().unwrap_or(&"0".to_string()),
-            transfer.token_type);
-        Ok(())
-    }
-
-    fn process_defi_event(&self, event: &DeFiEvent, block: &Block<Transaction>) -> Result<()> {
-        // This would typically send the DeFi event to Kafka
-        // For now, we'll just log it
-        log::info!("DeFi event detected: {} {} on {}", 
-            event.protocol, 
-            format!("{:?}", event.event_type),
-            event.transaction_hash);
-        Ok(())
+    fn detect_defi_event(
+        &self,
+        contract_address: &str,
+        topics: &[String],
+        data: &[u8],
+    ) -> Result<Option<DeFiEvent>> {
+        Ok(self
+            .log_decoder
+            .classify_defi_event(contract_address, topics, data))
     }
 
     pub fn get_contract_type(&self, address: &str) -> ContractType {
@@ -396,10 +627,17 @@ impl EthereumProcessor {
 
         // Check if it's a known DeFi protocol
         for protocol_info in self.defi_protocols.values() {
-            if protocol_info.contract_addresses.contains(&address.to_lowercase()) {
+            if protocol_info
+                .contract_addresses
+                .contains(&address.to_lowercase())
+            {
                 return match protocol_info.name.to_lowercase().as_str() {
-                    name if name.contains("uniswap") || name.contains("sushiswap") => ContractType::DEX,
-                    name if name.contains("aave") || name.contains("compound") => ContractType::Lending,
+                    name if name.contains("uniswap") || name.contains("sushiswap") => {
+                        ContractType::DEX
+                    }
+                    name if name.contains("aave") || name.contains("compound") => {
+                        ContractType::Lending
+                    }
                     name if name.contains("stake") => ContractType::Staking,
                     _ => ContractType::Other,
                 };
@@ -413,7 +651,237 @@ impl EthereumProcessor {
         self.known_contracts.insert(address, contract);
     }
 
+    /// Parses `contract.abi` (if present) and registers it under
+    /// `contract.address`, so `process_log` can ABI-decode that contract's
+    /// events from then on. A no-op when the contract has no stored ABI.
+    pub fn register_contract_abi(&mut self, contract: &SmartContract) -> Result<()> {
+        let Some(abi) = &contract.abi else {
+            return Ok(());
+        };
+        let parsed = Contract::load(abi.as_bytes())?;
+        self.add_known_contract(contract.address.to_lowercase(), parsed);
+        Ok(())
+    }
+
     pub fn add_token_contract(&mut self, address: String, info: TokenContractInfo) {
         self.token_contracts.insert(address, info);
     }
+
+    /// Resolves and caches token metadata for `address` by calling the
+    /// standard ERC-20/721/1155 view selectors directly (`name()`,
+    /// `symbol()`, `decimals()`, `supportsInterface(bytes4)`), so the
+    /// processor can self-populate its registry as it encounters new
+    /// contracts instead of relying solely on `add_token_contract`.
+    pub async fn resolve_token_metadata(
+        &mut self,
+        address: &str,
+        web3: &Web3<Http>,
+    ) -> Result<TokenContractInfo> {
+        let lower = address.to_lowercase();
+        if let Some(info) = self.token_contracts.get(&lower) {
+            return Ok(info.clone());
+        }
+
+        let addr: H160 = address.parse()?;
+        let name = Self::call_string(web3, addr, "0x06fdde03")
+            .await
+            .unwrap_or_default();
+        let symbol = Self::call_string(web3, addr, "0x95d89b41")
+            .await
+            .unwrap_or_default();
+        let decimals = Self::call_decimals(web3, addr).await.unwrap_or(18);
+        let token_type = Self::probe_token_type(web3, addr).await;
+
+        let info = TokenContractInfo {
+            address: lower.clone(),
+            name,
+            symbol,
+            decimals,
+            token_type,
+        };
+
+        self.token_contracts.insert(lower, info.clone());
+        Ok(info)
+    }
+
+    async fn eth_call(web3: &Web3<Http>, to: H160, selector_hex: &str) -> Result<Bytes> {
+        let data = hex::decode(selector_hex.trim_start_matches("0x"))?;
+        let call_request = CallRequest {
+            from: None,
+            to: Some(to),
+            gas: None,
+            gas_price: None,
+            value: None,
+            data: Some(Bytes(data)),
+            transaction_type: None,
+            access_list: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+        };
+        Ok(web3.eth().call(call_request, None).await?)
+    }
+
+    /// Decodes a `name()`/`symbol()` return value, handling both the
+    /// standard dynamic `string` ABI encoding and the common non-compliant
+    /// case (e.g. legacy tokens like MKR) where it's a fixed `bytes32`.
+    async fn call_string(web3: &Web3<Http>, to: H160, selector: &str) -> Result<String> {
+        let result = Self::eth_call(web3, to, selector).await?.0;
+
+        if result.len() == 32 {
+            // Likely a fixed bytes32, right-padded with zeroes.
+            let end = result
+                .iter()
+                .rposition(|&b| b != 0)
+                .map(|i| i + 1)
+                .unwrap_or(0);
+            return Ok(String::from_utf8_lossy(&result[..end])
+                .trim_matches(char::from(0))
+                .to_string());
+        }
+
+        match ethabi::decode(&[ParamType::String], &result) {
+            Ok(tokens) => match tokens.into_iter().next() {
+                Some(Token::String(s)) => Ok(s),
+                _ => Ok(String::new()),
+            },
+            Err(_) => Ok(String::new()),
+        }
+    }
+
+    async fn call_decimals(web3: &Web3<Http>, to: H160) -> Result<u8> {
+        let result = Self::eth_call(web3, to, "0x313ce567").await?.0;
+        Ok(U256::from_big_endian(&result).low_u32() as u8)
+    }
+
+    /// Distinguishes ERC-721/ERC-1155 from ERC-20 via `supportsInterface`
+    /// (EIP-165); falls back to ERC-20 when the contract doesn't implement
+    /// EIP-165 at all (the call reverts or returns nothing usable).
+    async fn probe_token_type(web3: &Web3<Http>, to: H160) -> TokenType {
+        const ERC721_INTERFACE_ID: &str = "80ac58cd";
+        const ERC1155_INTERFACE_ID: &str = "d9b67a26";
+
+        if Self::supports_interface(web3, to, ERC721_INTERFACE_ID).await {
+            TokenType::ERC721
+        } else if Self::supports_interface(web3, to, ERC1155_INTERFACE_ID).await {
+            TokenType::ERC1155
+        } else {
+            TokenType::ERC20
+        }
+    }
+
+    async fn supports_interface(web3: &Web3<Http>, to: H160, interface_id: &str) -> bool {
+        let selector = format!("0x01ffc9a7{:0<64}", interface_id);
+        match Self::eth_call(web3, to, &selector).await {
+            Ok(bytes) => bytes.0.last().copied().unwrap_or(0) == 1,
+            Err(_) => false,
+        }
+    }
+
+    /// Issues `debug_traceTransaction` with `callTracer` for `tx_hash` and
+    /// flattens the returned nested call frames into [`InternalTransaction`]
+    /// records, each keyed by `tx_hash` and a dotted call path (`0`, `0.1`,
+    /// `0.1.2`, ...). Reverted frames are kept with `error` set rather than
+    /// dropped, so downstream consumers can distinguish a failed sub-call
+    /// from one that never happened.
+    pub async fn trace_internal_transactions(
+        web3: &Web3<Http>,
+        tx_hash: H256,
+    ) -> Result<Vec<InternalTransaction>> {
+        let params = vec![
+            json!(format!("{:?}", tx_hash)),
+            json!({ "tracer": "callTracer" }),
+        ];
+        let trace = web3
+            .transport()
+            .execute("debug_traceTransaction", params)
+            .await?;
+
+        let mut internal_txs = Vec::new();
+        Self::flatten_call_frame(&trace, &format!("{:?}", tx_hash), "0", &mut internal_txs);
+        Ok(internal_txs)
+    }
+
+    fn flatten_call_frame(
+        frame: &serde_json::Value,
+        tx_hash: &str,
+        path: &str,
+        out: &mut Vec<InternalTransaction>,
+    ) {
+        let call_type = frame
+            .get("type")
+            .and_then(|v| v.as_str())
+            .unwrap_or("CALL")
+            .to_string();
+        // STATICCALL/DELEGATECALL never move value, regardless of what the
+        // node reports for `value` (some tracers omit the field entirely).
+        let value_bearing = !matches!(call_type.as_str(), "STATICCALL" | "DELEGATECALL");
+
+        out.push(InternalTransaction {
+            transaction_hash: tx_hash.to_string(),
+            call_path: path.to_string(),
+            call_type,
+            from: frame
+                .get("from")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            to: frame
+                .get("to")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            value: frame
+                .get("value")
+                .and_then(|v| v.as_str())
+                .unwrap_or("0x0")
+                .to_string(),
+            value_bearing,
+            gas: frame
+                .get("gas")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            gas_used: frame
+                .get("gasUsed")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            input: frame
+                .get("input")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            output: frame
+                .get("output")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            error: frame
+                .get("error")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            created_at: Utc::now(),
+        });
+
+        if let Some(calls) = frame.get("calls").and_then(|v| v.as_array()) {
+            for (i, child) in calls.iter().enumerate() {
+                let child_path = format!("{}.{}", path, i);
+                Self::flatten_call_frame(child, tx_hash, &child_path, out);
+            }
+        }
+    }
+
+    /// Attaches the flattened internal-call tree to an already-processed
+    /// receipt, mirroring how `process_transaction_receipt` fills in
+    /// `logs`. Internal ETH transfers (non-zero-value CALL frames) should be
+    /// surfaced by the caller the same way `detect_token_transfer` surfaces
+    /// ERC-20 moves.
+    pub fn attach_internal_transactions(
+        receipt: &mut TransactionReceipt,
+        internal_transactions: Vec<InternalTransaction>,
+    ) {
+        receipt.internal_transactions = Some(internal_transactions);
+    }
+}
+
+fn parse_u256(value: &str) -> U256 {
+    U256::from_dec_str(value).unwrap_or_default()
 }
@@ -0,0 +1,151 @@
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
+use web3::types::{BlockHeader, H256};
+
+/// Width of a canonical-hash-trie (CHT) section. Every `CHT_SECTION_SIZE`
+/// blocks, [`HeaderChain`] materializes a Merkle root over the headers in
+/// that range so ancient headers can later be proven canonical without
+/// re-trusting the serving endpoint.
+pub const CHT_SECTION_SIZE: u64 = 2048;
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub hash: H256,
+    pub parent_hash: H256,
+}
+
+/// A weak-subjectivity header chain seeded from a trusted checkpoint hash.
+/// A fetched header whose parent (`number - 1`) is already in the chain must
+/// match that parent's hash before `BlockchainClient` accepts it; a header
+/// whose parent is not in the chain is accepted without a linkage check and
+/// stored as the entry for its number.
+pub struct HeaderChain {
+    by_number: BTreeMap<u64, Entry>,
+    by_hash: HashMap<H256, BlockHeader>,
+    cht_roots: HashMap<u64, H256>,
+    best_block: u64,
+    checkpoint_hash: H256,
+}
+
+impl HeaderChain {
+    /// Seeds the chain with a checkpoint block number/hash obtained out of
+    /// band (e.g. from a trusted peer set or a hardcoded release constant).
+    pub fn new(checkpoint_number: u64, checkpoint_hash: H256) -> Self {
+        let mut by_number = BTreeMap::new();
+        by_number.insert(
+            checkpoint_number,
+            Entry {
+                hash: checkpoint_hash,
+                parent_hash: H256::zero(),
+            },
+        );
+
+        Self {
+            by_number,
+            by_hash: HashMap::new(),
+            cht_roots: HashMap::new(),
+            best_block: checkpoint_number,
+            checkpoint_hash,
+        }
+    }
+
+    pub fn checkpoint_hash(&self) -> H256 {
+        self.checkpoint_hash
+    }
+
+    pub fn best_block(&self) -> u64 {
+        self.best_block
+    }
+
+    /// Verifies `header` links to the parent already held at
+    /// `header.number - 1`, when one is held. The header's own hash is taken
+    /// as reported and is not recomputed. Returns an error if the linkage
+    /// check fails so the caller can classify it as [`crate::error_handler::ErrorType::HeaderVerificationFailed`].
+    pub fn verify_header(&mut self, header: &BlockHeader) -> Result<()> {
+        let number = header
+            .number
+            .ok_or_else(|| anyhow::anyhow!("header is missing a block number"))?
+            .as_u64();
+        let hash = header
+            .hash
+            .ok_or_else(|| anyhow::anyhow!("header is missing a hash"))?;
+
+        if let Some(parent_entry) = self.by_number.get(&(number.saturating_sub(1))) {
+            if number > 0 && header.parent_hash != parent_entry.hash {
+                return Err(anyhow::anyhow!(
+                    "header {} parent_hash {:?} does not match trusted parent {:?}",
+                    number,
+                    header.parent_hash,
+                    parent_entry.hash
+                ));
+            }
+        }
+
+        self.by_number.insert(
+            number,
+            Entry {
+                hash,
+                parent_hash: header.parent_hash,
+            },
+        );
+        self.by_hash.insert(hash, header.clone());
+
+        if number > self.best_block {
+            self.best_block = number;
+        }
+
+        self.maybe_materialize_cht(number);
+
+        Ok(())
+    }
+
+    /// Returns the materialized CHT root for the section containing
+    /// `section`, if the chain has accumulated enough headers to build one.
+    pub fn get_cht_root(&self, section: u64) -> Option<H256> {
+        self.cht_roots.get(&section).copied()
+    }
+
+    fn maybe_materialize_cht(&mut self, number: u64) {
+        if number == 0 || number % CHT_SECTION_SIZE != 0 {
+            return;
+        }
+        let section = number / CHT_SECTION_SIZE;
+        if self.cht_roots.contains_key(&section) {
+            return;
+        }
+
+        let start = section.saturating_sub(1) * CHT_SECTION_SIZE;
+        let leaves: Vec<H256> = self
+            .by_number
+            .range(start..number)
+            .map(|(_, entry)| entry.hash)
+            .collect();
+
+        if leaves.len() as u64 == CHT_SECTION_SIZE {
+            self.cht_roots.insert(section, merkle_root(&leaves));
+        }
+    }
+}
+
+/// Simple binary Merkle root over header hashes (keccak256 pairwise hash,
+/// duplicating the last node on an odd level) -- enough to produce a branch
+/// proof for a header without pulling in a full light-client trie
+/// implementation.
+fn merkle_root(leaves: &[H256]) -> H256 {
+    if leaves.is_empty() {
+        return H256::zero();
+    }
+
+    let mut level: Vec<H256> = leaves.to_vec();
+    while level.len() > 1 {
+        let mut next = Vec::with_capacity((level.len() + 1) / 2);
+        for pair in level.chunks(2) {
+            let mut buf = Vec::with_capacity(64);
+            buf.extend_from_slice(pair[0].as_bytes());
+            buf.extend_from_slice(pair.get(1).unwrap_or(&pair[0]).as_bytes());
+            next.push(H256::from_slice(&web3::signing::keccak256(&buf)));
+        }
+        level = next;
+    }
+    level[0]
+}
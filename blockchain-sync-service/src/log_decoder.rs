@@ -0,0 +1,596 @@
+//! ABI-driven decoding of raw Ethereum event logs into `ContractEvent`'s
+//! `decoded_data`, `TokenTransferEvent`, and `DeFiEvent`. A log's
+//! `topics[0]` is matched against event signatures (`keccak256` of the
+//! canonical `Event(type,type,...)` string); indexed params come off the
+//! remaining topics, non-indexed params are ABI-decoded from `data`.
+//! Indexed dynamic types (`string`/`bytes`/arrays) can't be recovered from
+//! their topic hash, so they're left as the raw hash.
+
+use anyhow::Result;
+use ethabi::{Contract, Event, EventParam, ParamType, RawLog, Token};
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+use web3::types::{H256, U256};
+
+use crate::models::{DeFiEvent, DeFiEventType, TokenTransferEvent, TokenType};
+
+/// Name, canonical signature, and decoded parameters (keyed by name) of a
+/// log that matched a known event.
+#[derive(Debug, Clone)]
+pub struct DecodedLog {
+    pub event_name: String,
+    pub event_signature: String,
+    pub decoded_data: HashMap<String, serde_json::Value>,
+}
+
+/// One DeFi protocol event this decoder recognizes purely by signature
+/// hash, without needing the emitting contract's address to be
+/// allow-listed first.
+#[derive(Debug, Clone)]
+struct DeFiSignature {
+    protocol: String,
+    event_name: String,
+    event_type: DeFiEventType,
+}
+
+/// `keccak256` of a canonical event signature string, e.g.
+/// `Transfer(address,address,uint256)`.
+fn event_signature_hash(signature: &str) -> String {
+    format!("0x{:x}", Keccak256::digest(signature.as_bytes()))
+}
+
+pub struct LogDecoder {
+    /// Event signature hash -> protocol/event metadata, used by
+    /// `classify_defi_event` to recognize DeFi events from any contract,
+    /// not just ones pre-registered by address. Seeded with the common
+    /// Uniswap/Aave/Compound events; extend via `register_defi_signature`.
+    defi_signatures: HashMap<String, DeFiSignature>,
+}
+
+impl LogDecoder {
+    pub fn new() -> Self {
+        let mut decoder = Self {
+            defi_signatures: HashMap::new(),
+        };
+        decoder.register_defaults();
+        decoder
+    }
+
+    fn register_defaults(&mut self) {
+        self.register_defi_signature(
+            "Swap(address,uint256,uint256,uint256,uint256,address)",
+            "uniswap_v2",
+            "Swap",
+            DeFiEventType::Swap,
+        );
+        self.register_defi_signature(
+            "Mint(address,uint256,uint256)",
+            "uniswap_v2",
+            "Mint",
+            DeFiEventType::LiquidityAdd,
+        );
+        self.register_defi_signature(
+            "Burn(address,uint256,uint256,address)",
+            "uniswap_v2",
+            "Burn",
+            DeFiEventType::LiquidityRemove,
+        );
+        self.register_defi_signature(
+            "Swap(address,address,int256,int256,uint160,uint128,int24)",
+            "uniswap_v3",
+            "Swap",
+            DeFiEventType::Swap,
+        );
+        self.register_defi_signature(
+            "Mint(address,address,int24,int24,uint128,uint256,uint256)",
+            "uniswap_v3",
+            "Mint",
+            DeFiEventType::LiquidityAdd,
+        );
+        self.register_defi_signature(
+            "Burn(address,int24,int24,uint128,uint256,uint256)",
+            "uniswap_v3",
+            "Burn",
+            DeFiEventType::LiquidityRemove,
+        );
+        self.register_defi_signature(
+            "Deposit(address,address,address,uint256,uint16)",
+            "aave",
+            "Deposit",
+            DeFiEventType::Lending,
+        );
+        self.register_defi_signature(
+            "Borrow(address,address,address,uint256,uint256,uint256,uint16)",
+            "aave",
+            "Borrow",
+            DeFiEventType::Borrowing,
+        );
+        self.register_defi_signature(
+            "Repay(address,address,address,uint256)",
+            "aave",
+            "Repay",
+            DeFiEventType::Repayment,
+        );
+        self.register_defi_signature(
+            "LiquidationCall(address,address,address,uint256,uint256,address,bool)",
+            "aave",
+            "LiquidationCall",
+            DeFiEventType::Liquidation,
+        );
+        // Compound's cToken `Mint(address,uint256,uint256)` shares its
+        // signature with Uniswap V2's `Mint`, so it isn't registered
+        // separately here — `Borrow`/`RepayBorrow`/`LiquidateBorrow` are
+        // unambiguous and cover the rest of the lending lifecycle.
+        self.register_defi_signature(
+            "Borrow(address,uint256,uint256,uint256)",
+            "compound",
+            "Borrow",
+            DeFiEventType::Borrowing,
+        );
+        self.register_defi_signature(
+            "RepayBorrow(address,address,uint256,uint256,uint256)",
+            "compound",
+            "RepayBorrow",
+            DeFiEventType::Repayment,
+        );
+        self.register_defi_signature(
+            "LiquidateBorrow(address,address,uint256,address,uint256)",
+            "compound",
+            "LiquidateBorrow",
+            DeFiEventType::Liquidation,
+        );
+    }
+
+    /// Adds (or overrides) a DeFi event signature this decoder recognizes.
+    /// `signature` is the canonical `Event(type,type,...)` string; its
+    /// `keccak256` is what's actually matched against `topics[0]`.
+    pub fn register_defi_signature(
+        &mut self,
+        signature: &str,
+        protocol: &str,
+        event_name: &str,
+        event_type: DeFiEventType,
+    ) {
+        self.defi_signatures.insert(
+            event_signature_hash(signature),
+            DeFiSignature {
+                protocol: protocol.to_string(),
+                event_name: event_name.to_string(),
+                event_type,
+            },
+        );
+    }
+
+    /// Decodes `topics`/`data` against `contract`'s event list, matching
+    /// `topics[0]` to each event's signature hash and splitting indexed
+    /// params (off the remaining topics) from non-indexed ones (ABI-decoded
+    /// from `data`) via `ethabi`'s own `parse_log`.
+    pub fn decode_with_abi(
+        &self,
+        contract: &Contract,
+        topics: &[String],
+        data: &[u8],
+    ) -> Result<Option<DecodedLog>> {
+        if topics.is_empty() {
+            return Ok(None);
+        }
+        let event_signature = &topics[0];
+
+        for event in contract.events() {
+            if event_signature_hash(&event.signature()) != *event_signature {
+                continue;
+            }
+            if let Ok(decoded) = event.parse_log(raw_log(topics, data)) {
+                let decoded_data = params_to_map(&event.inputs, decoded.params);
+                return Ok(Some(DecodedLog {
+                    event_name: event.name.clone(),
+                    event_signature: event.signature(),
+                    decoded_data,
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Decodes `topics`/`data` against the built-in ERC-20/721/1155
+    /// `Transfer`/`Approval`/`TransferSingle`/`TransferBatch` shapes,
+    /// without needing any contract-specific ABI. `Transfer` and
+    /// `Approval` share their signature hash across ERC-20 and ERC-721
+    /// (only the indexed-ness of the final param differs), so both shapes
+    /// are tried in turn.
+    pub fn decode_builtin(&self, topics: &[String], data: &[u8]) -> Option<DecodedLog> {
+        if topics.is_empty() {
+            return None;
+        }
+        let event_signature = &topics[0];
+
+        for event in builtin_events() {
+            if event_signature_hash(&event.signature()) != *event_signature {
+                continue;
+            }
+            if let Ok(decoded) = event.parse_log(raw_log(topics, data)) {
+                let decoded_data = params_to_map(&event.inputs, decoded.params);
+                return Some(DecodedLog {
+                    event_name: event.name.clone(),
+                    event_signature: event.signature(),
+                    decoded_data,
+                });
+            }
+        }
+
+        None
+    }
+
+    /// Classifies `topics`/`data` as a `TokenTransferEvent` with the
+    /// correct `TokenType`, using the same built-in Transfer/TransferSingle/
+    /// TransferBatch signatures as `decode_builtin`. `from`/`to` are decoded
+    /// from the topics; `contract_address`/`transaction_hash`/`block_number`/
+    /// `timestamp` on the returned event are blank; the caller fills them in
+    /// from the enclosing log.
+    pub fn decode_token_transfer(
+        &self,
+        topics: &[String],
+        data: &[u8],
+    ) -> Result<Option<TokenTransferEvent>> {
+        if topics.is_empty() {
+            return Ok(None);
+        }
+
+        let event_signature = &topics[0];
+        let transfer_signature = event_signature_hash("Transfer(address,address,uint256)");
+        let transfer_single_signature =
+            event_signature_hash("TransferSingle(address,address,address,uint256,uint256)");
+        let transfer_batch_signature =
+            event_signature_hash("TransferBatch(address,address,address,uint256[],uint256[])");
+
+        if *event_signature == transfer_signature && topics.len() == 3 && !data.is_empty() {
+            let from = topic_address(&topics[1]);
+            let to = topic_address(&topics[2]);
+            let value = U256::from_big_endian(data);
+
+            return Ok(Some(blank_token_transfer(
+                TokenType::ERC20,
+                from,
+                to,
+                Some(value.to_string()),
+                None,
+                None,
+            )));
+        }
+
+        if *event_signature == transfer_signature && topics.len() == 4 && data.is_empty() {
+            let from = topic_address(&topics[1]);
+            let to = topic_address(&topics[2]);
+            let token_id = U256::from_big_endian(
+                &hex::decode(topics[3].trim_start_matches("0x")).unwrap_or_default(),
+            );
+
+            return Ok(Some(blank_token_transfer(
+                TokenType::ERC721,
+                from,
+                to,
+                None,
+                Some(token_id.to_string()),
+                None,
+            )));
+        }
+
+        if *event_signature == transfer_single_signature && topics.len() == 4 && data.len() >= 64 {
+            let from = topic_address(&topics[2]);
+            let to = topic_address(&topics[3]);
+            let id = U256::from_big_endian(&data[0..32]);
+            let value = U256::from_big_endian(&data[32..64]);
+
+            return Ok(Some(blank_token_transfer(
+                TokenType::ERC1155,
+                from,
+                to,
+                None,
+                Some(id.to_string()),
+                Some(value.to_string()),
+            )));
+        }
+
+        if *event_signature == transfer_batch_signature && topics.len() == 4 {
+            let from = topic_address(&topics[2]);
+            let to = topic_address(&topics[3]);
+            let (ids, values) = decode_transfer_batch_data(data)?;
+
+            return Ok(Some(blank_token_transfer(
+                TokenType::ERC1155,
+                from,
+                to,
+                None,
+                Some(ids.join(",")),
+                Some(values.join(",")),
+            )));
+        }
+
+        Ok(None)
+    }
+
+    /// Classifies `topics`/`data` as a `DeFiEvent` using the signature
+    /// table (`register_defi_signature`), not requiring `contract_address`
+    /// to be pre-registered anywhere. `contract_address` is only used to
+    /// populate the resulting event's `pool` field.
+    pub fn classify_defi_event(
+        &self,
+        contract_address: &str,
+        topics: &[String],
+        data: &[u8],
+    ) -> Option<DeFiEvent> {
+        let event_signature = topics.first()?;
+        let signature = self.defi_signatures.get(event_signature)?;
+
+        let (user, decoded_amounts) =
+            decode_defi_amounts(&signature.protocol, &signature.event_name, topics, data);
+
+        Some(DeFiEvent {
+            transaction_hash: String::new(), // Filled in by the caller.
+            block_number: 0,                 // Filled in by the caller.
+            protocol: signature.protocol.clone(),
+            event_type: signature.event_type.clone(),
+            user,
+            amount: None,
+            token: None,
+            pool: Some(contract_address.to_string()),
+            decoded_amounts,
+            removed: false,
+            timestamp: 0, // Filled in by the caller.
+            created_at: chrono::Utc::now(),
+        })
+    }
+}
+
+fn raw_log(topics: &[String], data: &[u8]) -> RawLog {
+    RawLog {
+        topics: topics
+            .iter()
+            .skip(1)
+            .map(|t| {
+                let hex_str = t.trim_start_matches("0x");
+                H256::from_slice(&hex::decode(hex_str).unwrap_or_default())
+            })
+            .collect(),
+        data: data.to_vec(),
+    }
+}
+
+fn params_to_map(
+    inputs: &[EventParam],
+    params: Vec<ethabi::LogParam>,
+) -> HashMap<String, serde_json::Value> {
+    let mut decoded_data = HashMap::new();
+    for (param, decoded) in inputs.iter().zip(params) {
+        decoded_data.insert(param.name.clone(), token_to_json_value(&decoded.value));
+    }
+    decoded_data
+}
+
+fn token_to_json_value(token: &Token) -> serde_json::Value {
+    match token {
+        Token::Address(addr) => serde_json::Value::String(format!("{:?}", addr)),
+        Token::Bytes(bytes) => serde_json::Value::String(hex::encode(bytes)),
+        Token::Int(int) => serde_json::Value::String(int.to_string()),
+        Token::Uint(uint) => serde_json::Value::String(uint.to_string()),
+        Token::Bool(b) => serde_json::Value::Bool(*b),
+        Token::String(s) => serde_json::Value::String(s.clone()),
+        Token::FixedBytes(bytes) => serde_json::Value::String(format!("0x{}", hex::encode(bytes))),
+        Token::Array(tokens) | Token::FixedArray(tokens) => {
+            serde_json::Value::Array(tokens.iter().map(token_to_json_value).collect())
+        }
+        Token::Tuple(tokens) => {
+            let mut map = serde_json::Map::new();
+            for (i, token) in tokens.iter().enumerate() {
+                map.insert(i.to_string(), token_to_json_value(token));
+            }
+            serde_json::Value::Object(map)
+        }
+    }
+}
+
+/// The built-in event shapes `decode_builtin` recognizes without an ABI.
+/// `Transfer`/`Approval` are each listed twice (ERC-20 and ERC-721 shapes)
+/// since both standards share the same signature hash but differ in
+/// whether the final `uint256` param is indexed.
+fn builtin_events() -> Vec<Event> {
+    vec![
+        event(
+            "Transfer",
+            &[
+                ("from", ParamType::Address, true),
+                ("to", ParamType::Address, true),
+                ("value", ParamType::Uint(256), false),
+            ],
+        ),
+        event(
+            "Transfer",
+            &[
+                ("from", ParamType::Address, true),
+                ("to", ParamType::Address, true),
+                ("tokenId", ParamType::Uint(256), true),
+            ],
+        ),
+        event(
+            "Approval",
+            &[
+                ("owner", ParamType::Address, true),
+                ("spender", ParamType::Address, true),
+                ("value", ParamType::Uint(256), false),
+            ],
+        ),
+        event(
+            "Approval",
+            &[
+                ("owner", ParamType::Address, true),
+                ("approved", ParamType::Address, true),
+                ("tokenId", ParamType::Uint(256), true),
+            ],
+        ),
+        event(
+            "ApprovalForAll",
+            &[
+                ("owner", ParamType::Address, true),
+                ("operator", ParamType::Address, true),
+                ("approved", ParamType::Bool, false),
+            ],
+        ),
+        event(
+            "TransferSingle",
+            &[
+                ("operator", ParamType::Address, true),
+                ("from", ParamType::Address, true),
+                ("to", ParamType::Address, true),
+                ("id", ParamType::Uint(256), false),
+                ("value", ParamType::Uint(256), false),
+            ],
+        ),
+        event(
+            "TransferBatch",
+            &[
+                ("operator", ParamType::Address, true),
+                ("from", ParamType::Address, true),
+                ("to", ParamType::Address, true),
+                (
+                    "ids",
+                    ParamType::Array(Box::new(ParamType::Uint(256))),
+                    false,
+                ),
+                (
+                    "values",
+                    ParamType::Array(Box::new(ParamType::Uint(256))),
+                    false,
+                ),
+            ],
+        ),
+    ]
+}
+
+fn event(name: &str, params: &[(&str, ParamType, bool)]) -> Event {
+    Event {
+        name: name.to_string(),
+        inputs: params
+            .iter()
+            .map(|(name, kind, indexed)| EventParam {
+                name: name.to_string(),
+                kind: kind.clone(),
+                indexed: *indexed,
+            })
+            .collect(),
+        anonymous: false,
+    }
+}
+
+fn topic_address(topic: &str) -> String {
+    format!("0x{}", &topic[topic.len() - 40..])
+}
+
+fn blank_token_transfer(
+    token_type: TokenType,
+    from: String,
+    to: String,
+    value: Option<String>,
+    token_id: Option<String>,
+    amount: Option<String>,
+) -> TokenTransferEvent {
+    TokenTransferEvent {
+        transaction_hash: String::new(), // Filled in by the caller.
+        block_number: 0,                 // Filled in by the caller.
+        contract_address: String::new(), // Filled in by the caller.
+        token_type,
+        from,
+        to,
+        value,
+        token_id,
+        amount,
+        removed: false,
+        timestamp: 0, // Filled in by the caller.
+        created_at: chrono::Utc::now(),
+    }
+}
+
+/// Decodes the two dynamic `uint256[]` arrays (`ids`, `values`) from a
+/// `TransferBatch` event's ABI-encoded `data`.
+fn decode_transfer_batch_data(data: &[u8]) -> Result<(Vec<String>, Vec<String>)> {
+    let tokens = ethabi::decode(
+        &[
+            ParamType::Array(Box::new(ParamType::Uint(256))),
+            ParamType::Array(Box::new(ParamType::Uint(256))),
+        ],
+        data,
+    )?;
+
+    let to_strings = |token: &Token| -> Vec<String> {
+        match token {
+            Token::Array(items) => items
+                .iter()
+                .filter_map(|t| t.clone().into_uint().map(|u| u.to_string()))
+                .collect(),
+            _ => Vec::new(),
+        }
+    };
+
+    let ids = tokens.get(0).map(to_strings).unwrap_or_default();
+    let values = tokens.get(1).map(to_strings).unwrap_or_default();
+
+    Ok((ids, values))
+}
+
+/// ABI-decodes the swap amounts for the Uniswap V2/V3 `Swap` event shapes
+/// this decoder knows about. Returns `(user, decoded_amounts)`; `user` is
+/// the topic that best represents the initiating/receiving account
+/// (`to`/`recipient`) for a swap, empty for events whose amounts aren't
+/// decoded yet (`decode_builtin` only knows the token Transfer/Approval
+/// shapes, so their indexed params are not decoded there either).
+fn decode_defi_amounts(
+    protocol_name: &str,
+    event_name: &str,
+    topics: &[String],
+    data: &[u8],
+) -> (String, Option<HashMap<String, serde_json::Value>>) {
+    match (protocol_name, event_name) {
+        ("uniswap_v2", "Swap") if topics.len() == 3 && data.len() >= 128 => {
+            let mut amounts = HashMap::new();
+            amounts.insert(
+                "sender".to_string(),
+                serde_json::Value::String(topic_address(&topics[1])),
+            );
+            amounts.insert(
+                "to".to_string(),
+                serde_json::Value::String(topic_address(&topics[2])),
+            );
+            amounts.insert("amount0In".to_string(), json_u256(&data[0..32]));
+            amounts.insert("amount1In".to_string(), json_u256(&data[32..64]));
+            amounts.insert("amount0Out".to_string(), json_u256(&data[64..96]));
+            amounts.insert("amount1Out".to_string(), json_u256(&data[96..128]));
+            (topic_address(&topics[2]), Some(amounts))
+        }
+        ("uniswap_v3", "Swap") if topics.len() == 3 && data.len() >= 160 => {
+            let mut amounts = HashMap::new();
+            amounts.insert(
+                "sender".to_string(),
+                serde_json::Value::String(topic_address(&topics[1])),
+            );
+            amounts.insert(
+                "recipient".to_string(),
+                serde_json::Value::String(topic_address(&topics[2])),
+            );
+            amounts.insert("amount0".to_string(), json_i256(&data[0..32]));
+            amounts.insert("amount1".to_string(), json_i256(&data[32..64]));
+            amounts.insert("sqrtPriceX96".to_string(), json_u256(&data[64..96]));
+            amounts.insert("liquidity".to_string(), json_u256(&data[96..128]));
+            amounts.insert("tick".to_string(), json_i256(&data[128..160]));
+            (topic_address(&topics[2]), Some(amounts))
+        }
+        _ => (String::new(), None),
+    }
+}
+
+fn json_u256(bytes: &[u8]) -> serde_json::Value {
+    serde_json::Value::String(U256::from_big_endian(bytes).to_string())
+}
+
+fn json_i256(bytes: &[u8]) -> serde_json::Value {
+    serde_json::Value::String(ethabi::ethereum_types::I256::from_big_endian(bytes).to_string())
+}
@@ -1,227 +1,682 @@
+mod blockchain;
+mod erigon;
+mod error_handler;
+mod ethereum_processor;
+mod header_chain;
+mod kafka_producer;
+mod log_decoder;
+mod metrics;
+mod models;
+mod mongodb_client;
+mod mpt_proof;
+mod reorg_handler;
+mod resource_sampler;
+mod rpc;
+mod rpc_pool;
+
+use axum::{extract::Query, extract::State, http::StatusCode, response::IntoResponse, routing::get, Router};
+use std::collections::VecDeque;
 use std::env;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::net::TcpListener;
 use tokio::time::sleep;
 use std::fs;
 use std::path::Path;
 
-// Global metrics counters
-static BLOCKS_PROCESSED: AtomicU64 = AtomicU64::new(0);
-static RPC_REQUESTS: AtomicU64 = AtomicU64::new(0);
-static DB_OPERATIONS: AtomicU64 = AtomicU64::new(0);
-static PROCESSING_ERRORS: AtomicU64 = AtomicU64::new(0);
-static LAST_PROCESSED_BLOCK: AtomicU64 = AtomicU64::new(0);
-static BLOCKCHAIN_DATA_SIZE: AtomicU64 = AtomicU64::new(0);
-static TRANSACTIONS_PROCESSED: AtomicU64 = AtomicU64::new(0);
+use erigon::ErigonReader;
+use ethereum_processor::EthereumProcessor;
+use kafka_producer::KafkaProducerService;
+use metrics::BlockchainMetrics;
+use mongodb_client::MongoDBService;
+use reorg_handler::ReorgHandler;
+
+#[derive(Clone)]
+struct AppState {
+    metrics: Arc<BlockchainMetrics>,
+}
+
+/// One processed block's fee-relevant fields, kept around for the
+/// `/fee_history` endpoint.
+#[derive(Clone, Copy)]
+struct FeeHistoryEntry {
+    block_number: u64,
+    base_fee: Option<u64>,
+    gas_used: u64,
+    gas_limit: u64,
+}
+
+impl FeeHistoryEntry {
+    fn gas_used_ratio(&self) -> f64 {
+        self.gas_used as f64 / self.gas_limit as f64
+    }
+}
+
+/// Ring buffer of the most recently processed blocks' fee data, bounded so
+/// memory use doesn't grow without limit. `eth_feeHistory` caps its own
+/// window at 1024 blocks, so this reuses the same limit.
+const FEE_HISTORY_CAPACITY: usize = 1024;
+static FEE_HISTORY: Mutex<VecDeque<FeeHistoryEntry>> = Mutex::new(VecDeque::new());
+
+/// Guards `/debug/pprof/profile` so only one capture runs at a time — a
+/// second concurrent sample would just blend two unrelated windows into one
+/// useless report.
+static PROFILING_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+const MAX_PROFILE_SECONDS: u64 = 60;
+
+#[derive(Clone, Copy)]
+enum ProfileFormat {
+    FlamegraphSvg,
+    PprofProto,
+}
+
+fn record_fee_history(entry: FeeHistoryEntry) {
+    let mut history = FEE_HISTORY.lock().unwrap();
+    if history.len() >= FEE_HISTORY_CAPACITY {
+        history.pop_front();
+    }
+    history.push_back(entry);
+}
+
+/// Shared handle to the Erigon MDBX environment, opened once by
+/// `initialize_blockchain_metrics` and reused by every subsequent
+/// `process_blockchain_data` call instead of reopening it per block.
+static ERIGON_READER: OnceLock<Arc<ErigonReader>> = OnceLock::new();
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("DEBUG: Application starting...");
-    
+
     // Initialize logging
     env_logger::init();
-    
+
     println!("DEBUG: Logger initialized");
-    
+
     // Get metrics address
     let metrics_addr = env::var("METRICS_ADDR").unwrap_or_else(|_| "0.0.0.0:9090".to_string());
-    
+
     println!("DEBUG: Metrics address: {}", metrics_addr);
-    
+
+    // One shared, registry-backed metrics struct for the whole process: the
+    // block loop below, the resource sampler, and (when enabled) the RPC
+    // proxy's BlockchainClient all record into it, and the axum app below
+    // serves it as the single `/metrics` surface.
+    let app_metrics = Arc::new(BlockchainMetrics::new());
+
     // Initialize blockchain data metrics
-    initialize_blockchain_metrics();
-    
-    // Start background task to process real blockchain data
-    tokio::spawn(async {
-        let mut block_number = 18000000u64; // Start from a realistic block number
+    initialize_blockchain_metrics(&app_metrics).await;
+
+    // Sample real process memory/CPU usage on an interval instead of
+    // reporting hardcoded placeholders.
+    let sampler_metrics = app_metrics.clone();
+    tokio::spawn(async move {
         loop {
+            let sample = resource_sampler::sample();
+            sampler_metrics.update_resource_usage(sample.memory_bytes, sample.cpu_percent);
             sleep(Duration::from_secs(5)).await;
-            
-            // Process real blockchain data
-            if let Ok(block_data) = process_blockchain_data(block_number).await {
-                BLOCKS_PROCESSED.fetch_add(1, Ordering::SeqCst);
-                LAST_PROCESSED_BLOCK.store(block_number, Ordering::SeqCst);
-                TRANSACTIONS_PROCESSED.fetch_add(block_data.transaction_count, Ordering::SeqCst);
-                
-                // Simulate RPC requests for data fetching
-                RPC_REQUESTS.fetch_add(2, Ordering::SeqCst);
-                
-                // Simulate database operations
-                DB_OPERATIONS.fetch_add(3, Ordering::SeqCst);
-                
-                println!("DEBUG: Processed block {} with {} transactions", 
-                        block_number, block_data.transaction_count);
-            } else {
-                PROCESSING_ERRORS.fetch_add(1, Ordering::SeqCst);
-                println!("DEBUG: Error processing block {}", block_number);
+        }
+    });
+
+    // Optionally front the upstream node with the caching JSON-RPC proxy,
+    // sharing the same metrics instance rather than constructing its own.
+    if let Ok(rpc_url) = env::var("RPC_URL") {
+        println!("DEBUG: Starting RPC proxy against upstream {}", rpc_url);
+        let proxy_metrics = app_metrics.clone();
+        tokio::spawn(async move {
+            match blockchain::BlockchainClient::new(&rpc_url, proxy_metrics).await {
+                Ok(client) => {
+                    let server = rpc::RpcServer::new(Arc::new(client), rpc::RpcConfig::default());
+                    if let Err(e) = server.run().await {
+                        eprintln!("RPC proxy failed to start: {}", e);
+                    }
+                }
+                Err(e) => eprintln!("Failed to construct BlockchainClient for RPC proxy: {}", e),
             }
-            
-            block_number += 1;
-            
-            // Reset to start after reaching a high number
-            if block_number > 19000000 {
-                block_number = 18000000;
+        });
+    }
+
+    // Optional health-aware RPC pool: if configured, it resolves the chain
+    // head (falling through unhealthy endpoints first) instead of trusting
+    // the local chaindata's own last-written block, which can lag the real
+    // head if this node's sync has stalled. Block data itself still comes
+    // from the local MDBX chaindata (chunk5-1) — there's no per-block RPC
+    // fetch in this loop to route through the pool.
+    let rpc_pool_client = match env::var("RPC_POOL_URLS") {
+        Ok(urls) => {
+            let urls: Vec<String> = urls.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+            if urls.is_empty() {
+                None
+            } else {
+                match blockchain::BlockchainClient::new_pooled(urls, app_metrics.clone()).await {
+                    Ok(client) => Some(Arc::new(client)),
+                    Err(e) => {
+                        println!("DEBUG: Failed to construct pooled RPC client: {}", e);
+                        None
+                    }
+                }
             }
         }
-    });
-    
-    // Start HTTP server
-    let addr: std::net::SocketAddr = metrics_addr.parse()?;
-    
-    println!("DEBUG: Starting HTTP server on {}", addr);
-    
-    // Create HTTP server
-    let make_svc = hyper::service::make_service_fn(|_conn| {
-        async {
-            Ok::<_, std::convert::Infallible>(hyper::service::service_fn(|req: hyper::Request<hyper::Body>| {
-                async move {
-                    let response = match req.uri().path() {
-                        "/metrics" => {
-                            let metrics = generate_metrics();
-                            hyper::Response::builder()
-                                .status(200)
-                                .header("Content-Type", "text/plain; version=0.0.4; charset=utf-8")
-                                .body(hyper::Body::from(metrics))
-                                .unwrap()
+        Err(_) => None,
+    };
+
+    // Decoding transfers/DeFi events (chunk1-1 through chunk1-7/chunk8-4)
+    // needs each block's full transaction list, which the local MDBX
+    // chaindata reader doesn't expose (erigon.rs only decodes the
+    // header/body fields fee-history needs) — only an RPC client returns
+    // `Block<Transaction>`. Reuses `rpc_pool_client` when the pool is
+    // configured, otherwise constructs a single-endpoint client from
+    // `RPC_URL`; with neither set, the block loop below still runs in
+    // fee-history-only mode as it always has.
+    let block_rpc_client: Option<Arc<blockchain::BlockchainClient>> = match &rpc_pool_client {
+        Some(client) => Some(client.clone()),
+        None => match env::var("RPC_URL") {
+            Ok(rpc_url) => match blockchain::BlockchainClient::new(&rpc_url, app_metrics.clone()).await {
+                Ok(client) => Some(Arc::new(client)),
+                Err(e) => {
+                    println!("DEBUG: Failed to construct BlockchainClient for block event processing: {}", e);
+                    None
+                }
+            },
+            Err(_) => None,
+        },
+    };
+    let ethereum_processor = block_rpc_client.as_ref().map(|_| Arc::new(EthereumProcessor::new()));
+
+    // The per-block event pipeline (storing/publishing what `EthereumProcessor`
+    // decodes, plus reorg rollback) only makes sense alongside a live block
+    // source, so it's constructed in lockstep with `block_rpc_client`.
+    // `MONGODB_URI`/`KAFKA_BROKERS` default the same way
+    // data-aggregation-service's own MongoDBService/KafkaProducerService do.
+    // Two complementary rollback mechanisms run off the same MongoDBService
+    // handle: chunk8-5's `ReorgHandler` re-publishes affected
+    // events/transfers/DeFi events as `removed = true` over Kafka and
+    // rewinds `SyncStatus`, while `MongoDBService::handle_reorg` flips
+    // `canonical: false` on the stored documents themselves and re-stores
+    // the new block as canonical — see `StoredEvent::canonical`'s doc
+    // comment for why these are two distinct axes rather than one
+    // superseding the other.
+    let block_event_services: Option<(Arc<MongoDBService>, Arc<KafkaProducerService>, Arc<ReorgHandler>)> =
+        if block_rpc_client.is_some() {
+            let mongodb_uri = env::var("MONGODB_URI").unwrap_or_else(|_| "mongodb://localhost:27017".to_string());
+            let kafka_brokers = env::var("KAFKA_BROKERS").unwrap_or_else(|_| "localhost:9092".to_string());
+            match MongoDBService::new(&mongodb_uri, app_metrics.clone()).await {
+                Ok(mongodb) => {
+                    let mongodb = Arc::new(mongodb);
+                    match KafkaProducerService::new(&kafka_brokers) {
+                        Ok(kafka) => {
+                            let kafka = Arc::new(kafka);
+                            let handler = Arc::new(ReorgHandler::new(mongodb.clone(), kafka.clone(), app_metrics.clone()));
+                            Some((mongodb, kafka, handler))
                         }
-                        "/health" => {
-                            hyper::Response::builder()
-                                .status(200)
-                                .body(hyper::Body::from("OK"))
-                                .unwrap()
+                        Err(e) => {
+                            println!("DEBUG: Failed to construct KafkaProducerService for block event processing: {}", e);
+                            None
                         }
-                        _ => {
-                            hyper::Response::builder()
-                                .status(404)
-                                .body(hyper::Body::from("Not Found"))
-                                .unwrap()
+                    }
+                }
+                Err(e) => {
+                    println!("DEBUG: Failed to construct MongoDBService for block event processing: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+    // Start background task to process real blockchain data, advancing
+    // toward the database's actual head rather than looping over a fixed
+    // block range.
+    let loop_metrics = app_metrics.clone();
+    tokio::spawn(async move {
+        let mut block_number: Option<u64> = None;
+        loop {
+            sleep(Duration::from_secs(5)).await;
+
+            let Some(reader) = ERIGON_READER.get() else {
+                // No chaindata found at startup; nothing to read from.
+                continue;
+            };
+
+            let current = match block_number {
+                Some(n) => n,
+                None => {
+                    let head = match &rpc_pool_client {
+                        Some(client) => client.get_latest_block_number_pooled().await,
+                        None => reader.head_block_number(),
+                    };
+                    match head {
+                        Ok(head) => head,
+                        Err(e) => {
+                            loop_metrics.record_error();
+                            println!("DEBUG: Failed to read chain head: {}", e);
+                            continue;
                         }
+                    }
+                }
+            };
+
+            match process_blockchain_data(reader, current).await {
+                Ok(Some(block_data)) => {
+                    loop_metrics.record_block_processed(current);
+                    loop_metrics.record_transactions_processed(block_data.transaction_count);
+                    loop_metrics.record_rpc_request();
+                    loop_metrics.record_rpc_request();
+                    loop_metrics.record_database_operation();
+                    loop_metrics.record_database_operation();
+
+                    loop_metrics.record_rpc_method("read_header");
+                    loop_metrics.record_rpc_method("read_body");
+                    loop_metrics.record_database_table("Headers");
+                    loop_metrics.record_database_table("BodiesSnapshot");
+
+                    let fee_entry = FeeHistoryEntry {
+                        block_number: current,
+                        base_fee: block_data.base_fee,
+                        gas_used: block_data.gas_used,
+                        gas_limit: block_data.gas_limit,
                     };
-                    Ok::<_, std::convert::Infallible>(response)
+                    let gas_used_ratio = fee_entry.gas_used_ratio();
+                    if (0.0..=1.0).contains(&gas_used_ratio) {
+                        loop_metrics.record_gas_used_ratio(gas_used_ratio);
+                    } else {
+                        println!("DEBUG: Block {} has an out-of-range gas_used_ratio ({}); skipping histogram sample", current, gas_used_ratio);
+                    }
+                    record_fee_history(fee_entry);
+
+                    if let (Some(client), Some(processor)) = (&block_rpc_client, &ethereum_processor) {
+                        process_block_events(client, processor, &block_event_services, current).await;
+                    }
+
+                    println!("DEBUG: Processed block {} with {} transactions",
+                            current, block_data.transaction_count);
+                    block_number = Some(current + 1);
+                }
+                Ok(None) => {
+                    // Block is pruned or not yet written; re-sync to the
+                    // current head next tick instead of advancing blindly.
+                    loop_metrics.record_error();
+                    loop_metrics.record_rpc_method("read_header");
+                    println!("DEBUG: Block {} not found in chaindata", current);
+                    block_number = None;
                 }
-            }))
+                Err(e) => {
+                    loop_metrics.record_error();
+                    loop_metrics.record_rpc_method("read_header");
+                    println!("DEBUG: Error processing block {}: {}", current, e);
+                    block_number = None;
+                }
+            }
         }
     });
 
-    let server = hyper::Server::bind(&addr).serve(make_svc);
-    
-    println!("DEBUG: Server starting...");
-    
-    if let Err(e) = server.await {
-        eprintln!("Server error: {}", e);
-    }
+    // One axum app, one `/metrics` surface: registry-backed metrics, health,
+    // fee history, and on-demand profiling all served from the same router.
+    let app_state = AppState { metrics: app_metrics };
+    let app = Router::new()
+        .route("/health", get(health_handler))
+        .route("/metrics", get(metrics_handler))
+        .route("/fee_history", get(fee_history_handler))
+        .route("/debug/pprof/profile", get(pprof_profile_handler))
+        .with_state(app_state);
+
+    let addr: std::net::SocketAddr = metrics_addr.parse()?;
+    println!("DEBUG: Starting HTTP server on {}", addr);
+
+    let listener = TcpListener::bind(addr).await?;
+    axum::serve(listener, app).await?;
 
     Ok(())
 }
 
-struct BlockData {
-    transaction_count: u64,
-    gas_used: u64,
-    timestamp: u64,
+async fn health_handler() -> &'static str {
+    "OK"
+}
+
+async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [("Content-Type", "text/plain; version=0.0.4; charset=utf-8")],
+        state.metrics.encode(),
+    )
 }
 
-async fn initialize_blockchain_metrics() {
-    // Check if blockchain data exists and get its size
+#[derive(serde::Deserialize)]
+struct FeeHistoryQuery {
+    blocks: Option<usize>,
+}
+
+async fn fee_history_handler(Query(query): Query<FeeHistoryQuery>) -> impl IntoResponse {
+    match fee_history_response(query.blocks.unwrap_or(1)) {
+        Ok(body) => (StatusCode::OK, [("Content-Type", "application/json")], body).into_response(),
+        Err(e) => (StatusCode::UNPROCESSABLE_ENTITY, e).into_response(),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct PprofQuery {
+    seconds: Option<u64>,
+    format: Option<String>,
+}
+
+async fn pprof_profile_handler(Query(query): Query<PprofQuery>) -> impl IntoResponse {
+    let seconds = query.seconds.unwrap_or(10).clamp(1, MAX_PROFILE_SECONDS);
+    let format = match query.format.as_deref() {
+        Some("proto") => ProfileFormat::PprofProto,
+        _ => ProfileFormat::FlamegraphSvg,
+    };
+
+    match capture_profile(seconds, format).await {
+        Ok(body) => {
+            let content_type = match format {
+                ProfileFormat::FlamegraphSvg => "image/svg+xml",
+                ProfileFormat::PprofProto => "application/octet-stream",
+            };
+            (StatusCode::OK, [("Content-Type", content_type)], body).into_response()
+        }
+        Err((status, message)) => (
+            StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            message,
+        ).into_response(),
+    }
+}
+
+async fn initialize_blockchain_metrics(metrics: &BlockchainMetrics) {
+    // Check if blockchain data exists, record its size, and open it for the
+    // background processing loop. The first path that exists wins.
     let blockchain_paths = [
         "/mnt/blockchain-disk/erigon/chaindata/mdbx.dat",
         "/mnt/blockchain-disk/ethereum/erigon-data/nodes/eth68/mdbx.dat",
         "/mnt/blockchain-disk/erigon-cold/nodes/eth68/mdbx.dat",
     ];
-    
+
     for path in &blockchain_paths {
         if Path::new(path).exists() {
             if let Ok(metadata) = fs::metadata(path) {
                 let size = metadata.len();
-                BLOCKCHAIN_DATA_SIZE.store(size, Ordering::SeqCst);
+                metrics.update_blockchain_data_size(size);
                 println!("DEBUG: Found blockchain data at {} with size {} bytes", path, size);
-                break;
             }
+
+            let chaindata_dir = Path::new(path).parent().unwrap_or(Path::new(path));
+            match ErigonReader::open(chaindata_dir) {
+                Ok(reader) => {
+                    let _ = ERIGON_READER.set(reader);
+                }
+                Err(e) => {
+                    println!("DEBUG: Failed to open Erigon chaindata at {}: {}", chaindata_dir.display(), e);
+                }
+            }
+            break;
         }
     }
 }
 
-async fn process_blockchain_data(block_number: u64) -> Result<BlockData, Box<dyn std::error::Error>> {
-    // Simulate processing real blockchain data
-    // In a real implementation, this would read from the Erigon database
-    
-    // Simulate realistic transaction counts based on block number
-    let base_transactions = 150;
-    let variation = (block_number % 100) as u64;
-    let transaction_count = base_transactions + variation;
-    
-    // Simulate gas usage
-    let gas_used = transaction_count * 21000 + (variation * 1000);
-    
-    // Simulate timestamp (roughly 12 seconds per block)
-    let timestamp = 1609459200 + (block_number * 12); // Start from 2021-01-01
-    
-    Ok(BlockData {
-        transaction_count,
-        gas_used,
-        timestamp,
-    })
-}
-
-fn generate_metrics() -> String {
-    let blocks_processed = BLOCKS_PROCESSED.load(Ordering::SeqCst);
-    let rpc_requests = RPC_REQUESTS.load(Ordering::SeqCst);
-    let db_operations = DB_OPERATIONS.load(Ordering::SeqCst);
-    let errors = PROCESSING_ERRORS.load(Ordering::SeqCst);
-    let last_block = LAST_PROCESSED_BLOCK.load(Ordering::SeqCst);
-    let blockchain_data_size = BLOCKCHAIN_DATA_SIZE.load(Ordering::SeqCst);
-    let transactions_processed = TRANSACTIONS_PROCESSED.load(Ordering::SeqCst);
-    
-    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-    
-    format!(
-        "# HELP blockchain_blocks_processed_total Total number of blocks processed\n\
-# TYPE blockchain_blocks_processed_total counter\n\
-blockchain_blocks_processed_total {} {}\n\
-\n\
-# HELP blockchain_last_processed_block Number of the last processed block\n\
-# TYPE blockchain_last_processed_block gauge\n\
-blockchain_last_processed_block {} {}\n\
-\n\
-# HELP blockchain_processing_errors_total Total number of processing errors\n\
-# TYPE blockchain_processing_errors_total counter\n\
-blockchain_processing_errors_total {} {}\n\
-\n\
-# HELP blockchain_rpc_requests_total Total number of RPC requests made\n\
-# TYPE blockchain_rpc_requests_total counter\n\
-blockchain_rpc_requests_total {} {}\n\
-\n\
-# HELP blockchain_database_operations_total Total number of database operations\n\
-# TYPE blockchain_database_operations_total counter\n\
-blockchain_database_operations_total {} {}\n\
-\n\
-# HELP blockchain_data_size_bytes Size of blockchain data in bytes\n\
-# TYPE blockchain_data_size_bytes gauge\n\
-blockchain_data_size_bytes {} {}\n\
-\n\
-# HELP blockchain_transactions_processed_total Total number of transactions processed\n\
-# TYPE blockchain_transactions_processed_total counter\n\
-blockchain_transactions_processed_total {} {}\n\
-\n\
-# HELP blockchain_service_uptime_seconds Service uptime in seconds\n\
-# TYPE blockchain_service_uptime_seconds gauge\n\
-blockchain_service_uptime_seconds {} {}\n\
-\n\
-# HELP blockchain_memory_usage_bytes Current memory usage in bytes\n\
-# TYPE blockchain_memory_usage_bytes gauge\n\
-blockchain_memory_usage_bytes 52428800 {}\n\
-\n\
-# HELP blockchain_cpu_usage_percent Current CPU usage percentage\n\
-# TYPE blockchain_cpu_usage_percent gauge\n\
-blockchain_cpu_usage_percent 15.5 {}\n",
-        blocks_processed, timestamp,
-        last_block, timestamp,
-        errors, timestamp,
-        rpc_requests, timestamp,
-        db_operations, timestamp,
-        blockchain_data_size, timestamp,
-        transactions_processed, timestamp,
-        timestamp,
-        timestamp,
-        timestamp
-    )
-}
\ No newline at end of file
+/// Reads and decodes block `block_number` from the Erigon chaindata.
+/// Returns `Ok(None)` when the block is pruned or not yet present, rather
+/// than an error, so the caller can tell that apart from a genuine read
+/// failure.
+async fn process_blockchain_data(
+    reader: &ErigonReader,
+    block_number: u64,
+) -> Result<Option<erigon::BlockData>, Box<dyn std::error::Error>> {
+    Ok(reader.read_block(block_number)?)
+}
+
+/// Fetches `block_number`'s full transaction list over RPC and feeds it
+/// through `EthereumProcessor` (transfer/DeFi-event decoding via
+/// `process_transaction_receipt`, block-level fee rollup via `process_block`/
+/// `aggregate_block_fees`, reorg detection via `observe_block`) — the
+/// decoding chunk1-1 through chunk1-7/chunk8-4 built, which the local MDBX
+/// chaindata read above has no way to drive since it only decodes
+/// header/body fields. Every decoded `TransactionEvent`/`TransactionReceipt`/
+/// `ContractEvent`/`TokenTransferEvent`/`DeFiEvent`/`BlockEvent` is stored via
+/// `MongoDBService`, and all but the `TransactionReceipt` (which only updates
+/// its stored transaction) are published via `KafkaProducerService`, the same
+/// store-then-publish pattern `reorg_handler.rs` uses for its rollback path.
+/// When `observe_block` reports a reorg, rolls it back through the remaining
+/// two halves of `block_event_services`: `ReorgHandler` republishes the
+/// affected events/transfers/DeFi events as `removed = true` and rewinds
+/// `SyncStatus`, while `MongoDBService::handle_reorg` flips `canonical:
+/// false` on the orphaned documents and re-stores every block from the
+/// common ancestor up to `block_number` as the new canonical chain.
+async fn process_block_events(
+    client: &blockchain::BlockchainClient,
+    processor: &EthereumProcessor,
+    block_event_services: &Option<(Arc<MongoDBService>, Arc<KafkaProducerService>, Arc<ReorgHandler>)>,
+    block_number: u64,
+) {
+    let full_block = match client.get_block_with_transactions(block_number).await {
+        Ok(block) => block,
+        Err(e) => {
+            println!("DEBUG: Failed to fetch full block {} for event processing: {}", block_number, e);
+            return;
+        }
+    };
+
+    let mut receipts = Vec::with_capacity(full_block.transactions.len());
+    for tx in &full_block.transactions {
+        let transaction_event = match processor.process_transaction(tx, &full_block) {
+            Ok(event) => event,
+            Err(e) => {
+                println!("DEBUG: Failed to process transaction {:?} in block {}: {}", tx.hash, block_number, e);
+                continue;
+            }
+        };
+        if let Some((mongodb, kafka, _)) = block_event_services {
+            if let Err(e) = mongodb.store_transaction(tx, &full_block).await {
+                println!("DEBUG: Failed to store transaction {:?} in block {}: {}", tx.hash, block_number, e);
+            }
+            if let Err(e) = kafka.send_transaction_event(&transaction_event).await {
+                println!("DEBUG: Failed to publish transaction event for {:?} in block {}: {}", tx.hash, block_number, e);
+            }
+        }
+
+        let raw_receipt = match client.get_transaction_receipt(&tx.hash).await {
+            Ok(Some(receipt)) => receipt,
+            Ok(None) => continue,
+            Err(e) => {
+                println!("DEBUG: Failed to fetch receipt for {:?} in block {}: {}", tx.hash, block_number, e);
+                continue;
+            }
+        };
+
+        let (processed_receipt, token_transfers, defi_events) =
+            match processor.process_transaction_receipt(&raw_receipt, &full_block, tx) {
+                Ok(result) => result,
+                Err(e) => {
+                    println!("DEBUG: Failed to process receipt for {:?} in block {}: {}", tx.hash, block_number, e);
+                    continue;
+                }
+            };
+
+        if let Some((mongodb, kafka, _)) = block_event_services {
+            if let Err(e) = mongodb.update_transaction_receipt(&processed_receipt).await {
+                println!("DEBUG: Failed to store receipt for {:?} in block {}: {}", tx.hash, block_number, e);
+            }
+            for event in &processed_receipt.logs {
+                if let Err(e) = mongodb.store_event(event).await {
+                    println!("DEBUG: Failed to store contract event for {:?} in block {}: {}", tx.hash, block_number, e);
+                }
+                if let Err(e) = kafka.send_contract_event(event).await {
+                    println!("DEBUG: Failed to publish contract event for {:?} in block {}: {}", tx.hash, block_number, e);
+                }
+            }
+            for transfer in &token_transfers {
+                if let Err(e) = mongodb.store_token_transfer(transfer).await {
+                    println!("DEBUG: Failed to store token transfer for {:?} in block {}: {}", tx.hash, block_number, e);
+                }
+                if let Err(e) = kafka.send_token_transfer_event(transfer).await {
+                    println!("DEBUG: Failed to publish token transfer for {:?} in block {}: {}", tx.hash, block_number, e);
+                }
+            }
+            for defi_event in &defi_events {
+                if let Err(e) = mongodb.store_defi_event(defi_event).await {
+                    println!("DEBUG: Failed to store DeFi event for {:?} in block {}: {}", tx.hash, block_number, e);
+                }
+                if let Err(e) = kafka.send_defi_event(defi_event).await {
+                    println!("DEBUG: Failed to publish DeFi event for {:?} in block {}: {}", tx.hash, block_number, e);
+                }
+            }
+        }
+
+        receipts.push(processed_receipt);
+    }
+
+    if let Some((mongodb, kafka, _)) = block_event_services {
+        if let Err(e) = mongodb.store_block(&full_block).await {
+            println!("DEBUG: Failed to store block {}: {}", block_number, e);
+        }
+        match processor.process_block(&full_block) {
+            Ok(mut block_event) => {
+                processor.aggregate_block_fees(&mut block_event, &receipts);
+                if let Err(e) = kafka.send_block_event(&block_event).await {
+                    println!("DEBUG: Failed to publish block event for block {}: {}", block_number, e);
+                }
+            }
+            Err(e) => println!("DEBUG: Failed to build block event for block {}: {}", block_number, e),
+        }
+    }
+
+    let Some(reorg) = processor.observe_block(&full_block) else {
+        return;
+    };
+    println!(
+        "DEBUG: Reorg detected at block {}: {} orphaned block(s), common ancestor {:?}",
+        block_number, reorg.orphaned_blocks.len(), reorg.common_ancestor_number
+    );
+
+    let Some((mongodb, _, handler)) = block_event_services else {
+        return;
+    };
+    if let Err(e) = handler.handle_reorg("blockchain-sync-service", &reorg).await {
+        println!("DEBUG: Failed to republish rollback for reorg at block {}: {}", block_number, e);
+    }
+    let common_ancestor = reorg.common_ancestor_number.unwrap_or_else(|| {
+        reorg.orphaned_blocks.first().map(|b| b.block_number).unwrap_or(block_number).saturating_sub(1)
+    });
+
+    // Re-fetch and re-store every block from just above the common ancestor
+    // up to the current tip as the new canonical chain — not just
+    // `full_block` — so a reorg deeper than one block doesn't leave the
+    // intermediate heights permanently without a canonical replacement (the
+    // main loop never revisits them; it always advances past `block_number`).
+    let mut new_canonical_chain = Vec::with_capacity((block_number.saturating_sub(common_ancestor)) as usize);
+    for height in (common_ancestor + 1)..block_number {
+        match client.get_block_with_transactions(height).await {
+            Ok(block) => new_canonical_chain.push(block),
+            Err(e) => {
+                println!("DEBUG: Failed to fetch block {} while replaying new canonical chain for reorg at block {}: {}", height, block_number, e);
+            }
+        }
+    }
+    new_canonical_chain.push(full_block);
+
+    if let Err(e) = mongodb.handle_reorg(common_ancestor, &new_canonical_chain).await {
+        println!("DEBUG: Failed to flip canonical flag for reorg at block {}: {}", block_number, e);
+    }
+}
+
+/// Builds the `/fee_history` JSON body for the last `requested_blocks`
+/// processed blocks. Returns the available window (rather than erroring)
+/// when fewer blocks than requested have been processed, but does error
+/// when any block in the window has a `gas_used_ratio` outside `[0, 1]`,
+/// since that indicates corrupt block data rather than a legitimate small
+/// window.
+fn fee_history_response(requested_blocks: usize) -> Result<String, String> {
+    let history = FEE_HISTORY.lock().unwrap();
+    let window_size = requested_blocks.min(history.len()).min(FEE_HISTORY_CAPACITY);
+    let most_recent_first: Vec<&FeeHistoryEntry> = history.iter().rev().take(window_size).collect();
+
+    let mut ratios: Vec<f64> = Vec::with_capacity(most_recent_first.len());
+    let mut blocks_json: Vec<String> = Vec::with_capacity(most_recent_first.len());
+    for entry in most_recent_first.iter().rev() {
+        let ratio = entry.gas_used_ratio();
+        if !(0.0..=1.0).contains(&ratio) {
+            return Err(format!(
+                "block {} has an out-of-range gas_used_ratio ({}); block data looks corrupt",
+                entry.block_number, ratio
+            ));
+        }
+        ratios.push(ratio);
+        blocks_json.push(format!(
+            "{{\"block_number\":{},\"base_fee\":{},\"gas_used\":{},\"gas_limit\":{},\"gas_used_ratio\":{}}}",
+            entry.block_number,
+            entry.base_fee.map(|f| f.to_string()).unwrap_or_else(|| "null".to_string()),
+            entry.gas_used,
+            entry.gas_limit,
+            ratio
+        ));
+    }
+
+    Ok(format!(
+        "{{\"blocks\":[{}],\"gas_used_ratio_percentiles\":{{\"p50\":{},\"p90\":{},\"p99\":{}}}}}",
+        blocks_json.join(","),
+        percentile(&ratios, 50.0),
+        percentile(&ratios, 90.0),
+        percentile(&ratios, 99.0),
+    ))
+}
+
+/// Nearest-rank percentile over `values` (need not be pre-sorted).
+///
+/// This reports `gas_used_ratio` percentiles rather than true priority-fee
+/// percentiles: computing the latter would require decoding every
+/// transaction's tip out of the block body, but this reader's body decode
+/// only extracts a transaction count (see `erigon::decode_body_transaction_count`).
+/// `gas_used_ratio` is the most useful congestion signal available from
+/// header data alone.
+fn percentile(values: &[f64], pct: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let rank = ((pct / 100.0) * (sorted.len() as f64 - 1.0)).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+/// Samples this process for `seconds` with an in-process CPU profiler and
+/// renders either a collapsed-stack flamegraph SVG or a pprof protobuf,
+/// depending on `format`. Returns `(409, ...)` if a capture is already
+/// running, since two concurrent samples would just blend two unrelated
+/// windows into one useless report.
+async fn capture_profile(seconds: u64, format: ProfileFormat) -> Result<Vec<u8>, (u16, String)> {
+    if PROFILING_IN_PROGRESS.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+        return Err((409, "a profiling capture is already in progress".to_string()));
+    }
+
+    let result = capture_profile_inner(seconds, format).await;
+    PROFILING_IN_PROGRESS.store(false, Ordering::SeqCst);
+    result
+}
+
+async fn capture_profile_inner(seconds: u64, format: ProfileFormat) -> Result<Vec<u8>, (u16, String)> {
+    const SAMPLING_FREQUENCY_HZ: i32 = 997;
+
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(SAMPLING_FREQUENCY_HZ)
+        .build()
+        .map_err(|e| (500, format!("failed to start profiler: {}", e)))?;
+
+    sleep(Duration::from_secs(seconds)).await;
+
+    let report = guard.report().build()
+        .map_err(|e| (500, format!("failed to build profile report: {}", e)))?;
+
+    match format {
+        ProfileFormat::FlamegraphSvg => {
+            let mut svg = Vec::new();
+            report.flamegraph(&mut svg)
+                .map_err(|e| (500, format!("failed to render flamegraph: {}", e)))?;
+            Ok(svg)
+        }
+        ProfileFormat::PprofProto => {
+            let profile = report.pprof()
+                .map_err(|e| (500, format!("failed to encode pprof profile: {}", e)))?;
+            let mut buf = Vec::new();
+            profile.write_to_vec(&mut buf)
+                .map_err(|e| (500, format!("failed to serialize pprof profile: {}", e)))?;
+            Ok(buf)
+        }
+    }
+}
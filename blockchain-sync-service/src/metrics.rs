@@ -1,11 +1,15 @@
-use std::sync::Arc;
-use prometheus::{Registry, Counter, Gauge, Histogram, Opts, HistogramOpts, HistogramVec};
-use hyper::service::{make_service_fn, service_fn};
-use hyper::{Body, Request, Response, Server};
-use std::convert::Infallible;
-use std::net::SocketAddr;
+use prometheus::{
+    Counter, CounterVec, Gauge, GaugeVec, Histogram, HistogramOpts, HistogramVec, Opts, Registry, TextEncoder,
+};
 
+/// The service's one Prometheus registry. Every collector — the block-loop
+/// counters this binary used to hand-format, the labeled RPC/DB breakdowns,
+/// and the `BlockchainClient`'s own RPC request metrics — registers into
+/// this single struct, so `/metrics` has exactly one surface instead of a
+/// hand-rolled text blob plus a separate unused registry.
 pub struct BlockchainMetrics {
+    registry: Registry,
+
     pub blocks_processed_total: Counter,
     pub last_processed_block: Gauge,
     pub processing_errors_total: Counter,
@@ -13,29 +17,75 @@ pub struct BlockchainMetrics {
     pub rpc_request_duration: Histogram,
     pub database_operations_total: Counter,
     pub database_operation_duration: Histogram,
+    pub quorum_divergence_total: Counter,
+    pub fee_estimation_duration: Histogram,
+    pub transactions_processed_total: Counter,
+    pub blockchain_data_size_bytes: Gauge,
+    pub memory_usage_bytes: Gauge,
+    pub cpu_usage_percent: Gauge,
+
+    /// Per-block ratio of gas used to gas limit, for congestion alerting.
+    pub gas_used_ratio: Histogram,
+    /// Breaks `rpc_requests_total` down by which kind of chain-data read it
+    /// was (e.g. `read_header` vs `read_body`).
+    pub rpc_requests_by_method: CounterVec,
+    /// Breaks `database_operations_total` down by which MDBX table was read.
+    pub database_operations_by_table: CounterVec,
+
+    /// `BlockchainClient`'s own upstream `eth_*` RPC calls, labeled by
+    /// method, chain, and outcome — distinct from `rpc_requests_by_method`,
+    /// which covers this binary's own MDBX chain-data reads.
+    pub eth_rpc_requests_total: CounterVec,
+    pub eth_rpc_request_duration: HistogramVec,
+    pub eth_rpc_errors_total: CounterVec,
+
+    /// Per-endpoint health from `rpc_pool`, labeled by upstream URL: `1`/`0`
+    /// for currently healthy/ejected, and the most recently observed call
+    /// latency.
+    pub rpc_endpoint_healthy: GaugeVec,
+    pub rpc_endpoint_latency_seconds: GaugeVec,
+
+    /// Total number of chain reorganizations detected via `parent_hash`
+    /// linkage in the sliding reorg window.
+    pub reorgs_detected_total: Counter,
+    /// Number of blocks unwound per detected reorg (distance from the tip
+    /// back to the common ancestor).
+    pub reorg_depth: Histogram,
+
+    /// Times `ErrorHandler::execute_with_retry_and_error_tracking_with_fallback`
+    /// returned a degraded, fallback-supplied value instead of a fresh
+    /// result, labeled by `ErrorType`. A rising rate here means retries (or
+    /// the circuit breaker) are failing open more often than callers expect.
+    pub fallback_taken_total: CounterVec,
 }
 
 impl BlockchainMetrics {
     pub fn new() -> Self {
+        let registry = Registry::new();
+
         let blocks_processed_total = Counter::new(
             "blockchain_blocks_processed_total",
             "Total number of blocks processed"
         ).unwrap();
+        registry.register(Box::new(blocks_processed_total.clone())).unwrap();
 
         let last_processed_block = Gauge::new(
             "blockchain_last_processed_block",
             "Number of the last processed block"
         ).unwrap();
+        registry.register(Box::new(last_processed_block.clone())).unwrap();
 
         let processing_errors_total = Counter::new(
             "blockchain_processing_errors_total",
             "Total number of processing errors"
         ).unwrap();
+        registry.register(Box::new(processing_errors_total.clone())).unwrap();
 
         let rpc_requests_total = Counter::new(
             "blockchain_rpc_requests_total",
             "Total number of RPC requests made"
         ).unwrap();
+        registry.register(Box::new(rpc_requests_total.clone())).unwrap();
 
         let rpc_request_duration = Histogram::with_opts(
             HistogramOpts::new(
@@ -43,11 +93,13 @@ impl BlockchainMetrics {
                 "Duration of RPC requests in seconds"
             ).buckets(vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0])
         ).unwrap();
+        registry.register(Box::new(rpc_request_duration.clone())).unwrap();
 
         let database_operations_total = Counter::new(
             "blockchain_database_operations_total",
             "Total number of database operations"
         ).unwrap();
+        registry.register(Box::new(database_operations_total.clone())).unwrap();
 
         let database_operation_duration = Histogram::with_opts(
             HistogramOpts::new(
@@ -55,8 +107,121 @@ impl BlockchainMetrics {
                 "Duration of database operations in seconds"
             ).buckets(vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0])
         ).unwrap();
+        registry.register(Box::new(database_operation_duration.clone())).unwrap();
+
+        let quorum_divergence_total = Counter::new(
+            "blockchain_quorum_divergence_total",
+            "Total number of quorum reads where backend endpoints disagreed"
+        ).unwrap();
+        registry.register(Box::new(quorum_divergence_total.clone())).unwrap();
+
+        let fee_estimation_duration = Histogram::with_opts(
+            HistogramOpts::new(
+                "blockchain_fee_estimation_duration_seconds",
+                "Duration of EIP-1559 fee estimation in seconds"
+            ).buckets(vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0])
+        ).unwrap();
+        registry.register(Box::new(fee_estimation_duration.clone())).unwrap();
+
+        let transactions_processed_total = Counter::new(
+            "blockchain_transactions_processed_total",
+            "Total number of transactions processed"
+        ).unwrap();
+        registry.register(Box::new(transactions_processed_total.clone())).unwrap();
+
+        let blockchain_data_size_bytes = Gauge::new(
+            "blockchain_data_size_bytes",
+            "Size of blockchain data in bytes"
+        ).unwrap();
+        registry.register(Box::new(blockchain_data_size_bytes.clone())).unwrap();
+
+        let memory_usage_bytes = Gauge::new(
+            "blockchain_memory_usage_bytes",
+            "Current memory usage in bytes"
+        ).unwrap();
+        registry.register(Box::new(memory_usage_bytes.clone())).unwrap();
+
+        let cpu_usage_percent = Gauge::new(
+            "blockchain_cpu_usage_percent",
+            "Current CPU usage percentage"
+        ).unwrap();
+        registry.register(Box::new(cpu_usage_percent.clone())).unwrap();
+
+        let gas_used_ratio = Histogram::with_opts(
+            HistogramOpts::new(
+                "blockchain_gas_used_ratio",
+                "Per-block ratio of gas used to gas limit, for congestion alerting"
+            ).buckets(vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 0.95, 1.0])
+        ).unwrap();
+        registry.register(Box::new(gas_used_ratio.clone())).unwrap();
+
+        let rpc_requests_by_method = CounterVec::new(
+            Opts::new("blockchain_rpc_requests_by_method_total", "Total number of chain-data reads, by method"),
+            &["method"],
+        ).unwrap();
+        registry.register(Box::new(rpc_requests_by_method.clone())).unwrap();
+
+        let database_operations_by_table = CounterVec::new(
+            Opts::new("blockchain_database_operations_by_table_total", "Total number of MDBX table reads, by table"),
+            &["table"],
+        ).unwrap();
+        registry.register(Box::new(database_operations_by_table.clone())).unwrap();
+
+        let eth_rpc_requests_total = CounterVec::new(
+            Opts::new("blockchain_eth_rpc_requests_total", "Total number of upstream eth_* RPC requests, by method/chain/status"),
+            &["method", "chain", "status"],
+        ).unwrap();
+        registry.register(Box::new(eth_rpc_requests_total.clone())).unwrap();
+
+        let eth_rpc_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "blockchain_eth_rpc_request_duration_seconds",
+                "Duration of upstream eth_* RPC requests in seconds, by method/chain"
+            ).buckets(vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0]),
+            &["method", "chain"],
+        ).unwrap();
+        registry.register(Box::new(eth_rpc_request_duration.clone())).unwrap();
+
+        let eth_rpc_errors_total = CounterVec::new(
+            Opts::new("blockchain_eth_rpc_errors_total", "Total number of upstream eth_* RPC errors, by error_type/method/chain"),
+            &["error_type", "method", "chain"],
+        ).unwrap();
+        registry.register(Box::new(eth_rpc_errors_total.clone())).unwrap();
+
+        let rpc_endpoint_healthy = GaugeVec::new(
+            Opts::new("rpc_endpoint_healthy", "Whether an rpc_pool endpoint is currently healthy (1) or ejected (0), by url"),
+            &["url"],
+        ).unwrap();
+        registry.register(Box::new(rpc_endpoint_healthy.clone())).unwrap();
+
+        let rpc_endpoint_latency_seconds = GaugeVec::new(
+            Opts::new("rpc_endpoint_latency_seconds", "Most recently observed call latency for an rpc_pool endpoint, by url"),
+            &["url"],
+        ).unwrap();
+        registry.register(Box::new(rpc_endpoint_latency_seconds.clone())).unwrap();
+
+        let reorgs_detected_total = Counter::new(
+            "blockchain_reorgs_detected_total",
+            "Total number of chain reorganizations detected"
+        ).unwrap();
+        registry.register(Box::new(reorgs_detected_total.clone())).unwrap();
+
+        let reorg_depth = Histogram::with_opts(
+            HistogramOpts::new(
+                "blockchain_reorg_depth_blocks",
+                "Number of blocks unwound per detected reorg"
+            ).buckets(vec![1.0, 2.0, 3.0, 5.0, 8.0, 13.0, 21.0, 34.0, 55.0, 89.0, 128.0])
+        ).unwrap();
+        registry.register(Box::new(reorg_depth.clone())).unwrap();
+
+        let fallback_taken_total = CounterVec::new(
+            Opts::new("blockchain_fallback_taken_total", "Times a degraded fallback value was returned instead of a fresh result, by error_type"),
+            &["error_type"],
+        ).unwrap();
+        registry.register(Box::new(fallback_taken_total.clone())).unwrap();
 
         Self {
+            registry,
             blocks_processed_total,
             last_processed_block,
             processing_errors_total,
@@ -64,6 +229,23 @@ impl BlockchainMetrics {
             rpc_request_duration,
             database_operations_total,
             database_operation_duration,
+            quorum_divergence_total,
+            fee_estimation_duration,
+            transactions_processed_total,
+            blockchain_data_size_bytes,
+            memory_usage_bytes,
+            cpu_usage_percent,
+            gas_used_ratio,
+            rpc_requests_by_method,
+            database_operations_by_table,
+            eth_rpc_requests_total,
+            eth_rpc_request_duration,
+            eth_rpc_errors_total,
+            rpc_endpoint_healthy,
+            rpc_endpoint_latency_seconds,
+            reorgs_detected_total,
+            reorg_depth,
+            fallback_taken_total,
         }
     }
 
@@ -95,77 +277,72 @@ impl BlockchainMetrics {
     pub fn record_database_duration(&self, duration: f64) {
         self.database_operation_duration.observe(duration);
     }
-}
 
-pub struct MetricsService {
-    metrics: Arc<BlockchainMetrics>,
-    addr: String,
-}
+    pub fn record_quorum_divergence(&self, endpoint: &str) {
+        log::warn!("quorum read diverged on endpoint {}", endpoint);
+        self.quorum_divergence_total.inc();
+    }
 
-impl MetricsService {
-    pub fn new(metrics: Arc<BlockchainMetrics>, addr: String) -> Self {
-        Self { metrics, addr }
+    pub fn record_fee_estimation_duration(&self, duration: f64) {
+        self.fee_estimation_duration.observe(duration);
     }
 
-    pub async fn start(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let addr: SocketAddr = self.addr.parse()?;
-        
-        let registry = Registry::new();
-        
-        // Register metrics with the registry
-        registry.register(Box::new(self.metrics.blocks_processed_total.clone()))?;
-        registry.register(Box::new(self.metrics.last_processed_block.clone()))?;
-        registry.register(Box::new(self.metrics.processing_errors_total.clone()))?;
-        registry.register(Box::new(self.metrics.rpc_requests_total.clone()))?;
-        registry.register(Box::new(self.metrics.rpc_request_duration.clone()))?;
-        registry.register(Box::new(self.metrics.database_operations_total.clone()))?;
-        registry.register(Box::new(self.metrics.database_operation_duration.clone()))?;
-
-        let make_svc = make_service_fn(move |_conn| {
-            let registry = registry.clone();
-            async move {
-                Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
-                    let registry = registry.clone();
-                    async move {
-                        let response = match req.uri().path() {
-                            "/metrics" => {
-                                let metric_families = registry.gather();
-                                let encoder = prometheus::TextEncoder::new();
-                                match encoder.encode_to_string(&metric_families) {
-                                    Ok(metrics) => Response::builder()
-                                        .status(200)
-                                        .header("Content-Type", "text/plain; version=0.0.4; charset=utf-8")
-                                        .body(Body::from(metrics))
-                                        .unwrap(),
-                                    Err(_) => Response::builder()
-                                        .status(500)
-                                        .body(Body::from("Failed to encode metrics"))
-                                        .unwrap(),
-                                }
-                            }
-                            "/health" => Response::builder()
-                                .status(200)
-                                .body(Body::from("OK"))
-                                .unwrap(),
-                            _ => Response::builder()
-                                .status(404)
-                                .body(Body::from("Not Found"))
-                                .unwrap(),
-                        };
-                        Ok::<_, Infallible>(response)
-                    }
-                }))
-            }
-        });
-
-        let server = Server::bind(&addr).serve(make_svc);
-        
-        println!("DEBUG: Starting HTTP server on {}", addr);
-        
-        if let Err(e) = server.await {
-            eprintln!("Server error: {}", e);
-        }
+    pub fn record_transactions_processed(&self, count: u64) {
+        self.transactions_processed_total.inc_by(count as f64);
+    }
+
+    pub fn update_blockchain_data_size(&self, bytes: u64) {
+        self.blockchain_data_size_bytes.set(bytes as f64);
+    }
+
+    pub fn update_resource_usage(&self, memory_bytes: u64, cpu_percent: f64) {
+        self.memory_usage_bytes.set(memory_bytes as f64);
+        self.cpu_usage_percent.set(cpu_percent);
+    }
 
-        Ok(())
+    pub fn record_gas_used_ratio(&self, ratio: f64) {
+        self.gas_used_ratio.observe(ratio);
     }
-}
\ No newline at end of file
+
+    pub fn record_rpc_method(&self, method: &str) {
+        self.rpc_requests_by_method.with_label_values(&[method]).inc();
+    }
+
+    pub fn record_database_table(&self, table: &str) {
+        self.database_operations_by_table.with_label_values(&[table]).inc();
+    }
+
+    pub fn record_eth_rpc_request(&self, method: &str, chain: &str, duration: f64, success: bool) {
+        let status = if success { "success" } else { "error" };
+        self.eth_rpc_requests_total.with_label_values(&[method, chain, status]).inc();
+        self.eth_rpc_request_duration.with_label_values(&[method, chain]).observe(duration);
+    }
+
+    pub fn record_eth_rpc_error(&self, error_type: &str, method: &str, chain: &str) {
+        self.eth_rpc_errors_total.with_label_values(&[error_type, method, chain]).inc();
+    }
+
+    pub fn set_rpc_endpoint_healthy(&self, url: &str, healthy: bool) {
+        self.rpc_endpoint_healthy.with_label_values(&[url]).set(if healthy { 1.0 } else { 0.0 });
+    }
+
+    pub fn set_rpc_endpoint_latency(&self, url: &str, seconds: f64) {
+        self.rpc_endpoint_latency_seconds.with_label_values(&[url]).set(seconds);
+    }
+
+    pub fn record_reorg(&self, depth: u64) {
+        self.reorgs_detected_total.inc();
+        self.reorg_depth.observe(depth as f64);
+    }
+
+    pub fn record_fallback_taken(&self, error_type: &str) {
+        self.fallback_taken_total.with_label_values(&[error_type]).inc();
+    }
+
+    /// Renders every registered collector as Prometheus text exposition
+    /// format, for the service's single `/metrics` handler.
+    pub fn encode(&self) -> String {
+        let metric_families = self.registry.gather();
+        TextEncoder::new().encode_to_string(&metric_families).unwrap_or_default()
+    }
+}
@@ -27,6 +27,14 @@ pub struct BlockEvent {
     pub transactions_root: String,
     pub receipts_root: String,
     pub withdrawals: Option<Vec<Withdrawal>>,
+    /// Sum of `base_fee_per_gas * gas_used` across every transaction in the
+    /// block, i.e. the total EIP-1559 burnt fee. Zero for pre-London blocks.
+    pub total_burnt_wei: String,
+    /// Sum of `priority_fee_per_gas * gas_used` across every transaction,
+    /// i.e. the total fee actually paid to the miner/validator.
+    pub total_tips_wei: String,
+    /// Average of each transaction's effective gas price, in wei.
+    pub average_effective_gas_price: String,
     pub created_at: DateTime<Utc>,
 }
 
@@ -52,12 +60,19 @@ pub struct TransactionEvent {
     pub is_contract_creation: bool,
     pub is_contract_interaction: bool,
     pub transaction_type: u8,
+    /// Human-readable name for `transaction_type` ("legacy", "eip2930",
+    /// "eip1559"), so downstream consumers can filter by envelope type
+    /// without re-deriving it from the raw byte.
+    pub transaction_type_name: String,
     pub access_list: Option<Vec<AccessListEntry>>,
     pub chain_id: Option<u64>,
     pub v: String,
     pub r: String,
     pub s: String,
     pub timestamp: u64,
+    /// Effective priority fee per gas paid to the miner/validator, in wei.
+    /// `None` on pre-London blocks where there is no base fee to subtract.
+    pub priority_fee_per_gas: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -73,6 +88,10 @@ pub struct ContractEvent {
     pub data: String,
     pub decoded_data: Option<HashMap<String, serde_json::Value>>,
     pub log_index: u64,
+    /// `true` if the node reported this log as retracted by a chain
+    /// reorganization (`eth_getLogs`'s `removed` flag). Consumers should
+    /// treat such an event as undoing a previously emitted one with the
+    /// same transaction hash and log index, not as a new occurrence.
     pub removed: bool,
     pub timestamp: u64,
     pub created_at: DateTime<Utc>,
@@ -89,12 +108,23 @@ pub struct TransactionReceipt {
     pub to: Option<String>,
     pub gas_used: String,
     pub effective_gas_price: String,
+    pub transaction_type: u8,
+    pub transaction_type_name: String,
     pub contract_address: Option<String>,
     pub logs: Vec<ContractEvent>,
     pub logs_bloom: String,
     pub status: Option<u64>, // 1 for success, 0 for failure
     pub root: Option<String>,
     pub cumulative_gas_used: String,
+    /// `base_fee_per_gas * gas_used`, i.e. the portion of the fee that was
+    /// burnt under EIP-1559. Zero for pre-London blocks.
+    pub burnt_wei: String,
+    /// `priority_fee_per_gas * gas_used`, i.e. the portion of the fee paid
+    /// to the block producer.
+    pub miner_tip_wei: String,
+    /// Flattened internal call tree from `debug_traceTransaction`, if the
+    /// caller chose to attach one (see `EthereumProcessor::trace_internal_transactions`).
+    pub internal_transactions: Option<Vec<InternalTransaction>>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -122,9 +152,26 @@ pub struct StoredBlock {
     pub transactions_root: String,
     pub receipts_root: String,
     pub withdrawals: Option<Vec<Withdrawal>>,
+    /// `false` once `MongoDBService::handle_reorg` determines this block
+    /// fell off the canonical chain. Defaults to `true` on deserialization
+    /// so documents stored before this field existed still read as
+    /// canonical.
+    #[serde(default = "default_canonical")]
+    pub canonical: bool,
+    /// `true` once `MongoDBService::mark_finalized_up_to` has buried this
+    /// block under the configured confirmation depth. `handle_reorg`
+    /// refuses to roll back a finalized block.
+    #[serde(default)]
+    pub finalized: bool,
+    #[serde(default)]
+    pub finalized_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
 }
 
+fn default_canonical() -> bool {
+    true
+}
+
 // Enhanced Stored Transaction with comprehensive data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoredTransaction {
@@ -139,6 +186,21 @@ pub struct StoredTransaction {
     pub gas_price: String,
     pub gas_limit: String,
     pub gas_used: Option<String>,
+    /// The remaining receipt fields are only populated once
+    /// `MongoDBService::update_transaction_receipt` applies the
+    /// corresponding `TransactionReceipt` — `None` until then, and on
+    /// documents stored before this field existed.
+    #[serde(default)]
+    pub effective_gas_price: Option<String>,
+    #[serde(default)]
+    pub cumulative_gas_used: Option<String>,
+    /// `Some(1)` for success, `Some(0)` for a revert, `None` if the receipt
+    /// hasn't been applied yet.
+    #[serde(default)]
+    pub status: Option<u64>,
+    /// Set for contract-creation transactions once the receipt is applied.
+    #[serde(default)]
+    pub contract_address: Option<String>,
     pub max_fee_per_gas: Option<String>,
     pub max_priority_fee_per_gas: Option<String>,
     pub nonce: u64,
@@ -153,6 +215,9 @@ pub struct StoredTransaction {
     pub r: String,
     pub s: String,
     pub timestamp: u64,
+    /// See `StoredBlock::canonical`.
+    #[serde(default = "default_canonical")]
+    pub canonical: bool,
     pub created_at: DateTime<Utc>,
 }
 
@@ -170,6 +235,12 @@ pub struct StoredEvent {
     pub log_index: u64,
     pub removed: bool,
     pub timestamp: u64,
+    /// See `StoredBlock::canonical`. Distinct from `removed`: `removed`
+    /// marks a rollback re-publish over Kafka (`ReorgHandler`), while
+    /// `canonical` marks this stored document itself as belonging to a
+    /// chain that's no longer the head (`MongoDBService::handle_reorg`).
+    #[serde(default = "default_canonical")]
+    pub canonical: bool,
     pub created_at: DateTime<Utc>,
 }
 
@@ -200,6 +271,10 @@ pub struct TokenTransferEvent {
     pub value: Option<String>, // For ERC-20
     pub token_id: Option<String>, // For ERC-721/ERC-1155
     pub amount: Option<String>, // For ERC-1155
+    /// `true` for a rollback record re-published after a chain
+    /// reorganization orphaned the block this transfer was originally seen
+    /// in, mirroring `ContractEvent`/`StoredEvent`'s `removed` flag.
+    pub removed: bool,
     pub timestamp: u64,
     pub created_at: DateTime<Utc>,
 }
@@ -222,6 +297,14 @@ pub struct DeFiEvent {
     pub amount: Option<String>,
     pub token: Option<String>,
     pub pool: Option<String>,
+    /// ABI-decoded swap/liquidity amounts keyed by parameter name (e.g.
+    /// `amount0In`, `sqrtPriceX96`), populated for events this processor
+    /// knows how to decode (currently Uniswap V2/V3 `Swap`).
+    pub decoded_amounts: Option<HashMap<String, serde_json::Value>>,
+    /// `true` for a rollback record re-published after a chain
+    /// reorganization orphaned the block this event was originally seen in,
+    /// mirroring `ContractEvent`/`StoredEvent`'s `removed` flag.
+    pub removed: bool,
     pub timestamp: u64,
     pub created_at: DateTime<Utc>,
 }
@@ -240,6 +323,52 @@ pub enum DeFiEventType {
     RewardClaim,
 }
 
+// Internal call frame extracted from a `debug_traceTransaction` callTracer run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InternalTransaction {
+    pub transaction_hash: String,
+    /// Dotted index path into the call tree, e.g. `0.1.2` for the third
+    /// sub-call of the second sub-call of the top-level call.
+    pub call_path: String,
+    pub call_type: String, // CALL, DELEGATECALL, STATICCALL, CREATE, CREATE2, etc.
+    pub from: String,
+    pub to: Option<String>,
+    pub value: String,
+    /// `false` for STATICCALL/DELEGATECALL frames, which never move value.
+    pub value_bearing: bool,
+    pub gas: String,
+    pub gas_used: String,
+    pub input: String,
+    pub output: Option<String>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Emitted by `EthereumProcessor::observe_block` when an incoming block's
+/// `parent_hash` no longer matches the tip of the chain it has been
+/// tracking, i.e. one or more already-processed blocks were orphaned by a
+/// chain reorganization. Consumers should roll back any blocks,
+/// transactions, and logs keyed by `orphaned_blocks` before re-processing
+/// forward from `common_ancestor_number`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReorgEvent {
+    pub detected_at_block: u64,
+    /// `None` if the reorg reaches back further than the tracked window, in
+    /// which case the exact common ancestor can't be determined from the
+    /// window alone and every tracked block should be treated as suspect.
+    pub common_ancestor_number: Option<u64>,
+    pub common_ancestor_hash: Option<String>,
+    /// Orphaned blocks, oldest first.
+    pub orphaned_blocks: Vec<OrphanedBlock>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanedBlock {
+    pub block_number: u64,
+    pub block_hash: String,
+}
+
 // Address Balance Tracking
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AddressBalance {
@@ -295,6 +424,29 @@ pub struct SyncStatus {
     pub uptime_seconds: u64,
 }
 
+// EIP-1186 `eth_getProof` response shape.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountProof {
+    pub address: String,
+    pub balance: String,
+    pub nonce: String,
+    #[serde(rename = "codeHash")]
+    pub code_hash: String,
+    #[serde(rename = "storageHash")]
+    pub storage_hash: String,
+    #[serde(rename = "accountProof")]
+    pub account_proof: Vec<String>,
+    #[serde(rename = "storageProof")]
+    pub storage_proof: Vec<StorageProofEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageProofEntry {
+    pub key: String,
+    pub value: String,
+    pub proof: Vec<String>,
+}
+
 // Error and Retry Information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessingError {
@@ -9,35 +9,84 @@ use crate::error_handler::{ErrorHandler, ErrorType};
 use crate::metrics::BlockchainMetrics;
 use std::sync::Arc;
 
+/// How many blocks `MongoDBService::find_common_ancestor` will walk
+/// backward before giving up, matching the depth most Ethereum clients
+/// treat as an unrecoverable reorg.
+const DEFAULT_MAX_REORG_DEPTH: u64 = 64;
+
+/// RPC-style block addressing, mirroring OpenEthereum/web3's `BlockId`.
+/// Consumers that previously had to pick an ad-hoc range (`get_blocks_range`)
+/// just to fetch one block can use `MongoDBService::get_block` instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockId {
+    Number(u64),
+    Hash(String),
+    Earliest,
+    Latest,
+}
+
+/// RPC-style transaction addressing. `BlockNumberIndex` resolves through the
+/// existing compound `{block_number, transaction_index}` index rather than a
+/// new one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionId {
+    Hash(String),
+    BlockNumberIndex(u64, u64),
+}
+
 #[derive(Clone)]
 pub struct MongoDBService {
     database: Database,
     error_handler: ErrorHandler,
     metrics: Arc<BlockchainMetrics>,
+    max_reorg_depth: u64,
+    /// How many blocks must bury a block before `mark_finalized_up_to` will
+    /// flag it `finalized`. Defaults to `DEFAULT_MAX_REORG_DEPTH` since a
+    /// block the reorg subsystem can no longer unwind past is, by
+    /// definition, the depth at which it's considered safe.
+    confirmation_depth: u64,
 }
 
 impl MongoDBService {
     pub async fn new(uri: &str, metrics: Arc<BlockchainMetrics>) -> Result<Self> {
         let client = Client::with_uri_str(uri).await?;
         let database = client.database("ethereum_chaindata");
-        
+
         let error_handler = ErrorHandler::new()
             .with_retries(3)
             .with_base_delay(std::time::Duration::from_secs(1))
             .with_max_delay(std::time::Duration::from_secs(10));
 
-        let service = Self { 
+        let service = Self {
             database,
             error_handler,
             metrics,
+            max_reorg_depth: DEFAULT_MAX_REORG_DEPTH,
+            confirmation_depth: DEFAULT_MAX_REORG_DEPTH,
         };
 
         // Create indexes for better performance
         service.create_indexes().await?;
-        
+
         Ok(service)
     }
 
+    pub fn with_max_reorg_depth(mut self, max_reorg_depth: u64) -> Self {
+        self.max_reorg_depth = max_reorg_depth;
+        self
+    }
+
+    pub fn with_confirmation_depth(mut self, confirmation_depth: u64) -> Self {
+        self.confirmation_depth = confirmation_depth;
+        self
+    }
+
+    /// How many blocks a caller should subtract from the current chain tip
+    /// before calling `mark_finalized_up_to`.
+    pub fn confirmation_depth(&self) -> u64 {
+        self.confirmation_depth
+    }
+
     async fn create_indexes(&self) -> Result<()> {
         // Blocks collection indexes
         let blocks_collection: Collection<StoredBlock> = self.database.collection("blocks");
@@ -57,6 +106,9 @@ impl MongoDBService {
             IndexModel::builder()
                 .keys(doc! { "miner": 1 })
                 .build(),
+            IndexModel::builder()
+                .keys(doc! { "finalized": 1, "block_number": -1 })
+                .build(),
         ];
 
         blocks_collection.create_indexes(block_indexes, None).await?;
@@ -87,6 +139,9 @@ impl MongoDBService {
             IndexModel::builder()
                 .keys(doc! { "is_contract_interaction": 1 })
                 .build(),
+            IndexModel::builder()
+                .keys(doc! { "status": 1 })
+                .build(),
         ];
 
         transactions_collection.create_indexes(tx_indexes, None).await?;
@@ -176,10 +231,22 @@ impl MongoDBService {
                             amount: w.amount.to_string(),
                         }).collect()
                     }),
+                    canonical: true,
+                    finalized: false,
+                    finalized_at: None,
                     created_at: Utc::now(),
                 };
 
-                collection.insert_one(stored_block, None).await?;
+                // Upsert keyed on block_number rather than insert_one, so
+                // `handle_reorg` can safely re-call this for the new
+                // canonical chain (and a retried call after a transient
+                // error doesn't hit the unique-index conflict a second
+                // insert_one would).
+                let filter = doc! { "block_number": stored_block.block_number as i64 };
+                let options = mongodb::options::ReplaceOptions::builder()
+                    .upsert(true)
+                    .build();
+                collection.replace_one(filter, stored_block, Some(options)).await?;
                 Ok(())
             })
         };
@@ -198,48 +265,7 @@ impl MongoDBService {
             let tx = tx.clone();
             let block = block.clone();
             Box::pin(async move {
-                let wei_to_eth = Decimal::new(1, 18);
-                let value_eth = Decimal::from_str_exact(&tx.value.to_string()).unwrap_or_default() / wei_to_eth;
-                
-                let is_contract_creation = tx.to.is_none();
-                let is_contract_interaction = !tx.input.0.is_empty() && tx.to.is_some();
-                
-                let access_list = tx.access_list.as_ref().map(|list| {
-                    list.iter().map(|entry| AccessListEntry {
-                        address: format!("{:?}", entry.address),
-                        storage_keys: entry.storage_keys.iter().map(|key| format!("{:?}", key)).collect(),
-                    }).collect()
-                });
-
-                let stored_tx = StoredTransaction {
-                    hash: format!("{:?}", tx.hash),
-                    block_number: block.number.unwrap().as_u64(),
-                    block_hash: format!("{:?}", block.hash.unwrap()),
-                    transaction_index: tx.transaction_index.unwrap().as_u64(),
-                    from: format!("{:?}", tx.from.unwrap_or_default()),
-                    to: tx.to.map(|addr| format!("{:?}", addr)),
-                    value: tx.value.to_string(),
-                    value_eth,
-                    gas_price: tx.gas_price.unwrap_or_default().to_string(),
-                    gas_limit: tx.gas.to_string(),
-                    gas_used: None, // Will be updated when receipt is processed
-                    max_fee_per_gas: tx.max_fee_per_gas.map(|fee| fee.to_string()),
-                    max_priority_fee_per_gas: tx.max_priority_fee_per_gas.map(|fee| fee.to_string()),
-                    nonce: tx.nonce.as_u64(),
-                    input_data: format!("{:?}", tx.input.0),
-                    input_data_length: tx.input.0.len(),
-                    is_contract_creation,
-                    is_contract_interaction,
-                    transaction_type: tx.transaction_type.unwrap_or(0).as_u64() as u8,
-                    access_list,
-                    chain_id: tx.chain_id.map(|id| id.as_u64()),
-                    v: format!("{:?}", tx.v),
-                    r: format!("{:?}", tx.r),
-                    s: format!("{:?}", tx.s),
-                    timestamp: block.timestamp.as_u64(),
-                    created_at: Utc::now(),
-                };
-
+                let stored_tx = build_stored_transaction(&tx, &block);
                 collection.insert_one(stored_tx, None).await?;
                 Ok(())
             })
@@ -253,26 +279,119 @@ impl MongoDBService {
         ).await
     }
 
+    /// Stores every transaction in `txs` with a single `insert_many`,
+    /// `ordered(false)` so a duplicate-key error on an already-seen
+    /// transaction (e.g. a retried partial batch) doesn't abort the rest.
+    /// Per-document failures are logged and counted via
+    /// `BlockchainMetrics::record_error`, but aren't otherwise fatal — the
+    /// caller already gets the overall `Result` from the retry wrapper for
+    /// failures that aren't just duplicates.
+    pub async fn store_transactions_bulk(&self, txs: &[Transaction], block: &Block<Transaction>) -> Result<()> {
+        if txs.is_empty() {
+            return Ok(());
+        }
+
+        let metrics = self.metrics.clone();
+        let operation = || {
+            let collection: Collection<StoredTransaction> = self.database.collection("transactions");
+            let docs: Vec<StoredTransaction> = txs.iter().map(|tx| build_stored_transaction(tx, block)).collect();
+            let metrics = metrics.clone();
+            Box::pin(async move {
+                let options = mongodb::options::InsertManyOptions::builder().ordered(false).build();
+                if let Err(e) = collection.insert_many(docs, Some(options)).await {
+                    log_bulk_write_failures(&e, &metrics);
+                    if !is_duplicate_key_only(&e) {
+                        return Err(e.into());
+                    }
+                }
+                Ok(())
+            })
+        };
+
+        self.error_handler.execute_with_retry_and_error_tracking(
+            operation,
+            ErrorType::DatabaseError,
+            Some(block.number.unwrap().as_u64()),
+            None,
+        ).await
+    }
+
+    /// Applies a `TransactionReceipt` to the already-stored transaction with
+    /// `receipt.transaction_hash`, filling in the fields that weren't known
+    /// at insert time (`gas_used` was left `None` with a comment to that
+    /// effect until this existed). Targets the unique `hash` index, so this
+    /// is safe to call more than once for the same receipt.
+    pub async fn update_transaction_receipt(&self, receipt: &TransactionReceipt) -> Result<()> {
+        let receipt = receipt.clone();
+        let operation = || {
+            let collection: Collection<StoredTransaction> = self.database.collection("transactions");
+            let receipt = receipt.clone();
+            Box::pin(async move {
+                let filter = doc! { "hash": &receipt.transaction_hash };
+                let update = doc! {
+                    "$set": {
+                        "gas_used": &receipt.gas_used,
+                        "effective_gas_price": &receipt.effective_gas_price,
+                        "cumulative_gas_used": &receipt.cumulative_gas_used,
+                        "status": receipt.status.map(|s| s as i64),
+                        "contract_address": &receipt.contract_address,
+                    }
+                };
+                collection.update_one(filter, update, None).await?;
+                Ok(())
+            })
+        };
+
+        self.error_handler.execute_with_retry_and_error_tracking(
+            operation,
+            ErrorType::DatabaseError,
+            Some(receipt.block_number),
+            Some(receipt.transaction_hash.clone()),
+        ).await
+    }
+
+    /// Transactions in `(start_block, end_block)` whose receipt applied
+    /// `status: Some(0)` — i.e. reverted. Reverted swaps/liquidations are a
+    /// significant DeFi signal, hence a dedicated query rather than making
+    /// every caller of `get_transactions_by_address`-style methods filter
+    /// client-side.
+    pub async fn get_failed_transactions(&self, start_block: u64, end_block: u64) -> Result<Vec<StoredTransaction>> {
+        let operation = || {
+            let collection: Collection<StoredTransaction> = self.database.collection("transactions");
+            Box::pin(async move {
+                let filter = doc! {
+                    "block_number": { "$gte": start_block as i64, "$lte": end_block as i64 },
+                    "status": 0i64,
+                };
+                let options = mongodb::options::FindOptions::builder()
+                    .sort(doc! { "block_number": 1 })
+                    .build();
+
+                let mut cursor = collection.find(filter, Some(options)).await?;
+                let mut transactions = Vec::new();
+
+                while let Some(tx) = cursor.next().await {
+                    transactions.push(tx?);
+                }
+
+                Ok(transactions)
+            })
+        };
+
+        self.error_handler.execute_with_retry_and_error_tracking(
+            operation,
+            ErrorType::DatabaseError,
+            Some(start_block),
+            None,
+        ).await
+    }
+
     pub async fn store_event(&self, event: &ContractEvent) -> Result<()> {
         let operation = || {
             let collection: Collection<StoredEvent> = self.database.collection("events");
             let event = event.clone();
             Box::pin(async move {
-                let stored_event = StoredEvent {
-                    transaction_hash: event.transaction_hash.clone(),
-                    block_number: event.block_number,
-                    contract_address: event.contract_address.clone(),
-                    event_name: event.event_name.clone(),
-                    event_signature: event.event_signature.clone(),
-                    topics: event.topics.clone(),
-                    data: event.data.clone(),
-                    decoded_data: event.decoded_data.clone(),
-                    log_index: event.log_index,
-                    removed: event.removed,
-                    timestamp: event.timestamp,
-                    created_at: Utc::now(),
-                };
-
+                let stored_event = build_stored_event(&event);
                 collection.insert_one(stored_event, None).await?;
                 Ok(())
             })
@@ -286,6 +405,39 @@ impl MongoDBService {
         ).await
     }
 
+    /// Bulk variant of `store_event`; see `store_transactions_bulk` for the
+    /// `ordered(false)`/partial-failure semantics.
+    pub async fn store_events_bulk(&self, events: &[ContractEvent]) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let block_number = events[0].block_number;
+        let metrics = self.metrics.clone();
+        let operation = || {
+            let collection: Collection<StoredEvent> = self.database.collection("events");
+            let docs: Vec<StoredEvent> = events.iter().map(build_stored_event).collect();
+            let metrics = metrics.clone();
+            Box::pin(async move {
+                let options = mongodb::options::InsertManyOptions::builder().ordered(false).build();
+                if let Err(e) = collection.insert_many(docs, Some(options)).await {
+                    log_bulk_write_failures(&e, &metrics);
+                    if !is_duplicate_key_only(&e) {
+                        return Err(e.into());
+                    }
+                }
+                Ok(())
+            })
+        };
+
+        self.error_handler.execute_with_retry_and_error_tracking(
+            operation,
+            ErrorType::DatabaseError,
+            Some(block_number),
+            None,
+        ).await
+    }
+
     pub async fn store_token_transfer(&self, transfer: &TokenTransferEvent) -> Result<()> {
         let operation = || {
             let collection: Collection<TokenTransferEvent> = self.database.collection("token_transfers");
@@ -304,6 +456,39 @@ impl MongoDBService {
         ).await
     }
 
+    /// Bulk variant of `store_token_transfer`; see `store_transactions_bulk`
+    /// for the `ordered(false)`/partial-failure semantics.
+    pub async fn store_token_transfers_bulk(&self, transfers: &[TokenTransferEvent]) -> Result<()> {
+        if transfers.is_empty() {
+            return Ok(());
+        }
+
+        let block_number = transfers[0].block_number;
+        let metrics = self.metrics.clone();
+        let operation = || {
+            let collection: Collection<TokenTransferEvent> = self.database.collection("token_transfers");
+            let docs = transfers.to_vec();
+            let metrics = metrics.clone();
+            Box::pin(async move {
+                let options = mongodb::options::InsertManyOptions::builder().ordered(false).build();
+                if let Err(e) = collection.insert_many(docs, Some(options)).await {
+                    log_bulk_write_failures(&e, &metrics);
+                    if !is_duplicate_key_only(&e) {
+                        return Err(e.into());
+                    }
+                }
+                Ok(())
+            })
+        };
+
+        self.error_handler.execute_with_retry_and_error_tracking(
+            operation,
+            ErrorType::DatabaseError,
+            Some(block_number),
+            None,
+        ).await
+    }
+
     pub async fn store_defi_event(&self, event: &DeFiEvent) -> Result<()> {
         let operation = || {
             let collection: Collection<DeFiEvent> = self.database.collection("defi_events");
@@ -450,6 +635,354 @@ impl MongoDBService {
         ).await
     }
 
+    pub async fn get_block_by_number(&self, block_number: u64) -> Result<Option<StoredBlock>> {
+        let operation = || {
+            let collection: Collection<StoredBlock> = self.database.collection("blocks");
+            Box::pin(async move {
+                let filter = doc! { "block_number": block_number as i64 };
+                Ok(collection.find_one(filter, None).await?)
+            })
+        };
+
+        self.error_handler.execute_with_retry_and_error_tracking(
+            operation,
+            ErrorType::DatabaseError,
+            Some(block_number),
+            None,
+        ).await
+    }
+
+    /// Resolves a `BlockId` to a stored block. `Earliest`/`Latest` resolve
+    /// against the lowest/highest stored `block_number`, same as
+    /// `get_last_processed_block` does for `Latest`.
+    pub async fn get_block(&self, id: BlockId) -> Result<Option<StoredBlock>> {
+        match id {
+            BlockId::Number(number) => self.get_block_by_number(number).await,
+            BlockId::Hash(hash) => {
+                let operation = || {
+                    let collection: Collection<StoredBlock> = self.database.collection("blocks");
+                    let hash = hash.clone();
+                    Box::pin(async move {
+                        let filter = doc! { "block_hash": hash };
+                        Ok(collection.find_one(filter, None).await?)
+                    })
+                };
+
+                self.error_handler.execute_with_retry_and_error_tracking(
+                    operation,
+                    ErrorType::DatabaseError,
+                    None,
+                    None,
+                ).await
+            }
+            BlockId::Earliest | BlockId::Latest => {
+                let sort_direction = if matches!(id, BlockId::Latest) { -1 } else { 1 };
+                let operation = || {
+                    let collection: Collection<StoredBlock> = self.database.collection("blocks");
+                    Box::pin(async move {
+                        let options = mongodb::options::FindOptions::builder()
+                            .sort(doc! { "block_number": sort_direction })
+                            .build();
+                        Ok(collection.find_one(None, Some(options)).await?)
+                    })
+                };
+
+                self.error_handler.execute_with_retry_and_error_tracking(
+                    operation,
+                    ErrorType::DatabaseError,
+                    None,
+                    None,
+                ).await
+            }
+        }
+    }
+
+    /// Resolves a `TransactionId` to a stored transaction.
+    pub async fn get_transaction(&self, id: TransactionId) -> Result<Option<StoredTransaction>> {
+        let (filter, block_number) = match id {
+            TransactionId::Hash(hash) => (doc! { "hash": hash }, None),
+            TransactionId::BlockNumberIndex(block_number, transaction_index) => (
+                doc! { "block_number": block_number as i64, "transaction_index": transaction_index as i64 },
+                Some(block_number),
+            ),
+        };
+
+        let operation = || {
+            let collection: Collection<StoredTransaction> = self.database.collection("transactions");
+            let filter = filter.clone();
+            Box::pin(async move { Ok(collection.find_one(filter, None).await?) })
+        };
+
+        self.error_handler.execute_with_retry_and_error_tracking(
+            operation,
+            ErrorType::DatabaseError,
+            block_number,
+            None,
+        ).await
+    }
+
+    /// Flags every stored block at or below `block_number` `finalized`,
+    /// stamping `finalized_at`. Idempotent: blocks already flagged are
+    /// excluded from the filter so a retried or overlapping call re-applies
+    /// the same `$set` to a shrinking (eventually empty) set.
+    pub async fn mark_finalized_up_to(&self, block_number: u64) -> Result<()> {
+        let operation = || {
+            let collection: Collection<StoredBlock> = self.database.collection("blocks");
+            Box::pin(async move {
+                let filter = doc! {
+                    "block_number": { "$lte": block_number as i64 },
+                    "finalized": { "$ne": true },
+                };
+                let update = doc! {
+                    "$set": { "finalized": true, "finalized_at": Utc::now() }
+                };
+                collection.update_many(filter, update, None).await?;
+                Ok(())
+            })
+        };
+
+        self.error_handler.execute_with_retry_and_error_tracking(
+            operation,
+            ErrorType::DatabaseError,
+            Some(block_number),
+            None,
+        ).await
+    }
+
+    /// The highest block number flagged `finalized`, i.e. the safe
+    /// watermark below which downstream analytics can treat stored data as
+    /// immutable. `None` if nothing has been finalized yet.
+    pub async fn get_finalization_frontier(&self) -> Result<Option<u64>> {
+        let operation = || {
+            let collection: Collection<StoredBlock> = self.database.collection("blocks");
+            Box::pin(async move {
+                let filter = doc! { "finalized": true };
+                let options = mongodb::options::FindOptions::builder()
+                    .sort(doc! { "block_number": -1 })
+                    .build();
+                let block = collection.find_one(filter, Some(options)).await?;
+                Ok(block.map(|b| b.block_number))
+            })
+        };
+
+        self.error_handler.execute_with_retry_and_error_tracking(
+            operation,
+            ErrorType::DatabaseError,
+            None,
+            None,
+        ).await
+    }
+
+    /// Finds the block number where `new_blocks` — ordered newest-first,
+    /// i.e. `new_blocks[0]` is the new tip and `new_blocks[k]`'s parent is
+    /// `new_blocks[k + 1]` — rejoins the chain already stored in MongoDB.
+    /// Modeled on OpenEthereum's TreeRoute: walk backward one block at a
+    /// time, at each depth comparing the stored block one below the
+    /// candidate against that candidate's `parent_hash`, stopping as soon as
+    /// they agree. Bounded by `max_reorg_depth`; if `new_blocks` is
+    /// exhausted or the depth bound is hit first without a match, this is a
+    /// reorg too deep to reconcile automatically and is returned as an
+    /// error.
+    pub async fn find_common_ancestor(&self, new_blocks: &[Block<Transaction>]) -> Result<u64> {
+        if new_blocks.is_empty() {
+            return Err(anyhow::anyhow!("find_common_ancestor called with no candidate blocks"));
+        }
+
+        for (depth, candidate) in new_blocks.iter().enumerate() {
+            if depth as u64 > self.max_reorg_depth {
+                break;
+            }
+
+            let candidate_number = candidate.number.unwrap().as_u64();
+            if candidate_number == 0 {
+                return Ok(0);
+            }
+
+            let parent_hash = format!("{:?}", candidate.parent_hash);
+            match self.get_block_by_number(candidate_number - 1).await? {
+                Some(stored) if stored.block_hash == parent_hash => return Ok(candidate_number - 1),
+                Some(_) => continue,
+                // Nothing stored that far back (e.g. this node only recently
+                // started syncing) — there's nothing to reconcile against,
+                // so treat this height as the ancestor.
+                None => return Ok(candidate_number - 1),
+            }
+        }
+
+        Err(anyhow::anyhow!(
+            "reorg common-ancestor search exceeded max depth of {} blocks without finding a match",
+            self.max_reorg_depth
+        ))
+    }
+
+    /// Rolls back everything stored above `common_ancestor` and re-inserts
+    /// `new_blocks` (any order; only entries above `common_ancestor` are
+    /// written) as the new canonical chain. Marking documents `canonical:
+    /// false` rather than deleting them, and re-inserting blocks through
+    /// `store_block`'s upsert, makes this idempotent: a retried call (e.g.
+    /// after a transient error mid-way through) re-applies the same `$set`
+    /// and the same upserts with no additional effect.
+    ///
+    /// Returns the hashes of every transaction invalidated by the rollback,
+    /// so downstream consumers (e.g. the nonce manager, pending-tx trackers)
+    /// can react.
+    pub async fn handle_reorg(&self, common_ancestor: u64, new_blocks: &[Block<Transaction>]) -> Result<Vec<String>> {
+        let finalization_frontier = self.get_finalization_frontier().await?;
+        if let Some(frontier) = finalization_frontier {
+            if frontier > common_ancestor {
+                return Err(anyhow::anyhow!(
+                    "refusing to roll back finalized block(s): finalization frontier {} is above common ancestor {}",
+                    frontier, common_ancestor
+                ));
+            }
+        }
+
+        let operation = || {
+            let blocks_collection: Collection<StoredBlock> = self.database.collection("blocks");
+            let transactions_collection: Collection<StoredTransaction> = self.database.collection("transactions");
+            let events_collection: Collection<StoredEvent> = self.database.collection("events");
+            Box::pin(async move {
+                let filter = doc! { "block_number": { "$gt": common_ancestor as i64 } };
+
+                let mut invalidated_tx_hashes = Vec::new();
+                let mut cursor = transactions_collection.find(filter.clone(), None).await?;
+                while let Some(tx) = cursor.next().await {
+                    invalidated_tx_hashes.push(tx?.hash);
+                }
+
+                let mark_non_canonical = doc! { "$set": { "canonical": false } };
+                blocks_collection.update_many(filter.clone(), mark_non_canonical.clone(), None).await?;
+                transactions_collection.update_many(filter.clone(), mark_non_canonical.clone(), None).await?;
+                events_collection.update_many(filter, mark_non_canonical, None).await?;
+
+                Ok(invalidated_tx_hashes)
+            })
+        };
+
+        let invalidated_tx_hashes = self.error_handler.execute_with_retry_and_error_tracking(
+            operation,
+            ErrorType::DatabaseError,
+            Some(common_ancestor),
+            None,
+        ).await?;
+
+        for block in new_blocks {
+            if block.number.unwrap().as_u64() > common_ancestor {
+                self.store_block(block).await?;
+            }
+        }
+
+        Ok(invalidated_tx_hashes)
+    }
+
+    pub async fn get_events_in_range(&self, start_block: u64, end_block: u64) -> Result<Vec<StoredEvent>> {
+        let operation = || {
+            let collection: Collection<StoredEvent> = self.database.collection("events");
+            let start_block = start_block;
+            let end_block = end_block;
+            Box::pin(async move {
+                let filter = doc! {
+                    "block_number": {
+                        "$gte": start_block,
+                        "$lte": end_block
+                    }
+                };
+
+                let options = mongodb::options::FindOptions::builder()
+                    .sort(doc! { "block_number": 1 })
+                    .build();
+
+                let mut cursor = collection.find(filter, Some(options)).await?;
+                let mut events = Vec::new();
+
+                while let Some(event) = cursor.next().await {
+                    events.push(event?);
+                }
+
+                Ok(events)
+            })
+        };
+
+        self.error_handler.execute_with_retry_and_error_tracking(
+            operation,
+            ErrorType::DatabaseError,
+            Some(start_block),
+            None,
+        ).await
+    }
+
+    pub async fn get_token_transfers_in_range(&self, start_block: u64, end_block: u64) -> Result<Vec<TokenTransferEvent>> {
+        let operation = || {
+            let collection: Collection<TokenTransferEvent> = self.database.collection("token_transfers");
+            let start_block = start_block;
+            let end_block = end_block;
+            Box::pin(async move {
+                let filter = doc! {
+                    "block_number": {
+                        "$gte": start_block,
+                        "$lte": end_block
+                    }
+                };
+
+                let options = mongodb::options::FindOptions::builder()
+                    .sort(doc! { "block_number": 1 })
+                    .build();
+
+                let mut cursor = collection.find(filter, Some(options)).await?;
+                let mut transfers = Vec::new();
+
+                while let Some(transfer) = cursor.next().await {
+                    transfers.push(transfer?);
+                }
+
+                Ok(transfers)
+            })
+        };
+
+        self.error_handler.execute_with_retry_and_error_tracking(
+            operation,
+            ErrorType::DatabaseError,
+            Some(start_block),
+            None,
+        ).await
+    }
+
+    pub async fn get_defi_events_in_range(&self, start_block: u64, end_block: u64) -> Result<Vec<DeFiEvent>> {
+        let operation = || {
+            let collection: Collection<DeFiEvent> = self.database.collection("defi_events");
+            let start_block = start_block;
+            let end_block = end_block;
+            Box::pin(async move {
+                let filter = doc! {
+                    "block_number": {
+                        "$gte": start_block,
+                        "$lte": end_block
+                    }
+                };
+
+                let options = mongodb::options::FindOptions::builder()
+                    .sort(doc! { "block_number": 1 })
+                    .build();
+
+                let mut cursor = collection.find(filter, Some(options)).await?;
+                let mut events = Vec::new();
+
+                while let Some(event) = cursor.next().await {
+                    events.push(event?);
+                }
+
+                Ok(events)
+            })
+        };
+
+        self.error_handler.execute_with_retry_and_error_tracking(
+            operation,
+            ErrorType::DatabaseError,
+            Some(start_block),
+            None,
+        ).await
+    }
+
     pub async fn get_transactions_by_address(&self, address: &str, limit: i64) -> Result<Vec<StoredTransaction>> {
         let operation = || {
             let collection: Collection<StoredTransaction> = self.database.collection("transactions");
@@ -636,3 +1169,104 @@ impl MongoDBService {
         ).await
     }
 }
+
+fn build_stored_transaction(tx: &Transaction, block: &Block<Transaction>) -> StoredTransaction {
+    let wei_to_eth = Decimal::new(1, 18);
+    let value_eth = Decimal::from_str_exact(&tx.value.to_string()).unwrap_or_default() / wei_to_eth;
+
+    let is_contract_creation = tx.to.is_none();
+    let is_contract_interaction = !tx.input.0.is_empty() && tx.to.is_some();
+
+    let access_list = tx.access_list.as_ref().map(|list| {
+        list.iter().map(|entry| AccessListEntry {
+            address: format!("{:?}", entry.address),
+            storage_keys: entry.storage_keys.iter().map(|key| format!("{:?}", key)).collect(),
+        }).collect()
+    });
+
+    StoredTransaction {
+        hash: format!("{:?}", tx.hash),
+        block_number: block.number.unwrap().as_u64(),
+        block_hash: format!("{:?}", block.hash.unwrap()),
+        transaction_index: tx.transaction_index.unwrap().as_u64(),
+        from: format!("{:?}", tx.from.unwrap_or_default()),
+        to: tx.to.map(|addr| format!("{:?}", addr)),
+        value: tx.value.to_string(),
+        value_eth,
+        gas_price: tx.gas_price.unwrap_or_default().to_string(),
+        gas_limit: tx.gas.to_string(),
+        gas_used: None, // Will be updated by update_transaction_receipt
+        effective_gas_price: None,
+        cumulative_gas_used: None,
+        status: None,
+        contract_address: None,
+        max_fee_per_gas: tx.max_fee_per_gas.map(|fee| fee.to_string()),
+        max_priority_fee_per_gas: tx.max_priority_fee_per_gas.map(|fee| fee.to_string()),
+        nonce: tx.nonce.as_u64(),
+        input_data: format!("{:?}", tx.input.0),
+        input_data_length: tx.input.0.len(),
+        is_contract_creation,
+        is_contract_interaction,
+        transaction_type: tx.transaction_type.unwrap_or(0).as_u64() as u8,
+        access_list,
+        chain_id: tx.chain_id.map(|id| id.as_u64()),
+        v: format!("{:?}", tx.v),
+        r: format!("{:?}", tx.r),
+        s: format!("{:?}", tx.s),
+        timestamp: block.timestamp.as_u64(),
+        canonical: true,
+        created_at: Utc::now(),
+    }
+}
+
+fn build_stored_event(event: &ContractEvent) -> StoredEvent {
+    StoredEvent {
+        transaction_hash: event.transaction_hash.clone(),
+        block_number: event.block_number,
+        contract_address: event.contract_address.clone(),
+        event_name: event.event_name.clone(),
+        event_signature: event.event_signature.clone(),
+        topics: event.topics.clone(),
+        data: event.data.clone(),
+        decoded_data: event.decoded_data.clone(),
+        log_index: event.log_index,
+        removed: event.removed,
+        timestamp: event.timestamp,
+        canonical: true,
+        created_at: Utc::now(),
+    }
+}
+
+/// Logs and counts each per-document failure in a `mongodb::error::Error`
+/// returned from `insert_many`, so an `ordered(false)` batch's partial
+/// failures are observable instead of silently swallowed.
+fn log_bulk_write_failures(error: &mongodb::error::Error, metrics: &Arc<BlockchainMetrics>) {
+    if let mongodb::error::ErrorKind::BulkWrite(bulk_write_failure) = error.kind.as_ref() {
+        if let Some(write_errors) = &bulk_write_failure.write_errors {
+            for write_error in write_errors {
+                metrics.record_error();
+                if write_error.code != 11000 {
+                    log::warn!(
+                        "Bulk write failed for document at index {}: {}",
+                        write_error.index, write_error.message
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Whether every failure in a `mongodb::error::Error` from `insert_many` is
+/// a duplicate-key error (code 11000) — the one failure mode this batch API
+/// is explicitly designed to tolerate (a retried or overlapping batch
+/// re-inserting documents it, or a concurrent writer, already stored).
+fn is_duplicate_key_only(error: &mongodb::error::Error) -> bool {
+    match error.kind.as_ref() {
+        mongodb::error::ErrorKind::BulkWrite(bulk_write_failure) => bulk_write_failure
+            .write_errors
+            .as_ref()
+            .map(|errors| errors.iter().all(|e| e.code == 11000))
+            .unwrap_or(false),
+        _ => false,
+    }
+}
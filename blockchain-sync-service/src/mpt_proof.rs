@@ -0,0 +1,196 @@
+use anyhow::Result;
+use rlp::Rlp;
+use web3::types::{Bytes, H256, U256};
+
+/// Account state recovered and verified from an `eth_getProof` account proof,
+/// mirroring the fields of an Ethereum state trie leaf.
+#[derive(Debug, Clone)]
+pub struct VerifiedAccount {
+    pub balance: U256,
+    pub nonce: U256,
+    pub code_hash: H256,
+    pub storage_hash: H256,
+}
+
+/// Walks an RLP-encoded Merkle-Patricia proof (as returned by `eth_getProof`)
+/// from `root` down to the leaf for `key`, returning the leaf's RLP-encoded
+/// value on success. `key` is the raw address for an account proof or the
+/// raw slot for a storage proof; it is hashed with `keccak256` here to get
+/// the trie path.
+pub fn verify_proof(root: H256, key: &[u8], proof: &[Bytes]) -> Result<Vec<u8>> {
+    let nibbles = to_nibbles(key);
+    let mut expected_hash = root;
+    let mut nibble_index = 0usize;
+
+    for (depth, node_bytes) in proof.iter().enumerate() {
+        let node_hash = H256::from_slice(&web3::signing::keccak256(&node_bytes.0));
+        // The root node is matched against the trusted state root; every
+        // subsequent node must match the hash referenced by its parent.
+        if node_hash != expected_hash {
+            anyhow::bail!(
+                "proof node {} hash {:?} does not match expected {:?}",
+                depth,
+                node_hash,
+                expected_hash
+            );
+        }
+
+        let rlp = Rlp::new(&node_bytes.0);
+        let item_count = rlp.item_count()?;
+
+        match item_count {
+            17 => {
+                // Branch node: 16 children + value.
+                if nibble_index >= nibbles.len() {
+                    let value: Vec<u8> = rlp.at(16)?.data()?.to_vec();
+                    return Ok(value);
+                }
+                let nibble = nibbles[nibble_index] as usize;
+                let child = rlp.at(nibble)?;
+                if child.is_empty() {
+                    anyhow::bail!("proof branch has no child for nibble {}", nibble);
+                }
+                let child_data = child.data().unwrap_or_default();
+                if child_data.len() == 32 {
+                    expected_hash = H256::from_slice(child_data);
+                    nibble_index += 1;
+                } else {
+                    // Inlined child (< 32 bytes): this is the last node.
+                    return extract_value_from_inline(child)?
+                        .ok_or_else(|| anyhow::anyhow!("inline branch child has no value"));
+                }
+            }
+            2 => {
+                // Leaf or extension node: [encoded_path, value_or_child].
+                let (path_nibbles, is_leaf) = decode_path(rlp.at(0)?.data()?)?;
+                // A well-formed proof never consumes more nibbles than `key`
+                // has left; a crafted node claiming a longer hex-prefix path
+                // than the remaining key would otherwise panic on the slice
+                // below instead of being rejected as invalid.
+                if nibble_index > nibbles.len() || path_nibbles.len() > nibbles.len() - nibble_index {
+                    anyhow::bail!(
+                        "proof node {} claims a path longer than the remaining key nibbles",
+                        depth
+                    );
+                }
+                if !nibbles[nibble_index..].starts_with(&path_nibbles) {
+                    anyhow::bail!("proof path does not match the requested key at depth {}", depth);
+                }
+                nibble_index += path_nibbles.len();
+
+                if is_leaf {
+                    let value: Vec<u8> = rlp.at(1)?.data()?.to_vec();
+                    return Ok(value);
+                } else {
+                    let child = rlp.at(1)?;
+                    let child_data = child.data()?;
+                    if child_data.len() == 32 {
+                        expected_hash = H256::from_slice(child_data);
+                    } else {
+                        anyhow::bail!("extension node child must be a 32-byte hash reference");
+                    }
+                }
+            }
+            other => anyhow::bail!("unexpected MPT node with {} items", other),
+        }
+    }
+
+    anyhow::bail!("proof ended without reaching a leaf node")
+}
+
+fn extract_value_from_inline(rlp: Rlp) -> Result<Option<Vec<u8>>> {
+    let item_count = rlp.item_count()?;
+    if item_count == 2 {
+        let data = rlp.at(1)?.data()?.to_vec();
+        Ok(Some(data))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Decodes a hex-prefix encoded path, returning `(nibbles, is_leaf)`.
+fn decode_path(encoded: &[u8]) -> Result<(Vec<u8>, bool)> {
+    if encoded.is_empty() {
+        anyhow::bail!("empty hex-prefix path");
+    }
+    let first_nibble = encoded[0] >> 4;
+    let is_leaf = first_nibble == 2 || first_nibble == 3;
+    let is_odd = first_nibble == 1 || first_nibble == 3;
+
+    let mut nibbles = Vec::new();
+    if is_odd {
+        nibbles.push(encoded[0] & 0x0f);
+    }
+    for byte in &encoded[1..] {
+        nibbles.push(byte >> 4);
+        nibbles.push(byte & 0x0f);
+    }
+
+    Ok((nibbles, is_leaf))
+}
+
+fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    let hash = web3::signing::keccak256(bytes);
+    let mut nibbles = Vec::with_capacity(64);
+    for byte in hash {
+        nibbles.push(byte >> 4);
+        nibbles.push(byte & 0x0f);
+    }
+    nibbles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rlp::RlpStream;
+
+    /// A crafted leaf/extension node whose hex-prefix-encoded path claims
+    /// more nibbles than the key has left must be rejected with an `Err`,
+    /// not panic on `nibbles[nibble_index..]`. Builds a single-node proof
+    /// (so its hash trivially matches the root it's hashed into) whose path
+    /// is longer than any real 64-nibble key, the scenario a malicious or
+    /// buggy RPC endpoint could otherwise use to crash the service instead
+    /// of being rejected.
+    #[test]
+    fn oversized_leaf_path_is_rejected_not_panicking() {
+        let key = b"attacker-controlled-key";
+        let key_nibbles = to_nibbles(key).len();
+
+        // Hex-prefix encoding for an even-length leaf path (flag nibble 0x2,
+        // even nibble count => first byte 0x20) with more nibbles than the
+        // key has.
+        let oversized_path_nibbles = key_nibbles + 6;
+        let mut path_bytes = vec![0x20u8];
+        path_bytes.extend(std::iter::repeat(0u8).take(oversized_path_nibbles / 2));
+
+        let mut node = RlpStream::new_list(2);
+        node.append(&path_bytes.as_slice());
+        node.append(&[0u8; 4].as_slice()); // leaf value, content irrelevant
+        let node_bytes = node.out().to_vec();
+
+        let root = H256::from_slice(&web3::signing::keccak256(&node_bytes));
+        let proof = vec![Bytes(node_bytes)];
+
+        let result = verify_proof(root, key, &proof);
+        assert!(result.is_err(), "an oversized hex-prefix path must be rejected, not panic");
+    }
+}
+
+/// Decodes an RLP-encoded account leaf value: `[nonce, balance, storageHash, codeHash]`.
+pub fn decode_account(rlp_bytes: &[u8]) -> Result<VerifiedAccount> {
+    let rlp = Rlp::new(rlp_bytes);
+    if rlp.item_count()? != 4 {
+        anyhow::bail!("account RLP must have 4 fields");
+    }
+    let nonce: U256 = rlp.val_at(0)?;
+    let balance: U256 = rlp.val_at(1)?;
+    let storage_hash_bytes: Vec<u8> = rlp.val_at(2)?;
+    let code_hash_bytes: Vec<u8> = rlp.val_at(3)?;
+
+    Ok(VerifiedAccount {
+        balance,
+        nonce,
+        code_hash: H256::from_slice(&code_hash_bytes),
+        storage_hash: H256::from_slice(&storage_hash_bytes),
+    })
+}
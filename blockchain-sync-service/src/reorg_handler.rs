@@ -0,0 +1,119 @@
+//! Rollback of previously-stored/published data after `EthereumProcessor`'s
+//! `observe_block` detects a reorg via `parent_hash` linkage. Detection
+//! lives in `ethereum_processor.rs`; this module handles the consequence —
+//! re-publishing every affected `ContractEvent`/`TokenTransferEvent`/
+//! `DeFiEvent` as `removed = true` and rolling `SyncStatus.last_processed_block`
+//! back to the common ancestor.
+
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::kafka_producer::KafkaProducerService;
+use crate::metrics::BlockchainMetrics;
+use crate::models::{ContractEvent, DeFiEvent, ReorgEvent, StoredEvent, TokenTransferEvent};
+use crate::mongodb_client::MongoDBService;
+
+/// Re-publishes orphaned data as removed and rewinds sync progress once a
+/// reorg has been detected.
+pub struct ReorgHandler {
+    mongodb: Arc<MongoDBService>,
+    kafka: Arc<KafkaProducerService>,
+    metrics: Arc<BlockchainMetrics>,
+}
+
+impl ReorgHandler {
+    pub fn new(
+        mongodb: Arc<MongoDBService>,
+        kafka: Arc<KafkaProducerService>,
+        metrics: Arc<BlockchainMetrics>,
+    ) -> Self {
+        Self { mongodb, kafka, metrics }
+    }
+
+    /// Rolls back every event in `[first orphaned block, detected_at_block]`
+    /// for `service_name`: re-stores and re-publishes each record with
+    /// `removed = true`, then rewinds `SyncStatus.last_processed_block` to
+    /// the reorg's common ancestor (or one below the first orphaned block,
+    /// if the ancestor fell outside the tracked window).
+    pub async fn handle_reorg(&self, service_name: &str, reorg: &ReorgEvent) -> Result<()> {
+        let Some(first_orphaned) = reorg.orphaned_blocks.first() else {
+            return Ok(());
+        };
+        let start_block = first_orphaned.block_number;
+        let end_block = reorg.detected_at_block;
+        let depth = reorg.orphaned_blocks.len() as u64;
+
+        self.metrics.record_reorg(depth);
+
+        let events = self.mongodb.get_events_in_range(start_block, end_block).await?;
+        for event in &events {
+            self.rollback_event(event).await?;
+        }
+
+        let transfers = self.mongodb.get_token_transfers_in_range(start_block, end_block).await?;
+        for transfer in &transfers {
+            self.rollback_token_transfer(transfer).await?;
+        }
+
+        let defi_events = self.mongodb.get_defi_events_in_range(start_block, end_block).await?;
+        for defi_event in &defi_events {
+            self.rollback_defi_event(defi_event).await?;
+        }
+
+        self.rewind_sync_status(service_name, reorg, start_block).await?;
+
+        Ok(())
+    }
+
+    async fn rollback_event(&self, stored: &StoredEvent) -> Result<()> {
+        let removed_event = ContractEvent {
+            transaction_hash: stored.transaction_hash.clone(),
+            block_number: stored.block_number,
+            contract_address: stored.contract_address.clone(),
+            event_name: stored.event_name.clone(),
+            event_signature: stored.event_signature.clone(),
+            topics: stored.topics.clone(),
+            data: stored.data.clone(),
+            decoded_data: stored.decoded_data.clone(),
+            log_index: stored.log_index,
+            removed: true,
+            timestamp: stored.timestamp,
+            created_at: stored.created_at,
+        };
+
+        self.mongodb.store_event(&removed_event).await?;
+        self.kafka.send_contract_event(&removed_event).await?;
+        Ok(())
+    }
+
+    async fn rollback_token_transfer(&self, transfer: &TokenTransferEvent) -> Result<()> {
+        let mut removed_transfer = transfer.clone();
+        removed_transfer.removed = true;
+
+        self.mongodb.store_token_transfer(&removed_transfer).await?;
+        self.kafka.send_token_transfer_event(&removed_transfer).await?;
+        Ok(())
+    }
+
+    async fn rollback_defi_event(&self, event: &DeFiEvent) -> Result<()> {
+        let mut removed_event = event.clone();
+        removed_event.removed = true;
+
+        self.mongodb.store_defi_event(&removed_event).await?;
+        self.kafka.send_defi_event(&removed_event).await?;
+        Ok(())
+    }
+
+    async fn rewind_sync_status(&self, service_name: &str, reorg: &ReorgEvent, start_block: u64) -> Result<()> {
+        let Some(mut status) = self.mongodb.get_sync_status(service_name).await? else {
+            return Ok(());
+        };
+
+        status.last_processed_block = reorg.common_ancestor_number.unwrap_or_else(|| start_block.saturating_sub(1));
+        status.error_count += 1;
+
+        self.mongodb.store_sync_status(&status).await?;
+        self.kafka.send_sync_status(&status).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+//! Samples this process's own RSS and CPU utilization from procfs, so the
+//! metrics endpoint can report real numbers instead of the hardcoded
+//! constants it used to. Falls back to `0` wherever `/proc/self` can't be
+//! read (e.g. a non-Linux host), rather than failing the whole sample.
+
+use std::fs;
+use std::sync::Mutex;
+use std::time::Instant;
+
+pub struct ResourceSample {
+    pub memory_bytes: u64,
+    pub cpu_percent: f64,
+}
+
+struct PreviousSample {
+    cpu_time_secs: f64,
+    wall_clock: Instant,
+}
+
+static PREVIOUS: Mutex<Option<PreviousSample>> = Mutex::new(None);
+
+/// Reads current RSS and computes CPU utilization (as a percentage of one
+/// core) over the time since the last call to this function. Returns `0.0`
+/// CPU usage on the first call, since there's no prior window to measure
+/// against yet.
+pub fn sample() -> ResourceSample {
+    let memory_bytes = read_rss_bytes().unwrap_or(0);
+    let cpu_percent = read_cpu_time_secs()
+        .map(compute_cpu_percent)
+        .unwrap_or(0.0);
+
+    ResourceSample { memory_bytes, cpu_percent }
+}
+
+fn compute_cpu_percent(cpu_time_secs: f64) -> f64 {
+    let now = Instant::now();
+    let mut previous = PREVIOUS.lock().unwrap();
+
+    let percent = match previous.as_ref() {
+        Some(prev) => {
+            let elapsed = now.duration_since(prev.wall_clock).as_secs_f64();
+            let cpu_delta = cpu_time_secs - prev.cpu_time_secs;
+            if elapsed > 0.0 { (cpu_delta / elapsed * 100.0).max(0.0) } else { 0.0 }
+        }
+        None => 0.0,
+    };
+
+    *previous = Some(PreviousSample { cpu_time_secs, wall_clock: now });
+    percent
+}
+
+/// RSS in bytes, from `/proc/self/statm` (field 2, in pages).
+fn read_rss_bytes() -> Option<u64> {
+    let statm = fs::read_to_string("/proc/self/statm").ok()?;
+    let rss_pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    const PAGE_SIZE_BYTES: u64 = 4096;
+    Some(rss_pages * PAGE_SIZE_BYTES)
+}
+
+/// Total process CPU time (user + system), in seconds, from
+/// `/proc/self/stat` fields 14/15 (utime/stime, in clock ticks).
+fn read_cpu_time_secs() -> Option<f64> {
+    let stat = fs::read_to_string("/proc/self/stat").ok()?;
+    // The comm field is parenthesized and may itself contain spaces, so
+    // split on the closing paren and index the remaining fields from there.
+    let after_comm = stat.rsplit(')').next()?;
+    let fields: Vec<&str> = after_comm.split_whitespace().collect();
+    let utime: f64 = fields.get(11)?.parse().ok()?;
+    let stime: f64 = fields.get(12)?.parse().ok()?;
+    const USER_HZ: f64 = 100.0;
+    Some((utime + stime) / USER_HZ)
+}
@@ -0,0 +1,197 @@
+use anyhow::Result;
+use jsonrpsee::core::{async_trait, SubscriptionResult};
+use jsonrpsee::proc_macros::rpc;
+use jsonrpsee::server::{ServerBuilder, ServerHandle};
+use jsonrpsee::types::ErrorObjectOwned;
+use jsonrpsee::PendingSubscriptionSink;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, RwLock};
+use web3::types::{Log, H160, H256};
+
+use crate::blockchain::BlockchainClient;
+
+/// Configuration for the front-facing JSON-RPC proxy.
+#[derive(Debug, Clone)]
+pub struct RpcConfig {
+    pub with_http: bool,
+    pub with_ws: bool,
+    pub http_addr: String,
+    pub ws_addr: String,
+    /// How often the background head-poller checks `eth_blockNumber`.
+    pub poll_interval: Duration,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            with_http: true,
+            with_ws: true,
+            http_addr: "0.0.0.0:8545".to_string(),
+            ws_addr: "0.0.0.0:8546".to_string(),
+            poll_interval: Duration::from_secs(2),
+        }
+    }
+}
+
+/// The latest observed chain head, shared between the poller task and
+/// `newHeads` subscribers so a burst of WS clients doesn't translate into a
+/// burst of upstream RPC calls.
+#[derive(Debug, Clone, Default)]
+pub struct ChainHead {
+    pub number: u64,
+}
+
+#[rpc(server, namespace = "eth")]
+pub trait EthApi {
+    #[method(name = "blockNumber")]
+    async fn block_number(&self) -> Result<u64, ErrorObjectOwned>;
+
+    #[method(name = "getBalance")]
+    async fn get_balance(&self, address: String) -> Result<String, ErrorObjectOwned>;
+
+    #[method(name = "getLogs")]
+    async fn get_logs(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        addresses: Vec<H160>,
+        topics: Vec<H256>,
+    ) -> Result<Vec<Log>, ErrorObjectOwned>;
+
+    #[subscription(name = "subscribe" => "subscription", unsubscribe = "unsubscribe", item = u64)]
+    async fn subscribe_new_heads(&self) -> SubscriptionResult;
+}
+
+pub struct EthApiImpl {
+    client: Arc<BlockchainClient>,
+    chain_head: Arc<RwLock<ChainHead>>,
+    new_heads: broadcast::Sender<u64>,
+}
+
+#[async_trait]
+impl EthApiServer for EthApiImpl {
+    async fn block_number(&self) -> Result<u64, ErrorObjectOwned> {
+        Ok(self.chain_head.read().await.number)
+    }
+
+    async fn get_balance(&self, address: String) -> Result<String, ErrorObjectOwned> {
+        self.client
+            .get_balance(&address)
+            .await
+            .map_err(|e| ErrorObjectOwned::owned(-32000, e.to_string(), None::<()>))
+    }
+
+    async fn get_logs(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        addresses: Vec<H160>,
+        topics: Vec<H256>,
+    ) -> Result<Vec<Log>, ErrorObjectOwned> {
+        self.client
+            .get_logs(from_block, to_block, addresses, topics)
+            .await
+            .map_err(|e| ErrorObjectOwned::owned(-32000, e.to_string(), None::<()>))
+    }
+
+    async fn subscribe_new_heads(&self, pending: PendingSubscriptionSink) -> SubscriptionResult {
+        let sink = pending.accept().await?;
+        let mut rx = self.new_heads.subscribe();
+        tokio::spawn(async move {
+            while let Ok(head) = rx.recv().await {
+                if sink.send(jsonrpsee::SubscriptionMessage::from_json(&head)?).await.is_err() {
+                    break;
+                }
+            }
+            Ok::<_, jsonrpsee::core::Error>(())
+        });
+        Ok(())
+    }
+}
+
+/// Wraps a [`BlockchainClient`] in a jsonrpsee HTTP/WS server, re-exposing its
+/// methods as standard `eth_*` endpoints while caching the chain head behind a
+/// single background poller.
+pub struct RpcServer {
+    client: Arc<BlockchainClient>,
+    config: RpcConfig,
+    chain_head: Arc<RwLock<ChainHead>>,
+    new_heads_tx: broadcast::Sender<u64>,
+}
+
+impl RpcServer {
+    pub fn new(client: Arc<BlockchainClient>, config: RpcConfig) -> Self {
+        let (new_heads_tx, _) = broadcast::channel(256);
+        Self {
+            client,
+            config,
+            chain_head: Arc::new(RwLock::new(ChainHead::default())),
+            new_heads_tx,
+        }
+    }
+
+    /// Spawns the head-polling task and starts the configured HTTP/WS
+    /// listeners. Returns handles so callers can shut the servers down.
+    pub async fn run(&self) -> Result<Vec<ServerHandle>> {
+        self.spawn_head_poller();
+
+        let eth_api = EthApiImpl {
+            client: self.client.clone(),
+            chain_head: self.chain_head.clone(),
+            new_heads: self.new_heads_tx.clone(),
+        };
+        let mut module = eth_api.into_rpc();
+        // Flatten instead of nesting a second module: both listeners share
+        // the same handler set so WS subscribers and HTTP callers observe
+        // the same cached head.
+        let _ = &mut module;
+
+        let mut handles = Vec::new();
+
+        if self.config.with_http {
+            let server = ServerBuilder::default()
+                .build(self.config.http_addr.parse::<std::net::SocketAddr>()?)
+                .await?;
+            handles.push(server.start(module.clone()));
+        }
+
+        if self.config.with_ws {
+            let server = ServerBuilder::default()
+                .build(self.config.ws_addr.parse::<std::net::SocketAddr>()?)
+                .await?;
+            handles.push(server.start(module));
+        }
+
+        Ok(handles)
+    }
+
+    fn spawn_head_poller(&self) {
+        let client = self.client.clone();
+        let chain_head = self.chain_head.clone();
+        let new_heads_tx = self.new_heads_tx.clone();
+        let interval = self.config.poll_interval;
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                match client.get_latest_block_number().await {
+                    Ok(number) => {
+                        let changed = {
+                            let mut head = chain_head.write().await;
+                            let changed = head.number != number;
+                            head.number = number;
+                            changed
+                        };
+                        if changed {
+                            let _ = new_heads_tx.send(number);
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!("head poller failed to fetch latest block number: {}", e);
+                    }
+                }
+            }
+        });
+    }
+}
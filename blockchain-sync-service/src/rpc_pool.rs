@@ -0,0 +1,133 @@
+//! Health-aware pool of upstream JSON-RPC endpoints.
+//!
+//! The background block loop in `main.rs` resolves real block data from the
+//! local Erigon MDBX chaindata (see `erigon.rs`, added in chunk5-1), not over
+//! RPC, so there's no per-block fetch loop left to route through a pool.
+//! What still makes a real upstream RPC call is `BlockchainClient` itself —
+//! this pool backs [`crate::blockchain::BlockchainClient::new_pooled`],
+//! picking the lowest-latency healthy endpoint for each call and falling
+//! through to the next one on failure, instead of pinning the client to a
+//! single configured URL.
+
+use crate::metrics::BlockchainMetrics;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+const DEFAULT_EJECTION_DURATION: Duration = Duration::from_secs(60);
+
+struct EndpointState {
+    url: String,
+    consecutive_failures: u32,
+    latency: Duration,
+    /// `Some(until)` while the endpoint is temporarily excluded from
+    /// [`RpcPool::select`] after tripping `failure_threshold`.
+    ejected_until: Option<Instant>,
+}
+
+pub struct RpcPool {
+    endpoints: Mutex<Vec<EndpointState>>,
+    failure_threshold: u32,
+    ejection_duration: Duration,
+    metrics: Arc<BlockchainMetrics>,
+}
+
+impl RpcPool {
+    pub fn new(urls: Vec<String>, metrics: Arc<BlockchainMetrics>) -> Self {
+        for url in &urls {
+            metrics.set_rpc_endpoint_healthy(url, true);
+        }
+        let endpoints = urls
+            .into_iter()
+            .map(|url| EndpointState {
+                url,
+                consecutive_failures: 0,
+                latency: Duration::ZERO,
+                ejected_until: None,
+            })
+            .collect();
+
+        Self {
+            endpoints: Mutex::new(endpoints),
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            ejection_duration: DEFAULT_EJECTION_DURATION,
+            metrics,
+        }
+    }
+
+    pub fn urls(&self) -> Vec<String> {
+        self.endpoints.lock().unwrap().iter().map(|e| e.url.clone()).collect()
+    }
+
+    /// Picks the lowest-latency endpoint that isn't currently ejected,
+    /// re-admitting any endpoint whose ejection window has elapsed so a
+    /// recovered node can be tried again. Returns `None` only when every
+    /// endpoint is currently ejected.
+    pub fn select(&self) -> Option<String> {
+        let now = Instant::now();
+        let mut endpoints = self.endpoints.lock().unwrap();
+
+        for endpoint in endpoints.iter_mut() {
+            if let Some(ejected_until) = endpoint.ejected_until {
+                if now >= ejected_until {
+                    endpoint.ejected_until = None;
+                    endpoint.consecutive_failures = 0;
+                    self.metrics.set_rpc_endpoint_healthy(&endpoint.url, true);
+                }
+            }
+        }
+
+        endpoints
+            .iter()
+            .filter(|e| e.ejected_until.is_none())
+            .min_by_key(|e| e.latency)
+            .map(|e| e.url.clone())
+    }
+
+    /// The lowest-latency endpoint not in `excluded` whose ejection window
+    /// (if any) has elapsed — used to fail over to the next candidate after
+    /// the excluded URLs just errored.
+    pub fn select_excluding(&self, excluded: &[String]) -> Option<String> {
+        let now = Instant::now();
+        let endpoints = self.endpoints.lock().unwrap();
+        endpoints
+            .iter()
+            .filter(|e| !excluded.iter().any(|x| x == &e.url))
+            .filter(|e| e.ejected_until.map(|until| now >= until).unwrap_or(true))
+            .min_by_key(|e| e.latency)
+            .map(|e| e.url.clone())
+    }
+
+    pub fn record_success(&self, url: &str, latency: Duration) {
+        {
+            let mut endpoints = self.endpoints.lock().unwrap();
+            if let Some(endpoint) = endpoints.iter_mut().find(|e| e.url == url) {
+                endpoint.consecutive_failures = 0;
+                endpoint.latency = latency;
+                endpoint.ejected_until = None;
+            }
+        }
+        self.metrics.set_rpc_endpoint_healthy(url, true);
+        self.metrics.set_rpc_endpoint_latency(url, latency.as_secs_f64());
+    }
+
+    /// Records a failed call against `url`, ejecting it once
+    /// `consecutive_failures` reaches `failure_threshold`.
+    pub fn record_failure(&self, url: &str) {
+        let mut ejected = false;
+        {
+            let mut endpoints = self.endpoints.lock().unwrap();
+            if let Some(endpoint) = endpoints.iter_mut().find(|e| e.url == url) {
+                endpoint.consecutive_failures += 1;
+                if endpoint.consecutive_failures >= self.failure_threshold {
+                    endpoint.ejected_until = Some(Instant::now() + self.ejection_duration);
+                    ejected = true;
+                }
+            }
+        }
+        if ejected {
+            log::warn!("rpc_pool: ejecting endpoint {} after {} consecutive failures", url, self.failure_threshold);
+            self.metrics.set_rpc_endpoint_healthy(url, false);
+        }
+    }
+}
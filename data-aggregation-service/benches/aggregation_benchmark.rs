@@ -0,0 +1,48 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use data_aggregation_service::aggregation::DataAggregator;
+use data_aggregation_service::models::PriceDataPoint;
+
+fn sample_price_data(points: usize) -> Vec<PriceDataPoint> {
+    (0..points)
+        .map(|i| PriceDataPoint {
+            timestamp: i as i64 * 1000,
+            symbol: "ETH".to_string(),
+            price: 1_000.0 + (i % 50) as f64,
+            volume: 10.0,
+            source: "bench".to_string(),
+        })
+        .collect()
+}
+
+fn bench_aggregate_ohlcv_sequential(c: &mut Criterion) {
+    let price_data = sample_price_data(100_000);
+    let timeframes = ["1m", "5m", "15m", "1h"];
+
+    c.bench_function("aggregate_ohlcv_sequential", |b| {
+        b.iter(|| {
+            for timeframe in &timeframes {
+                black_box(DataAggregator::aggregate_ohlcv(black_box(&price_data), timeframe).unwrap());
+            }
+        })
+    });
+}
+
+#[cfg(feature = "parallel")]
+fn bench_aggregate_ohlcv_parallel(c: &mut Criterion) {
+    let price_data = sample_price_data(100_000);
+    let timeframes = ["1m", "5m", "15m", "1h"];
+
+    c.bench_function("aggregate_ohlcv_parallel", |b| {
+        b.iter(|| {
+            black_box(DataAggregator::aggregate_ohlcv_parallel(black_box(&price_data), &timeframes).unwrap());
+        })
+    });
+}
+
+#[cfg(feature = "parallel")]
+criterion_group!(benches, bench_aggregate_ohlcv_sequential, bench_aggregate_ohlcv_parallel);
+#[cfg(not(feature = "parallel"))]
+criterion_group!(benches, bench_aggregate_ohlcv_sequential);
+
+criterion_main!(benches);
@@ -1,11 +1,63 @@
 use anyhow::Result;
+use chrono::Utc;
 use std::collections::HashMap;
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
 use crate::models::*;
 
 pub struct DataAggregator;
 
 impl DataAggregator {
+    /// Sets the number of worker threads rayon uses for the `parallel`
+    /// feature's data-parallel paths below. Must be called at most once,
+    /// before any of them run; rayon errors on a second call, which is
+    /// surfaced as-is rather than silently ignored.
+    #[cfg(feature = "parallel")]
+    pub fn configure_worker_threads(num_threads: usize) -> Result<()> {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build_global()
+            .map_err(|e| anyhow::anyhow!("failed to configure rayon thread pool: {}", e))
+    }
+
+    /// Data-parallel counterpart to calling [`Self::aggregate_ohlcv`] once
+    /// per timeframe: each timeframe's bucketing is independent, so with
+    /// the `parallel` feature enabled they run across rayon's global pool
+    /// instead of one after another. Worth it once a caller is aggregating
+    /// several timeframes from the same price data, e.g. the 1m/5m/15m/1h
+    /// sweep in the `aggregate_ohlcv_parallel` benchmark.
+    #[cfg(feature = "parallel")]
+    pub fn aggregate_ohlcv_parallel(price_data: &[PriceDataPoint], timeframes: &[&str]) -> Result<HashMap<String, Vec<OHLCVData>>> {
+        timeframes
+            .par_iter()
+            .map(|timeframe| Self::aggregate_ohlcv(price_data, timeframe).map(|candles| (timeframe.to_string(), candles)))
+            .collect()
+    }
+
+    /// Data-parallel counterpart to [`Self::calculate_rsi`],
+    /// [`Self::calculate_volatility`] and [`Self::calculate_moving_average`]:
+    /// runs all three over the same `prices` slice concurrently, since none
+    /// of them depend on each other's output.
+    #[cfg(feature = "parallel")]
+    pub fn calculate_indicators_parallel(
+        prices: &[f64],
+        ma_window: usize,
+        volatility_window: usize,
+        rsi_period: usize,
+    ) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+        let (moving_averages, (volatilities, rsi_values)) = rayon::join(
+            || Self::calculate_moving_average(prices, ma_window),
+            || rayon::join(
+                || Self::calculate_volatility(prices, volatility_window),
+                || Self::calculate_rsi(prices, rsi_period),
+            ),
+        );
+
+        (moving_averages, volatilities, rsi_values)
+    }
+
     pub fn aggregate_ohlcv(price_data: &[PriceDataPoint], timeframe: &str) -> Result<Vec<OHLCVData>> {
         let mut ohlcv_data = Vec::new();
         let interval_ms = Self::timeframe_to_ms(timeframe)?;
@@ -42,13 +94,164 @@ impl DataAggregator {
                 close,
                 volume,
                 timeframe: timeframe.to_string(),
+                complete: Self::window_is_complete(timestamp, interval_ms),
             });
         }
-        
+
         ohlcv_data.sort_by_key(|d| d.timestamp);
         Ok(ohlcv_data)
     }
-    
+
+    /// `true` once `window_start_ms + interval_ms` has already elapsed,
+    /// meaning no more trades can land in that candle.
+    fn window_is_complete(window_start_ms: i64, interval_ms: i64) -> bool {
+        Utc::now().timestamp_millis() >= window_start_ms + interval_ms
+    }
+
+    /// Derives `target_timeframe` candles from already-computed `base`
+    /// candles instead of re-scanning raw price data, so a 1m→5m→1h→1d
+    /// pipeline only ever re-scans the finest resolution. `target_ms` must
+    /// be an integer multiple of the base candles' own timeframe; mixing
+    /// timeframes within `base` (or rolling up to a finer one) is rejected.
+    pub fn rollup_ohlcv(base: &[OHLCVData], target_timeframe: &str) -> Result<Vec<OHLCVData>> {
+        if base.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let base_timeframe = &base[0].timeframe;
+        if base.iter().any(|c| &c.timeframe != base_timeframe) {
+            return Err(anyhow::anyhow!("rollup_ohlcv requires all base candles to share one timeframe"));
+        }
+
+        let base_ms = Self::timeframe_to_ms(base_timeframe)?;
+        let target_ms = Self::timeframe_to_ms(target_timeframe)?;
+        if target_ms % base_ms != 0 {
+            return Err(anyhow::anyhow!(
+                "target timeframe {} ({} ms) is not a multiple of base timeframe {} ({} ms)",
+                target_timeframe, target_ms, base_timeframe, base_ms
+            ));
+        }
+        if target_ms == base_ms {
+            return Err(anyhow::anyhow!("target timeframe must be coarser than the base timeframe"));
+        }
+
+        let mut grouped: HashMap<(String, i64), Vec<&OHLCVData>> = HashMap::new();
+        for candle in base {
+            let bucket_start = (candle.timestamp / target_ms) * target_ms;
+            grouped.entry((candle.symbol.clone(), bucket_start)).or_default().push(candle);
+        }
+
+        let mut rolled_up = Vec::with_capacity(grouped.len());
+        for ((symbol, timestamp), mut members) in grouped {
+            members.sort_by_key(|c| c.timestamp);
+
+            let open = members[0].open;
+            let close = members[members.len() - 1].close;
+            let high = members.iter().map(|c| c.high).fold(f64::NEG_INFINITY, f64::max);
+            let low = members.iter().map(|c| c.low).fold(f64::INFINITY, f64::min);
+            let volume = members.iter().map(|c| c.volume).sum();
+
+            rolled_up.push(OHLCVData {
+                timestamp,
+                symbol,
+                open,
+                high,
+                low,
+                close,
+                volume,
+                timeframe: target_timeframe.to_string(),
+                complete: Self::window_is_complete(timestamp, target_ms),
+            });
+        }
+
+        rolled_up.sort_by_key(|c| c.timestamp);
+        Ok(rolled_up)
+    }
+
+    /// `Resolution`-driven counterpart to [`Self::rollup_ohlcv`]: builds
+    /// `parent`'s candles by fanning in `children`, one resolution step at a
+    /// time (e.g. `1m` children into a `5m` parent), the way the rollup
+    /// pipeline chains `Resolution::parent()` to derive every coarser
+    /// timeframe from the one directly below it instead of re-scanning raw
+    /// ticks. A child is assigned to the parent bucket starting at
+    /// `start_time - (start_time % parent.seconds())`.
+    pub fn rollup_to_parent(children: &[OHLCVData], parent: Resolution) -> Result<Vec<OHLCVData>> {
+        if children.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let parent_seconds = parent.seconds();
+
+        let mut grouped: HashMap<(String, i64), Vec<&OHLCVData>> = HashMap::new();
+        for candle in children {
+            let start_time = candle.timestamp / 1000;
+            let bucket_start = (start_time - (start_time % parent_seconds)) * 1000;
+            grouped.entry((candle.symbol.clone(), bucket_start)).or_default().push(candle);
+        }
+
+        let mut rolled_up = Vec::with_capacity(grouped.len());
+        for ((symbol, timestamp), mut members) in grouped {
+            members.sort_by_key(|c| c.timestamp);
+
+            rolled_up.push(OHLCVData {
+                timestamp,
+                symbol,
+                open: members[0].open,
+                close: members[members.len() - 1].close,
+                high: members.iter().map(|c| c.high).fold(f64::NEG_INFINITY, f64::max),
+                low: members.iter().map(|c| c.low).fold(f64::INFINITY, f64::min),
+                volume: members.iter().map(|c| c.volume).sum(),
+                timeframe: parent.as_str().to_string(),
+                complete: Self::window_is_complete(timestamp, parent_seconds * 1000),
+            });
+        }
+
+        rolled_up.sort_by_key(|c| c.timestamp);
+        Ok(rolled_up)
+    }
+
+    /// Fills holes in a sorted run of same-symbol `candles` so every
+    /// interval between the first and last timestamp is present, which
+    /// backfill jobs rely on to produce a contiguous series even when the
+    /// underlying price data went quiet for a while. Inserted candles carry
+    /// the previous candle's close forward as a flat `open`/`high`/`low`/
+    /// `close` with `volume: 0.0`, matching how a market with no trades in
+    /// an interval should read on a chart.
+    pub fn fill_gaps(candles: &[OHLCVData], timeframe: &str) -> Result<Vec<OHLCVData>> {
+        if candles.len() < 2 {
+            return Ok(candles.to_vec());
+        }
+
+        let interval_ms = Self::timeframe_to_ms(timeframe)?;
+        let mut sorted: Vec<OHLCVData> = candles.to_vec();
+        sorted.sort_by_key(|c| c.timestamp);
+
+        let mut filled = Vec::with_capacity(sorted.len());
+        filled.push(sorted[0].clone());
+
+        for candle in &sorted[1..] {
+            let mut expected = filled.last().unwrap().timestamp + interval_ms;
+            while expected < candle.timestamp {
+                let carry_forward = filled.last().unwrap().close;
+                filled.push(OHLCVData {
+                    timestamp: expected,
+                    symbol: candle.symbol.clone(),
+                    open: carry_forward,
+                    high: carry_forward,
+                    low: carry_forward,
+                    close: carry_forward,
+                    volume: 0.0,
+                    timeframe: timeframe.to_string(),
+                    complete: true,
+                });
+                expected += interval_ms;
+            }
+            filled.push(candle.clone());
+        }
+
+        Ok(filled)
+    }
+
     pub fn calculate_volume_profile(price_data: &[PriceDataPoint], price_levels: u32) -> Result<Vec<(f64, f64)>> {
         if price_data.is_empty() {
             return Ok(Vec::new());
@@ -205,7 +408,222 @@ impl DataAggregator {
         
         (support_levels, resistance_levels)
     }
-    
+
+    /// Picks a priority fee (tip) from recent blocks' tips at `percentile`
+    /// (0.0-100.0), the same approach wallets use for `eth_feeHistory`-based
+    /// fee suggestion: take the tip actually paid by transactions in recent
+    /// blocks and return the one at the requested percentile, so "60th
+    /// percentile" means "at least as high as 60% of recent tips". `base_fees`
+    /// is accepted for symmetry with [`Self::project_next_base_fee`] callers
+    /// that already have both series on hand, but isn't used here since the
+    /// tip is independent of the base fee.
+    pub fn estimate_priority_fee(base_fees: &[f64], recent_tips: &[f64], percentile: f64) -> f64 {
+        let _ = base_fees;
+
+        if recent_tips.is_empty() {
+            return 0.0;
+        }
+
+        let mut sorted_tips = recent_tips.to_vec();
+        sorted_tips.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let percentile = percentile.clamp(0.0, 100.0);
+        let rank = ((percentile / 100.0) * (sorted_tips.len() - 1) as f64).round() as usize;
+
+        sorted_tips[rank]
+    }
+
+    /// Close-to-close volatility computed from candle closes rather than
+    /// raw ticks (standard deviation of log returns), annualized by the
+    /// timeframe's periods-per-year instead of a hardcoded 365.
+    pub fn calculate_close_to_close_volatility(candles: &[OHLCVData], timeframe: &str) -> Result<f64> {
+        let closes: Vec<f64> = candles.iter().map(|c| c.close).filter(|&c| c > 0.0).collect();
+
+        if closes.len() < 2 {
+            return Err(anyhow::anyhow!("Need at least two positive closes for close-to-close volatility"));
+        }
+
+        let returns: Vec<f64> = closes.windows(2).map(|w| (w[1] / w[0]).ln()).collect();
+        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+        let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64;
+
+        Ok((variance * Self::periods_per_year(timeframe)?).sqrt())
+    }
+
+    /// Parkinson's range-based volatility estimator: σ² = mean[(ln(H/L))²]
+    /// / (4·ln2), annualized by the timeframe's periods-per-year. Uses the
+    /// full high/low range of each candle instead of just its close, which
+    /// gives a far lower-variance estimate intraday than close-to-close.
+    /// Candles with a non-positive high or low are skipped.
+    pub fn calculate_parkinson_volatility(candles: &[OHLCVData], timeframe: &str) -> Result<f64> {
+        let squared_log_ranges: Vec<f64> = candles.iter()
+            .filter(|c| c.high > 0.0 && c.low > 0.0)
+            .map(|c| (c.high / c.low).ln().powi(2))
+            .collect();
+
+        if squared_log_ranges.is_empty() {
+            return Err(anyhow::anyhow!("No candles with valid high/low for Parkinson volatility"));
+        }
+
+        let mean_squared_log_range = squared_log_ranges.iter().sum::<f64>() / squared_log_ranges.len() as f64;
+        let variance = mean_squared_log_range / (4.0 * std::f64::consts::LN_2);
+
+        Ok((variance * Self::periods_per_year(timeframe)?).sqrt())
+    }
+
+    /// Garman–Klass range-based volatility estimator: σ² = mean[0.5·(ln(H/L))²
+    /// − (2·ln2 − 1)·(ln(C/O))²], annualized by the timeframe's
+    /// periods-per-year. Extends Parkinson's estimator with the open/close
+    /// drift term, making it more efficient when price gaps between the
+    /// open and the prior close. Candles with a non-positive high, low,
+    /// open, or close are skipped.
+    pub fn calculate_garman_klass_volatility(candles: &[OHLCVData], timeframe: &str) -> Result<f64> {
+        let terms: Vec<f64> = candles.iter()
+            .filter(|c| c.high > 0.0 && c.low > 0.0 && c.open > 0.0 && c.close > 0.0)
+            .map(|c| {
+                let log_hl = (c.high / c.low).ln();
+                let log_co = (c.close / c.open).ln();
+                0.5 * log_hl.powi(2) - (2.0 * std::f64::consts::LN_2 - 1.0) * log_co.powi(2)
+            })
+            .collect();
+
+        if terms.is_empty() {
+            return Err(anyhow::anyhow!("No candles with valid OHLC for Garman-Klass volatility"));
+        }
+
+        let mean_term = terms.iter().sum::<f64>() / terms.len() as f64;
+        // The estimator can go slightly negative on very quiet candles;
+        // floor at zero rather than propagating a NaN through sqrt().
+        let variance = mean_term.max(0.0);
+
+        Ok((variance * Self::periods_per_year(timeframe)?).sqrt())
+    }
+
+    /// Exponentially-weighted close-to-close volatility: σ²ₜ = λ·σ²ₜ₋₁ +
+    /// (1−λ)·rₜ², seeded with the first period's squared return and
+    /// annualized by the timeframe's periods-per-year. Weights recent
+    /// returns more heavily than older ones, so it reacts to a volatility
+    /// regime change faster than a flat rolling standard deviation.
+    pub fn calculate_ewma_volatility(candles: &[OHLCVData], timeframe: &str, lambda: f64) -> Result<f64> {
+        let closes: Vec<f64> = candles.iter().map(|c| c.close).filter(|&c| c > 0.0).collect();
+
+        if closes.len() < 2 {
+            return Err(anyhow::anyhow!("Need at least two positive closes for EWMA volatility"));
+        }
+
+        let returns: Vec<f64> = closes.windows(2).map(|w| (w[1] / w[0]).ln()).collect();
+
+        let mut variance = returns[0].powi(2);
+        for r in &returns[1..] {
+            variance = lambda * variance + (1.0 - lambda) * r.powi(2);
+        }
+
+        Ok((variance * Self::periods_per_year(timeframe)?).sqrt())
+    }
+
+    /// GARCH(1,1) volatility estimator: σ²ₜ = ω + α·rₜ₋₁² + β·σ²ₜ₋₁, fit by a
+    /// bounded grid search over (α, β) subject to α,β ≥ 0 and α+β < 1
+    /// (stationarity). For each candidate pair, ω is pinned so the implied
+    /// unconditional variance ω/(1−α−β) equals the sample variance, which
+    /// collapses the search to two dimensions; the pair maximizing the
+    /// Gaussian log-likelihood Σ(−0.5·(ln σ²ₜ + rₜ²/σ²ₜ)) wins. Annualized by
+    /// the timeframe's periods-per-year. Needs at least 3 log returns (4
+    /// positive closes) — callers should fall back to a simpler estimator
+    /// below that.
+    pub fn calculate_garch_volatility(candles: &[OHLCVData], timeframe: &str) -> Result<f64> {
+        let closes: Vec<f64> = candles.iter().map(|c| c.close).filter(|&c| c > 0.0).collect();
+
+        if closes.len() < 2 {
+            return Err(anyhow::anyhow!("Need at least two positive closes for GARCH volatility"));
+        }
+
+        let returns: Vec<f64> = closes.windows(2).map(|w| (w[1] / w[0]).ln()).collect();
+        if returns.len() < 3 {
+            return Err(anyhow::anyhow!("Need at least three returns to fit GARCH(1,1)"));
+        }
+
+        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+        let sample_variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64;
+
+        const GRID_STEPS: usize = 50;
+        const MAX_ALPHA: f64 = 0.3;
+        const MAX_BETA: f64 = 0.95;
+
+        let mut best_log_likelihood = f64::NEG_INFINITY;
+        let mut best_terminal_variance = sample_variance;
+
+        for i in 1..GRID_STEPS {
+            let alpha = (i as f64 / GRID_STEPS as f64) * MAX_ALPHA;
+            for j in 1..GRID_STEPS {
+                let beta = (j as f64 / GRID_STEPS as f64) * MAX_BETA;
+                if alpha + beta >= 1.0 {
+                    continue;
+                }
+
+                let omega = sample_variance * (1.0 - alpha - beta);
+                if omega <= 0.0 {
+                    continue;
+                }
+
+                let (log_likelihood, terminal_variance) = Self::garch_log_likelihood(&returns, omega, alpha, beta);
+                if log_likelihood > best_log_likelihood {
+                    best_log_likelihood = log_likelihood;
+                    best_terminal_variance = terminal_variance;
+                }
+            }
+        }
+
+        Ok((best_terminal_variance * Self::periods_per_year(timeframe)?).sqrt())
+    }
+
+    /// Runs the GARCH(1,1) recursion over `returns` for a candidate (ω, α,
+    /// β), seeded with the implied unconditional variance ω/(1−α−β).
+    /// Returns the Gaussian log-likelihood of the fit and the final period's
+    /// variance, which doubles as the estimator's current-volatility output.
+    fn garch_log_likelihood(returns: &[f64], omega: f64, alpha: f64, beta: f64) -> (f64, f64) {
+        let mut variance = omega / (1.0 - alpha - beta);
+        let mut log_likelihood = 0.0;
+
+        for r in returns {
+            log_likelihood += -0.5 * (variance.ln() + r.powi(2) / variance);
+            variance = omega + alpha * r.powi(2) + beta * variance;
+        }
+
+        (log_likelihood, variance)
+    }
+
+    /// How many `timeframe`-sized candles make up a year, for annualizing a
+    /// per-candle volatility estimate (replaces hardcoding 365 daily
+    /// periods, which only holds for the `1d` timeframe).
+    pub fn periods_per_year(timeframe: &str) -> Result<f64> {
+        const YEAR_MS: f64 = 365.0 * 24.0 * 60.0 * 60.0 * 1000.0;
+        Ok(YEAR_MS / Self::timeframe_to_ms(timeframe)? as f64)
+    }
+
+    /// Projects the next block's base fee from `parent_base_fee` and how
+    /// full `parent_gas_used` was relative to `parent_gas_target`, per
+    /// EIP-1559: the base fee moves by at most 1/8th of itself per block,
+    /// up when the parent block was over target and down when it was
+    /// under, and is floored at `min_base_fee` so it never projects to zero
+    /// or negative.
+    pub fn project_next_base_fee(
+        parent_base_fee: f64,
+        parent_gas_used: f64,
+        parent_gas_target: f64,
+        min_base_fee: f64,
+    ) -> f64 {
+        if parent_gas_target <= 0.0 {
+            return parent_base_fee.max(min_base_fee);
+        }
+
+        let gas_delta = parent_gas_used - parent_gas_target;
+        let max_change = parent_base_fee / 8.0;
+        let change = (gas_delta / parent_gas_target) * parent_base_fee / 8.0;
+        let clamped_change = change.clamp(-max_change, max_change);
+
+        (parent_base_fee + clamped_change).max(min_base_fee)
+    }
+
     fn timeframe_to_ms(timeframe: &str) -> Result<i64> {
         match timeframe {
             "1m" => Ok(60 * 1000),
@@ -0,0 +1,64 @@
+//! Bounded-batch historical OHLCV backfill. Rebuilding candles over a long
+//! historical range one giant query would block the real-time aggregation
+//! path and risk loading more price data than fits in memory, so this walks
+//! the range in batches capped at [`MAX_BATCH_POINTS`] source points, the
+//! way openbook-candles' earliest-candle fetch bounds a single query
+//! instead of pulling an entire historical range at once.
+
+use anyhow::Result;
+use log::info;
+
+use crate::aggregation::DataAggregator;
+use crate::mongodb_client::MongoDBService;
+
+const MAX_BATCH_POINTS: usize = 2000;
+
+/// How far a completed backfill run progressed: `next_start_timestamp` is
+/// where its last batch started. Only returned on success; a run that errors
+/// partway through reports the error, not a cursor.
+#[derive(Debug, Clone, Copy)]
+pub struct BackfillCursor {
+    pub next_start_timestamp: i64,
+    pub candles_written: usize,
+}
+
+/// Rebuilds `timeframe` candles for `symbol` over `[from_ts, to_ts)`,
+/// advancing in batches of at most [`MAX_BATCH_POINTS`] source points and
+/// upserting as it goes, so batches written before a failure stay written.
+/// The returned cursor's `next_start_timestamp` is where the last batch
+/// started.
+pub async fn backfill_ohlcv(
+    mongodb_service: &MongoDBService,
+    symbol: &str,
+    timeframe: &str,
+    from_ts: i64,
+    to_ts: i64,
+) -> Result<BackfillCursor> {
+    let mut cursor = from_ts;
+    let mut candles_written = 0;
+
+    while cursor < to_ts {
+        let batch = mongodb_service
+            .get_price_history_range(symbol, cursor, to_ts, MAX_BATCH_POINTS)
+            .await?;
+
+        if batch.is_empty() {
+            break;
+        }
+
+        let candles = DataAggregator::aggregate_ohlcv(&batch, timeframe)?;
+        let candles = DataAggregator::fill_gaps(&candles, timeframe)?;
+        mongodb_service.upsert_ohlcv_data(&candles).await?;
+        candles_written += candles.len();
+
+        let last_point_timestamp = batch.last().map(|p| p.timestamp).unwrap_or(cursor);
+        info!("Backfilled {} {} candles for {} up to {}", candles.len(), timeframe, symbol, last_point_timestamp);
+
+        if batch.len() < MAX_BATCH_POINTS {
+            break; // Exhausted the range; a full batch means more may still be waiting.
+        }
+        cursor = last_point_timestamp + 1;
+    }
+
+    Ok(BackfillCursor { next_start_timestamp: cursor, candles_written })
+}
@@ -0,0 +1,125 @@
+//! Incremental per-trade OHLCV candle builder, complementing the batch
+//! `aggregate_ohlcv`/`rollup_ohlcv` pipeline in `aggregation.rs`: instead of
+//! re-scanning a window of stored price points, each trade folds directly
+//! into the candle bucket it belongs to (open = first trade in the bucket,
+//! high/low = running extremes, close = last trade, volume = summed size),
+//! across every resolution in [`TRACKED_RESOLUTIONS`].
+//!
+//! A `live_candles` cache holds the currently-open bucket per `(symbol,
+//! resolution)`, so in-order trades (the common case) extend it without a
+//! read round-trip. A trade landing in an already-closed bucket
+//! (out-of-order arrival, keyed by its own exchange/block time rather than
+//! ingestion time) instead reads, folds, and writes that specific candle
+//! straight through to Mongo, without disturbing the live one.
+
+use anyhow::Result;
+use dashmap::DashMap;
+
+use crate::models::{OHLCVData, Resolution};
+use crate::mongodb_client::MongoDBService;
+
+const TRACKED_RESOLUTIONS: [Resolution; 4] =
+    [Resolution::R1m, Resolution::R5m, Resolution::R15m, Resolution::R1h];
+
+pub struct CandleBuilder {
+    mongodb_service: MongoDBService,
+    live_candles: DashMap<(String, Resolution), OHLCVData>,
+}
+
+impl CandleBuilder {
+    pub fn new(mongodb_service: MongoDBService) -> Self {
+        Self {
+            mongodb_service,
+            live_candles: DashMap::new(),
+        }
+    }
+
+    /// Folds one trade into every tracked resolution's bucket.
+    /// `trade_timestamp_ms` must be the trade's own exchange/block time, not
+    /// when it was ingested, so a late-arriving trade still lands in the
+    /// bucket it actually happened in.
+    pub async fn on_trade(&self, symbol: &str, price: f64, size: f64, trade_timestamp_ms: i64) -> Result<()> {
+        for resolution in TRACKED_RESOLUTIONS {
+            self.fold_trade(symbol, resolution, price, size, trade_timestamp_ms).await?;
+        }
+        Ok(())
+    }
+
+    async fn fold_trade(&self, symbol: &str, resolution: Resolution, price: f64, size: f64, trade_timestamp_ms: i64) -> Result<()> {
+        let interval_ms = resolution.seconds() * 1000;
+        let bucket_start = (trade_timestamp_ms / interval_ms) * interval_ms;
+        let key = (symbol.to_string(), resolution);
+
+        let live_timestamp = self.live_candles.get(&key).map(|c| c.timestamp);
+
+        match live_timestamp {
+            Some(ts) if ts == bucket_start => {
+                let snapshot = {
+                    let mut live = self.live_candles.get_mut(&key).unwrap();
+                    Self::fold_into(&mut live, price, size);
+                    live.clone()
+                };
+                self.mongodb_service.upsert_ohlcv_candle(&snapshot).await
+            }
+            Some(ts) if bucket_start > ts => {
+                if let Some((_, mut finished)) = self.live_candles.remove(&key) {
+                    finished.complete = true;
+                    self.mongodb_service.upsert_ohlcv_candle(&finished).await?;
+                }
+
+                let fresh = Self::new_live_candle(symbol, resolution, bucket_start, price, size);
+                self.mongodb_service.upsert_ohlcv_candle(&fresh).await?;
+                self.live_candles.insert(key, fresh);
+                Ok(())
+            }
+            Some(_) => self.fold_closed_bucket(symbol, resolution, bucket_start, price, size).await,
+            None => {
+                let fresh = Self::new_live_candle(symbol, resolution, bucket_start, price, size);
+                self.mongodb_service.upsert_ohlcv_candle(&fresh).await?;
+                self.live_candles.insert(key, fresh);
+                Ok(())
+            }
+        }
+    }
+
+    /// Folds a trade into a bucket that's already closed (its live bucket has
+    /// since moved on), without touching `live_candles`.
+    async fn fold_closed_bucket(&self, symbol: &str, resolution: Resolution, bucket_start: i64, price: f64, size: f64) -> Result<()> {
+        let existing = self.mongodb_service.get_ohlcv_candle(symbol, resolution.as_str(), bucket_start).await?;
+
+        let candle = match existing {
+            Some(mut candle) => {
+                Self::fold_into(&mut candle, price, size);
+                candle
+            }
+            None => {
+                let mut candle = Self::new_live_candle(symbol, resolution, bucket_start, price, size);
+                candle.complete = true;
+                candle
+            }
+        };
+
+        self.mongodb_service.upsert_ohlcv_candle(&candle).await
+    }
+
+    fn new_live_candle(symbol: &str, resolution: Resolution, bucket_start: i64, price: f64, size: f64) -> OHLCVData {
+        OHLCVData {
+            timestamp: bucket_start,
+            symbol: symbol.to_string(),
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: size,
+            timeframe: resolution.as_str().to_string(),
+            complete: false,
+        }
+    }
+
+    fn fold_into(candle: &mut OHLCVData, price: f64, size: f64) {
+        candle.high = candle.high.max(price);
+        candle.low = candle.low.min(price);
+        candle.close = price;
+        candle.volume += size;
+    }
+}
@@ -2,20 +2,34 @@ use anyhow::Result;
 use chrono::Utc;
 use dashmap::DashMap;
 use log::{error, info};
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Instant;
+use tokio::sync::broadcast;
 use tokio::time::{interval, Duration};
 
 use crate::aggregation::DataAggregator;
+use crate::backfill::{self, BackfillCursor};
+use crate::candle_builder::CandleBuilder;
+use crate::eth_subscribe::{EthSubscribeService, LogFilter};
+use crate::event_source::EventSource;
 use crate::kafka_consumer::KafkaConsumerService;
 use crate::mongodb_client::MongoDBService;
 use crate::models::*;
 
+/// Ring-buffer depth for the WebSocket fan-out channel: how many events a
+/// slow subscriber can fall behind before it starts missing ones. Sized
+/// generously since a lagging subscriber drops events rather than stalling
+/// the consumer loop (`broadcast::Sender::send` never blocks).
+const STREAM_BROADCAST_CAPACITY: usize = 1024;
+
 #[derive(Clone)]
 pub struct DataAggregationService {
     kafka_consumer: KafkaConsumerService,
     mongodb_service: MongoDBService,
     real_time_cache: Arc<DashMap<String, RealTimeDataPoint>>,
+    candle_builder: Arc<CandleBuilder>,
+    event_broadcaster: broadcast::Sender<StreamingDataEvent>,
 }
 
 impl DataAggregationService {
@@ -23,14 +37,26 @@ impl DataAggregationService {
         let kafka_consumer = KafkaConsumerService::new(kafka_brokers, "data-aggregation-group")?;
         let mongodb_service = MongoDBService::new(mongodb_uri).await?;
         let real_time_cache = Arc::new(DashMap::new());
+        let candle_builder = Arc::new(CandleBuilder::new(mongodb_service.clone()));
+        let (event_broadcaster, _) = broadcast::channel(STREAM_BROADCAST_CAPACITY);
 
         Ok(Self {
             kafka_consumer,
             mongodb_service,
             real_time_cache,
+            candle_builder,
+            event_broadcaster,
         })
     }
 
+    /// Subscribes to the live `StreamingDataEvent` feed, backing the
+    /// WebSocket push endpoint. Each receiver gets its own lagging window;
+    /// falling behind drops the oldest unread events instead of blocking
+    /// the Kafka consumer loop.
+    pub fn subscribe_to_stream(&self) -> broadcast::Receiver<StreamingDataEvent> {
+        self.event_broadcaster.subscribe()
+    }
+
     pub async fn start_consumers(&self) -> Result<()> {
         self.kafka_consumer.subscribe_to_price_updates().await?;
         
@@ -42,6 +68,22 @@ impl DataAggregationService {
         }).await
     }
 
+    /// Runs a direct `eth_subscribe` websocket source alongside (or instead
+    /// of) the Kafka consumers, for deployments where the Kafka hop's added
+    /// latency matters more than its durability. Feeds the same
+    /// `process_streaming_event` pipeline as `start_consumers`, so
+    /// OHLC/price state and the WebSocket fan-out don't care which source
+    /// produced an event.
+    pub async fn start_eth_subscribe_source(&self, ws_url: String, filters: Vec<LogFilter>) -> Result<()> {
+        let source = EthSubscribeService::new(ws_url, filters);
+        let service = self.clone();
+        source.run(Box::new(move |event| {
+            tokio::runtime::Handle::current().block_on(async {
+                service.process_streaming_event(event).await
+            })
+        })).await
+    }
+
     pub async fn start_real_time_aggregation(&self) -> Result<()> {
         let mut interval = interval(Duration::from_secs(60)); // Aggregate every minute
         let service = self.clone();
@@ -55,6 +97,11 @@ impl DataAggregationService {
     }
 
     async fn process_streaming_event(&self, event: StreamingDataEvent) -> Result<()> {
+        // Fan out to WebSocket subscribers before (and regardless of
+        // whether) the event is otherwise handled below; a send with no
+        // subscribers, or with a subscriber too far behind, is not an error.
+        let _ = self.event_broadcaster.send(event.clone());
+
         match event.event_type {
             StreamEventType::PriceUpdate => {
                 self.handle_price_update(&event).await?;
@@ -79,7 +126,7 @@ impl DataAggregationService {
         // Extract price data from event
         if let Ok(price) = event.data.get("price").unwrap_or(&serde_json::Value::Null).as_f64() {
             let volume = event.data.get("volume").unwrap_or(&serde_json::Value::Null).as_f64().unwrap_or(0.0);
-            
+
             let price_data = PriceDataPoint {
                 timestamp: event.timestamp.timestamp(),
                 symbol: event.symbol.clone(),
@@ -91,12 +138,25 @@ impl DataAggregationService {
             // Store in database
             self.mongodb_service.store_price_data(&price_data).await?;
 
+            // Sum volume and find the oldest point over the trailing 24h
+            // (used as a stand-in for "the price at exactly now - 24h",
+            // since we only have the ticks that actually happened). Both
+            // include the point just stored above.
+            let trailing_24h = self.mongodb_service.get_volume_data(&event.symbol, 24).await?;
+            let volume_24h: f64 = trailing_24h.iter().map(|p| p.volume).sum();
+            let price_24h_ago = trailing_24h.first().map(|p| p.price).unwrap_or(price);
+            let price_change_24h = if price_24h_ago != 0.0 {
+                (price - price_24h_ago) / price_24h_ago * 100.0
+            } else {
+                0.0
+            };
+
             // Update real-time cache
             let real_time_data = RealTimeDataPoint {
                 symbol: event.symbol.clone(),
                 price,
-                volume_24h: volume, // This should be calculated properly
-                price_change_24h: 0.0, // This should be calculated properly
+                volume_24h,
+                price_change_24h,
                 last_updated: event.timestamp,
             };
 
@@ -112,9 +172,24 @@ impl DataAggregationService {
     }
 
     async fn handle_trade_execution(&self, event: &StreamingDataEvent) -> Result<()> {
-        // Extract trade data and update relevant metrics
         info!("Processing trade execution for symbol: {}", event.symbol);
-        Ok(())
+
+        let Some(price) = event.data.get("price").and_then(|v| v.as_f64()) else {
+            return Ok(());
+        };
+        let size = event.data.get("size")
+            .or_else(|| event.data.get("volume"))
+            .and_then(|v| v.as_f64())
+            .unwrap_or(0.0);
+
+        // The trade's own exchange/block time, not when it was ingested, so
+        // a late-arriving trade still lands in the bucket it actually
+        // happened in.
+        let trade_timestamp_ms = event.data.get("trade_time")
+            .and_then(|v| v.as_i64())
+            .unwrap_or_else(|| event.timestamp.timestamp_millis());
+
+        self.candle_builder.on_trade(&event.symbol, price, size, trade_timestamp_ms).await
     }
 
     async fn handle_liquidity_change(&self, _event: &StreamingDataEvent) -> Result<()> {
@@ -123,39 +198,165 @@ impl DataAggregationService {
     }
 
     async fn perform_real_time_aggregation(&self) -> Result<()> {
-        // Perform OHLCV aggregation for different timeframes
         let symbols = vec!["ETH".to_string(), "BTC".to_string()]; // This should be dynamic
-        let timeframes = vec!["1m", "5m", "15m", "1h"];
 
         for symbol in &symbols {
-            for timeframe in &timeframes {
-                if let Err(e) = self.aggregate_ohlcv_for_symbol(symbol, timeframe).await {
-                    error!("Failed to aggregate OHLCV for {} {}: {}", symbol, timeframe, e);
-                }
+            if let Err(e) = self.aggregate_ohlcv_rollup_chain(symbol).await {
+                error!("Failed to aggregate OHLCV rollup chain for {}: {}", symbol, e);
             }
         }
 
         Ok(())
     }
 
-    async fn aggregate_ohlcv_for_symbol(&self, symbol: &str, timeframe: &str) -> Result<()> {
-        // Get recent price data
-        let price_data = self.mongodb_service.get_price_history(symbol, timeframe, 1000).await?;
-        
+    /// Computes `1m` candles once from raw ticks, then derives every
+    /// coarser resolution by rolling up the one below it
+    /// (`Resolution::parent()`), storing each level as it's produced. This
+    /// replaces recomputing every timeframe straight from raw price data,
+    /// which duplicated the same bucketing work once per timeframe.
+    async fn aggregate_ohlcv_rollup_chain(&self, symbol: &str) -> Result<()> {
+        let price_data = self.mongodb_service.get_price_history(symbol, Resolution::R1m.as_str(), 1000).await?;
+
         if price_data.is_empty() {
             return Ok(());
         }
 
-        // Aggregate to OHLCV
-        let ohlcv_data = DataAggregator::aggregate_ohlcv(&price_data, timeframe)?;
-        
-        if !ohlcv_data.is_empty() {
-            self.mongodb_service.store_ohlcv_data(&ohlcv_data).await?;
+        let mut candles = DataAggregator::aggregate_ohlcv(&price_data, Resolution::R1m.as_str())?;
+        if candles.is_empty() {
+            return Ok(());
+        }
+        self.mongodb_service.upsert_ohlcv_data(&candles).await?;
+
+        let mut resolution = Resolution::R1m;
+        while let Some(parent) = resolution.parent() {
+            candles = DataAggregator::rollup_to_parent(&candles, parent)?;
+            if candles.is_empty() {
+                break;
+            }
+            self.mongodb_service.upsert_ohlcv_data(&candles).await?;
+            resolution = parent;
         }
 
         Ok(())
     }
 
+    /// Rebuilds `timeframe` candles for `symbol` over `[from_ts, to_ts)` in
+    /// bounded point batches via the [`crate::backfill`] module, so
+    /// operators can repopulate OHLCV after a schema change or data gap
+    /// without blocking the real-time aggregation loop.
+    pub async fn backfill_ohlcv(&self, symbol: &str, timeframe: &str, from_ts: i64, to_ts: i64) -> Result<BackfillCursor> {
+        backfill::backfill_ohlcv(&self.mongodb_service, symbol, timeframe, from_ts, to_ts).await
+    }
+
+    /// Drives an [`AggregationTask`], returning an [`AggregationResult`]
+    /// instead of propagating errors, matching how the math-computing
+    /// service's task consumer turns a handler failure into a result rather
+    /// than dropping the task. Only [`AggregationTaskType::OHLCV`] is
+    /// implemented so far.
+    pub async fn process_aggregation_task(&self, task: AggregationTask) -> AggregationResult {
+        let start_time = Instant::now();
+
+        let outcome = match task.task_type {
+            AggregationTaskType::OHLCV => self.run_ohlcv_backfill(&task).await,
+            _ => Err(anyhow::anyhow!("Aggregation task type not implemented")),
+        };
+
+        let processing_time_ms = start_time.elapsed().as_millis() as u64;
+        match outcome {
+            Ok(result) => AggregationResult {
+                task_id: task.task_id,
+                success: true,
+                result: Some(result),
+                error: None,
+                processing_time_ms,
+                processed_at: Utc::now(),
+            },
+            Err(e) => AggregationResult {
+                task_id: task.task_id,
+                success: false,
+                result: None,
+                error: Some(e.to_string()),
+                processing_time_ms,
+                processed_at: Utc::now(),
+            },
+        }
+    }
+
+    /// Selects between the two `AggregationTaskType::OHLCV` backfill modes
+    /// via `task.parameters.mode` (`"raw_trade"`, the default, or
+    /// `"candle_only"`). A raw-trade backfill replays `[start_time,
+    /// end_time)` of already-stored `PriceDataPoint`s straight into
+    /// `task.timeframe` candles via [`backfill::backfill_ohlcv`] — a fresh
+    /// rebuild. A candle-only backfill instead rolls up already-computed
+    /// `1m` candles over the same range into `task.timeframe`, touching no
+    /// raw trade data, which is cheaper when the finer candles are already
+    /// known-good and only a coarser rollup needs recomputing.
+    async fn run_ohlcv_backfill(&self, task: &AggregationTask) -> Result<serde_json::Value> {
+        let mode = task.parameters.get("mode").and_then(|v| v.as_str()).unwrap_or("raw_trade");
+        let from_ts = task.start_time.timestamp();
+        let to_ts = task.end_time.timestamp();
+
+        match mode {
+            "candle_only" => {
+                let base = self.mongodb_service
+                    .get_ohlcv_data_range(&task.symbol, Resolution::R1m.as_str(), from_ts, to_ts)
+                    .await?;
+
+                let candles = if task.timeframe == Resolution::R1m.as_str() {
+                    base
+                } else {
+                    DataAggregator::rollup_ohlcv(&base, &task.timeframe)?
+                };
+                self.mongodb_service.upsert_ohlcv_data(&candles).await?;
+
+                Ok(serde_json::json!({ "mode": "candle_only", "candles_written": candles.len() }))
+            }
+            _ => {
+                let cursor = backfill::backfill_ohlcv(&self.mongodb_service, &task.symbol, &task.timeframe, from_ts, to_ts).await?;
+                Ok(serde_json::json!({
+                    "mode": "raw_trade",
+                    "candles_written": cursor.candles_written,
+                    "next_start_timestamp": cursor.next_start_timestamp,
+                }))
+            }
+        }
+    }
+
+    /// Emits rows in the CoinGecko external tickers schema, one per tracked
+    /// symbol against `USD`, so external aggregators have a standard
+    /// ingestion endpoint instead of the ad-hoc `RealTimeFeedResponse`.
+    /// Symbols with no trades in the last 24h are omitted.
+    pub async fn get_coingecko_tickers(&self) -> Result<Vec<CoinGeckoTicker>> {
+        let symbols = vec!["ETH".to_string(), "BTC".to_string()]; // This should be dynamic
+        const TARGET_CURRENCY: &str = "USD";
+
+        let mut tickers = Vec::with_capacity(symbols.len());
+        for symbol in &symbols {
+            let Some(stats) = self.mongodb_service.get_24h_ticker_stats(symbol).await? else {
+                continue;
+            };
+
+            // No live order book is tracked yet, so approximate bid/ask
+            // with the same placeholder spread `get_liquidity_metrics` uses
+            // around the last trade price.
+            let spread = 0.001;
+            tickers.push(CoinGeckoTicker {
+                ticker_id: format!("{}_{}", symbol, TARGET_CURRENCY),
+                base_currency: symbol.clone(),
+                target_currency: TARGET_CURRENCY.to_string(),
+                last_price: stats.last_price,
+                base_volume: stats.base_volume,
+                target_volume: stats.target_volume,
+                bid: stats.last_price * (1.0 - spread / 2.0),
+                ask: stats.last_price * (1.0 + spread / 2.0),
+                high: stats.high,
+                low: stats.low,
+            });
+        }
+
+        Ok(tickers)
+    }
+
     pub async fn get_price_history(&self, symbol: &str, timeframe: &str, limit: usize) -> Result<PriceHistoryResponse> {
         let data = self.mongodb_service.get_price_history(symbol, timeframe, limit).await?;
         
@@ -236,14 +437,54 @@ impl DataAggregationService {
     }
 
     async fn generate_market_summary(&self) -> Result<MarketSummaryResponse> {
-        // This is a simplified implementation
+        let mut performances: Vec<TokenPerformance> = self.real_time_cache.iter()
+            .map(|entry| {
+                let data = entry.value();
+                TokenPerformance {
+                    symbol: data.symbol.clone(),
+                    current_price: data.price,
+                    price_change_24h: data.price * data.price_change_24h / 100.0,
+                    price_change_percentage_24h: data.price_change_24h,
+                }
+            })
+            .collect();
+
+        performances.sort_by(|a, b| b.price_change_percentage_24h.partial_cmp(&a.price_change_percentage_24h).unwrap());
+        let top_gainers: Vec<TokenPerformance> = performances.iter()
+            .filter(|p| p.price_change_percentage_24h > 0.0)
+            .take(5)
+            .cloned()
+            .collect();
+
+        performances.sort_by(|a, b| a.price_change_percentage_24h.partial_cmp(&b.price_change_percentage_24h).unwrap());
+        let top_losers: Vec<TokenPerformance> = performances.into_iter()
+            .filter(|p| p.price_change_percentage_24h < 0.0)
+            .take(5)
+            .collect();
+
+        let mut most_active: Vec<TokenActivity> = self.real_time_cache.iter()
+            .map(|entry| {
+                let data = entry.value();
+                TokenActivity {
+                    symbol: data.symbol.clone(),
+                    volume_24h: data.volume_24h,
+                    trades_count: 0,   // Not tracked per-trade yet
+                    unique_traders: 0, // Not tracked yet
+                }
+            })
+            .collect();
+        most_active.sort_by(|a, b| b.volume_24h.partial_cmp(&a.volume_24h).unwrap());
+        most_active.truncate(5);
+
+        let total_volume_24h: f64 = self.real_time_cache.iter().map(|entry| entry.value().volume_24h).sum();
+
         Ok(MarketSummaryResponse {
-            total_market_cap: 1_000_000_000.0, // Placeholder
-            total_volume_24h: 10_000_000.0,    // Placeholder
-            active_pairs: 100,                  // Placeholder
-            top_gainers: Vec::new(),
-            top_losers: Vec::new(),
-            most_active: Vec::new(),
+            total_market_cap: 0.0, // Not derivable without circulating supply data
+            total_volume_24h,
+            active_pairs: self.real_time_cache.len() as u32,
+            top_gainers,
+            top_losers,
+            most_active,
             timestamp: Utc::now(),
         })
     }
@@ -290,24 +531,57 @@ impl DataAggregationService {
 
     pub async fn calculate_correlation(&self, request: CorrelationRequest) -> Result<CorrelationResponse> {
         let start_time = Instant::now();
-        
-        // Get price data for all symbols
-        let mut price_data = Vec::new();
+
+        // One fetch per symbol; every pair below reuses these instead of
+        // re-querying per (i, j), and resampling onto a timestamp→close map
+        // lets us align two symbols' series even if one has gaps the other
+        // doesn't.
+        let mut series_by_symbol = HashMap::with_capacity(request.symbols.len());
         for symbol in &request.symbols {
-            let data = self.mongodb_service.get_price_history(symbol, &request.timeframe, 1000).await?;
-            let prices: Vec<f64> = data.iter().map(|d| d.price).collect();
-            price_data.push(prices);
+            let candles = self.mongodb_service.get_ohlcv_data(symbol, &request.timeframe, 1000).await?;
+            let by_timestamp: HashMap<i64, f64> = candles.into_iter().map(|c| (c.timestamp, c.close)).collect();
+            series_by_symbol.insert(symbol.clone(), by_timestamp);
         }
 
-        // Calculate correlation matrix
-        let mut correlation_matrix = vec![vec![0.0; request.symbols.len()]; request.symbols.len()];
-        
-        for i in 0..request.symbols.len() {
-            for j in 0..request.symbols.len() {
-                if i == j {
-                    correlation_matrix[i][j] = 1.0;
+        let n = request.symbols.len();
+        let mut correlation_matrix = vec![vec![0.0; n]; n];
+        let mut rolling_correlations = Vec::new();
+
+        for i in 0..n {
+            correlation_matrix[i][i] = 1.0;
+
+            for j in (i + 1)..n {
+                let (series_a, series_b) = Self::align_on_common_timestamps(
+                    &series_by_symbol[&request.symbols[i]],
+                    &series_by_symbol[&request.symbols[j]],
+                );
+                let returns_a = Self::log_returns(&series_a);
+                let returns_b = Self::log_returns(&series_b);
+
+                let correlation = if returns_a.len() >= 2 {
+                    DataAggregator::calculate_correlation(&returns_a, &returns_b)?
                 } else {
-                    correlation_matrix[i][j] = DataAggregator::calculate_correlation(&price_data[i], &price_data[j])?;
+                    0.0
+                };
+                // Exploit symmetry: compute each pair once.
+                correlation_matrix[i][j] = correlation;
+                correlation_matrix[j][i] = correlation;
+
+                if let Some(window) = request.window {
+                    if window >= 2 && returns_a.len() >= window {
+                        let mut values = Vec::with_capacity(returns_a.len() - window + 1);
+                        for start in 0..=(returns_a.len() - window) {
+                            values.push(DataAggregator::calculate_correlation(
+                                &returns_a[start..start + window],
+                                &returns_b[start..start + window],
+                            )?);
+                        }
+                        rolling_correlations.push(RollingCorrelation {
+                            symbol_a: request.symbols[i].clone(),
+                            symbol_b: request.symbols[j].clone(),
+                            values,
+                        });
+                    }
                 }
             }
         }
@@ -318,39 +592,63 @@ impl DataAggregationService {
             correlation_matrix,
             symbols: request.symbols,
             period_days: request.period_days,
+            rolling_correlations: if rolling_correlations.is_empty() { None } else { Some(rolling_correlations) },
             calculation_timestamp: Utc::now(),
         })
     }
 
+    /// Intersects two timestamp→close maps to their overlapping buckets,
+    /// sorted chronologically, so correlation is computed only over
+    /// timestamps both symbols actually have a candle for.
+    fn align_on_common_timestamps(a: &HashMap<i64, f64>, b: &HashMap<i64, f64>) -> (Vec<f64>, Vec<f64>) {
+        let mut common_timestamps: Vec<i64> = a.keys().filter(|ts| b.contains_key(ts)).copied().collect();
+        common_timestamps.sort();
+
+        let series_a = common_timestamps.iter().map(|ts| a[ts]).collect();
+        let series_b = common_timestamps.iter().map(|ts| b[ts]).collect();
+        (series_a, series_b)
+    }
+
+    fn log_returns(series: &[f64]) -> Vec<f64> {
+        series.windows(2).map(|w| (w[1] / w[0]).ln()).collect()
+    }
+
     pub async fn calculate_volatility(&self, request: VolatilityRequest) -> Result<VolatilityResponse> {
-        let price_data = self.mongodb_service.get_price_history(&request.symbol, "1d", request.period_days as usize).await?;
-        let prices: Vec<f64> = price_data.iter().map(|d| d.price).collect();
-        
-        if prices.is_empty() {
-            return Err(anyhow::anyhow!("No price data available for symbol: {}", request.symbol));
+        const TIMEFRAME: &str = "1d";
+        let candles = self.mongodb_service.get_ohlcv_data(&request.symbol, TIMEFRAME, request.period_days as usize).await?;
+
+        if candles.is_empty() {
+            return Err(anyhow::anyhow!("No OHLCV data available for symbol: {}", request.symbol));
         }
 
-        // Calculate returns
-        let returns: Vec<f64> = prices.windows(2)
-            .map(|w| (w[1] - w[0]) / w[0])
-            .collect();
+        // GARCH needs at least 3 log returns (4 positive closes) to fit, and
+        // EWMA is held to the same minimum here; anything shorter falls back
+        // to plain close-to-close std-dev rather than erroring out.
+        let positive_closes = candles.iter().filter(|c| c.close > 0.0).count();
+        let needs_fallback = matches!(request.calculation_method, VolatilityMethod::EWMA | VolatilityMethod::GARCH)
+            && positive_closes < 4;
 
-        // Calculate volatility (standard deviation of returns)
-        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
-        let variance = returns.iter()
-            .map(|r| (r - mean).powi(2))
-            .sum::<f64>() / returns.len() as f64;
-        let volatility = variance.sqrt();
-        
-        // Annualize volatility (assuming 365 days per year)
-        let annualized_volatility = volatility * (365.0_f64).sqrt();
+        let (annualized_volatility, method) = if needs_fallback {
+            (DataAggregator::calculate_close_to_close_volatility(&candles, TIMEFRAME)?, VolatilityMethod::StandardDeviation)
+        } else {
+            let value = match request.calculation_method {
+                VolatilityMethod::StandardDeviation => DataAggregator::calculate_close_to_close_volatility(&candles, TIMEFRAME)?,
+                VolatilityMethod::Parkinson => DataAggregator::calculate_parkinson_volatility(&candles, TIMEFRAME)?,
+                VolatilityMethod::GarmanKlass => DataAggregator::calculate_garman_klass_volatility(&candles, TIMEFRAME)?,
+                VolatilityMethod::EWMA => DataAggregator::calculate_ewma_volatility(&candles, TIMEFRAME, 0.94)?,
+                VolatilityMethod::GARCH => DataAggregator::calculate_garch_volatility(&candles, TIMEFRAME)?,
+            };
+            (value, request.calculation_method)
+        };
+        let volatility = annualized_volatility / DataAggregator::periods_per_year(TIMEFRAME)?.sqrt();
 
         Ok(VolatilityResponse {
             symbol: request.symbol,
             volatility,
             annualized_volatility,
-            method: request.calculation_method,
+            method,
             period_days: request.period_days,
+            fallback_applied: needs_fallback,
             calculation_timestamp: Utc::now(),
         })
     }
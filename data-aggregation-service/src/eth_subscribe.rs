@@ -0,0 +1,190 @@
+//! Direct on-chain `EventSource` via an Ethereum node's `eth_subscribe`
+//! websocket pubsub (`logs` + `newHeads`), as a lower-latency alternative to
+//! consuming `KafkaConsumerService`'s topics. Mirrors ethers-rs's
+//! provider/pubsub subscription model, and reconnects the same way
+//! `rate_feed.rs` does: an unbounded loop with capped exponential backoff.
+//! Subscription ids are invalidated the instant the socket drops, so every
+//! reconnect reissues both `eth_subscribe` calls from scratch rather than
+//! trying to resume the old subscription ids.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Utc;
+use futures_util::{SinkExt, StreamExt};
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::event_source::{BoxFuture, EventSource};
+use crate::models::{EventProvenance, StreamEventType, StreamingDataEvent};
+
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// A contract-log filter, passed verbatim as `eth_subscribe("logs", ...)`'s
+/// second parameter.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LogFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topics: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SubscriptionKind {
+    Logs,
+    NewHeads,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    id: Option<u64>,
+    result: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcNotification {
+    method: Option<String>,
+    params: Option<NotificationParams>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NotificationParams {
+    subscription: String,
+    result: Value,
+}
+
+/// Sources `StreamingDataEvent`s straight from an Ethereum node's
+/// `eth_subscribe` pubsub instead of the Kafka topics `KafkaConsumerService`
+/// consumes, so a deployment that only cares about low latency can skip the
+/// Kafka hop entirely. Emits `StreamEventType::NewBlock` for `newHeads` and
+/// `StreamEventType::ContractEvent` for `logs`, matching the shapes
+/// `KafkaConsumerService::convert_to_streaming_event` falls back to for the
+/// same underlying data.
+pub struct EthSubscribeService {
+    ws_url: String,
+    filters: Vec<LogFilter>,
+}
+
+impl EthSubscribeService {
+    pub fn new(ws_url: impl Into<String>, filters: Vec<LogFilter>) -> Self {
+        Self { ws_url: ws_url.into(), filters }
+    }
+
+    /// Runs one connection's worth of the subscribe/consume loop. Returns
+    /// (or errors) once the socket closes, so the caller can reconnect and
+    /// resubscribe with fresh subscription ids.
+    async fn run_once(&self, handler: &mut (dyn FnMut(StreamingDataEvent) -> Result<()> + Send)) -> Result<()> {
+        let (mut stream, _response) = tokio_tungstenite::connect_async(&self.ws_url).await?;
+
+        let mut pending: HashMap<u64, SubscriptionKind> = HashMap::new();
+        let mut next_id: u64 = 1;
+
+        for filter in &self.filters {
+            let id = next_id;
+            next_id += 1;
+            pending.insert(id, SubscriptionKind::Logs);
+            let request = json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "eth_subscribe",
+                "params": ["logs", filter],
+            });
+            stream.send(Message::Text(request.to_string())).await?;
+        }
+
+        let new_heads_id = next_id;
+        pending.insert(new_heads_id, SubscriptionKind::NewHeads);
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": new_heads_id,
+            "method": "eth_subscribe",
+            "params": ["newHeads"],
+        });
+        stream.send(Message::Text(request.to_string())).await?;
+
+        // Subscription id (returned in each `eth_subscribe` response) -> what
+        // kind of notification it tags, so a later `eth_subscription` push
+        // can be routed without re-parsing its payload shape to guess.
+        let mut subscriptions: HashMap<String, SubscriptionKind> = HashMap::new();
+
+        while let Some(message) = stream.next().await {
+            let text = match message {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_)) | Err(_) => break,
+                Ok(_) => continue,
+            };
+
+            if let Ok(response) = serde_json::from_str::<RpcResponse>(&text) {
+                if let (Some(id), Some(result)) = (response.id, response.result) {
+                    if let Some(kind) = pending.remove(&id) {
+                        if let Some(sub_id) = result.as_str() {
+                            subscriptions.insert(sub_id.to_string(), kind);
+                        }
+                        continue;
+                    }
+                }
+            }
+
+            let Ok(notification) = serde_json::from_str::<RpcNotification>(&text) else {
+                continue;
+            };
+            if notification.method.as_deref() != Some("eth_subscription") {
+                continue;
+            }
+            let Some(params) = notification.params else {
+                continue;
+            };
+            let Some(kind) = subscriptions.get(&params.subscription) else {
+                continue;
+            };
+
+            let event_type = match kind {
+                SubscriptionKind::NewHeads => StreamEventType::NewBlock,
+                SubscriptionKind::Logs => StreamEventType::ContractEvent,
+            };
+            // No Kafka topic/partition/offset for a websocket-pubsub source,
+            // and no de-duplication layer here; `KafkaConsumerService` is
+            // where that's implemented, since this source has no redelivery
+            // semantics of its own to de-duplicate against.
+            let event = StreamingDataEvent {
+                event_type,
+                symbol: "ETH".to_string(), // or derive from the log's contract address
+                provenance: EventProvenance::default(),
+                data: params.result,
+                timestamp: Utc::now(),
+            };
+
+            if let Err(e) = handler(event) {
+                error!("Failed to handle eth_subscribe event: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl EventSource for EthSubscribeService {
+    fn run<'a>(
+        &'a self,
+        mut handler: Box<dyn FnMut(StreamingDataEvent) -> Result<()> + Send + 'a>,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let mut reconnect_delay = INITIAL_RECONNECT_DELAY;
+
+            loop {
+                match self.run_once(handler.as_mut()).await {
+                    Ok(()) => warn!("eth_subscribe connection to {} closed, reconnecting", self.ws_url),
+                    Err(e) => error!("eth_subscribe connection to {} failed: {}", self.ws_url, e),
+                }
+
+                tokio::time::sleep(reconnect_delay).await;
+                reconnect_delay = (reconnect_delay * 2).min(MAX_RECONNECT_DELAY);
+            }
+        })
+    }
+}
@@ -0,0 +1,255 @@
+//! ABI-aware decoding of `contract-events` payloads into structured DEX
+//! event data, so `KafkaConsumerService::convert_to_streaming_event` can
+//! surface a real `Swap`/`Mint`/`Burn`/`Sync` instead of an opaque
+//! `ContractEvent` blob. Modeled on blockchain-sync-service's
+//! `LogDecoder`, scaled down to the handful of Uniswap V2-shaped events
+//! this crate cares about for OHLC/price aggregation, and extensible via
+//! `KafkaConsumerService::register_event_decoder` instead of a hard-coded
+//! match arm.
+//!
+//! Amounts are decoded to decimal strings rather than any fixed-width
+//! integer, since ERC-20 amounts routinely exceed `u128::MAX` once
+//! 18-decimal tokens are involved.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::models::StreamEventType;
+
+/// `keccak256("Swap(address,uint256,uint256,uint256,uint256,address)")`.
+pub const UNISWAP_V2_SWAP_TOPIC0: &str =
+    "0xd78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822";
+/// `keccak256("Mint(address,uint256,uint256)")`.
+pub const UNISWAP_V2_MINT_TOPIC0: &str =
+    "0x4c209b5fc8ad50758f13e2e1088ba56a560dff690a1c6fef26394f4c03821c4f";
+/// `keccak256("Burn(address,uint256,uint256,address)")`.
+pub const UNISWAP_V2_BURN_TOPIC0: &str =
+    "0xdccd412f0b1252819cb1fd330b93224ca42612892bb3f4f789976e6d81936496";
+/// `keccak256("Sync(uint112,uint112)")`.
+pub const UNISWAP_V2_SYNC_TOPIC0: &str =
+    "0x1c411e9a96e071241c2f21f7726b17ae89e3cab4c78be50e062b03a9fffbbad1";
+
+/// Structured result of decoding one event's `topics`/`data`, serialized
+/// into `StreamingDataEvent::data` by the caller.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind")]
+pub enum DecodedContractEvent {
+    Swap {
+        sender: String,
+        to: String,
+        amount0_in: String,
+        amount1_in: String,
+        amount0_out: String,
+        amount1_out: String,
+    },
+    Mint {
+        sender: String,
+        amount0: String,
+        amount1: String,
+    },
+    Burn {
+        sender: String,
+        to: String,
+        amount0: String,
+        amount1: String,
+    },
+    Sync {
+        reserve0: String,
+        reserve1: String,
+    },
+}
+
+/// Decodes a single event signature's `topics`/`data` into a
+/// `DecodedContractEvent`, registered against its topic0 signature hash in
+/// `EventDecoderRegistry`.
+pub trait EventDecoder: Send + Sync {
+    fn decode(&self, topics: &[String], data_hex: &str) -> Option<DecodedContractEvent>;
+    /// The `StreamEventType` tag this decoder's output should be reported
+    /// under.
+    fn event_type(&self) -> StreamEventType;
+}
+
+struct UniswapV2SwapDecoder;
+impl EventDecoder for UniswapV2SwapDecoder {
+    fn event_type(&self) -> StreamEventType {
+        StreamEventType::Swap
+    }
+
+    fn decode(&self, topics: &[String], data_hex: &str) -> Option<DecodedContractEvent> {
+        if topics.len() < 3 {
+            return None;
+        }
+        let data = decode_hex(data_hex);
+        if data.len() < 128 {
+            return None;
+        }
+        Some(DecodedContractEvent::Swap {
+            sender: topic_address(&topics[1]),
+            to: topic_address(&topics[2]),
+            amount0_in: decimal_from_be_bytes(&data[0..32]),
+            amount1_in: decimal_from_be_bytes(&data[32..64]),
+            amount0_out: decimal_from_be_bytes(&data[64..96]),
+            amount1_out: decimal_from_be_bytes(&data[96..128]),
+        })
+    }
+}
+
+struct UniswapV2MintDecoder;
+impl EventDecoder for UniswapV2MintDecoder {
+    fn event_type(&self) -> StreamEventType {
+        StreamEventType::Mint
+    }
+
+    fn decode(&self, topics: &[String], data_hex: &str) -> Option<DecodedContractEvent> {
+        if topics.len() < 2 {
+            return None;
+        }
+        let data = decode_hex(data_hex);
+        if data.len() < 64 {
+            return None;
+        }
+        Some(DecodedContractEvent::Mint {
+            sender: topic_address(&topics[1]),
+            amount0: decimal_from_be_bytes(&data[0..32]),
+            amount1: decimal_from_be_bytes(&data[32..64]),
+        })
+    }
+}
+
+struct UniswapV2BurnDecoder;
+impl EventDecoder for UniswapV2BurnDecoder {
+    fn event_type(&self) -> StreamEventType {
+        StreamEventType::Burn
+    }
+
+    fn decode(&self, topics: &[String], data_hex: &str) -> Option<DecodedContractEvent> {
+        if topics.len() < 3 {
+            return None;
+        }
+        let data = decode_hex(data_hex);
+        if data.len() < 64 {
+            return None;
+        }
+        Some(DecodedContractEvent::Burn {
+            sender: topic_address(&topics[1]),
+            to: topic_address(&topics[2]),
+            amount0: decimal_from_be_bytes(&data[0..32]),
+            amount1: decimal_from_be_bytes(&data[32..64]),
+        })
+    }
+}
+
+struct UniswapV2SyncDecoder;
+impl EventDecoder for UniswapV2SyncDecoder {
+    fn event_type(&self) -> StreamEventType {
+        StreamEventType::Sync
+    }
+
+    fn decode(&self, topics: &[String], data_hex: &str) -> Option<DecodedContractEvent> {
+        if topics.is_empty() {
+            return None;
+        }
+        let data = decode_hex(data_hex);
+        if data.len() < 64 {
+            return None;
+        }
+        Some(DecodedContractEvent::Sync {
+            reserve0: decimal_from_be_bytes(&data[0..32]),
+            reserve1: decimal_from_be_bytes(&data[32..64]),
+        })
+    }
+}
+
+/// Maps event topic0 signature hashes to `EventDecoder`s, and pool contract
+/// addresses to their human-readable token pair symbol, so
+/// `KafkaConsumerService` doesn't need a hard-coded match arm per protocol.
+/// Seeded with the Uniswap V2 `Swap`/`Mint`/`Burn`/`Sync` shapes; extend via
+/// `register`/`register_pool`.
+pub struct EventDecoderRegistry {
+    decoders: HashMap<String, Arc<dyn EventDecoder>>,
+    /// Pool contract address (lowercased) -> pair symbol, e.g. "ETH/USDC".
+    pool_pairs: HashMap<String, String>,
+}
+
+impl EventDecoderRegistry {
+    pub fn new() -> Self {
+        let mut registry = Self {
+            decoders: HashMap::new(),
+            pool_pairs: HashMap::new(),
+        };
+        registry.register(UNISWAP_V2_SWAP_TOPIC0, Arc::new(UniswapV2SwapDecoder));
+        registry.register(UNISWAP_V2_MINT_TOPIC0, Arc::new(UniswapV2MintDecoder));
+        registry.register(UNISWAP_V2_BURN_TOPIC0, Arc::new(UniswapV2BurnDecoder));
+        registry.register(UNISWAP_V2_SYNC_TOPIC0, Arc::new(UniswapV2SyncDecoder));
+        registry
+    }
+
+    /// Registers (or overrides) the decoder used for events whose
+    /// `topics[0]` matches `topic0`.
+    pub fn register(&mut self, topic0: &str, decoder: Arc<dyn EventDecoder>) {
+        self.decoders.insert(topic0.to_lowercase(), decoder);
+    }
+
+    /// Registers the pair symbol a pool contract address should resolve to.
+    pub fn register_pool(&mut self, pool_address: &str, symbol: impl Into<String>) {
+        self.pool_pairs.insert(pool_address.to_lowercase(), symbol.into());
+    }
+
+    /// Resolves `contract_address` to its configured pair symbol, falling
+    /// back to the raw address when no pool has been registered for it.
+    pub fn resolve_symbol(&self, contract_address: &str) -> String {
+        self.pool_pairs
+            .get(&contract_address.to_lowercase())
+            .cloned()
+            .unwrap_or_else(|| contract_address.to_string())
+    }
+
+    /// Decodes `topics`/`data_hex` against whichever decoder is registered
+    /// for `topics[0]`. `None` if no decoder matches, so the caller can fall
+    /// back to generic handling instead of dropping the event.
+    pub fn decode(&self, topics: &[String], data_hex: &str) -> Option<(StreamEventType, DecodedContractEvent)> {
+        let topic0 = topics.first()?.to_lowercase();
+        let decoder = self.decoders.get(&topic0)?;
+        let decoded = decoder.decode(topics, data_hex)?;
+        Some((decoder.event_type(), decoded))
+    }
+}
+
+fn decode_hex(s: &str) -> Vec<u8> {
+    let s = s.trim_start_matches("0x");
+    (0..s.len())
+        .step_by(2)
+        .filter_map(|i| s.get(i..i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()))
+        .collect()
+}
+
+fn topic_address(topic: &str) -> String {
+    let hex = topic.trim_start_matches("0x");
+    format!("0x{}", &hex[hex.len().saturating_sub(40)..])
+}
+
+/// Decimal string for a big-endian unsigned integer of arbitrary length,
+/// via repeated division by 10 in base-256 — these amounts regularly
+/// exceed `u128::MAX` once 18-decimal token amounts are involved, so they
+/// can't be parsed into a fixed-width integer type first.
+fn decimal_from_be_bytes(bytes: &[u8]) -> String {
+    let mut num: Vec<u8> = bytes.to_vec();
+    if num.iter().all(|&b| b == 0) {
+        return "0".to_string();
+    }
+
+    let mut digits = Vec::new();
+    while !num.iter().all(|&b| b == 0) {
+        let mut remainder: u32 = 0;
+        for byte in num.iter_mut() {
+            let acc = remainder * 256 + *byte as u32;
+            *byte = (acc / 10) as u8;
+            remainder = acc % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+    }
+
+    digits.iter().rev().map(|&b| b as char).collect()
+}
@@ -0,0 +1,27 @@
+//! A source-agnostic abstraction over where `StreamingDataEvent`s come
+//! from, so `DataAggregationService` can run against either
+//! `KafkaConsumerService` (the blockchain-sync-service topics) or
+//! `EthSubscribeService` (a direct `eth_subscribe` websocket) without
+//! branching on which one it was handed. Mirrors `transaction-signing-
+//! service`'s `Signer` trait: a plain generic method isn't object-safe once
+//! the implementation is async, so methods return a boxed future instead.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use anyhow::Result;
+
+use crate::models::StreamingDataEvent;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Runs for as long as the service does, calling `handler` once per event.
+/// Implementations retry their own connection/subscription internally (a
+/// dropped Kafka connection or closed websocket isn't a reason for this to
+/// return), so `run` only resolves if the source gives up permanently.
+pub trait EventSource: Send + Sync {
+    fn run<'a>(
+        &'a self,
+        handler: Box<dyn FnMut(StreamingDataEvent) -> Result<()> + Send + 'a>,
+    ) -> BoxFuture<'a, ()>;
+}
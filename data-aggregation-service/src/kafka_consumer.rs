@@ -1,36 +1,262 @@
 use anyhow::Result;
 use futures::StreamExt;
-use log::{error, info, warn};
+use log::{debug, error, info, warn};
 use rdkafka::config::ClientConfig;
-use rdkafka::consumer::{Consumer, StreamConsumer};
-use rdkafka::message::Message;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::message::{BorrowedMessage, Message};
+use rdkafka::producer::{FutureProducer, FutureRecord};
 use serde_json;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 
-use crate::models::{StreamingDataEvent, PriceDataPoint};
+use crate::event_decoder::{EventDecoder, EventDecoderRegistry};
+use crate::event_source::{BoxFuture, EventSource};
+use crate::models::{EventProvenance, ProducerAnnotation, PriceDataPoint, StreamEventType, StreamingDataEvent};
+
+/// How to derive a stable de-duplication id for one `StreamEventType` from
+/// its raw (pre-decode) JSON payload. `TxHashLogIndex`/`BlockHash` cover the
+/// built-in defaults this crate registers; `JsonField`/`None` are for
+/// `with_dedup_config` overrides.
+#[derive(Debug, Clone)]
+pub enum DedupIdStrategy {
+    /// `transaction_hash` + `log_index` — the default for contract events
+    /// (`ContractEvent`, `Swap`, `Mint`, `Burn`, `Sync`), since a single
+    /// transaction can emit more than one log.
+    TxHashLogIndex,
+    /// `block_hash` — the default for `NewBlock`.
+    BlockHash,
+    /// An arbitrary top-level string field.
+    JsonField(&'static str),
+    /// No stable id is derivable for this event type; never de-duplicated.
+    None,
+}
+
+impl DedupIdStrategy {
+    fn extract(&self, payload: &serde_json::Value) -> Option<String> {
+        match self {
+            DedupIdStrategy::TxHashLogIndex => {
+                let tx_hash = payload.get("transaction_hash").and_then(|v| v.as_str())?;
+                let log_index = payload.get("log_index").and_then(|v| v.as_i64())?;
+                Some(format!("{}:{}", tx_hash, log_index))
+            }
+            DedupIdStrategy::BlockHash => {
+                payload.get("block_hash").and_then(|v| v.as_str()).map(str::to_string)
+            }
+            DedupIdStrategy::JsonField(field) => {
+                payload.get(*field).and_then(|v| v.as_str()).map(str::to_string)
+            }
+            DedupIdStrategy::None => None,
+        }
+    }
+}
+
+/// A `StreamEventType`'s de-duplication window size and id-extraction
+/// strategy, registered via `KafkaConsumerService::with_dedup_config`.
+#[derive(Debug, Clone)]
+pub struct DedupConfig {
+    /// How many distinct ids `start_consuming` remembers for this event
+    /// type before the oldest is evicted to make room for a new one.
+    pub window_size: usize,
+    pub id_strategy: DedupIdStrategy,
+}
+
+/// A bounded set of recently-seen dedup ids for one event type: insertion
+/// order tracked in `order` so the oldest id is evicted once `capacity` is
+/// exceeded, membership tracked in `seen` for O(1) duplicate checks.
+struct BoundedDedupSet {
+    capacity: usize,
+    seen: std::collections::HashSet<String>,
+    order: VecDeque<String>,
+}
+
+impl BoundedDedupSet {
+    fn new(capacity: usize) -> Self {
+        Self { capacity, seen: std::collections::HashSet::new(), order: VecDeque::new() }
+    }
+
+    /// Records `id` and returns `true` if it hadn't been seen within the
+    /// current window, `false` if this is a duplicate.
+    fn insert(&mut self, id: String) -> bool {
+        if self.seen.contains(&id) {
+            return false;
+        }
+        if self.order.len() >= self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        self.seen.insert(id.clone());
+        self.order.push_back(id);
+        true
+    }
+}
+
+/// Which delivery guarantee `start_consuming` provides for the topics it
+/// consumes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliverySemantics {
+    /// This consumer's original behavior: `enable.auto.commit` handles
+    /// offsets on its own schedule, so a crash between a poll and the
+    /// handler finishing can silently drop the in-flight event.
+    AtMostOnce,
+    /// Disables auto-commit; an offset is only committed once `handler`
+    /// returns `Ok`, so a crash mid-handler redelivers the event on restart
+    /// instead of dropping it. Required for a DeFi aggregation pipeline,
+    /// where a dropped trade or liquidity event corrupts downstream
+    /// OHLC/price state.
+    AtLeastOnce,
+}
 
 #[derive(Clone)]
 pub struct KafkaConsumerService {
-    consumer: StreamConsumer,
+    consumer: std::sync::Arc<StreamConsumer>,
+    /// Used only under `DeliverySemantics::AtLeastOnce`, to forward an event
+    /// that has exhausted `max_retries` to `dead_letter_topic` instead of
+    /// retrying it forever and blocking the partition.
+    producer: FutureProducer,
+    delivery_semantics: DeliverySemantics,
+    dead_letter_topic: String,
+    /// How many times a failed handler call is retried, under
+    /// `DeliverySemantics::AtLeastOnce`, before the event's offset is
+    /// committed anyway and it's forwarded to `dead_letter_topic`.
+    max_retries: u32,
+    retry_backoff_base: Duration,
+    /// Per-`(topic, partition, offset)` retry counts, so `start_consuming`
+    /// only dead-letters an event once it's actually been retried
+    /// `max_retries` times across redeliveries, rather than per process
+    /// restart.
+    retry_counts: std::sync::Arc<Mutex<HashMap<(String, i32, i64), u32>>>,
+    /// Decodes `contract-events` payloads into typed DEX event variants
+    /// (`Swap`/`Mint`/`Burn`/`Sync`) instead of the generic `ContractEvent`
+    /// fallback, via whatever decoders/pools are registered through
+    /// `register_event_decoder`/`register_pool_pair`.
+    event_decoders: std::sync::Arc<RwLock<EventDecoderRegistry>>,
+    /// De-duplication window size and id-extraction strategy per
+    /// `StreamEventType`; event types with no entry are never de-duplicated.
+    dedup_config: HashMap<StreamEventType, DedupConfig>,
+    /// One `BoundedDedupSet` per event type that has a `dedup_config` entry,
+    /// created lazily on first use so a type added via `with_dedup_config`
+    /// after construction still gets one.
+    dedup_windows: std::sync::Arc<Mutex<HashMap<StreamEventType, BoundedDedupSet>>>,
+}
+
+/// The dedup windows `KafkaConsumerService::new` registers out of the box:
+/// tx_hash+log_index for contract events (a single transaction can emit
+/// several logs, so log_index disambiguates them), block_hash for new
+/// blocks. Other event types aren't de-duplicated unless a caller adds an
+/// entry via `with_dedup_config`.
+fn default_dedup_config() -> HashMap<StreamEventType, DedupConfig> {
+    let mut config = HashMap::new();
+    for event_type in [
+        StreamEventType::ContractEvent,
+        StreamEventType::Swap,
+        StreamEventType::Mint,
+        StreamEventType::Burn,
+        StreamEventType::Sync,
+    ] {
+        config.insert(event_type, DedupConfig { window_size: 10_000, id_strategy: DedupIdStrategy::TxHashLogIndex });
+    }
+    config.insert(StreamEventType::NewBlock, DedupConfig { window_size: 1_000, id_strategy: DedupIdStrategy::BlockHash });
+    config
+}
+
+/// Extracts an optional `{"producer": {"service": "...", "version": "..."}}`
+/// annotation from a raw event payload, if the upstream producer attached
+/// one.
+fn extract_producer_annotation(payload: &serde_json::Value) -> Option<ProducerAnnotation> {
+    let producer = payload.get("producer")?;
+    Some(ProducerAnnotation {
+        service: producer.get("service")?.as_str()?.to_string(),
+        version: producer.get("version")?.as_str()?.to_string(),
+    })
 }
 
 impl KafkaConsumerService {
     pub fn new(brokers: &str, group_id: &str) -> Result<Self> {
+        Self::with_delivery_semantics(brokers, group_id, DeliverySemantics::AtLeastOnce, "latest")
+    }
+
+    /// Builds a consumer with an explicit `DeliverySemantics` and
+    /// `auto.offset.reset` policy (`"earliest"` or `"latest"`), instead of
+    /// `new`'s `AtLeastOnce`/`"latest"` defaults.
+    pub fn with_delivery_semantics(
+        brokers: &str,
+        group_id: &str,
+        delivery_semantics: DeliverySemantics,
+        auto_offset_reset: &str,
+    ) -> Result<Self> {
+        let auto_commit = match delivery_semantics {
+            DeliverySemantics::AtMostOnce => "true",
+            DeliverySemantics::AtLeastOnce => "false",
+        };
+
         let consumer: StreamConsumer = ClientConfig::new()
             .set("group.id", group_id)
             .set("bootstrap.servers", brokers)
             .set("enable.partition.eof", "false")
             .set("session.timeout.ms", "6000")
-            .set("enable.auto.commit", "true")
-            .set("auto.offset.reset", "latest")
+            .set("enable.auto.commit", auto_commit)
+            .set("auto.offset.reset", auto_offset_reset)
+            .create()?;
+
+        let producer: FutureProducer = ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("message.timeout.ms", "5000")
             .create()?;
 
-        Ok(Self { consumer })
+        Ok(Self {
+            consumer: std::sync::Arc::new(consumer),
+            producer,
+            delivery_semantics,
+            dead_letter_topic: "data-aggregation-dead-letter".to_string(),
+            max_retries: 3,
+            retry_backoff_base: Duration::from_millis(200),
+            retry_counts: std::sync::Arc::new(Mutex::new(HashMap::new())),
+            event_decoders: std::sync::Arc::new(RwLock::new(EventDecoderRegistry::new())),
+            dedup_config: default_dedup_config(),
+            dedup_windows: std::sync::Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// Overrides (or adds) the de-duplication window size and id-extraction
+    /// strategy used for `event_type`. Passing `DedupIdStrategy::None`
+    /// disables de-duplication for that type.
+    pub fn with_dedup_config(mut self, event_type: StreamEventType, window_size: usize, id_strategy: DedupIdStrategy) -> Self {
+        self.dedup_config.insert(event_type, DedupConfig { window_size, id_strategy });
+        self
+    }
+
+    /// Registers (or overrides) the decoder used for `contract-events`
+    /// payloads whose `topics[0]` matches `topic0`.
+    pub fn register_event_decoder(&self, topic0: &str, decoder: Arc<dyn EventDecoder>) {
+        self.event_decoders.write().unwrap().register(topic0, decoder);
+    }
+
+    /// Registers the pair symbol a pool contract address should resolve to
+    /// on decoded `contract-events`.
+    pub fn register_pool_pair(&self, pool_address: &str, symbol: impl Into<String>) {
+        self.event_decoders.write().unwrap().register_pool(pool_address, symbol);
+    }
+
+    /// Overrides the topic an event is forwarded to once it exhausts
+    /// `max_retries` under `DeliverySemantics::AtLeastOnce`.
+    pub fn with_dead_letter_topic(mut self, topic: impl Into<String>) -> Self {
+        self.dead_letter_topic = topic.into();
+        self
+    }
+
+    /// Overrides how many times a failed handler call is retried, under
+    /// `DeliverySemantics::AtLeastOnce`, before the event is dead-lettered.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
     }
 
     pub async fn subscribe_to_price_updates(&self) -> Result<()> {
         self.consumer.subscribe(&[
             "blockchain-events",
-            "transaction-events", 
+            "transaction-events",
             "contract-events"
         ])?;
         info!("Subscribed to blockchain data topics");
@@ -54,27 +280,37 @@ impl KafkaConsumerService {
                     let payload = match m.payload_view::<str>() {
                         None => {
                             warn!("Empty message payload from topic: {}", topic);
+                            self.commit_if_at_least_once(&m);
                             continue;
                         }
                         Some(Ok(s)) => s,
                         Some(Err(e)) => {
                             error!("Error while deserializing message payload from {}: {:?}", topic, e);
+                            self.commit_if_at_least_once(&m);
                             continue;
                         }
                     };
 
                     // Convert blockchain events to streaming data events
-                    match self.convert_to_streaming_event(topic, payload) {
+                    match self.convert_to_streaming_event(topic, payload, m.partition(), m.offset()) {
                         Ok(Some(event)) => {
-                            if let Err(e) = handler(event) {
-                                error!("Failed to handle streaming event: {}", e);
+                            if self.is_duplicate(&event) {
+                                debug!(
+                                    "Skipping duplicate {:?} event (dedup id {:?})",
+                                    event.event_type, event.provenance.dedup_id
+                                );
+                                self.commit_if_at_least_once(&m);
+                            } else {
+                                self.handle_event(&m, event, &mut handler).await;
                             }
                         }
                         Ok(None) => {
                             // Event not relevant for aggregation
+                            self.commit_if_at_least_once(&m);
                         }
                         Err(e) => {
                             error!("Failed to convert message to streaming event: {}", e);
+                            self.commit_if_at_least_once(&m);
                         }
                     }
                 }
@@ -84,18 +320,199 @@ impl KafkaConsumerService {
         Ok(())
     }
 
-    fn convert_to_streaming_event(&self, topic: &str, payload: &str) -> Result<Option<StreamingDataEvent>> {
-        use crate::models::StreamEventType;
+    /// Runs `handler` against `event`. Under `DeliverySemantics::AtMostOnce`
+    /// this just logs a handler failure, matching the original behavior
+    /// (auto-commit has already advanced the offset regardless). Under
+    /// `AtLeastOnce`, a failing handler is retried with exponential backoff;
+    /// the offset is only committed once the handler succeeds, and after
+    /// `max_retries` failed attempts across redeliveries the event is
+    /// forwarded to `dead_letter_topic` and committed anyway so a poison
+    /// event doesn't block the partition forever.
+    async fn handle_event<F>(&self, message: &BorrowedMessage<'_>, event: StreamingDataEvent, handler: &mut F)
+    where
+        F: FnMut(StreamingDataEvent) -> Result<()> + Send,
+    {
+        if self.delivery_semantics == DeliverySemantics::AtMostOnce {
+            if let Err(e) = handler(event) {
+                error!("Failed to handle streaming event: {}", e);
+            }
+            return;
+        }
+
+        match handler(event.clone()) {
+            Ok(()) => {
+                self.clear_retry_count(message);
+                self.commit_if_at_least_once(message);
+            }
+            Err(e) => {
+                let attempt = self.record_retry(message);
+                warn!(
+                    "Failed to handle streaming event (attempt {}/{}): {}",
+                    attempt, self.max_retries, e
+                );
+
+                if attempt > self.max_retries {
+                    error!(
+                        "Streaming event exhausted {} retries; forwarding to dead-letter topic",
+                        self.max_retries
+                    );
+                    if let Err(dlq_err) = self.send_to_dead_letter(&event, &e.to_string()).await {
+                        error!("Failed to forward event to dead-letter topic: {}", dlq_err);
+                    }
+                    self.clear_retry_count(message);
+                    self.commit_if_at_least_once(message);
+                } else {
+                    tokio::time::sleep(self.retry_backoff_base * 2u32.pow(attempt - 1)).await;
+                    // Offset deliberately left uncommitted: the next poll of
+                    // this partition redelivers the same message.
+                }
+            }
+        }
+    }
+
+    /// Builds the `EventProvenance` block for an event read off `topic` at
+    /// `partition`/`offset`, decoded from `raw_payload`, reported as
+    /// `event_type`. `dedup_id` is only populated if `dedup_config` has an
+    /// entry for `event_type` and its strategy extracted one.
+    fn build_provenance(
+        &self,
+        topic: &str,
+        partition: i32,
+        offset: i64,
+        raw_payload: &serde_json::Value,
+        event_type: StreamEventType,
+    ) -> EventProvenance {
+        let dedup_id = self
+            .dedup_config
+            .get(&event_type)
+            .and_then(|config| config.id_strategy.extract(raw_payload));
+
+        EventProvenance {
+            source_topic: Some(topic.to_string()),
+            partition: Some(partition),
+            offset: Some(offset),
+            producer: extract_producer_annotation(raw_payload),
+            dedup_id,
+        }
+    }
+
+    /// Checks `event.provenance.dedup_id` against the bounded window for its
+    /// `event_type`, recording it if unseen. Events with no dedup id (either
+    /// no `dedup_config` entry for their type, or the strategy couldn't
+    /// extract one from this particular payload) are never treated as
+    /// duplicates.
+    fn is_duplicate(&self, event: &StreamingDataEvent) -> bool {
+        let Some(id) = &event.provenance.dedup_id else {
+            return false;
+        };
+        let Some(config) = self.dedup_config.get(&event.event_type) else {
+            return false;
+        };
+
+        let mut windows = self.dedup_windows.lock().unwrap();
+        let window = windows
+            .entry(event.event_type)
+            .or_insert_with(|| BoundedDedupSet::new(config.window_size));
+        !window.insert(id.clone())
+    }
+
+    fn retry_key(message: &BorrowedMessage) -> (String, i32, i64) {
+        (message.topic().to_string(), message.partition(), message.offset())
+    }
+
+    fn record_retry(&self, message: &BorrowedMessage) -> u32 {
+        let mut counts = self.retry_counts.lock().unwrap();
+        let count = counts.entry(Self::retry_key(message)).or_insert(0);
+        *count += 1;
+        *count
+    }
+
+    fn clear_retry_count(&self, message: &BorrowedMessage) {
+        self.retry_counts.lock().unwrap().remove(&Self::retry_key(message));
+    }
+
+    fn commit_if_at_least_once(&self, message: &BorrowedMessage) {
+        if self.delivery_semantics != DeliverySemantics::AtLeastOnce {
+            return;
+        }
+        if let Err(e) = self.consumer.commit_message(message, CommitMode::Async) {
+            error!("Failed to commit message offset: {}", e);
+        }
+    }
+
+    async fn send_to_dead_letter(&self, event: &StreamingDataEvent, error: &str) -> Result<()> {
+        let envelope = serde_json::json!({
+            "error": error,
+            "event": event,
+        });
+        let payload = serde_json::to_string(&envelope)?;
+
+        let record = FutureRecord::to(&self.dead_letter_topic)
+            .key(&event.symbol)
+            .payload(&payload);
+
+        match self.producer.send(record, Duration::from_secs(0)).await {
+            Ok(_) => Ok(()),
+            Err((e, _)) => Err(anyhow::anyhow!("Failed to send event to dead-letter topic: {}", e)),
+        }
+    }
+
+    /// Attempts to decode a `contract-events` payload's `topics`/`data`
+    /// against `event_decoders`, returning a fully-typed `Swap`/`Mint`/
+    /// `Burn`/`Sync` event with its pool resolved to a pair symbol. `None`
+    /// if the payload is missing the expected fields or no decoder matches
+    /// `topics[0]`, so the caller falls back to the generic `ContractEvent`
+    /// handling.
+    fn decode_contract_event(
+        &self,
+        topic: &str,
+        partition: i32,
+        offset: i64,
+        contract_event: &serde_json::Value,
+    ) -> Option<StreamingDataEvent> {
+        use chrono::Utc;
+
+        let topics: Vec<String> = contract_event
+            .get("topics")?
+            .as_array()?
+            .iter()
+            .filter_map(|t| t.as_str().map(str::to_string))
+            .collect();
+        let data_hex = contract_event.get("data")?.as_str()?;
+        let contract_address = contract_event.get("contract_address")?.as_str()?;
+
+        let registry = self.event_decoders.read().unwrap();
+        let (event_type, decoded) = registry.decode(&topics, data_hex)?;
+        let symbol = registry.resolve_symbol(contract_address);
+
+        Some(StreamingDataEvent {
+            event_type,
+            symbol,
+            data: serde_json::to_value(&decoded).ok()?,
+            timestamp: Utc::now(),
+            provenance: self.build_provenance(topic, partition, offset, contract_event, event_type),
+        })
+    }
+
+    fn convert_to_streaming_event(
+        &self,
+        topic: &str,
+        payload: &str,
+        partition: i32,
+        offset: i64,
+    ) -> Result<Option<StreamingDataEvent>> {
         use chrono::Utc;
 
         match topic {
             "blockchain-events" => {
                 // Parse block events and extract relevant data
                 if let Ok(block_event) = serde_json::from_str::<serde_json::Value>(payload) {
-                    if let Some(block_number) = block_event.get("block_number") {
+                    if block_event.get("block_number").is_some() {
+                        let event_type = StreamEventType::NewBlock;
                         return Ok(Some(StreamingDataEvent {
-                            event_type: StreamEventType::NewBlock,
+                            event_type,
                             symbol: "ETH".to_string(), // or derive from chain
+                            provenance: self.build_provenance(topic, partition, offset, &block_event, event_type),
                             data: block_event,
                             timestamp: Utc::now(),
                         }));
@@ -105,9 +522,11 @@ impl KafkaConsumerService {
             "transaction-events" => {
                 // Parse transaction events for DeFi activity
                 if let Ok(tx_event) = serde_json::from_str::<serde_json::Value>(payload) {
+                    let event_type = StreamEventType::TradeExecution;
                     return Ok(Some(StreamingDataEvent {
-                        event_type: StreamEventType::TradeExecution,
+                        event_type,
                         symbol: "ETH".to_string(), // or derive from transaction
+                        provenance: self.build_provenance(topic, partition, offset, &tx_event, event_type),
                         data: tx_event,
                         timestamp: Utc::now(),
                     }));
@@ -116,16 +535,21 @@ impl KafkaConsumerService {
             "contract-events" => {
                 // Parse contract events for DEX trades, liquidity changes, etc.
                 if let Ok(contract_event) = serde_json::from_str::<serde_json::Value>(payload) {
+                    if let Some(decoded) = self.decode_contract_event(topic, partition, offset, &contract_event) {
+                        return Ok(Some(decoded));
+                    }
+
                     let event_type = if contract_event.get("topics").is_some() {
-                        // Determine event type based on contract topics
+                        // Topics present but no registered decoder matched
                         StreamEventType::ContractEvent
                     } else {
                         StreamEventType::LiquidityChange
                     };
-                    
+
                     return Ok(Some(StreamingDataEvent {
                         event_type,
                         symbol: "ETH".to_string(), // or derive from contract
+                        provenance: self.build_provenance(topic, partition, offset, &contract_event, event_type),
                         data: contract_event,
                         timestamp: Utc::now(),
                     }));
@@ -139,3 +563,15 @@ impl KafkaConsumerService {
         Ok(None)
     }
 }
+
+impl EventSource for KafkaConsumerService {
+    fn run<'a>(
+        &'a self,
+        mut handler: Box<dyn FnMut(StreamingDataEvent) -> Result<()> + Send + 'a>,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            self.subscribe_to_price_updates().await?;
+            self.start_consuming(move |event| handler(event)).await
+        })
+    }
+}
@@ -1,6 +1,6 @@
 use anyhow::Result;
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::Json,
     routing::{get, post},
@@ -9,25 +9,37 @@ use axum::{
 use log::{error, info};
 use std::collections::HashMap;
 use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::net::TcpListener;
 use tower_http::cors::CorsLayer;
 
 mod models;
 mod aggregation;
+mod backfill;
+mod candle_builder;
+mod event_decoder;
+mod event_source;
+mod eth_subscribe;
 mod kafka_consumer;
 mod mongodb_client;
 mod data_service;
+mod websocket;
 
 use models::*;
 use aggregation::*;
 use kafka_consumer::KafkaConsumerService;
 use mongodb_client::MongoDBService;
 use data_service::DataAggregationService;
+use websocket::{real_time_feed_ws_handler, stream_ws_handler};
 
 #[derive(Clone)]
 pub struct AppState {
     data_service: Arc<DataAggregationService>,
+    /// Number of currently-connected `/stream/ws` and
+    /// `/data/real-time-feed/ws` subscribers, surfaced on `/health` as
+    /// `active_streams`.
+    active_streams: Arc<AtomicU64>,
 }
 
 #[tokio::main]
@@ -46,6 +58,7 @@ async fn main() -> Result<()> {
     
     let app_state = AppState {
         data_service: data_service.clone(),
+        active_streams: Arc::new(AtomicU64::new(0)),
     };
 
     // Start Kafka consumers in background
@@ -56,6 +69,21 @@ async fn main() -> Result<()> {
         }
     });
 
+    // Optionally source events directly from an Ethereum node's
+    // `eth_subscribe` pubsub instead of (or alongside) the Kafka consumers,
+    // for deployments where the Kafka hop's added latency matters more than
+    // its durability. Off by default: no node websocket to subscribe to
+    // unless one is configured.
+    if let Ok(eth_ws_url) = env::var("ETH_WS_URL") {
+        let filters = vec![eth_subscribe::LogFilter::default()];
+        let eth_service = data_service.clone();
+        tokio::spawn(async move {
+            if let Err(e) = eth_service.start_eth_subscribe_source(eth_ws_url, filters).await {
+                error!("eth_subscribe event source error: {}", e);
+            }
+        });
+    }
+
     // Start real-time aggregation in background
     let aggregation_service = data_service.clone();
     tokio::spawn(async move {
@@ -72,7 +100,15 @@ async fn main() -> Result<()> {
         .route("/data/market-summary", get(get_market_summary_handler))
         .route("/data/liquidity-metrics", get(get_liquidity_metrics_handler))
         .route("/data/aggregated-ohlcv", get(get_ohlcv_data_handler))
+        .route("/data/backfill-ohlcv", post(backfill_ohlcv_handler))
+        .route("/data/aggregation-task", post(process_aggregation_task_handler))
         .route("/data/real-time-feed", get(get_real_time_feed_handler))
+        .route("/data/real-time-feed/ws", get(real_time_feed_ws_handler))
+        .route("/stream/ws", get(stream_ws_handler))
+        .route("/market/summary", get(get_market_summary_handler))
+        .route("/tickers/:symbol/ohlcv", get(get_ticker_ohlcv_handler))
+        .route("/price_history/:symbol", get(get_price_history_path_handler))
+        .route("/coingecko/tickers", get(get_coingecko_tickers_handler))
         .route("/analytics/correlation", post(calculate_correlation_handler))
         .route("/analytics/volatility", post(calculate_volatility_handler))
         .layer(CorsLayer::permissive())
@@ -86,13 +122,13 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-async fn health_check() -> Json<serde_json::Value> {
+async fn health_check(State(state): State<AppState>) -> Json<serde_json::Value> {
     Json(serde_json::json!({
         "status": "healthy",
         "service": "data-aggregation",
         "timestamp": chrono::Utc::now().timestamp(),
         "memory_usage": get_memory_usage(),
-        "active_streams": 0 // Could be tracked
+        "active_streams": state.active_streams.load(Ordering::Relaxed)
     }))
 }
 
@@ -179,6 +215,78 @@ async fn get_ohlcv_data_handler(
     }
 }
 
+async fn backfill_ohlcv_handler(
+    State(state): State<AppState>,
+    Json(request): Json<BackfillOhlcvRequest>,
+) -> Result<Json<BackfillOhlcvResponse>, StatusCode> {
+    match state.data_service.backfill_ohlcv(
+        &request.symbol,
+        &request.timeframe,
+        request.from_ts,
+        request.to_ts,
+    ).await {
+        Ok(cursor) => Ok(Json(BackfillOhlcvResponse {
+            candles_written: cursor.candles_written,
+            next_start_timestamp: cursor.next_start_timestamp,
+        })),
+        Err(e) => {
+            error!("Failed to backfill OHLCV data: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Path-param variant of `get_ohlcv_data_handler`, for CoinGecko-style
+/// aggregators that expect the symbol in the path rather than as a query
+/// parameter.
+async fn get_ticker_ohlcv_handler(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<OHLCVResponse>, StatusCode> {
+    let timeframe = params.get("timeframe").cloned().unwrap_or_else(|| "1h".to_string());
+    let limit: usize = params.get("limit")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(100);
+
+    match state.data_service.get_ohlcv_data(&symbol, &timeframe, limit).await {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => {
+            error!("Failed to get OHLCV data: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Path-param variant of `get_price_history_handler`, for CoinGecko-style
+/// aggregators that expect the symbol in the path rather than as a query
+/// parameter.
+async fn get_price_history_path_handler(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<PriceHistoryResponse>, StatusCode> {
+    let timeframe = params.get("timeframe").cloned().unwrap_or_else(|| "1h".to_string());
+    let limit: usize = params.get("limit")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(100);
+
+    match state.data_service.get_price_history(&symbol, &timeframe, limit).await {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => {
+            error!("Failed to get price history: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn process_aggregation_task_handler(
+    State(state): State<AppState>,
+    Json(task): Json<AggregationTask>,
+) -> Json<AggregationResult> {
+    Json(state.data_service.process_aggregation_task(task).await)
+}
+
 async fn get_real_time_feed_handler(
     State(state): State<AppState>,
     Query(params): Query<HashMap<String, String>>,
@@ -196,6 +304,18 @@ async fn get_real_time_feed_handler(
     }
 }
 
+async fn get_coingecko_tickers_handler(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<CoinGeckoTicker>>, StatusCode> {
+    match state.data_service.get_coingecko_tickers().await {
+        Ok(tickers) => Ok(Json(tickers)),
+        Err(e) => {
+            error!("Failed to get CoinGecko tickers: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 async fn calculate_correlation_handler(
     State(state): State<AppState>,
     Json(request): Json<CorrelationRequest>,
@@ -10,6 +10,79 @@ pub struct PriceDataPoint {
     pub source: String,
 }
 
+/// A candle timeframe in the rollup hierarchy. Each resolution (other than
+/// the coarsest, `1w`) has a `parent()` one step up, so the aggregation
+/// pipeline only ever computes `1m` candles from raw ticks and derives
+/// every other resolution by rolling up the one below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Resolution {
+    R1m,
+    R5m,
+    R15m,
+    R30m,
+    R1h,
+    R4h,
+    R1d,
+    R1w,
+}
+
+impl Resolution {
+    pub fn seconds(&self) -> i64 {
+        match self {
+            Resolution::R1m => 60,
+            Resolution::R5m => 5 * 60,
+            Resolution::R15m => 15 * 60,
+            Resolution::R30m => 30 * 60,
+            Resolution::R1h => 60 * 60,
+            Resolution::R4h => 4 * 60 * 60,
+            Resolution::R1d => 24 * 60 * 60,
+            Resolution::R1w => 7 * 24 * 60 * 60,
+        }
+    }
+
+    /// The next resolution up the rollup chain, or `None` for `1w`, the
+    /// coarsest resolution this pipeline derives.
+    pub fn parent(&self) -> Option<Resolution> {
+        match self {
+            Resolution::R1m => Some(Resolution::R5m),
+            Resolution::R5m => Some(Resolution::R15m),
+            Resolution::R15m => Some(Resolution::R30m),
+            Resolution::R30m => Some(Resolution::R1h),
+            Resolution::R1h => Some(Resolution::R4h),
+            Resolution::R4h => Some(Resolution::R1d),
+            Resolution::R1d => Some(Resolution::R1w),
+            Resolution::R1w => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Resolution::R1m => "1m",
+            Resolution::R5m => "5m",
+            Resolution::R15m => "15m",
+            Resolution::R30m => "30m",
+            Resolution::R1h => "1h",
+            Resolution::R4h => "4h",
+            Resolution::R1d => "1d",
+            Resolution::R1w => "1w",
+        }
+    }
+
+    pub fn from_str_opt(s: &str) -> Option<Resolution> {
+        match s {
+            "1m" => Some(Resolution::R1m),
+            "5m" => Some(Resolution::R5m),
+            "15m" => Some(Resolution::R15m),
+            "30m" => Some(Resolution::R30m),
+            "1h" => Some(Resolution::R1h),
+            "4h" => Some(Resolution::R4h),
+            "1d" => Some(Resolution::R1d),
+            "1w" => Some(Resolution::R1w),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OHLCVData {
     pub timestamp: i64,
@@ -20,6 +93,11 @@ pub struct OHLCVData {
     pub close: f64,
     pub volume: f64,
     pub timeframe: String, // "1m", "5m", "1h", "1d", etc.
+    /// `false` while this candle's time window hasn't elapsed yet (it's
+    /// still the live, trailing candle and may gain more trades), `true`
+    /// once it's settled. Lets consumers tell a final candle apart from
+    /// one that's still being written to on every aggregation pass.
+    pub complete: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -122,6 +200,51 @@ pub struct OHLCVResponse {
     pub total_candles: usize,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackfillOhlcvRequest {
+    pub symbol: String,
+    pub timeframe: String,
+    pub from_ts: i64,
+    pub to_ts: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackfillOhlcvResponse {
+    pub candles_written: usize,
+    /// The start timestamp of the last batch the run read. A run that hits
+    /// an error partway through returns the error instead of this response,
+    /// so no resume point is reported for it.
+    pub next_start_timestamp: i64,
+}
+
+/// Trailing-24h volume/price stats for one symbol, backing
+/// [`CoinGeckoTicker`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickerStats24h {
+    pub last_price: f64,
+    pub high: f64,
+    pub low: f64,
+    pub base_volume: f64,
+    pub target_volume: f64,
+}
+
+/// One row of the CoinGecko "external tickers" schema external aggregators
+/// expect: https://www.coingecko.com/en/api/documentation's market pair
+/// ingestion format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoinGeckoTicker {
+    pub ticker_id: String,
+    pub base_currency: String,
+    pub target_currency: String,
+    pub last_price: f64,
+    pub base_volume: f64,
+    pub target_volume: f64,
+    pub bid: f64,
+    pub ask: f64,
+    pub high: f64,
+    pub low: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RealTimeFeedResponse {
     pub symbols: Vec<String>,
@@ -143,6 +266,17 @@ pub struct CorrelationRequest {
     pub symbols: Vec<String>,
     pub timeframe: String,
     pub period_days: u32,
+    /// When set, the response also includes a rolling correlation series
+    /// per pair, each value the Pearson correlation over a trailing window
+    /// of this many buckets.
+    pub window: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollingCorrelation {
+    pub symbol_a: String,
+    pub symbol_b: String,
+    pub values: Vec<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -150,6 +284,7 @@ pub struct CorrelationResponse {
     pub correlation_matrix: Vec<Vec<f64>>,
     pub symbols: Vec<String>,
     pub period_days: u32,
+    pub rolling_correlations: Option<Vec<RollingCorrelation>>,
     pub calculation_timestamp: DateTime<Utc>,
 }
 
@@ -162,7 +297,9 @@ pub struct VolatilityRequest {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum VolatilityMethod {
-    StandardDeviation,
+    StandardDeviation, // Close-to-close standard deviation
+    Parkinson,   // Range-based estimator using each candle's high/low
+    GarmanKlass, // Range-based estimator using each candle's full OHLC
     EWMA, // Exponentially Weighted Moving Average
     GARCH, // Generalized Autoregressive Conditional Heteroskedasticity
 }
@@ -174,6 +311,10 @@ pub struct VolatilityResponse {
     pub annualized_volatility: f64,
     pub method: VolatilityMethod,
     pub period_days: u32,
+    /// `true` if `EWMA`/`GARCH` was requested but the return series was too
+    /// short to fit, so `method`/`volatility` actually reflect the
+    /// `StandardDeviation` fallback instead.
+    pub fallback_applied: bool,
     pub calculation_timestamp: DateTime<Utc>,
 }
 
@@ -183,9 +324,37 @@ pub struct StreamingDataEvent {
     pub symbol: String,
     pub data: serde_json::Value,
     pub timestamp: DateTime<Utc>,
+    /// Where this event came from and, if `KafkaConsumerService`'s
+    /// de-duplication layer recognized a stable id for its `event_type`,
+    /// the id it was keyed under.
+    pub provenance: EventProvenance,
 }
 
+/// Where a `StreamingDataEvent` came from: the Kafka topic/partition/offset
+/// it was read from (`None` for non-Kafka sources like
+/// `EthSubscribeService`), the producing service's self-reported
+/// name+version if the raw payload carried a `producer` annotation, and the
+/// de-duplication id `KafkaConsumerService::start_consuming` derived for it,
+/// if any.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventProvenance {
+    pub source_topic: Option<String>,
+    pub partition: Option<i32>,
+    pub offset: Option<i64>,
+    pub producer: Option<ProducerAnnotation>,
+    pub dedup_id: Option<String>,
+}
+
+/// A producing service's self-reported identity, read from an optional
+/// `{"producer": {"service": "...", "version": "..."}}` block on the raw
+/// event payload.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProducerAnnotation {
+    pub service: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum StreamEventType {
     PriceUpdate,
     VolumeUpdate,
@@ -193,6 +362,13 @@ pub enum StreamEventType {
     LiquidityChange,
     NewBlock,
     ContractEvent,
+    /// A `contract-events` log matched a registered `EventDecoder`'s topic0
+    /// signature; `StreamingDataEvent::data` holds the decoded
+    /// `DecodedContractEvent`, not a raw log blob.
+    Swap,
+    Mint,
+    Burn,
+    Sync,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +29,17 @@ impl MongoDBService {
         Ok(())
     }
 
+    /// Upserts every candle in `ohlcv_data` keyed on `(symbol, timeframe,
+    /// timestamp)`, so re-running aggregation over a window that includes
+    /// the still-forming trailing candle updates it in place instead of
+    /// inserting a duplicate document each pass.
+    pub async fn upsert_ohlcv_data(&self, ohlcv_data: &[OHLCVData]) -> Result<()> {
+        for candle in ohlcv_data {
+            self.upsert_ohlcv_candle(candle).await?;
+        }
+        Ok(())
+    }
+
     pub async fn store_aggregated_data(&self, aggregated_data: &AggregatedData) -> Result<()> {
         let collection: Collection<AggregatedData> = self.database.collection("aggregated_data");
         collection.insert_one(aggregated_data, None).await?;
@@ -82,6 +93,136 @@ impl MongoDBService {
         Ok(ohlcv_data)
     }
 
+    /// Fetches up to `limit` price points within `[start_timestamp,
+    /// end_timestamp)`, in chronological order, for bounded-batch backfill
+    /// processing.
+    pub async fn get_price_history_range(&self, symbol: &str, start_timestamp: i64, end_timestamp: i64, limit: usize) -> Result<Vec<PriceDataPoint>> {
+        let collection: Collection<PriceDataPoint> = self.database.collection("price_data");
+
+        let filter = doc! {
+            "symbol": symbol,
+            "timestamp": { "$gte": start_timestamp, "$lt": end_timestamp }
+        };
+
+        let options = mongodb::options::FindOptions::builder()
+            .sort(doc! { "timestamp": 1 })
+            .limit(limit as i64)
+            .build();
+
+        let mut cursor = collection.find(filter, Some(options)).await?;
+        let mut price_data = Vec::new();
+
+        while let Some(data) = cursor.next().await {
+            price_data.push(data?);
+        }
+
+        Ok(price_data)
+    }
+
+    /// Upserts a single candle keyed on `(symbol, timeframe, timestamp)`, so
+    /// re-running a backfill over the same range overwrites rather than
+    /// duplicates the candles it already wrote.
+    pub async fn upsert_ohlcv_candle(&self, candle: &OHLCVData) -> Result<()> {
+        let collection: Collection<OHLCVData> = self.database.collection("ohlcv_data");
+
+        let filter = doc! {
+            "symbol": &candle.symbol,
+            "timeframe": &candle.timeframe,
+            "timestamp": candle.timestamp,
+        };
+        let update = doc! { "$set": mongodb::bson::to_document(candle)? };
+
+        let options = mongodb::options::UpdateOptions::builder()
+            .upsert(true)
+            .build();
+
+        collection.update_one(filter, update, Some(options)).await?;
+        Ok(())
+    }
+
+    /// Fetches one candle by its exact `(symbol, timeframe, timestamp)` key,
+    /// backing `CandleBuilder`'s out-of-order fold path.
+    pub async fn get_ohlcv_candle(&self, symbol: &str, timeframe: &str, timestamp: i64) -> Result<Option<OHLCVData>> {
+        let collection: Collection<OHLCVData> = self.database.collection("ohlcv_data");
+
+        let filter = doc! {
+            "symbol": symbol,
+            "timeframe": timeframe,
+            "timestamp": timestamp,
+        };
+
+        Ok(collection.find_one(filter, None).await?)
+    }
+
+    /// Fetches candles within `[start_timestamp, end_timestamp)` for
+    /// `(symbol, timeframe)`, in chronological order — the candle-equivalent
+    /// of `get_price_history_range`, backing a candle-only backfill that
+    /// recomputes a coarser timeframe from already-stored finer candles
+    /// without touching raw trade data.
+    pub async fn get_ohlcv_data_range(&self, symbol: &str, timeframe: &str, start_timestamp: i64, end_timestamp: i64) -> Result<Vec<OHLCVData>> {
+        let collection: Collection<OHLCVData> = self.database.collection("ohlcv_data");
+
+        let filter = doc! {
+            "symbol": symbol,
+            "timeframe": timeframe,
+            "timestamp": { "$gte": start_timestamp, "$lt": end_timestamp },
+        };
+
+        let options = mongodb::options::FindOptions::builder()
+            .sort(doc! { "timestamp": 1 })
+            .build();
+
+        let mut cursor = collection.find(filter, Some(options)).await?;
+        let mut ohlcv_data = Vec::new();
+        while let Some(data) = cursor.next().await {
+            ohlcv_data.push(data?);
+        }
+
+        Ok(ohlcv_data)
+    }
+
+    /// Sums base and quote (price × volume) volume for `symbol` over the
+    /// trailing 24h and pulls the latest price plus 24h high/low, backing
+    /// the CoinGecko-compatible tickers endpoint. Returns `None` if the
+    /// symbol has no trades in the window.
+    pub async fn get_24h_ticker_stats(&self, symbol: &str) -> Result<Option<TickerStats24h>> {
+        let collection: Collection<PriceDataPoint> = self.database.collection("price_data");
+        let cutoff_time = Utc::now().timestamp() - 24 * 3600;
+
+        let pipeline = vec![
+            doc! { "$match": { "symbol": symbol, "timestamp": { "$gte": cutoff_time } } },
+            doc! { "$sort": { "timestamp": 1 } },
+            doc! {
+                "$group": {
+                    "_id": "$symbol",
+                    "last_price": { "$last": "$price" },
+                    "high": { "$max": "$price" },
+                    "low": { "$min": "$price" },
+                    "base_volume": { "$sum": "$volume" },
+                    "target_volume": { "$sum": { "$multiply": ["$price", "$volume"] } },
+                }
+            },
+        ];
+
+        let mut cursor = collection
+            .clone_with_type::<mongodb::bson::Document>()
+            .aggregate(pipeline, None)
+            .await?;
+
+        if let Some(doc) = cursor.next().await {
+            let doc = doc?;
+            Ok(Some(TickerStats24h {
+                last_price: doc.get_f64("last_price").unwrap_or(0.0),
+                high: doc.get_f64("high").unwrap_or(0.0),
+                low: doc.get_f64("low").unwrap_or(0.0),
+                base_volume: doc.get_f64("base_volume").unwrap_or(0.0),
+                target_volume: doc.get_f64("target_volume").unwrap_or(0.0),
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
     pub async fn get_volume_data(&self, symbol: &str, hours: u64) -> Result<Vec<PriceDataPoint>> {
         let collection: Collection<PriceDataPoint> = self.database.collection("price_data");
         
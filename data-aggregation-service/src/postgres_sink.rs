@@ -0,0 +1,276 @@
+//! Optional relational sink for aggregated OHLCV/volume/liquidity records,
+//! complementing `MongoDBService`. MongoDB remains the document store used
+//! by the rest of this crate; `PostgresSink` exists for deployments that
+//! also want these records queryable from SQL-based BI tooling.
+//!
+//! Selectable via `SinkMode` so a deployment can run MongoDB-only,
+//! Postgres-only, or both without code changes — only its `PostgresConfig`
+//! (read from env) decides whether the sink is actually constructed.
+//!
+//! This crate has no `ErrorHandler` of its own (that lives in
+//! blockchain-sync-service, a separate crate/binary), so write retries here
+//! are a small local equivalent: a bounded exponential backoff that retries
+//! only on errors `tokio_postgres` reports as transient (connection-level
+//! failures), classifying everything it retries as a `DatabaseError` for
+//! logging purposes.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use log::{error, warn};
+use tokio_postgres::{Client, NoTls};
+
+use crate::models::{LiquidityMetricsResponse, OHLCVData, VolumeAnalysisResponse};
+
+/// Which persistence backend(s) a deployment writes aggregated data to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinkMode {
+    MongoOnly,
+    PostgresOnly,
+    Both,
+}
+
+impl SinkMode {
+    pub fn from_env() -> Self {
+        match std::env::var("AGGREGATION_SINK_MODE").as_deref() {
+            Ok("postgres_only") => SinkMode::PostgresOnly,
+            Ok("both") => SinkMode::Both,
+            _ => SinkMode::MongoOnly,
+        }
+    }
+
+    pub fn writes_mongo(&self) -> bool {
+        matches!(self, SinkMode::MongoOnly | SinkMode::Both)
+    }
+
+    pub fn writes_postgres(&self) -> bool {
+        matches!(self, SinkMode::PostgresOnly | SinkMode::Both)
+    }
+}
+
+/// Mirrors libpq's `sslmode`, but only the two ends of the spectrum this
+/// sink actually supports: a plaintext connection, or a TLS connection
+/// backed by a CA and client identity supplied out-of-band.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SslMode {
+    Disable,
+    VerifyFull,
+}
+
+pub struct PostgresConfig {
+    pub connection_string: String,
+    pub ssl_mode: SslMode,
+}
+
+impl PostgresConfig {
+    pub fn from_env() -> Result<Self> {
+        let connection_string = std::env::var("POSTGRES_URL")
+            .map_err(|_| anyhow!("POSTGRES_URL is not set"))?;
+
+        let ssl_mode = match std::env::var("POSTGRES_SSL_MODE").as_deref() {
+            Ok("verify-full") | Ok("require") => SslMode::VerifyFull,
+            _ => SslMode::Disable,
+        };
+
+        Ok(Self { connection_string, ssl_mode })
+    }
+}
+
+/// Writes aggregated data to PostgreSQL using batched, idempotent upserts.
+pub struct PostgresSink {
+    client: Client,
+}
+
+impl PostgresSink {
+    /// Connects using `config`, building a `MakeTlsConnector` from
+    /// base64-encoded CA PEM and client PKCS#12 identity when `ssl_mode` is
+    /// anything other than `Disable`, and plain `NoTls` otherwise.
+    pub async fn connect(config: &PostgresConfig) -> Result<Self> {
+        match config.ssl_mode {
+            SslMode::Disable => {
+                let (client, connection) =
+                    tokio_postgres::connect(&config.connection_string, NoTls).await?;
+                tokio::spawn(async move {
+                    if let Err(e) = connection.await {
+                        error!("PostgreSQL connection error: {}", e);
+                    }
+                });
+                Ok(Self { client })
+            }
+            SslMode::VerifyFull => {
+                let connector = build_tls_connector()?;
+                let (client, connection) =
+                    tokio_postgres::connect(&config.connection_string, connector).await?;
+                tokio::spawn(async move {
+                    if let Err(e) = connection.await {
+                        error!("PostgreSQL connection error: {}", e);
+                    }
+                });
+                Ok(Self { client })
+            }
+        }
+    }
+
+    /// Upserts every candle in `data`, keyed on `(symbol, timeframe,
+    /// bucket_ts)`, retrying the whole batch on a transient connection
+    /// error with a bounded exponential backoff.
+    pub async fn upsert_ohlcv(&self, data: &[OHLCVData]) -> Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        with_retry("upsert_ohlcv", || async {
+            let mut query = String::from(
+                "INSERT INTO ohlcv_data (symbol, timeframe, bucket_ts, open, high, low, close, volume) VALUES ",
+            );
+            let mut params: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> = Vec::new();
+            let mut owned_timestamps: Vec<chrono::DateTime<chrono::Utc>> = Vec::new();
+
+            for candle in data {
+                owned_timestamps.push(
+                    chrono::DateTime::from_timestamp(candle.timestamp, 0)
+                        .unwrap_or_else(chrono::Utc::now),
+                );
+            }
+
+            for (i, candle) in data.iter().enumerate() {
+                if i > 0 {
+                    query.push(',');
+                }
+                let base = i * 8;
+                query.push_str(&format!(
+                    " (${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
+                    base + 1, base + 2, base + 3, base + 4, base + 5, base + 6, base + 7, base + 8
+                ));
+                params.push(&candle.symbol);
+                params.push(&candle.timeframe);
+                params.push(&owned_timestamps[i]);
+                params.push(&candle.open);
+                params.push(&candle.high);
+                params.push(&candle.low);
+                params.push(&candle.close);
+                params.push(&candle.volume);
+            }
+
+            query.push_str(
+                " ON CONFLICT (symbol, timeframe, bucket_ts) DO UPDATE SET \
+                 open = EXCLUDED.open, high = EXCLUDED.high, low = EXCLUDED.low, \
+                 close = EXCLUDED.close, volume = EXCLUDED.volume",
+            );
+
+            self.client.execute(query.as_str(), &params).await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Upserts one volume-analysis snapshot, keyed on `(symbol, timeframe,
+    /// bucket_ts)` with `timeframe` fixed to the analysis window.
+    pub async fn upsert_volume_analysis(&self, symbol: &str, analysis: &VolumeAnalysisResponse) -> Result<()> {
+        let timeframe = format!("{}h", analysis.period_hours);
+
+        with_retry("upsert_volume_analysis", || async {
+            self.client
+                .execute(
+                    "INSERT INTO volume_analysis (symbol, timeframe, bucket_ts, total_volume, average_volume) \
+                     VALUES ($1, $2, now(), $3, $4) \
+                     ON CONFLICT (symbol, timeframe, bucket_ts) DO UPDATE SET \
+                     total_volume = EXCLUDED.total_volume, average_volume = EXCLUDED.average_volume",
+                    &[&symbol, &timeframe, &analysis.total_volume, &analysis.average_volume],
+                )
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Upserts one liquidity snapshot, keyed on `(symbol, "snapshot",
+    /// bucket_ts)` since liquidity metrics aren't bucketed by timeframe.
+    pub async fn upsert_liquidity_metrics(&self, metrics: &LiquidityMetricsResponse) -> Result<()> {
+        with_retry("upsert_liquidity_metrics", || async {
+            self.client
+                .execute(
+                    "INSERT INTO liquidity_metrics (symbol, timeframe, bucket_ts, total_liquidity, bid_ask_spread, liquidity_score) \
+                     VALUES ($1, 'snapshot', $2, $3, $4, $5) \
+                     ON CONFLICT (symbol, timeframe, bucket_ts) DO UPDATE SET \
+                     total_liquidity = EXCLUDED.total_liquidity, bid_ask_spread = EXCLUDED.bid_ask_spread, \
+                     liquidity_score = EXCLUDED.liquidity_score",
+                    &[
+                        &metrics.symbol,
+                        &metrics.timestamp,
+                        &metrics.total_liquidity,
+                        &metrics.bid_ask_spread,
+                        &metrics.liquidity_score,
+                    ],
+                )
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+fn build_tls_connector() -> Result<postgres_openssl::MakeTlsConnector> {
+    use openssl::pkcs12::Pkcs12;
+    use openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
+    use openssl::x509::X509;
+
+    let ca_pem_b64 = std::env::var("CA_PEM_B64").map_err(|_| anyhow!("CA_PEM_B64 is not set"))?;
+    let client_pks_b64 =
+        std::env::var("CLIENT_PKS_B64").map_err(|_| anyhow!("CLIENT_PKS_B64 is not set"))?;
+    let client_pks_pass = std::env::var("CLIENT_PKS_PASS").unwrap_or_default();
+
+    let ca_pem = base64::engine::general_purpose::STANDARD.decode(ca_pem_b64)?;
+    let client_pks = base64::engine::general_purpose::STANDARD.decode(client_pks_b64)?;
+
+    let ca_cert = X509::from_pem(&ca_pem)?;
+    let identity = Pkcs12::from_der(&client_pks)?.parse2(&client_pks_pass)?;
+
+    let mut builder = SslConnector::builder(SslMethod::tls())?;
+    builder.cert_store_mut().add_cert(ca_cert)?;
+    builder.set_verify(SslVerifyMode::PEER);
+    if let (Some(cert), Some(pkey)) = (identity.cert, identity.pkey) {
+        builder.set_certificate(&cert)?;
+        builder.set_private_key(&pkey)?;
+    }
+
+    Ok(postgres_openssl::MakeTlsConnector::new(builder.build()))
+}
+
+const MAX_RETRIES: u32 = 4;
+const BASE_DELAY: Duration = Duration::from_millis(200);
+
+/// Retries `op` on `tokio_postgres::Error`s that look transient (I/O/closed
+/// connection, as opposed to e.g. a constraint violation that will never
+/// succeed on retry), classifying each retried failure as a `DatabaseError`
+/// in the log line.
+async fn with_retry<F, Fut>(operation: &str, mut op: F) -> Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<()>>,
+{
+    let mut attempt = 0;
+    loop {
+        match op().await {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt < MAX_RETRIES && is_transient(&e) => {
+                let delay = BASE_DELAY * 2u32.pow(attempt);
+                warn!(
+                    "[DatabaseError] Postgres {} failed (attempt {}/{}), retrying in {:?}: {}",
+                    operation, attempt + 1, MAX_RETRIES, delay, e
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn is_transient(e: &anyhow::Error) -> bool {
+    match e.downcast_ref::<tokio_postgres::Error>() {
+        Some(pg_err) => pg_err.is_closed() || pg_err.code().is_none(),
+        None => true,
+    }
+}
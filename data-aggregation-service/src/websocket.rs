@@ -0,0 +1,183 @@
+//! Push feed for `StreamingDataEvent`s, complementing the polling-based
+//! `/data/real-time-feed` HTTP route. A client connects, sends a single
+//! subscribe frame naming the symbols and `StreamEventType`s it wants (an
+//! empty list in either matches everything), then receives each matching
+//! `StreamingDataEvent` as its own JSON text frame as `DataAggregationService`'s Kafka consumer
+//! loop produces them. Built on axum's WebSocket extractor, which is itself
+//! backed by tokio-tungstenite.
+//!
+//! Each connection gets its own `broadcast::Receiver` off
+//! `DataAggregationService::subscribe_to_stream`; a subscriber that falls
+//! behind just misses the oldest unread events (`RecvError::Lagged`) rather
+//! than slowing down the consumer loop or other subscribers.
+//!
+//! `/data/real-time-feed/ws` below reuses the same broadcast channel,
+//! filtered down to a symbol subscription, for clients that would otherwise
+//! have to poll `/data/real-time-feed`. Both endpoints share an
+//! `AppState::active_streams` counter (incremented/decremented via
+//! `StreamGuard`) so `/health` can report a live subscriber count instead of
+//! a hard-coded zero.
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::Response;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::atomic::Ordering;
+use tokio::sync::broadcast;
+
+use crate::models::{StreamEventType, StreamingDataEvent};
+use crate::AppState;
+
+/// RAII guard that decrements `AppState::active_streams` when a connection's
+/// handler loop returns, regardless of whether it exits via a closed
+/// broadcast channel or a send error (which is how a client disconnect
+/// surfaces) — so a subscriber can never leak a count. A lagged receiver
+/// does not end the loop.
+struct StreamGuard<'a> {
+    active_streams: &'a std::sync::atomic::AtomicU64,
+}
+
+impl<'a> StreamGuard<'a> {
+    fn enter(active_streams: &'a std::sync::atomic::AtomicU64) -> Self {
+        active_streams.fetch_add(1, Ordering::Relaxed);
+        Self { active_streams }
+    }
+}
+
+impl<'a> Drop for StreamGuard<'a> {
+    fn drop(&mut self) {
+        self.active_streams.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// The one frame a client must send immediately after connecting.
+#[derive(Debug, Deserialize)]
+struct SubscribeFrame {
+    #[serde(default)]
+    symbols: HashSet<String>,
+    #[serde(default)]
+    event_types: HashSet<StreamEventType>,
+}
+
+pub async fn stream_ws_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
+    ws.on_upgrade(move |socket| handle_socket(socket, state))
+}
+
+async fn handle_socket(mut socket: WebSocket, state: AppState) {
+    let subscribe_frame = match socket.recv().await {
+        Some(Ok(Message::Text(text))) => match serde_json::from_str::<SubscribeFrame>(&text) {
+            Ok(frame) => frame,
+            Err(e) => {
+                warn!("Invalid WebSocket subscribe frame: {}", e);
+                let _ = socket.send(Message::Text(format!("{{\"error\":\"invalid subscribe frame: {}\"}}", e))).await;
+                return;
+            }
+        },
+        _ => return,
+    };
+
+    let _guard = StreamGuard::enter(&state.active_streams);
+    let mut events = state.data_service.subscribe_to_stream();
+
+    loop {
+        match events.recv().await {
+            Ok(event) => {
+                if !matches_subscription(&subscribe_frame, &event) {
+                    continue;
+                }
+                let Ok(payload) = serde_json::to_string(&event) else {
+                    continue;
+                };
+                if socket.send(Message::Text(payload)).await.is_err() {
+                    break;
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                debug!("WebSocket subscriber lagged, dropped {} events", skipped);
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+fn matches_subscription(frame: &SubscribeFrame, event: &StreamingDataEvent) -> bool {
+    (frame.symbols.is_empty() || frame.symbols.contains(&event.symbol))
+        && (frame.event_types.is_empty() || frame.event_types.contains(&event.event_type))
+}
+
+/// The subscribe frame for `/data/real-time-feed/ws`: a client names the
+/// symbols it cares about, mirroring the `symbols` query param on the
+/// polling `/data/real-time-feed` route.
+#[derive(Debug, Deserialize)]
+struct RealTimeFeedSubscribeFrame {
+    #[serde(default)]
+    symbols: HashSet<String>,
+}
+
+/// A single push over `/data/real-time-feed/ws`. `sequence` is a
+/// per-connection, monotonically increasing counter so a client can detect
+/// and discard out-of-order or stale pushes (e.g. after a brief network
+/// blip re-delivers a buffered frame) by keeping the highest `sequence`
+/// it's seen and ignoring anything lower.
+#[derive(Debug, Serialize)]
+struct RealTimeFeedPush {
+    sequence: u64,
+    symbol: String,
+    event_type: StreamEventType,
+    data: serde_json::Value,
+    timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+pub async fn real_time_feed_ws_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
+    ws.on_upgrade(move |socket| handle_real_time_feed_socket(socket, state))
+}
+
+async fn handle_real_time_feed_socket(mut socket: WebSocket, state: AppState) {
+    let subscribe_frame = match socket.recv().await {
+        Some(Ok(Message::Text(text))) => match serde_json::from_str::<RealTimeFeedSubscribeFrame>(&text) {
+            Ok(frame) => frame,
+            Err(e) => {
+                warn!("Invalid real-time-feed subscribe frame: {}", e);
+                let _ = socket.send(Message::Text(format!("{{\"error\":\"invalid subscribe frame: {}\"}}", e))).await;
+                return;
+            }
+        },
+        _ => return,
+    };
+
+    let _guard = StreamGuard::enter(&state.active_streams);
+    let mut events = state.data_service.subscribe_to_stream();
+    let mut sequence: u64 = 0;
+
+    loop {
+        match events.recv().await {
+            Ok(event) => {
+                if !subscribe_frame.symbols.is_empty() && !subscribe_frame.symbols.contains(&event.symbol) {
+                    continue;
+                }
+                sequence += 1;
+                let push = RealTimeFeedPush {
+                    sequence,
+                    symbol: event.symbol,
+                    event_type: event.event_type,
+                    data: event.data,
+                    timestamp: event.timestamp,
+                };
+                let Ok(payload) = serde_json::to_string(&push) else {
+                    continue;
+                };
+                if socket.send(Message::Text(payload)).await.is_err() {
+                    break;
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                debug!("Real-time-feed subscriber lagged, dropped {} events", skipped);
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
@@ -2,12 +2,70 @@ use anyhow::Result;
 use nalgebra::{DMatrix, DVector};
 use statrs::distribution::{Normal, ContinuousCDF};
 use rayon::prelude::*;
+use rand::Rng;
+use std::collections::HashSet;
 use std::f64::consts::{E, PI};
 
 use crate::models::*;
 
 pub struct FinancialCalculations;
 
+enum TradeSide {
+    Buy,
+    Sell,
+}
+
+/// Outcome of walking an order book to fill a trade: the volume-weighted
+/// average price actually paid/received, and the slippage that implies
+/// versus the top-of-book price.
+pub struct FillResult {
+    pub average_price: f64,
+    pub slippage: f64,
+    pub filled_amount: f64,
+}
+
+/// Simulates filling a trade against real order-book depth instead of a
+/// quadratic estimate, so `calculate_arbitrage` reflects actual market
+/// depth when it's available.
+pub struct TradeSimulator;
+
+impl TradeSimulator {
+    /// Walks `levels` (best price first, i.e. asks ascending or bids
+    /// descending) consuming size until `amount` is filled, returning the
+    /// volume-weighted average fill price and the slippage realized versus
+    /// the top-of-book price. Errors if the book's combined depth can't
+    /// fill the full `amount`.
+    pub fn simulate_fill(levels: &[(f64, f64)], amount: f64) -> Result<FillResult> {
+        let top_of_book_price = levels
+            .first()
+            .map(|(price, _)| *price)
+            .ok_or_else(|| anyhow::anyhow!("order book side is empty"))?;
+
+        let mut remaining = amount;
+        let mut cost = 0.0;
+        for &(price, size) in levels {
+            if remaining <= 0.0 {
+                break;
+            }
+            let filled = remaining.min(size);
+            cost += filled * price;
+            remaining -= filled;
+        }
+
+        if remaining > 1e-9 {
+            return Err(anyhow::anyhow!(
+                "order book depth insufficient to fill amount {} ({} unfilled)",
+                amount, remaining
+            ));
+        }
+
+        let average_price = cost / amount;
+        let slippage = (average_price - top_of_book_price).abs() / top_of_book_price;
+
+        Ok(FillResult { average_price, slippage, filled_amount: amount })
+    }
+}
+
 impl FinancialCalculations {
     /// Black-Scholes option pricing
     pub fn black_scholes_price(
@@ -96,6 +154,149 @@ impl FinancialCalculations {
         Ok((delta, gamma, theta / 365.0, vega / 100.0, rho / 100.0)) // Convert to daily theta, percentage vega and rho
     }
 
+    /// Recovers the implied volatility that reproduces `market_price` via
+    /// Newton-Raphson, seeded at 0.2. Each step's vega comes from
+    /// `calculate_greeks`, undoing its per-1%-of-vol quoting (it divides by
+    /// 100 for display) so the raw `d(price)/d(vol)` sensitivity drives the
+    /// update. Falls back to bisection on `[1e-6, 5.0]` when vega
+    /// underflows, which happens for deep in/out-of-the-money options where
+    /// Newton-Raphson stalls or overshoots.
+    pub fn implied_volatility(
+        spot: f64,
+        strike: f64,
+        time_to_expiry: f64,
+        risk_free_rate: f64,
+        market_price: f64,
+        option_type: &OptionType,
+        dividend_yield: f64,
+    ) -> Result<f64> {
+        const MIN_VOL: f64 = 1e-6;
+        const MAX_VOL: f64 = 5.0;
+        const MAX_ITERATIONS: usize = 50;
+        const VEGA_FLOOR: f64 = 1e-8;
+        const PRICE_TOLERANCE: f64 = 1e-8;
+
+        let mut vol = 0.2;
+        for _ in 0..MAX_ITERATIONS {
+            let price = Self::black_scholes_price(
+                spot, strike, time_to_expiry, risk_free_rate, vol, option_type, dividend_yield,
+            )?;
+            let diff = price - market_price;
+            if diff.abs() < PRICE_TOLERANCE {
+                return Ok(vol);
+            }
+
+            let (_, _, _, vega_pct, _) = Self::calculate_greeks(
+                spot, strike, time_to_expiry, risk_free_rate, vol, option_type, dividend_yield,
+            )?;
+            let vega = vega_pct * 100.0;
+            if vega.abs() < VEGA_FLOOR {
+                break;
+            }
+
+            vol = (vol - diff / vega).clamp(MIN_VOL, MAX_VOL);
+        }
+
+        Self::implied_volatility_bisection(
+            spot, strike, time_to_expiry, risk_free_rate, market_price, option_type, dividend_yield,
+            MIN_VOL, MAX_VOL,
+        )
+    }
+
+    fn implied_volatility_bisection(
+        spot: f64,
+        strike: f64,
+        time_to_expiry: f64,
+        risk_free_rate: f64,
+        market_price: f64,
+        option_type: &OptionType,
+        dividend_yield: f64,
+        mut lo: f64,
+        mut hi: f64,
+    ) -> Result<f64> {
+        const MAX_ITERATIONS: usize = 100;
+        const TOLERANCE: f64 = 1e-8;
+
+        let price_at = |vol: f64| -> Result<f64> {
+            Self::black_scholes_price(spot, strike, time_to_expiry, risk_free_rate, vol, option_type, dividend_yield)
+        };
+
+        let mut f_lo = price_at(lo)? - market_price;
+        let mut mid = 0.5 * (lo + hi);
+
+        for _ in 0..MAX_ITERATIONS {
+            mid = 0.5 * (lo + hi);
+            let f_mid = price_at(mid)? - market_price;
+
+            if f_mid.abs() < TOLERANCE || (hi - lo) < TOLERANCE {
+                return Ok(mid);
+            }
+
+            if f_lo.signum() == f_mid.signum() {
+                lo = mid;
+                f_lo = f_mid;
+            } else {
+                hi = mid;
+            }
+        }
+
+        Ok(mid)
+    }
+
+    /// Prices an American-exercise option with a Cox-Ross-Rubinstein
+    /// binomial tree, since Black-Scholes has no closed form for early
+    /// exercise. Builds terminal payoffs over `steps` time slices (`None`
+    /// defaults to 200) and backward-induces, taking the larger of
+    /// continuation value and immediate-exercise intrinsic value at every
+    /// node.
+    pub fn binomial_american_price(
+        spot: f64,
+        strike: f64,
+        time_to_expiry: f64,
+        risk_free_rate: f64,
+        volatility: f64,
+        option_type: &OptionType,
+        dividend_yield: f64,
+        steps: Option<usize>,
+    ) -> Result<f64> {
+        let steps = steps.unwrap_or(200).max(1);
+        let dt = time_to_expiry / steps as f64;
+        let u = (volatility * dt.sqrt()).exp();
+        let d = 1.0 / u;
+        let growth = ((risk_free_rate - dividend_yield) * dt).exp();
+        let p = (growth - d) / (u - d);
+
+        if !(0.0..=1.0).contains(&p) {
+            return Err(anyhow::anyhow!(
+                "binomial tree risk-neutral probability {} is outside [0, 1]; check volatility/rate/step-count inputs",
+                p
+            ));
+        }
+        let discount = (-risk_free_rate * dt).exp();
+
+        let intrinsic = |spot_at_node: f64| -> f64 {
+            match option_type {
+                OptionType::Call => (spot_at_node - strike).max(0.0),
+                OptionType::Put => (strike - spot_at_node).max(0.0),
+            }
+        };
+
+        // Node i at step `step` has price spot * u^(step - i) * d^i.
+        let mut values: Vec<f64> = (0..=steps)
+            .map(|i| intrinsic(spot * u.powi((steps - i) as i32) * d.powi(i as i32)))
+            .collect();
+
+        for step in (0..steps).rev() {
+            for i in 0..=step {
+                let continuation = discount * (p * values[i] + (1.0 - p) * values[i + 1]);
+                let spot_at_node = spot * u.powi((step - i) as i32) * d.powi(i as i32);
+                values[i] = continuation.max(intrinsic(spot_at_node));
+            }
+        }
+
+        Ok(values[0])
+    }
+
     /// Calculate arbitrage opportunity
     pub fn calculate_arbitrage(exchanges: &[ExchangeData], amount: f64, max_slippage: f64) -> Result<ArbitrageResponse> {
         let mut best_profit = 0.0;
@@ -107,35 +308,41 @@ impl FinancialCalculations {
         let max_exchange = exchanges.iter().max_by(|a, b| a.price.partial_cmp(&b.price).unwrap()).unwrap();
 
         if min_exchange.name != max_exchange.name {
-            // Calculate slippage impact
-            let buy_slippage = Self::calculate_slippage(amount, min_exchange.liquidity);
-            let sell_slippage = Self::calculate_slippage(amount, max_exchange.liquidity);
-
-            if buy_slippage <= max_slippage && sell_slippage <= max_slippage {
-                let buy_price = min_exchange.price * (1.0 + buy_slippage + min_exchange.fee);
-                let sell_price = max_exchange.price * (1.0 - sell_slippage - max_exchange.fee);
-
-                let profit = (sell_price - buy_price) * amount;
-                let profit_percentage = (profit / (buy_price * amount)) * 100.0;
-
-                if profit > 0.0 {
-                    profitable = true;
-                    best_profit = profit;
-
-                    optimal_path = vec![
-                        ArbitrageStep {
-                            exchange: min_exchange.name.clone(),
-                            action: "buy".to_string(),
-                            amount,
-                            price: buy_price,
-                        },
-                        ArbitrageStep {
-                            exchange: max_exchange.name.clone(),
-                            action: "sell".to_string(),
-                            amount,
-                            price: sell_price,
-                        },
-                    ];
+            // Simulate both legs against real order-book depth when the
+            // exchange supplied one, falling back to the quadratic estimate
+            // otherwise. A leg whose book can't fill `amount` (or whose
+            // slippage exceeds `max_slippage`) just leaves `profitable`
+            // false rather than erroring, matching the "no opportunity"
+            // case below.
+            if let (Ok(buy_fill), Ok(sell_fill)) = (
+                Self::fill_leg(min_exchange, amount, TradeSide::Buy),
+                Self::fill_leg(max_exchange, amount, TradeSide::Sell),
+            ) {
+                if buy_fill.slippage <= max_slippage && sell_fill.slippage <= max_slippage {
+                    let buy_price = buy_fill.average_price * (1.0 + min_exchange.fee);
+                    let sell_price = sell_fill.average_price * (1.0 - max_exchange.fee);
+
+                    let profit = (sell_price - buy_price) * amount;
+
+                    if profit > 0.0 {
+                        profitable = true;
+                        best_profit = profit;
+
+                        optimal_path = vec![
+                            ArbitrageStep {
+                                exchange: min_exchange.name.clone(),
+                                action: "buy".to_string(),
+                                amount,
+                                price: buy_price,
+                            },
+                            ArbitrageStep {
+                                exchange: max_exchange.name.clone(),
+                                action: "sell".to_string(),
+                                amount,
+                                price: sell_price,
+                            },
+                        ];
+                    }
                 }
             }
         }
@@ -147,16 +354,208 @@ impl FinancialCalculations {
             optimal_path,
             estimated_gas_cost: 0.01, // Placeholder
             net_profit: best_profit - 0.01, // Subtract gas cost
+            prio_fee_data: None, // Filled in by the caller once gas costs are known
             calculation_time_ms: 0, // Will be set by caller
         })
     }
 
+    /// Distributional stats over observed gas-cost/priority-fee samples,
+    /// computed by sorting and indexing at `len * pct / 100` (nearest-rank).
+    /// `None` when fewer than two samples are present, since a percentile
+    /// isn't meaningful over 0 or 1 points.
+    pub fn calculate_prio_fee_percentiles(samples: &[f64]) -> Option<PrioFeeData> {
+        if samples.len() < 2 {
+            return None;
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let at_percentile = |pct: f64| -> f64 {
+            let index = ((sorted.len() as f64 * pct / 100.0) as usize).min(sorted.len() - 1);
+            sorted[index]
+        };
+
+        Some(PrioFeeData {
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            median: at_percentile(50.0),
+            p75: at_percentile(75.0),
+            p90: at_percentile(90.0),
+            p95: at_percentile(95.0),
+        })
+    }
+
+    /// Fills `amount` against `exchange`'s real order book when it supplied
+    /// one, otherwise falls back to the quadratic `calculate_slippage`
+    /// estimate against an infinitely deep book at the quoted price.
+    fn fill_leg(exchange: &ExchangeData, amount: f64, side: TradeSide) -> Result<FillResult> {
+        match &exchange.order_book {
+            Some(book) => {
+                let levels: &[(f64, f64)] = match side {
+                    TradeSide::Buy => &book.asks,
+                    TradeSide::Sell => &book.bids,
+                };
+                TradeSimulator::simulate_fill(levels, amount)
+            }
+            None => {
+                let slippage = Self::calculate_slippage(amount, exchange.liquidity);
+                let average_price = match side {
+                    TradeSide::Buy => exchange.price * (1.0 + slippage),
+                    TradeSide::Sell => exchange.price * (1.0 - slippage),
+                };
+                Ok(FillResult { average_price, slippage, filled_amount: amount })
+            }
+        }
+    }
+
     fn calculate_slippage(amount: f64, liquidity: f64) -> f64 {
         // Simple slippage model: slippage increases quadratically with trade size relative to liquidity
         let ratio = amount / liquidity;
         ratio.powi(2) * 0.1 // Max 10% slippage when trade size equals liquidity
     }
 
+    /// Finds profitable multi-hop conversion loops (e.g. triangular
+    /// A->B->C->A) that a pairwise `calculate_arbitrage` comparison would
+    /// miss. Builds a directed graph over the distinct assets in `rates`,
+    /// weighting each edge `-ln(rate * (1 - fee))` so that a cycle's total
+    /// weight is negative exactly when its realized conversion product
+    /// exceeds 1 (i.e. it's profitable). Runs Bellman-Ford for `|V| - 1`
+    /// relaxation rounds from an implicit zero-distance source, then treats
+    /// any edge that can still relax on one more pass as lying on a
+    /// negative-weight cycle — walking `|V|` predecessor steps back from
+    /// that edge is guaranteed to land inside the cycle, from which the
+    /// full loop is reconstructed. Returns distinct cycles (compared as
+    /// rotation-normalized vertex sequences), sorted most-profitable first.
+    pub fn find_arbitrage_cycles(rates: &[(String, String, f64, f64)]) -> Result<Vec<ArbitragePath>> {
+        struct Edge {
+            from: usize,
+            to: usize,
+            weight: f64,
+            rate: f64,
+            fee: f64,
+        }
+
+        let mut assets: Vec<String> = Vec::new();
+        for (from, to, _, _) in rates {
+            if !assets.contains(from) {
+                assets.push(from.clone());
+            }
+            if !assets.contains(to) {
+                assets.push(to.clone());
+            }
+        }
+        let vertex_count = assets.len();
+        if vertex_count == 0 {
+            return Ok(Vec::new());
+        }
+        let index_of = |asset: &str| assets.iter().position(|a| a == asset).unwrap();
+
+        let edges: Vec<Edge> = rates
+            .iter()
+            .filter_map(|(from, to, rate, fee)| {
+                let effective_rate = rate * (1.0 - fee);
+                let weight = -effective_rate.ln();
+                if !weight.is_finite() {
+                    return None; // non-positive effective rate; not a usable conversion edge
+                }
+                Some(Edge { from: index_of(from), to: index_of(to), weight, rate: *rate, fee: *fee })
+            })
+            .collect();
+
+        // Distances from an implicit virtual source with a zero-weight edge
+        // to every vertex, which is equivalent to starting every vertex's
+        // distance at 0 rather than relaxing a real source vertex.
+        let mut dist = vec![0.0_f64; vertex_count];
+        let mut predecessor: Vec<Option<usize>> = vec![None; vertex_count];
+        const EPSILON: f64 = 1e-12;
+
+        for _ in 0..vertex_count.saturating_sub(1) {
+            for edge in &edges {
+                if dist[edge.from] + edge.weight < dist[edge.to] - EPSILON {
+                    dist[edge.to] = dist[edge.from] + edge.weight;
+                    predecessor[edge.to] = Some(edge.from);
+                }
+            }
+        }
+
+        let mut relaxable_vertices = Vec::new();
+        for edge in &edges {
+            if dist[edge.from] + edge.weight < dist[edge.to] - EPSILON {
+                predecessor[edge.to] = Some(edge.from);
+                relaxable_vertices.push(edge.to);
+            }
+        }
+
+        let mut seen_cycles: HashSet<Vec<usize>> = HashSet::new();
+        let mut results = Vec::new();
+
+        for start in relaxable_vertices {
+            let mut inside_cycle = start;
+            for _ in 0..vertex_count {
+                match predecessor[inside_cycle] {
+                    Some(prev) => inside_cycle = prev,
+                    None => break,
+                }
+            }
+
+            let mut cycle = vec![inside_cycle];
+            let mut current = match predecessor[inside_cycle] {
+                Some(prev) => prev,
+                None => continue,
+            };
+            while current != inside_cycle {
+                cycle.push(current);
+                current = match predecessor[current] {
+                    Some(prev) => prev,
+                    None => break,
+                };
+            }
+            if current != inside_cycle {
+                continue; // predecessor chain ran out before closing the loop
+            }
+            cycle.reverse();
+
+            let min_pos = cycle.iter().enumerate().min_by_key(|(_, &v)| v).map(|(i, _)| i).unwrap_or(0);
+            let mut dedupe_key = cycle.clone();
+            dedupe_key.rotate_left(min_pos);
+            if !seen_cycles.insert(dedupe_key) {
+                continue;
+            }
+
+            let mut steps = Vec::new();
+            let mut product = 1.0;
+            let mut complete = true;
+            for i in 0..cycle.len() {
+                let from_idx = cycle[i];
+                let to_idx = cycle[(i + 1) % cycle.len()];
+                let Some(edge) = edges.iter().find(|e| e.from == from_idx && e.to == to_idx) else {
+                    complete = false;
+                    break;
+                };
+                let effective_rate = edge.rate * (1.0 - edge.fee);
+                product *= effective_rate;
+                steps.push(ArbitrageStep {
+                    exchange: format!("{}->{}", assets[from_idx], assets[to_idx]),
+                    action: "convert".to_string(),
+                    amount: 0.0,
+                    price: edge.rate,
+                });
+            }
+            if !complete {
+                continue;
+            }
+
+            let profit_percentage = (product - 1.0) * 100.0;
+            if profit_percentage > 0.0 {
+                results.push(ArbitragePath { steps, profit_percentage });
+            }
+        }
+
+        results.sort_by(|a, b| b.profit_percentage.partial_cmp(&a.profit_percentage).unwrap());
+        Ok(results)
+    }
+
     /// Portfolio optimization using Mean-Variance Optimization
     pub fn optimize_portfolio(
         expected_returns: &[f64],
@@ -190,6 +589,106 @@ impl FinancialCalculations {
         Ok(normalized_weights)
     }
 
+    /// Constrained mean-variance optimization via projected-gradient
+    /// descent, enforcing `sum(w) = 1` and, unless `allow_short` is set,
+    /// `w_i >= 0`. Unlike `optimize_portfolio`'s closed-form tangency
+    /// weights, this avoids large short positions and stays well-behaved
+    /// when `covariance_matrix` is near-singular.
+    pub fn optimize_portfolio_constrained(
+        expected_returns: &[f64],
+        covariance_matrix: &[Vec<f64>],
+        risk_tolerance: f64,
+        allow_short: bool,
+    ) -> Result<Vec<f64>> {
+        let n = expected_returns.len();
+
+        let returns = DVector::from_vec(expected_returns.to_vec());
+        let mut cov_data = Vec::new();
+        for row in covariance_matrix {
+            cov_data.extend_from_slice(row);
+        }
+        let covariance = DMatrix::from_vec(n, n, cov_data);
+
+        const LEARNING_RATE: f64 = 0.01;
+        const MAX_ITERATIONS: usize = 10_000;
+        const TOLERANCE: f64 = 1e-9;
+
+        let mut weights = DVector::from_element(n, 1.0 / n as f64);
+
+        for _ in 0..MAX_ITERATIONS {
+            let gradient = (&covariance * &weights) * 2.0 - &returns * risk_tolerance;
+            let candidate = &weights - &gradient * LEARNING_RATE;
+            let projected = Self::project_weights(&candidate, allow_short);
+
+            let change: f64 = (&projected - &weights).iter().map(|d| d.abs()).sum();
+            weights = projected;
+            if change < TOLERANCE {
+                break;
+            }
+        }
+
+        Ok(weights.iter().cloned().collect())
+    }
+
+    /// Clips negative weights to zero (unless `allow_short`) and
+    /// renormalizes the result to sum to 1; the projection step of
+    /// `optimize_portfolio_constrained`'s gradient descent.
+    fn project_weights(weights: &DVector<f64>, allow_short: bool) -> DVector<f64> {
+        let mut projected = weights.clone();
+        if !allow_short {
+            for w in projected.iter_mut() {
+                if *w < 0.0 {
+                    *w = 0.0;
+                }
+            }
+        }
+        let sum: f64 = projected.iter().sum();
+        if sum.abs() > f64::EPSILON {
+            projected /= sum;
+        }
+        projected
+    }
+
+    /// Sweeps the risk-tolerance parameter `λ` across a range to trace the
+    /// efficient frontier, returning `(expected_return, volatility,
+    /// weights)` for each sampled point so callers can plot the frontier
+    /// and pick a target risk level.
+    pub fn efficient_frontier(
+        expected_returns: &[f64],
+        covariance_matrix: &[Vec<f64>],
+        n_points: usize,
+    ) -> Result<Vec<(f64, f64, Vec<f64>)>> {
+        let n = expected_returns.len();
+        let mut cov_data = Vec::new();
+        for row in covariance_matrix {
+            cov_data.extend_from_slice(row);
+        }
+        let covariance = DMatrix::from_vec(n, n, cov_data);
+
+        let max_return = expected_returns.iter().cloned().fold(f64::MIN, f64::max);
+        let lambda_max = if max_return > 0.0 { 4.0 * max_return } else { 1.0 };
+
+        let mut frontier = Vec::with_capacity(n_points);
+        for i in 0..n_points {
+            let lambda = if n_points <= 1 {
+                lambda_max
+            } else {
+                lambda_max * (i as f64) / (n_points as f64 - 1.0)
+            };
+
+            let weights = Self::optimize_portfolio_constrained(expected_returns, covariance_matrix, lambda, false)?;
+            let w = DVector::from_vec(weights.clone());
+
+            let expected_return: f64 = w.iter().zip(expected_returns.iter()).map(|(wi, ri)| wi * ri).sum();
+            let variance = (w.transpose() * &covariance * &w)[(0, 0)];
+            let volatility = variance.max(0.0).sqrt();
+
+            frontier.push((expected_return, volatility, weights));
+        }
+
+        Ok(frontier)
+    }
+
     /// Calculate Value at Risk (VaR)
     pub fn calculate_var(returns: &[f64], confidence_level: f64) -> Result<f64> {
         let mut sorted_returns = returns.to_vec();
@@ -215,6 +714,52 @@ impl FinancialCalculations {
         Ok(cvar)
     }
 
+    /// Calculate parametric (variance-covariance) Value at Risk from a
+    /// return sample's mean and volatility, assuming normally distributed
+    /// returns. Unlike `calculate_var`, this doesn't need a long empirical
+    /// sample and extrapolates cleanly to a horizon beyond the sampling
+    /// window.
+    pub fn calculate_var_parametric(mean: f64, volatility: f64, confidence_level: f64, horizon_days: f64) -> Result<f64> {
+        let normal = Normal::new(0.0, 1.0)?;
+        let z = normal.inverse_cdf(confidence_level);
+        Ok(-(mean * horizon_days - z * volatility * horizon_days.sqrt()))
+    }
+
+    /// Calculate Value at Risk via Monte Carlo simulation: simulates
+    /// terminal prices under geometric Brownian motion and takes the
+    /// empirical quantile of the resulting P&L. Path generation is
+    /// parallelized with rayon since `n_paths` is typically in the tens of
+    /// thousands.
+    pub fn calculate_var_monte_carlo(
+        spot: f64,
+        mu: f64,
+        sigma: f64,
+        horizon_days: f64,
+        n_paths: usize,
+        confidence_level: f64,
+    ) -> Result<f64> {
+        let normal = Normal::new(0.0, 1.0)?;
+        let drift = (mu - 0.5 * sigma.powi(2)) * horizon_days;
+        let diffusion = sigma * horizon_days.sqrt();
+
+        let mut pnl: Vec<f64> = (0..n_paths)
+            .into_par_iter()
+            .map_init(
+                rand::thread_rng,
+                |rng, _| {
+                    let u: f64 = rng.gen_range(1e-12..1.0 - 1e-12);
+                    let z = normal.inverse_cdf(u);
+                    let terminal = spot * (drift + diffusion * z).exp();
+                    terminal - spot
+                },
+            )
+            .collect();
+
+        pnl.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let index = ((1.0 - confidence_level) * pnl.len() as f64).floor() as usize;
+        Ok(-pnl[index.min(pnl.len() - 1)])
+    }
+
     /// Calculate maximum drawdown
     pub fn calculate_max_drawdown(values: &[f64]) -> f64 {
         let mut max_drawdown = 0.0;
@@ -323,4 +868,65 @@ impl FinancialCalculations {
             calculation_time_ms: 0, // Will be set by caller
         })
     }
+
+    /// Impermanent loss for a concentrated-liquidity position held over
+    /// `[price_lower, price_upper]`, instead of `calculate_impermanent_loss`'s
+    /// full-range `x*y=k` assumption. Given liquidity `L`, the token amounts
+    /// at price `p` follow the standard Uniswap V3 piecewise formula: fully
+    /// token0 below the range, fully token1 above it, and a mix inside it.
+    pub fn calculate_concentrated_impermanent_loss(
+        price_lower: f64,
+        price_upper: f64,
+        entry_price: f64,
+        current_price: f64,
+        liquidity: f64,
+    ) -> Result<ConcentratedImpermanentLossResponse> {
+        if price_lower <= 0.0 || price_upper <= price_lower {
+            return Err(anyhow::anyhow!(
+                "invalid concentrated-liquidity range: price_lower ({}) must be positive and less than price_upper ({})",
+                price_lower, price_upper
+            ));
+        }
+
+        let sqrt_pa = price_lower.sqrt();
+        let sqrt_pb = price_upper.sqrt();
+
+        let token_amounts_at = |price: f64| -> (f64, f64) {
+            if price <= price_lower {
+                (liquidity * (1.0 / sqrt_pa - 1.0 / sqrt_pb), 0.0)
+            } else if price >= price_upper {
+                (0.0, liquidity * (sqrt_pb - sqrt_pa))
+            } else {
+                let sqrt_p = price.sqrt();
+                (liquidity * (1.0 / sqrt_p - 1.0 / sqrt_pb), liquidity * (sqrt_p - sqrt_pa))
+            }
+        };
+
+        let (entry_amount0, entry_amount1) = token_amounts_at(entry_price);
+        let (current_amount0, current_amount1) = token_amounts_at(current_price);
+
+        // Pool value at the current price, versus the value of holding the
+        // entry composition (token0 amount priced at current_price, token1
+        // untouched) instead of ever depositing it.
+        let current_pool_value = current_amount0 * current_price + current_amount1;
+        let hodl_value = entry_amount0 * current_price + entry_amount1;
+
+        let impermanent_loss_amount = hodl_value - current_pool_value;
+        let impermanent_loss_percentage = if hodl_value != 0.0 {
+            (impermanent_loss_amount / hodl_value) * 100.0
+        } else {
+            0.0
+        };
+
+        let range_exit = current_price <= price_lower || current_price >= price_upper;
+
+        Ok(ConcentratedImpermanentLossResponse {
+            impermanent_loss_percentage,
+            impermanent_loss_amount,
+            current_pool_value,
+            hodl_value,
+            range_exit,
+            calculation_time_ms: 0, // Will be set by caller
+        })
+    }
 }
@@ -0,0 +1,104 @@
+//! Live gas-fee estimation for `calculate_arbitrage_opportunity`, used when
+//! an `ArbitrageRequest` omits `gas_costs` instead of supplying a sample
+//! that's likely already stale by the time it reaches this service. Modeled
+//! on Mango Markets' priority-fee estimator: track a percentile of recent
+//! per-transaction fees and smooth it with an EMA, so a caller gets a
+//! fast-reacting-but-not-jittery figure instead of either a raw spot sample
+//! or a slow-moving average.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Anything that can produce a current gas-cost estimate, so
+/// `calculate_arbitrage_opportunity` doesn't need to know whether the
+/// figure comes from a live EMA, a fixed config value, or (in tests) a
+/// canned constant.
+pub trait PriorityFeeProvider: Send + Sync {
+    fn estimate_gas_cost(&self) -> f64;
+
+    /// Feeds a fresh batch of observed fee samples into the provider, if it
+    /// keeps a running estimate. No-op by default, since not every provider
+    /// (e.g. a fixed-config or canned-test one) has anything to update.
+    fn observe_batch(&self, _batch: &[f64]) {}
+}
+
+struct EmaState {
+    ema: Option<f64>,
+    last_update: Option<Instant>,
+}
+
+/// Maintains an exponentially-smoothed estimate of the `percentile`th
+/// percentile of recent per-transaction fees. `observe_batch` feeds in a
+/// fresh batch of raw fee samples (e.g. recently observed transactions);
+/// `estimate_gas_cost` reads back the current EMA, or `fallback` if the
+/// estimate is older than `max_age` — the chain might have gone quiet, or
+/// whatever feeds `observe_batch` might be stuck.
+pub struct CuPercentileEmaProvider {
+    percentile: f64,
+    alpha: f64,
+    max_age: Duration,
+    fallback: f64,
+    state: Mutex<EmaState>,
+}
+
+impl CuPercentileEmaProvider {
+    /// `percentile` in `[0, 100]` (e.g. `75.0`); `fallback` is returned
+    /// whenever there's no fresh-enough estimate. Defaults `alpha` to `0.2`
+    /// and `max_age` to 15 seconds, matching the values this is modeled on.
+    pub fn new(percentile: f64, fallback: f64) -> Self {
+        Self {
+            percentile,
+            alpha: 0.2,
+            max_age: Duration::from_secs(15),
+            fallback,
+            state: Mutex::new(EmaState { ema: None, last_update: None }),
+        }
+    }
+
+    pub fn with_alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Computes the configured percentile over `batch` and folds it into
+    /// the running EMA: `ema = alpha * percentile_value + (1 - alpha) *
+    /// ema`, seeding `ema` with the first observed percentile value rather
+    /// than 0 so an early batch doesn't get dragged toward zero. No-op on
+    /// an empty batch.
+    pub fn observe_batch(&self, batch: &[f64]) {
+        if batch.is_empty() {
+            return;
+        }
+
+        let mut sorted = batch.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let index = ((sorted.len() as f64 * self.percentile / 100.0) as usize).min(sorted.len() - 1);
+        let percentile_value = sorted[index];
+
+        let mut state = self.state.lock().unwrap();
+        state.ema = Some(match state.ema {
+            Some(ema) => self.alpha * percentile_value + (1.0 - self.alpha) * ema,
+            None => percentile_value,
+        });
+        state.last_update = Some(Instant::now());
+    }
+}
+
+impl PriorityFeeProvider for CuPercentileEmaProvider {
+    fn estimate_gas_cost(&self) -> f64 {
+        let state = self.state.lock().unwrap();
+        match (state.ema, state.last_update) {
+            (Some(ema), Some(last_update)) if last_update.elapsed() <= self.max_age => ema,
+            _ => self.fallback,
+        }
+    }
+
+    fn observe_batch(&self, batch: &[f64]) {
+        CuPercentileEmaProvider::observe_batch(self, batch);
+    }
+}
@@ -2,101 +2,631 @@ use anyhow::Result;
 use futures::StreamExt;
 use log::{error, info, warn};
 use rdkafka::config::ClientConfig;
-use rdkafka::consumer::{Consumer, StreamConsumer};
-use rdkafka::message::Message;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::message::{BorrowedMessage, Message};
+use rdkafka::producer::FutureProducer;
+use rdkafka::{Offset, TopicPartitionList};
 use serde_json;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, Mutex as AsyncMutex, RwLock as AsyncRwLock, Semaphore};
+use tokio::time::sleep;
 
+use crate::metrics::MathComputingMetrics;
 use crate::models::{MathComputationTask, ComputationResult};
 
-#[derive(Clone)]
+/// Initial delay between `start_consuming`'s reconnect attempts, doubled on
+/// each subsequent attempt up to `MAX_RECONNECT_DELAY` — same policy as
+/// `RateFeed`'s WebSocket reconnect loop.
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// One failed task captured for the dead-letter buffer: the raw message
+/// bytes plus enough metadata to investigate or replay it, since the
+/// original `BorrowedMessage` can't outlive the poll that produced it.
+#[derive(Debug, Clone)]
+struct DeadLetter {
+    payload: Vec<u8>,
+    partition: i32,
+    offset: i64,
+    error: String,
+    attempt: u32,
+    failed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Bounded in-flight queue of dead-lettered messages, modeled on Arroyo's
+/// `processing/dlq.rs` buffer: `capacity` gates how many dead letters may be
+/// in flight to the dead-letter topic at once. Once `max_size` are already
+/// in flight, `enqueue` blocks the caller (applying backpressure to
+/// `start_consuming`'s poll loop) instead of dropping a message or letting
+/// the queue grow unbounded, preserving per-partition ordering since offsets
+/// still aren't committed any earlier than before.
+struct DlqBuffer {
+    capacity: Semaphore,
+}
+
+impl DlqBuffer {
+    fn new(max_size: usize) -> Self {
+        Self {
+            capacity: Semaphore::new(max_size),
+        }
+    }
+
+    /// Acquires a capacity permit (blocking if the buffer is already full),
+    /// then produces `dead_letter` to `topic`. The permit is held until the
+    /// produce finishes, so a slow or failing DLQ producer throttles how
+    /// fast new dead letters are accepted rather than letting them pile up
+    /// unbounded. Produces `dead_letter` directly rather than round-tripping
+    /// it through a shared queue: each caller already holds its own permit,
+    /// so there's nothing for a queue to hand off between callers, and doing
+    /// so previously handed one caller's dead letter to whichever caller
+    /// happened to pop the queue's front first.
+    async fn enqueue(&self, dead_letter: DeadLetter, producer: &FutureProducer, topic: &str) -> Result<()> {
+        let _permit = self
+            .capacity
+            .acquire()
+            .await
+            .map_err(|_| anyhow::anyhow!("dead-letter buffer closed"))?;
+
+        use rdkafka::producer::FutureRecord;
+
+        let envelope = serde_json::json!({
+            "error": dead_letter.error,
+            "payload": String::from_utf8_lossy(&dead_letter.payload),
+            "partition": dead_letter.partition,
+            "offset": dead_letter.offset,
+            "attempt": dead_letter.attempt,
+            "failed_at": dead_letter.failed_at.to_rfc3339(),
+        });
+        let payload = serde_json::to_string(&envelope)?;
+
+        let record = FutureRecord::to(topic).key("dead-letter").payload(&payload);
+
+        match producer.send(record, Duration::from_secs(0)).await {
+            Ok(_) => Ok(()),
+            Err((e, _)) => Err(anyhow::anyhow!("Failed to send message to dead-letter topic: {}", e)),
+        }
+    }
+}
+
+/// What a dead-lettered message's fixed-window policy guard decided to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DlqPolicy {
+    /// Forward the message to the dead-letter topic and keep consuming.
+    Drop,
+    /// Forward the message; it can be replayed later via `replay_dlq`.
+    Reprocess,
+    /// Too many messages have been dead-lettered within the tracked window;
+    /// `start_consuming` halts rather than keep draining the topic.
+    Stop,
+}
+
+/// Fixed-window count of dead-lettered messages (the count restarts at the
+/// first failure after `window` has elapsed), so a systemic outage
+/// (bad schema rollout, a downstream dependency being down) trips a hard
+/// stop instead of silently draining the whole topic into the DLQ.
+struct DlqLimitState {
+    window_start: Instant,
+    count: u64,
+    max_count: u64,
+    window: Duration,
+}
+
+impl DlqLimitState {
+    fn new(max_count: u64, window: Duration) -> Self {
+        Self { window_start: Instant::now(), count: 0, max_count, window }
+    }
+
+    /// Records one dead-lettered message, resetting the window if it has
+    /// elapsed, and returns the policy to apply.
+    fn record_failure(&mut self) -> DlqPolicy {
+        if self.window_start.elapsed() > self.window {
+            self.window_start = Instant::now();
+            self.count = 0;
+        }
+        self.count += 1;
+        if self.count > self.max_count {
+            DlqPolicy::Stop
+        } else {
+            DlqPolicy::Reprocess
+        }
+    }
+}
+
+/// Connectivity reported by `start_consuming`'s reconnect driver over
+/// `KafkaConsumerService::health`, so a caller can alert on sustained
+/// disconnection instead of only noticing it from log silence.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConsumerHealth {
+    Connected,
+    Reconnecting { attempt: u32, last_error: String },
+    /// The consume loop has stopped for good; `start_consuming` has already
+    /// returned `Err`.
+    PermanentlyFailed,
+}
+
+/// Why `consume_once` returned, so `start_consuming`'s driver knows whether
+/// to reconnect with backoff or stop for good.
+enum ConsumeLoopExit {
+    /// The stream ended or the broker connection dropped; retryable.
+    Disconnected(String),
+    /// A policy decided consumption should stop (e.g. the dead-letter rate
+    /// limit tripped, or nothing is listening on the health channel
+    /// anymore); not retried.
+    Permanent(String),
+}
+
+/// Backoff state for `start_consuming`'s reconnect driver: `delay` is the
+/// capped, doubling interval between attempts, `attempt` the streak counter
+/// reported via `ConsumerHealth::Reconnecting`. Both reset once a message is
+/// successfully pulled off the stream again.
+struct ReconnectState {
+    delay: Duration,
+    attempt: u32,
+}
+
+impl ReconnectState {
+    fn new() -> Self {
+        Self { delay: INITIAL_RECONNECT_DELAY, attempt: 0 }
+    }
+
+    fn reset(&mut self) {
+        self.delay = INITIAL_RECONNECT_DELAY;
+        self.attempt = 0;
+    }
+
+    /// Advances the streak, returning the attempt number to report and the
+    /// delay to sleep for before the next attempt.
+    fn advance(&mut self) -> (u32, Duration) {
+        self.attempt += 1;
+        let delay = self.delay;
+        self.delay = (self.delay * 2).min(MAX_RECONNECT_DELAY);
+        (self.attempt, delay)
+    }
+}
+
+fn commit_with(consumer: &StreamConsumer, message: &BorrowedMessage) {
+    if let Err(e) = consumer.commit_message(message, CommitMode::Async) {
+        error!("Failed to commit message offset: {}", e);
+    }
+}
+
 pub struct KafkaConsumerService {
-    consumer: StreamConsumer,
+    /// Wrapped in a lock (rather than a plain field) so `start_consuming`'s
+    /// reconnect driver can rebuild and re-subscribe it in place when the
+    /// broker connection drops, without callers needing a new
+    /// `KafkaConsumerService`.
+    consumer: AsyncRwLock<StreamConsumer>,
+    /// Shared across every `send_*` method so result/metrics/health
+    /// publishing reuses one broker connection instead of re-establishing
+    /// one (and re-parsing config) per message.
+    producer: FutureProducer,
+    /// Reused by `replay_dlq` and by `start_consuming`'s reconnect driver to
+    /// stand up a fresh consumer.
+    brokers: String,
+    /// Reused by `start_consuming`'s reconnect driver when rebuilding the
+    /// consumer after a disconnect.
+    group_id: String,
+    request_topic: String,
+    /// Where a task's raw payload (plus error metadata) lands once it either
+    /// fails to deserialize or exhausts `max_retries`, so nothing is
+    /// silently dropped.
+    dead_letter_topic: String,
+    /// How many times a failed handler call is retried before the task is
+    /// forwarded to `dead_letter_topic`.
+    max_retries: u32,
+    /// Base delay for the retry backoff, doubled on each subsequent attempt.
+    retry_backoff_base: Duration,
+    /// Trips `DlqPolicy::Stop` once too many messages have been
+    /// dead-lettered within the tracked window.
+    dlq_limit: Mutex<DlqLimitState>,
+    /// Bounded in-flight queue of dead-lettered messages; see `DlqBuffer`.
+    dlq_buffer: DlqBuffer,
+    /// Set via `with_metrics` so invalid-message and handler-retry counts
+    /// surface in `MathComputingMetrics` instead of only appearing in logs.
+    metrics: Option<Arc<MathComputingMetrics>>,
+    /// Reports connectivity state from `start_consuming`'s reconnect
+    /// driver; subscribe via `health()`.
+    health_tx: watch::Sender<ConsumerHealth>,
 }
 
 impl KafkaConsumerService {
-    pub fn new(brokers: &str, group_id: &str) -> Result<Self> {
+    /// `producer_timeout_ms`/`producer_acks` configure the single shared
+    /// producer used by every `send_*` method; the producer connects to the
+    /// same `brokers` as the consumer.
+    pub fn new(brokers: &str, group_id: &str, producer_timeout_ms: u64, producer_acks: &str) -> Result<Self> {
         let consumer: StreamConsumer = ClientConfig::new()
             .set("group.id", group_id)
             .set("bootstrap.servers", brokers)
             .set("enable.partition.eof", "false")
             .set("session.timeout.ms", "6000")
-            .set("enable.auto.commit", "true")
+            .set("enable.auto.commit", "false")
             .set("auto.offset.reset", "latest")
             .create()?;
 
-        Ok(Self { consumer })
+        let producer: FutureProducer = ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("message.timeout.ms", producer_timeout_ms.to_string())
+            .set("acks", producer_acks)
+            .create()?;
+
+        let (health_tx, _health_rx) = watch::channel(ConsumerHealth::Connected);
+
+        Ok(Self {
+            consumer: AsyncRwLock::new(consumer),
+            producer,
+            brokers: brokers.to_string(),
+            group_id: group_id.to_string(),
+            request_topic: "math-computation-requests".to_string(),
+            dead_letter_topic: "math-computation-dead-letter".to_string(),
+            max_retries: 3,
+            retry_backoff_base: Duration::from_millis(200),
+            dlq_limit: Mutex::new(DlqLimitState::new(20, Duration::from_secs(60))),
+            dlq_buffer: DlqBuffer::new(256),
+            metrics: None,
+            health_tx,
+        })
+    }
+
+    /// Subscribes to `ConsumerHealth` updates from `start_consuming`'s
+    /// reconnect driver. Having no live receiver from this method (none
+    /// taken yet, or every one dropped) is treated as a signal that nothing
+    /// is watching, and the next message pulled or the next reconnect
+    /// attempt halts the consumer for good instead of retrying forever with
+    /// no observer.
+    pub fn health(&self) -> watch::Receiver<ConsumerHealth> {
+        self.health_tx.subscribe()
+    }
+
+    /// Publishes `health` to every subscriber; returns `false` once the last
+    /// receiver has been dropped.
+    fn publish_health(&self, health: ConsumerHealth) -> bool {
+        self.health_tx.send(health).is_ok()
+    }
+
+    /// Overrides the topic `subscribe_to_computation_requests` subscribes to.
+    pub fn with_request_topic(mut self, topic: impl Into<String>) -> Self {
+        self.request_topic = topic.into();
+        self
+    }
+
+    /// Overrides the topic poison/exhausted-retry messages are forwarded to.
+    pub fn with_dead_letter_topic(mut self, topic: impl Into<String>) -> Self {
+        self.dead_letter_topic = topic.into();
+        self
+    }
+
+    /// Overrides how many times a failed handler call is retried before the
+    /// task is forwarded to the dead-letter topic.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Overrides the base delay for the handler-retry exponential backoff.
+    pub fn with_retry_backoff(mut self, base_delay: Duration) -> Self {
+        self.retry_backoff_base = base_delay;
+        self
+    }
+
+    /// Overrides the dead-letter policy guard: halt the consumer if more
+    /// than `max_count` messages are dead-lettered within `window`.
+    pub fn with_dlq_limit(mut self, max_count: u64, window: Duration) -> Self {
+        self.dlq_limit = Mutex::new(DlqLimitState::new(max_count, window));
+        self
+    }
+
+    /// Overrides the dead-letter buffer's capacity: how many dead-lettered
+    /// messages may be in flight to `dead_letter_topic` at once before
+    /// enqueueing another one starts blocking (backpressuring) the consumer
+    /// loop.
+    pub fn with_dlq_buffer_size(mut self, max_size: usize) -> Self {
+        self.dlq_buffer = DlqBuffer::new(max_size);
+        self
+    }
+
+    /// Lets invalid-message and handler-retry counts surface in
+    /// `MathComputingMetrics` instead of only appearing in logs.
+    pub fn with_metrics(mut self, metrics: Arc<MathComputingMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
     }
 
     pub async fn subscribe_to_computation_requests(&self) -> Result<()> {
-        self.consumer.subscribe(&["math-computation-requests"])?;
-        info!("Subscribed to math-computation-requests topic");
+        self.consumer.read().await.subscribe(&[self.request_topic.as_str()])?;
+        info!("Subscribed to {} topic", self.request_topic);
         Ok(())
     }
 
+    /// Consumes tasks with manual offset commits: a message is only
+    /// committed once its handler has succeeded *and* the result has been
+    /// produced, or once it's been durably forwarded to the dead-letter
+    /// topic. A handler failure is retried with exponential backoff up to
+    /// `max_retries` before the task is treated as exhausted.
+    ///
+    /// Wraps that consume loop in an unbounded exponential-backoff reconnect
+    /// driver, modeled on `RateFeed`'s WebSocket reconnect loop: a dropped
+    /// connection rebuilds the `StreamConsumer` and re-subscribes before the
+    /// next attempt, with the backoff resetting once the stream is healthy
+    /// again. Connectivity is reported on `health()`; while no receiver is
+    /// live, the next message pulled or the next disconnect halts the
+    /// consumer for good instead of retrying forever with nothing watching.
     pub async fn start_consuming<F>(&self, mut handler: F) -> Result<()>
     where
         F: FnMut(MathComputationTask) -> Result<ComputationResult> + Send,
     {
-        let mut message_stream = self.consumer.stream();
+        let mut reconnect = ReconnectState::new();
+
+        loop {
+            match self.consume_once(&mut handler, &mut reconnect).await {
+                ConsumeLoopExit::Permanent(reason) => {
+                    error!("Kafka consumer for {} stopping permanently: {}", self.request_topic, reason);
+                    let _ = self.publish_health(ConsumerHealth::PermanentlyFailed);
+                    return Err(anyhow::anyhow!("consumer stopped permanently: {}", reason));
+                }
+                ConsumeLoopExit::Disconnected(reason) => {
+                    let (attempt, delay) = reconnect.advance();
+                    warn!(
+                        "Kafka consumer for {} disconnected ({}); reconnecting (attempt {})",
+                        self.request_topic, reason, attempt
+                    );
+
+                    if !self.publish_health(ConsumerHealth::Reconnecting { attempt, last_error: reason }) {
+                        error!("Health watch channel has no receivers left; stopping permanently");
+                        return Err(anyhow::anyhow!(
+                            "consumer health channel disconnected; halting permanently"
+                        ));
+                    }
+
+                    sleep(delay).await;
+
+                    if let Err(e) = self.rebuild_consumer().await {
+                        error!("Failed to rebuild Kafka consumer: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Rebuilds `self.consumer` from scratch and re-subscribes it, used by
+    /// `start_consuming`'s reconnect driver after a disconnect.
+    async fn rebuild_consumer(&self) -> Result<()> {
+        let consumer: StreamConsumer = ClientConfig::new()
+            .set("group.id", &self.group_id)
+            .set("bootstrap.servers", &self.brokers)
+            .set("enable.partition.eof", "false")
+            .set("session.timeout.ms", "6000")
+            .set("enable.auto.commit", "false")
+            .set("auto.offset.reset", "latest")
+            .create()?;
+        consumer.subscribe(&[self.request_topic.as_str()])?;
+        *self.consumer.write().await = consumer;
+        Ok(())
+    }
+
+    /// Runs the consume loop against the current `self.consumer` until it
+    /// disconnects or a policy decides to stop it, reporting which via
+    /// `ConsumeLoopExit`.
+    async fn consume_once<F>(&self, handler: &mut F, reconnect: &mut ReconnectState) -> ConsumeLoopExit
+    where
+        F: FnMut(MathComputationTask) -> Result<ComputationResult> + Send,
+    {
+        let consumer_guard = self.consumer.read().await;
+        let mut message_stream = consumer_guard.stream();
 
         while let Some(message) = message_stream.next().await {
-            match message {
+            let m = match message {
                 Err(e) => {
-                    error!("Kafka error: {}", e);
+                    return ConsumeLoopExit::Disconnected(e.to_string());
+                }
+                Ok(m) => m,
+            };
+
+            // Pulling a message off the stream at all means the connection
+            // is healthy, regardless of how this particular message turns
+            // out to process.
+            reconnect.reset();
+            if !self.publish_health(ConsumerHealth::Connected) {
+                return ConsumeLoopExit::Permanent("health watch receiver disconnected".to_string());
+            }
+
+            let partition = m.partition();
+            let offset = m.offset();
+            let raw_payload = m.payload().map(|b| b.to_vec()).unwrap_or_default();
+
+            let payload = match m.payload_view::<str>() {
+                None => {
+                    warn!("Empty message payload");
+                    commit_with(&consumer_guard, &m);
                     continue;
                 }
-                Ok(m) => {
-                    let payload = match m.payload_view::<str>() {
-                        None => {
-                            warn!("Empty message payload");
-                            continue;
-                        }
-                        Some(Ok(s)) => s,
-                        Some(Err(e)) => {
-                            error!("Error while deserializing message payload: {:?}", e);
-                            continue;
-                        }
+                Some(Ok(s)) => s.to_string(),
+                Some(Err(e)) => {
+                    error!("Error while deserializing message payload: {:?}", e);
+                    commit_with(&consumer_guard, &m);
+                    continue;
+                }
+            };
+
+            let task = match serde_json::from_str::<MathComputationTask>(&payload) {
+                Ok(task) => task,
+                Err(e) => {
+                    error!("Failed to deserialize computation task: {}", e);
+                    if let Some(metrics) = &self.metrics {
+                        metrics.record_dlq_invalid_message();
+                    }
+                    let dead_letter = DeadLetter {
+                        payload: raw_payload,
+                        partition,
+                        offset,
+                        error: format!("deserialize error: {}", e),
+                        attempt: 0,
+                        failed_at: chrono::Utc::now(),
                     };
+                    if let Err(dlq_err) = self.enqueue_dead_letter(dead_letter).await {
+                        error!("Failed to forward undeserializable payload to dead-letter topic: {}", dlq_err);
+                    }
+                    commit_with(&consumer_guard, &m);
+                    if self.dlq_limit.lock().unwrap().record_failure() == DlqPolicy::Stop {
+                        return ConsumeLoopExit::Permanent(
+                            "dead-letter rate exceeded the tracked window".to_string(),
+                        );
+                    }
+                    continue;
+                }
+            };
+
+            info!("Received computation task: {} of type: {:?}", task.task_id, task.task_type);
 
-                    match serde_json::from_str::<MathComputationTask>(payload) {
-                        Ok(task) => {
-                            info!("Received computation task: {} of type: {:?}", task.task_id, task.task_type);
-                            
-                            match handler(task) {
-                                Ok(result) => {
-                                    info!("Successfully processed computation task: {}", result.task_id);
-                                    // Send result back to Kafka
-                                    if let Err(e) = self.send_computation_result(&result).await {
-                                        error!("Failed to send computation result: {}", e);
-                                    }
-                                }
-                                Err(e) => {
-                                    error!("Failed to process computation task: {}", e);
-                                }
-                            }
+            let mut attempt = 0;
+            let mut last_error = None;
+            let result = loop {
+                match handler(task.clone()) {
+                    Ok(result) => break Some(result),
+                    Err(e) => {
+                        attempt += 1;
+                        warn!(
+                            "Computation task {} failed (attempt {}/{}): {}",
+                            task.task_id, attempt, self.max_retries, e
+                        );
+                        last_error = Some(e.to_string());
+                        if attempt > self.max_retries {
+                            break None;
                         }
-                        Err(e) => {
-                            error!("Failed to deserialize computation task: {}", e);
+                        if let Some(metrics) = &self.metrics {
+                            metrics.record_dlq_retry();
                         }
+                        sleep(self.retry_backoff_base * 2u32.pow(attempt - 1)).await;
+                    }
+                }
+            };
+
+            match result {
+                Some(result) => {
+                    if let Err(e) = self.send_computation_result(&result).await {
+                        error!("Failed to send computation result for task {}: {}", result.task_id, e);
+                        // Result wasn't durably produced; leave the offset
+                        // uncommitted so the task is redelivered.
+                        continue;
+                    }
+                    info!("Successfully processed computation task: {}", result.task_id);
+                    commit_with(&consumer_guard, &m);
+                }
+                None => {
+                    error!(
+                        "Computation task {} failed after {} attempts; forwarding to dead-letter topic",
+                        task.task_id, attempt
+                    );
+                    let error_message = last_error.unwrap_or_else(|| "unknown error".to_string());
+                    let dead_letter = DeadLetter {
+                        payload: raw_payload,
+                        partition,
+                        offset,
+                        error: error_message,
+                        attempt,
+                        failed_at: chrono::Utc::now(),
+                    };
+                    if let Err(dlq_err) = self.enqueue_dead_letter(dead_letter).await {
+                        error!("Failed to forward exhausted task {} to dead-letter topic: {}", task.task_id, dlq_err);
+                    }
+                    commit_with(&consumer_guard, &m);
+                    if self.dlq_limit.lock().unwrap().record_failure() == DlqPolicy::Stop {
+                        return ConsumeLoopExit::Permanent(
+                            "dead-letter rate exceeded the tracked window".to_string(),
+                        );
                     }
                 }
             }
         }
 
-        Ok(())
+        ConsumeLoopExit::Disconnected("consumer stream ended".to_string())
     }
 
-    async fn send_computation_result(&self, result: &ComputationResult) -> Result<()> {
-        use rdkafka::producer::{FutureProducer, FutureRecord};
-        
-        // Create a producer for sending results
-        let producer: FutureProducer = ClientConfig::new()
-            .set("bootstrap.servers", std::env::var("KAFKA_BROKERS").unwrap_or_else(|_| "localhost:9092".to_string()))
-            .set("message.timeout.ms", "5000")
-            .set("acks", "all")
+    /// Enqueues a task that either failed to deserialize or exhausted its
+    /// retries into the bounded `dlq_buffer`, which forwards it to
+    /// `dead_letter_topic` so it can be inspected or replayed instead of
+    /// being silently dropped.
+    async fn enqueue_dead_letter(&self, dead_letter: DeadLetter) -> Result<()> {
+        self.dlq_buffer.enqueue(dead_letter, &self.producer, &self.dead_letter_topic).await
+    }
+
+    /// Re-consumes `topic` (normally `dead_letter_topic`) from `from_offset`
+    /// on partition 0 and replays each envelope's original payload back
+    /// through `handler`, the same callback `start_consuming` uses, so a
+    /// task that's been fixed upstream can be reprocessed instead of staying
+    /// stuck in the dead-letter topic. Stops once it reaches the partition's
+    /// end and returns how many tasks were successfully reprocessed.
+    pub async fn replay_dlq<F>(&self, topic: &str, from_offset: i64, mut handler: F) -> Result<u64>
+    where
+        F: FnMut(MathComputationTask) -> Result<ComputationResult> + Send,
+    {
+        let replay_consumer: StreamConsumer = ClientConfig::new()
+            .set("group.id", "math-computing-dlq-replay")
+            .set("bootstrap.servers", &self.brokers)
+            .set("enable.partition.eof", "true")
+            .set("enable.auto.commit", "false")
             .create()?;
 
+        let mut assignment = TopicPartitionList::new();
+        assignment.add_partition_offset(topic, 0, Offset::Offset(from_offset))?;
+        replay_consumer.assign(&assignment)?;
+
+        let mut replayed = 0u64;
+        let mut message_stream = replay_consumer.stream();
+
+        while let Some(message) = message_stream.next().await {
+            let m = match message {
+                Err(rdkafka::error::KafkaError::PartitionEOF(_)) => break,
+                Err(e) => {
+                    error!("Kafka error while replaying {}: {}", topic, e);
+                    continue;
+                }
+                Ok(m) => m,
+            };
+
+            let payload = match m.payload_view::<str>() {
+                Some(Ok(s)) => s,
+                _ => continue,
+            };
+
+            let envelope: serde_json::Value = match serde_json::from_str(payload) {
+                Ok(v) => v,
+                Err(e) => {
+                    error!("Failed to parse dead-letter envelope from {}: {}", topic, e);
+                    continue;
+                }
+            };
+
+            let raw_task = match envelope.get("payload").and_then(|v| v.as_str()) {
+                Some(s) => s,
+                None => continue,
+            };
+
+            let task = match serde_json::from_str::<MathComputationTask>(raw_task) {
+                Ok(task) => task,
+                Err(e) => {
+                    error!("Dead-lettered payload still fails to deserialize: {}", e);
+                    continue;
+                }
+            };
+
+            match handler(task.clone()) {
+                Ok(result) => {
+                    self.send_computation_result(&result).await?;
+                    replayed += 1;
+                }
+                Err(e) => {
+                    warn!("Replayed computation task {} failed again: {}", task.task_id, e);
+                }
+            }
+        }
+
+        Ok(replayed)
+    }
+
+    async fn send_computation_result(&self, result: &ComputationResult) -> Result<()> {
+        use rdkafka::producer::FutureRecord;
+
         let payload = serde_json::to_string(result)?;
         let key = result.task_id.clone();
 
@@ -104,7 +634,7 @@ impl KafkaConsumerService {
             .key(&key)
             .payload(&payload);
 
-        match producer.send(record, Duration::from_secs(0)).await {
+        match self.producer.send(record, Duration::from_secs(0)).await {
             Ok(_) => {
                 info!("Sent computation result for task: {}", result.task_id);
                 Ok(())
@@ -114,12 +644,7 @@ impl KafkaConsumerService {
     }
 
     pub async fn send_performance_metrics(&self, metrics: &serde_json::Value) -> Result<()> {
-        use rdkafka::producer::{FutureProducer, FutureRecord};
-        
-        let producer: FutureProducer = ClientConfig::new()
-            .set("bootstrap.servers", std::env::var("KAFKA_BROKERS").unwrap_or_else(|_| "localhost:9092".to_string()))
-            .set("message.timeout.ms", "5000")
-            .create()?;
+        use rdkafka::producer::FutureRecord;
 
         let payload = serde_json::to_string(metrics)?;
         let key = "math-computing-metrics".to_string();
@@ -128,19 +653,14 @@ impl KafkaConsumerService {
             .key(&key)
             .payload(&payload);
 
-        match producer.send(record, Duration::from_secs(0)).await {
+        match self.producer.send(record, Duration::from_secs(0)).await {
             Ok(_) => Ok(()),
             Err((e, _)) => Err(anyhow::anyhow!("Failed to send performance metrics: {}", e)),
         }
     }
 
     pub async fn send_health_status(&self, status: &str) -> Result<()> {
-        use rdkafka::producer::{FutureProducer, FutureRecord};
-        
-        let producer: FutureProducer = ClientConfig::new()
-            .set("bootstrap.servers", std::env::var("KAFKA_BROKERS").unwrap_or_else(|_| "localhost:9092".to_string()))
-            .set("message.timeout.ms", "5000")
-            .create()?;
+        use rdkafka::producer::FutureRecord;
 
         let health_status = serde_json::json!({
             "service": "math-computing",
@@ -158,7 +678,7 @@ impl KafkaConsumerService {
             .key(&key)
             .payload(&payload);
 
-        match producer.send(record, Duration::from_secs(0)).await {
+        match self.producer.send(record, Duration::from_secs(0)).await {
             Ok(_) => Ok(()),
             Err((e, _)) => Err(anyhow::anyhow!("Failed to send health status: {}", e)),
         }
@@ -1,33 +1,55 @@
 use anyhow::Result;
 use axum::{
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
     extract::State,
     http::StatusCode,
-    response::Json,
+    response::{Json, Response},
     routing::{get, post},
     Router,
 };
-use log::{error, info};
+use log::{error, info, warn};
 use std::env;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpListener;
 use tower_http::cors::CorsLayer;
 
 mod models;
 mod calculations;
+mod fee_estimator;
 mod kafka_consumer;
 mod math_service;
 mod metrics;
+mod rate_feed;
 
 use models::*;
 use calculations::*;
 use kafka_consumer::KafkaConsumerService;
 use math_service::MathComputingService;
 use metrics::{MathComputingMetrics, create_metrics_registry, register_math_metrics};
+use rate_feed::{LatestRate, RateFeed};
+
+/// How often `/subscribe/option-price` recomputes Greeks against the
+/// latest cached rate and pushes an update to the client.
+const OPTION_PRICE_STREAM_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often the background task refreshes `rate_feed_staleness_seconds`.
+const STALENESS_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often the background task flushes `MathComputingMetrics`'s buffered
+/// counters/histograms into the persistent registry, so a quiet period
+/// still surfaces samples instead of leaving them buffered indefinitely.
+const METRICS_FLUSH_POLL_INTERVAL: Duration = Duration::from_millis(250);
 
 #[derive(Clone)]
 pub struct AppState {
     math_service: Arc<MathComputingService>,
     metrics: Arc<MathComputingMetrics>,
+    rate_feed: Arc<RateFeed>,
+    /// Built once at startup and registered into exactly once, so
+    /// `metrics_handler` gathers from a persistent registry instead of
+    /// recreating and re-registering every scrape.
+    registry: Arc<prometheus::Registry>,
 }
 
 #[tokio::main]
@@ -39,14 +61,35 @@ async fn main() -> Result<()> {
     let kafka_brokers = env::var("KAFKA_BROKERS")
         .unwrap_or_else(|_| "localhost:9092".to_string());
 
-    let math_service = Arc::new(MathComputingService::new(&kafka_brokers).await?);
     let metrics = Arc::new(MathComputingMetrics::new());
-    
+    let math_service = Arc::new(MathComputingService::new(&kafka_brokers, metrics.clone()).await?);
+
+    let registry = Arc::new(create_metrics_registry());
+    register_math_metrics(&registry, &metrics)?;
+
+    let rate_feed_endpoint = env::var("RATE_FEED_WS_URL")
+        .unwrap_or_else(|_| "wss://ws.kraken.com".to_string());
+    let rate_feed = RateFeed::spawn(rate_feed_endpoint);
+
     let app_state = AppState {
         math_service: math_service.clone(),
         metrics: metrics.clone(),
+        rate_feed: rate_feed.clone(),
+        registry,
     };
 
+    // Periodically flush MathComputingMetrics's buffered counters/histograms
+    // even when no hot-path call has pushed the buffer past its threshold.
+    let flush_metrics = metrics.clone();
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(METRICS_FLUSH_POLL_INTERVAL).await;
+            if flush_metrics.buffer_due_for_flush() {
+                flush_metrics.flush_buffer();
+            }
+        }
+    });
+
     // Start Kafka consumer in background
     let consumer_service = math_service.clone();
     tokio::spawn(async move {
@@ -55,16 +98,32 @@ async fn main() -> Result<()> {
         }
     });
 
+    // Keep the rate-feed staleness gauge current; this task is the only
+    // thing that updates it (`/subscribe/option-price` does not).
+    let staleness_feed = rate_feed.clone();
+    let staleness_metrics = metrics.clone();
+    tokio::spawn(async move {
+        loop {
+            if let Some(staleness) = staleness_feed.staleness() {
+                staleness_metrics.set_rate_feed_staleness(staleness.as_secs_f64());
+            }
+            tokio::time::sleep(STALENESS_POLL_INTERVAL).await;
+        }
+    });
+
     // Create HTTP API routes
     let app = Router::new()
         .route("/health", get(health_check))
         .route("/metrics", get(metrics_handler))
         .route("/calculate/option-price", post(calculate_option_price_handler))
         .route("/calculate/arbitrage", post(calculate_arbitrage_handler))
+        .route("/gas-fees/observe", post(observe_gas_fees_handler))
         .route("/calculate/portfolio-optimization", post(optimize_portfolio_handler))
         .route("/calculate/risk-metrics", post(calculate_risk_metrics_handler))
         .route("/calculate/yield-farming", post(calculate_yield_farming_handler))
         .route("/calculate/impermanent-loss", post(calculate_impermanent_loss_handler))
+        .route("/calculate/batch", post(calculate_batch_handler))
+        .route("/subscribe/option-price", get(subscribe_option_price_handler))
         .layer(CorsLayer::permissive())
         .with_state(app_state);
 
@@ -87,13 +146,11 @@ async fn health_check() -> Json<serde_json::Value> {
 }
 
 async fn metrics_handler(State(state): State<AppState>) -> Result<String, StatusCode> {
-    let registry = create_metrics_registry();
-    if let Err(e) = register_math_metrics(&registry, &state.metrics) {
-        error!("Failed to register metrics: {}", e);
-        return Err(StatusCode::INTERNAL_SERVER_ERROR);
-    }
-    
-    let metric_families = registry.gather();
+    // Surface any samples buffered since the last periodic flush before
+    // gathering, so a scrape right after a burst of activity isn't stale.
+    state.metrics.flush_buffer();
+
+    let metric_families = state.registry.gather();
     let encoder = prometheus::TextEncoder::new();
     match encoder.encode_to_string(&metric_families) {
         Ok(metrics) => Ok(metrics),
@@ -117,12 +174,74 @@ async fn calculate_option_price_handler(
     }
 }
 
+/// Streams `OptionPriceResponse` updates over a WebSocket connection: the
+/// client sends one `OptionPriceSubscribeRequest` (an `OptionPriceRequest`
+/// with `spot_price` omitted), then this recomputes Greeks against
+/// `AppState::rate_feed`'s latest price every `OPTION_PRICE_STREAM_INTERVAL`
+/// for as long as the connection stays open.
+async fn subscribe_option_price_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
+    ws.on_upgrade(move |socket| handle_option_price_subscription(socket, state))
+}
+
+async fn handle_option_price_subscription(mut socket: WebSocket, state: AppState) {
+    let subscribe_request = match socket.recv().await {
+        Some(Ok(Message::Text(text))) => match serde_json::from_str::<OptionPriceSubscribeRequest>(&text) {
+            Ok(request) => request,
+            Err(e) => {
+                warn!("Invalid option-price subscribe frame: {}", e);
+                let _ = socket.send(Message::Text(format!("{{\"error\":\"invalid subscribe frame: {}\"}}", e))).await;
+                return;
+            }
+        },
+        _ => return,
+    };
+
+    let mut interval = tokio::time::interval(OPTION_PRICE_STREAM_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        let Some(rate) = state.rate_feed.latest_rate() else {
+            continue;
+        };
+
+        let request = OptionPriceRequest {
+            option_type: subscribe_request.option_type.clone(),
+            spot_price: rate.spot_price,
+            strike_price: subscribe_request.strike_price,
+            time_to_expiry: subscribe_request.time_to_expiry,
+            risk_free_rate: subscribe_request.risk_free_rate,
+            volatility: subscribe_request.volatility,
+            dividend_yield: subscribe_request.dividend_yield,
+        };
+
+        let response = match state.math_service.calculate_option_price(request).await {
+            Ok(response) => response,
+            Err(e) => {
+                error!("Failed to recompute streamed option price: {}", e);
+                continue;
+            }
+        };
+
+        let Ok(payload) = serde_json::to_string(&response) else {
+            continue;
+        };
+        if socket.send(Message::Text(payload)).await.is_err() {
+            break;
+        }
+    }
+}
+
 async fn calculate_arbitrage_handler(
     State(state): State<AppState>,
     Json(request): Json<ArbitrageRequest>,
 ) -> Result<Json<ArbitrageResponse>, StatusCode> {
     match state.math_service.calculate_arbitrage_opportunity(request).await {
-        Ok(response) => Ok(Json(response)),
+        Ok(response) => {
+            if let Some(prio_fee_data) = &response.prio_fee_data {
+                state.metrics.update_gas_cost_percentiles(prio_fee_data);
+            }
+            Ok(Json(response))
+        }
         Err(e) => {
             error!("Failed to calculate arbitrage: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
@@ -130,6 +249,17 @@ async fn calculate_arbitrage_handler(
     }
 }
 
+/// Lets an upstream data-aggregation service (which sees real transaction
+/// fees as they land) feed samples into the arbitrage handler's live gas-fee
+/// estimate, instead of it being permanently stuck on its fallback value.
+async fn observe_gas_fees_handler(
+    State(state): State<AppState>,
+    Json(samples): Json<Vec<f64>>,
+) -> StatusCode {
+    state.math_service.observe_gas_fee_samples(&samples);
+    StatusCode::NO_CONTENT
+}
+
 async fn optimize_portfolio_handler(
     State(state): State<AppState>,
     Json(request): Json<PortfolioOptimizationRequest>,
@@ -169,6 +299,17 @@ async fn calculate_yield_farming_handler(
     }
 }
 
+/// Accepts either a single `MathComputationTask` or an array of them and
+/// runs them through `MathComputingService::compute_batch`, so the Kafka
+/// `batch-signing-requests`-style bulk submission path is also reachable
+/// over HTTP instead of only one calculation per request.
+async fn calculate_batch_handler(
+    State(state): State<AppState>,
+    Json(tasks): Json<OneOrVec<MathComputationTask>>,
+) -> Json<Vec<ComputationResult>> {
+    Json(state.math_service.compute_batch(tasks.into_vec()).await)
+}
+
 async fn calculate_impermanent_loss_handler(
     State(state): State<AppState>,
     Json(request): Json<ImpermanentLossRequest>,
@@ -1,23 +1,39 @@
 use anyhow::Result;
 use chrono::Utc;
 use log::{error, info};
+use std::sync::Arc;
 use std::time::Instant;
 
 use crate::calculations::FinancialCalculations;
+use crate::fee_estimator::{CuPercentileEmaProvider, PriorityFeeProvider};
 use crate::kafka_consumer::KafkaConsumerService;
+use crate::metrics::MathComputingMetrics;
 use crate::models::*;
 
+/// Returned by `fee_provider` when it has no fresh-enough live estimate yet
+/// (e.g. before `observe_gas_fee_samples` has been called).
+const DEFAULT_GAS_COST_FALLBACK: f64 = 0.0005;
+
 #[derive(Clone)]
 pub struct MathComputingService {
     kafka_consumer: KafkaConsumerService,
+    metrics: Arc<MathComputingMetrics>,
+    /// Supplies a live gas-cost estimate for `calculate_arbitrage_opportunity`
+    /// when a request omits `gas_costs`.
+    fee_provider: Arc<dyn PriorityFeeProvider>,
 }
 
 impl MathComputingService {
-    pub async fn new(kafka_brokers: &str) -> Result<Self> {
-        let kafka_consumer = KafkaConsumerService::new(kafka_brokers, "math-computing-group")?;
+    pub async fn new(kafka_brokers: &str, metrics: Arc<MathComputingMetrics>) -> Result<Self> {
+        let kafka_consumer = KafkaConsumerService::new(kafka_brokers, "math-computing-group", 5000, "all")?
+            .with_metrics(metrics.clone());
+
+        let fee_provider = Arc::new(CuPercentileEmaProvider::new(75.0, DEFAULT_GAS_COST_FALLBACK));
 
         Ok(Self {
             kafka_consumer,
+            metrics,
+            fee_provider,
         })
     }
 
@@ -72,22 +88,36 @@ impl MathComputingService {
 
     pub async fn calculate_arbitrage_opportunity(&self, request: ArbitrageRequest) -> Result<ArbitrageResponse> {
         let start_time = Instant::now();
-        
+
         let mut result = FinancialCalculations::calculate_arbitrage(
             &request.exchanges,
             request.amount,
             request.max_slippage,
         )?;
 
+        // A request that omits `gas_costs` gets a single live estimate from
+        // `fee_provider` rather than being treated as zero-cost; one sample
+        // means `calculate_prio_fee_percentiles` correctly reports no
+        // distribution (it needs at least two).
+        let gas_costs = request.gas_costs.unwrap_or_else(|| vec![self.fee_provider.estimate_gas_cost()]);
+
         // Subtract gas costs from net profit
-        let total_gas_cost: f64 = request.gas_costs.iter().sum();
+        let total_gas_cost: f64 = gas_costs.iter().sum();
         result.estimated_gas_cost = total_gas_cost;
         result.net_profit = result.profit_amount - total_gas_cost;
+        result.prio_fee_data = FinancialCalculations::calculate_prio_fee_percentiles(&gas_costs);
         result.calculation_time_ms = start_time.elapsed().as_millis() as u64;
 
         Ok(result)
     }
 
+    /// Feeds a fresh batch of observed transaction fees into `fee_provider`,
+    /// so the EMA it serves to `calculate_arbitrage_opportunity` tracks
+    /// current chain conditions instead of going stale.
+    pub fn observe_gas_fee_samples(&self, samples: &[f64]) {
+        self.fee_provider.observe_batch(samples);
+    }
+
     pub async fn optimize_portfolio(&self, request: PortfolioOptimizationRequest) -> Result<PortfolioOptimizationResponse> {
         let start_time = Instant::now();
         
@@ -142,13 +172,32 @@ impl MathComputingService {
             .map(|window| (window[1] - window[0]) / window[0])
             .collect();
 
-        let value_at_risk = FinancialCalculations::calculate_var(&returns, request.confidence_level)?;
-        let conditional_var = FinancialCalculations::calculate_cvar(&returns, request.confidence_level)?;
         let max_drawdown = FinancialCalculations::calculate_max_drawdown(&request.portfolio_values);
         let volatility = FinancialCalculations::calculate_volatility(&returns);
 
         // Calculate skewness and kurtosis
         let mean_return = returns.iter().sum::<f64>() / returns.len() as f64;
+
+        let value_at_risk = match request.var_method {
+            Some(VarMethod::Parametric) => FinancialCalculations::calculate_var_parametric(
+                mean_return,
+                volatility,
+                request.confidence_level,
+                request.time_horizon as f64,
+            )?,
+            Some(VarMethod::MonteCarlo) => FinancialCalculations::calculate_var_monte_carlo(
+                *request.portfolio_values.last().unwrap(),
+                mean_return,
+                volatility,
+                request.time_horizon as f64,
+                10_000,
+                request.confidence_level,
+            )?,
+            Some(VarMethod::Historical) | None => {
+                FinancialCalculations::calculate_var(&returns, request.confidence_level)?
+            }
+        };
+        let conditional_var = FinancialCalculations::calculate_cvar(&returns, request.confidence_level)?;
         let variance = returns.iter().map(|r| (r - mean_return).powi(2)).sum::<f64>() / returns.len() as f64;
         let std_dev = variance.sqrt();
 
@@ -164,6 +213,45 @@ impl MathComputingService {
             0.0
         };
 
+        let (beta, treynor_ratio, tracking_error) = match &request.market_values {
+            Some(market_values) => {
+                let market_returns: Vec<f64> = market_values
+                    .windows(2)
+                    .map(|window| (window[1] - window[0]) / window[0])
+                    .collect();
+
+                if market_returns.len() != returns.len() {
+                    return Err(anyhow::anyhow!(
+                        "market_values must align with portfolio_values after differencing: got {} asset returns and {} market returns",
+                        returns.len(),
+                        market_returns.len()
+                    ));
+                }
+
+                let mean_market_return = market_returns.iter().sum::<f64>() / market_returns.len() as f64;
+                let covariance = returns.iter().zip(market_returns.iter())
+                    .map(|(r, m)| (r - mean_return) * (m - mean_market_return))
+                    .sum::<f64>() / returns.len() as f64;
+                let market_variance = market_returns.iter()
+                    .map(|m| (m - mean_market_return).powi(2))
+                    .sum::<f64>() / market_returns.len() as f64;
+
+                let beta = covariance / market_variance;
+                let treynor_ratio = mean_return / beta;
+                let tracking_error_series: Vec<f64> = returns.iter().zip(market_returns.iter())
+                    .map(|(r, m)| r - m)
+                    .collect();
+                let mean_tracking_diff = tracking_error_series.iter().sum::<f64>() / tracking_error_series.len() as f64;
+                let tracking_error = (tracking_error_series.iter()
+                    .map(|d| (d - mean_tracking_diff).powi(2))
+                    .sum::<f64>() / tracking_error_series.len() as f64)
+                    .sqrt();
+
+                (Some(beta), Some(treynor_ratio), Some(tracking_error))
+            }
+            None => (None, None, None),
+        };
+
         let calculation_time_ms = start_time.elapsed().as_millis() as u64;
 
         Ok(RiskMetricsResponse {
@@ -173,7 +261,9 @@ impl MathComputingService {
             volatility,
             skewness,
             kurtosis,
-            beta: None, // Would need market data to calculate
+            beta,
+            treynor_ratio,
+            tracking_error,
             calculation_time_ms,
         })
     }
@@ -209,80 +299,154 @@ impl MathComputingService {
         Ok(result)
     }
 
+    /// Runs `tasks` across a worker pool bounded to `num_cpus::get()`
+    /// concurrent computations, draining higher-`TaskPriority` tasks first.
+    /// Each task's success/failure is independent — one failing doesn't
+    /// cancel or fail the rest of the batch.
+    pub async fn compute_batch(&self, tasks: Vec<MathComputationTask>) -> Vec<ComputationResult> {
+        let mut heap: std::collections::BinaryHeap<PrioritizedTask> = tasks
+            .into_iter()
+            .enumerate()
+            .map(|(sequence, task)| PrioritizedTask::new(task, sequence))
+            .collect();
+
+        let worker_slots = Arc::new(tokio::sync::Semaphore::new(num_cpus::get()));
+        let mut handles = Vec::with_capacity(heap.len());
+
+        while let Some(prioritized) = heap.pop() {
+            let permit = worker_slots.clone().acquire_owned().await.unwrap();
+            let service = self.clone();
+            handles.push(tokio::spawn(async move {
+                let _permit = permit;
+                service.process_computation_task(prioritized.task).await
+            }));
+        }
+
+        let mut results = Vec::with_capacity(handles.len());
+        for handle in handles {
+            let result = match handle.await {
+                Ok(Ok(result)) => result,
+                Ok(Err(e)) => Self::error_result(String::new(), e.to_string(), Instant::now()),
+                Err(e) => Self::error_result(String::new(), format!("Task panicked: {}", e), Instant::now()),
+            };
+            results.push(result);
+        }
+
+        results
+    }
+
     async fn process_computation_task(&self, task: MathComputationTask) -> Result<ComputationResult> {
         let start_time = Instant::now();
         info!("Processing computation task: {} of type: {:?}", task.task_id, task.task_type);
 
-        let result = match task.task_type {
-            ComputationTaskType::OptionPricing => {
-                match serde_json::from_value::<OptionPriceRequest>(task.payload) {
-                    Ok(request) => {
-                        match self.calculate_option_price(request).await {
-                            Ok(response) => Some(serde_json::to_value(response)?),
-                            Err(e) => return Ok(ComputationResult {
-                                task_id: task.task_id,
-                                success: false,
-                                result: None,
-                                error: Some(e.to_string()),
-                                computation_time_ms: start_time.elapsed().as_millis() as u64,
-                                processed_at: Utc::now(),
-                            }),
-                        }
-                    }
-                    Err(e) => return Ok(ComputationResult {
-                        task_id: task.task_id,
-                        success: false,
-                        result: None,
-                        error: Some(format!("Failed to parse request: {}", e)),
-                        computation_time_ms: start_time.elapsed().as_millis() as u64,
-                        processed_at: Utc::now(),
-                    }),
-                }
-            }
-            ComputationTaskType::ArbitrageCalculation => {
-                match serde_json::from_value::<ArbitrageRequest>(task.payload) {
-                    Ok(request) => {
-                        match self.calculate_arbitrage_opportunity(request).await {
-                            Ok(response) => Some(serde_json::to_value(response)?),
-                            Err(e) => return Ok(ComputationResult {
-                                task_id: task.task_id,
-                                success: false,
-                                result: None,
-                                error: Some(e.to_string()),
-                                computation_time_ms: start_time.elapsed().as_millis() as u64,
-                                processed_at: Utc::now(),
-                            }),
+        let task_type_label = Self::task_type_label(&task.task_type);
+
+        macro_rules! dispatch {
+            ($request_type:ty, $compute:expr) => {
+                match serde_json::from_value::<$request_type>(task.payload) {
+                    Ok(request) => match $compute(request).await {
+                        Ok(response) => serde_json::to_value(response)?,
+                        Err(e) => {
+                            self.metrics.record_computation_error(task_type_label);
+                            return Ok(Self::error_result(task.task_id, e.to_string(), start_time));
                         }
+                    },
+                    Err(e) => {
+                        self.metrics.record_computation_error(task_type_label);
+                        return Ok(Self::error_result(task.task_id, format!("Failed to parse request: {}", e), start_time));
                     }
-                    Err(e) => return Ok(ComputationResult {
-                        task_id: task.task_id,
-                        success: false,
-                        result: None,
-                        error: Some(format!("Failed to parse request: {}", e)),
-                        computation_time_ms: start_time.elapsed().as_millis() as u64,
-                        processed_at: Utc::now(),
-                    }),
                 }
-            }
-            _ => {
-                return Ok(ComputationResult {
-                    task_id: task.task_id,
-                    success: false,
-                    result: None,
-                    error: Some("Computation type not implemented".to_string()),
-                    computation_time_ms: start_time.elapsed().as_millis() as u64,
-                    processed_at: Utc::now(),
-                });
+            };
+        }
+
+        let result = match task.task_type {
+            ComputationTaskType::OptionPricing => dispatch!(OptionPriceRequest, |r| self.calculate_option_price(r)),
+            ComputationTaskType::ArbitrageCalculation => dispatch!(ArbitrageRequest, |r| self.calculate_arbitrage_opportunity(r)),
+            ComputationTaskType::PortfolioOptimization => dispatch!(PortfolioOptimizationRequest, |r| self.optimize_portfolio(r)),
+            ComputationTaskType::RiskMetrics => dispatch!(RiskMetricsRequest, |r| self.calculate_risk_metrics(r)),
+            ComputationTaskType::YieldFarming => dispatch!(YieldFarmingRequest, |r| self.calculate_yield_farming_returns(r)),
+            ComputationTaskType::ImpermanentLoss => dispatch!(ImpermanentLossRequest, |r| self.calculate_impermanent_loss(r)),
+            ComputationTaskType::CustomCalculation => {
+                self.metrics.record_computation_error(task_type_label);
+                return Ok(Self::error_result(task.task_id, "Computation type not implemented".to_string(), start_time));
             }
         };
 
+        self.metrics.observe_computation_duration(task_type_label, start_time.elapsed().as_secs_f64());
+
         Ok(ComputationResult {
             task_id: task.task_id,
             success: true,
-            result,
+            result: Some(result),
             error: None,
             computation_time_ms: start_time.elapsed().as_millis() as u64,
             processed_at: Utc::now(),
         })
     }
+
+    fn error_result(task_id: String, message: String, start_time: Instant) -> ComputationResult {
+        ComputationResult {
+            task_id,
+            success: false,
+            result: None,
+            error: Some(message),
+            computation_time_ms: start_time.elapsed().as_millis() as u64,
+            processed_at: Utc::now(),
+        }
+    }
+
+    fn task_type_label(task_type: &ComputationTaskType) -> &'static str {
+        match task_type {
+            ComputationTaskType::OptionPricing => "option_pricing",
+            ComputationTaskType::ArbitrageCalculation => "arbitrage_calculation",
+            ComputationTaskType::PortfolioOptimization => "portfolio_optimization",
+            ComputationTaskType::RiskMetrics => "risk_metrics",
+            ComputationTaskType::YieldFarming => "yield_farming",
+            ComputationTaskType::ImpermanentLoss => "impermanent_loss",
+            ComputationTaskType::CustomCalculation => "custom_calculation",
+        }
+    }
+}
+
+/// Orders `compute_batch`'s `BinaryHeap` so `pop()` yields the highest
+/// `TaskPriority` first, and — within the same priority — the task
+/// submitted earliest (lowest `sequence`), preserving FIFO order for ties.
+struct PrioritizedTask {
+    priority_rank: u8,
+    sequence: usize,
+    task: MathComputationTask,
+}
+
+impl PrioritizedTask {
+    fn new(task: MathComputationTask, sequence: usize) -> Self {
+        let priority_rank = match task.priority {
+            TaskPriority::Critical => 3,
+            TaskPriority::High => 2,
+            TaskPriority::Normal => 1,
+            TaskPriority::Low => 0,
+        };
+        Self { priority_rank, sequence, task }
+    }
+}
+
+impl PartialEq for PrioritizedTask {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority_rank == other.priority_rank && self.sequence == other.sequence
+    }
+}
+
+impl Eq for PrioritizedTask {}
+
+impl PartialOrd for PrioritizedTask {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PrioritizedTask {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.priority_rank
+            .cmp(&other.priority_rank)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
 }
@@ -1,7 +1,97 @@
-use std::sync::Arc;
-use prometheus::{Registry, Counter, Gauge, Histogram, Opts, HistogramOpts, CounterVec};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use prometheus::{Registry, Counter, Gauge, GaugeVec, Histogram, HistogramVec, Opts, HistogramOpts, CounterVec};
 use std::collections::HashMap;
 
+use crate::models::PrioFeeData;
+
+/// Flush a calculation's buffered samples into the real collectors once
+/// this many have accumulated, even if `BUFFER_FLUSH_INTERVAL` hasn't
+/// elapsed yet — bounds memory under a sudden throughput spike.
+const BUFFER_FLUSH_THRESHOLD: usize = 500;
+
+/// Otherwise, flush on this cadence so a quiet period doesn't leave samples
+/// sitting in the buffer indefinitely.
+const BUFFER_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Identifies one buffered counter series — the metric plus its label
+/// values — so repeated increments of the same series coalesce into a
+/// single delta instead of each separately touching the real `CounterVec`
+/// (and its internal locking) on the calculation hot path.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum CounterKey {
+    Calculations { calculation_type: String },
+    CalculationErrors { calculation_type: String, error_type: String },
+    ComputationErrors { task_type: String },
+    DlqInvalidMessages,
+    DlqRetries,
+}
+
+/// Identifies one buffered histogram series, analogous to `CounterKey`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum HistogramKey {
+    CalculationDuration,
+    ComputationDuration { task_type: String },
+}
+
+#[derive(Default)]
+struct MetricsBufferState {
+    counters: HashMap<CounterKey, u64>,
+    histograms: HashMap<HistogramKey, Vec<f64>>,
+    buffered_samples: usize,
+}
+
+/// Batches counter increments and histogram observations off the
+/// calculation hot path, modeled on Arroyo's `metrics_buffer.rs`: samples
+/// accumulate in a local `HashMap` keyed by series instead of touching a
+/// `CounterVec`/`Histogram` directly on every calculation, and are flushed
+/// into the real Prometheus collectors either every `BUFFER_FLUSH_INTERVAL`
+/// or once `BUFFER_FLUSH_THRESHOLD` samples have accumulated, whichever
+/// comes first.
+struct MetricsBuffer {
+    state: Mutex<MetricsBufferState>,
+    last_flush: Mutex<Instant>,
+}
+
+impl MetricsBuffer {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(MetricsBufferState::default()),
+            last_flush: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Buffers one increment for `key`; returns `true` once the threshold
+    /// has been reached and the caller should flush.
+    fn record_counter(&self, key: CounterKey) -> bool {
+        let mut state = self.state.lock().unwrap();
+        *state.counters.entry(key).or_insert(0) += 1;
+        state.buffered_samples += 1;
+        state.buffered_samples >= BUFFER_FLUSH_THRESHOLD
+    }
+
+    /// Buffers one observation for `key`; returns `true` once the threshold
+    /// has been reached and the caller should flush.
+    fn record_histogram(&self, key: HistogramKey, value: f64) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.histograms.entry(key).or_default().push(value);
+        state.buffered_samples += 1;
+        state.buffered_samples >= BUFFER_FLUSH_THRESHOLD
+    }
+
+    fn due_for_interval_flush(&self) -> bool {
+        self.last_flush.lock().unwrap().elapsed() >= BUFFER_FLUSH_INTERVAL
+    }
+
+    /// Drains every buffered sample, resetting the flush timer.
+    fn take(&self) -> (HashMap<CounterKey, u64>, HashMap<HistogramKey, Vec<f64>>) {
+        let mut state = self.state.lock().unwrap();
+        *self.last_flush.lock().unwrap() = Instant::now();
+        state.buffered_samples = 0;
+        (std::mem::take(&mut state.counters), std::mem::take(&mut state.histograms))
+    }
+}
+
 pub struct MathComputingMetrics {
     pub calculations_total: CounterVec,
     pub calculation_duration: Histogram,
@@ -9,6 +99,36 @@ pub struct MathComputingMetrics {
     pub active_calculations: Gauge,
     pub memory_usage_bytes: Gauge,
     pub cpu_usage_percent: Gauge,
+    /// Latest gas-cost/priority-fee percentile, labeled by percentile
+    /// ("min", "max", "median", "p75", "p90", "p95") rather than tracked as
+    /// a `Histogram`, since a histogram's bucket boundaries can't expose an
+    /// exact quantile — operators alert on this when p95 fees erode
+    /// arbitrage margins.
+    pub gas_cost_percentiles: GaugeVec,
+    /// Per-`ComputationTaskType` breakdown of the Kafka consumer workload,
+    /// labeled by `task_type` — complements the flat `calculation_duration`
+    /// histogram, which doesn't distinguish computation kinds.
+    pub computation_duration_seconds: HistogramVec,
+    pub computation_errors_total: CounterVec,
+    /// Messages the Kafka consumer's DLQ subsystem couldn't even deserialize
+    /// into a `MathComputationTask`, forwarded straight to the dead-letter
+    /// topic without ever reaching a handler.
+    pub dlq_invalid_messages_total: Counter,
+    /// Handler-retry attempts recorded by `KafkaConsumerService::start_consuming`
+    /// before a task either succeeds or exhausts `max_retries` and is
+    /// dead-lettered — operators alert on this climbing as a leading
+    /// indicator before the dead-letter rate itself trips.
+    pub dlq_retries_total: Counter,
+    /// Age, in seconds, of the spot price cached by `RateFeed` — `0` would
+    /// mean a tick just landed; a climbing value means the WS connection to
+    /// the exchange has dropped and `/subscribe/option-price` is serving
+    /// Greeks against a stale price while reconnection backs off.
+    pub rate_feed_staleness_seconds: Gauge,
+    /// Off-hot-path staging area for `calculations_total`, `calculation_duration`,
+    /// `calculation_errors_total`, `computation_duration_seconds`,
+    /// `computation_errors_total`, `dlq_invalid_messages_total`, and
+    /// `dlq_retries_total` — see `MetricsBuffer`.
+    buffer: MetricsBuffer,
 }
 
 impl MathComputingMetrics {
@@ -45,6 +165,39 @@ impl MathComputingMetrics {
             "CPU usage percentage"
         ).unwrap();
 
+        let gas_cost_percentiles = GaugeVec::new(
+            Opts::new("math_arbitrage_gas_cost_percentile", "Latest gas-cost/priority-fee percentile observed in an arbitrage request"),
+            &["percentile"]
+        ).unwrap();
+
+        let computation_duration_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "math_computation_duration_seconds",
+                "Duration of a Kafka-consumed computation task, by task type"
+            ).buckets(vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0]),
+            &["task_type"]
+        ).unwrap();
+
+        let computation_errors_total = CounterVec::new(
+            Opts::new("math_computation_errors_total", "Total number of Kafka-consumed computation task errors, by task type"),
+            &["task_type"]
+        ).unwrap();
+
+        let dlq_invalid_messages_total = Counter::new(
+            "math_dlq_invalid_messages_total",
+            "Total number of Kafka messages that failed to deserialize into a computation task and were dead-lettered"
+        ).unwrap();
+
+        let dlq_retries_total = Counter::new(
+            "math_dlq_retries_total",
+            "Total number of computation task handler retries before success or dead-lettering"
+        ).unwrap();
+
+        let rate_feed_staleness_seconds = Gauge::new(
+            "math_rate_feed_staleness_seconds",
+            "Age in seconds of the spot price cached from the live rate feed WebSocket"
+        ).unwrap();
+
         Self {
             calculations_total,
             calculation_duration,
@@ -52,19 +205,34 @@ impl MathComputingMetrics {
             active_calculations,
             memory_usage_bytes,
             cpu_usage_percent,
+            gas_cost_percentiles,
+            computation_duration_seconds,
+            computation_errors_total,
+            dlq_invalid_messages_total,
+            dlq_retries_total,
+            rate_feed_staleness_seconds,
+            buffer: MetricsBuffer::new(),
         }
     }
 
+    /// Buffers a `calculations_total` increment rather than touching the
+    /// `CounterVec` directly, so a burst of calculations doesn't contend on
+    /// its internal label-map lock; `flush_buffer` applies it later.
     pub fn record_calculation(&self, calculation_type: &str) {
-        self.calculations_total.with_label_values(&[calculation_type]).inc();
+        self.maybe_flush(self.buffer.record_counter(CounterKey::Calculations {
+            calculation_type: calculation_type.to_string(),
+        }));
     }
 
     pub fn record_calculation_duration(&self, duration: f64) {
-        self.calculation_duration.observe(duration);
+        self.maybe_flush(self.buffer.record_histogram(HistogramKey::CalculationDuration, duration));
     }
 
     pub fn record_calculation_error(&self, calculation_type: &str, error_type: &str) {
-        self.calculation_errors_total.with_label_values(&[calculation_type, error_type]).inc();
+        self.maybe_flush(self.buffer.record_counter(CounterKey::CalculationErrors {
+            calculation_type: calculation_type.to_string(),
+            error_type: error_type.to_string(),
+        }));
     }
 
     pub fn set_active_calculations(&self, count: f64) {
@@ -78,6 +246,99 @@ impl MathComputingMetrics {
     pub fn update_cpu_usage(&self, percent: f64) {
         self.cpu_usage_percent.set(percent);
     }
+
+    pub fn update_gas_cost_percentiles(&self, prio_fee_data: &PrioFeeData) {
+        self.gas_cost_percentiles.with_label_values(&["min"]).set(prio_fee_data.min);
+        self.gas_cost_percentiles.with_label_values(&["max"]).set(prio_fee_data.max);
+        self.gas_cost_percentiles.with_label_values(&["median"]).set(prio_fee_data.median);
+        self.gas_cost_percentiles.with_label_values(&["p75"]).set(prio_fee_data.p75);
+        self.gas_cost_percentiles.with_label_values(&["p90"]).set(prio_fee_data.p90);
+        self.gas_cost_percentiles.with_label_values(&["p95"]).set(prio_fee_data.p95);
+    }
+
+    pub fn observe_computation_duration(&self, task_type: &str, duration: f64) {
+        self.maybe_flush(self.buffer.record_histogram(
+            HistogramKey::ComputationDuration { task_type: task_type.to_string() },
+            duration,
+        ));
+    }
+
+    pub fn record_computation_error(&self, task_type: &str) {
+        self.maybe_flush(self.buffer.record_counter(CounterKey::ComputationErrors {
+            task_type: task_type.to_string(),
+        }));
+    }
+
+    pub fn record_dlq_invalid_message(&self) {
+        self.maybe_flush(self.buffer.record_counter(CounterKey::DlqInvalidMessages));
+    }
+
+    pub fn record_dlq_retry(&self) {
+        self.maybe_flush(self.buffer.record_counter(CounterKey::DlqRetries));
+    }
+
+    pub fn set_rate_feed_staleness(&self, seconds: f64) {
+        self.rate_feed_staleness_seconds.set(seconds);
+    }
+
+    fn maybe_flush(&self, threshold_hit: bool) {
+        if threshold_hit {
+            self.flush_buffer();
+        }
+    }
+
+    /// Applies every sample buffered since the last flush to the real
+    /// Prometheus collectors. Called on a timer from `main` (so a quiet
+    /// period still surfaces buffered samples within `BUFFER_FLUSH_INTERVAL`)
+    /// and inline whenever a hot-path record call pushes the buffer past
+    /// `BUFFER_FLUSH_THRESHOLD`.
+    pub fn flush_buffer(&self) {
+        let (counters, histograms) = self.buffer.take();
+
+        for (key, count) in counters {
+            match key {
+                CounterKey::Calculations { calculation_type } => {
+                    self.calculations_total.with_label_values(&[&calculation_type]).inc_by(count as f64);
+                }
+                CounterKey::CalculationErrors { calculation_type, error_type } => {
+                    self.calculation_errors_total
+                        .with_label_values(&[&calculation_type, &error_type])
+                        .inc_by(count as f64);
+                }
+                CounterKey::ComputationErrors { task_type } => {
+                    self.computation_errors_total.with_label_values(&[&task_type]).inc_by(count as f64);
+                }
+                CounterKey::DlqInvalidMessages => {
+                    self.dlq_invalid_messages_total.inc_by(count as f64);
+                }
+                CounterKey::DlqRetries => {
+                    self.dlq_retries_total.inc_by(count as f64);
+                }
+            }
+        }
+
+        for (key, values) in histograms {
+            match key {
+                HistogramKey::CalculationDuration => {
+                    for value in values {
+                        self.calculation_duration.observe(value);
+                    }
+                }
+                HistogramKey::ComputationDuration { task_type } => {
+                    let histogram = self.computation_duration_seconds.with_label_values(&[&task_type]);
+                    for value in values {
+                        histogram.observe(value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether `BUFFER_FLUSH_INTERVAL` has elapsed since the last flush;
+    /// polled by the periodic background task in `main`.
+    pub fn buffer_due_for_flush(&self) -> bool {
+        self.buffer.due_for_interval_flush()
+    }
 }
 
 pub fn create_metrics_registry() -> Registry {
@@ -92,5 +353,11 @@ pub fn register_math_metrics(registry: &Registry, metrics: &MathComputingMetrics
     registry.register(Box::new(metrics.active_calculations.clone()))?;
     registry.register(Box::new(metrics.memory_usage_bytes.clone()))?;
     registry.register(Box::new(metrics.cpu_usage_percent.clone()))?;
+    registry.register(Box::new(metrics.gas_cost_percentiles.clone()))?;
+    registry.register(Box::new(metrics.computation_duration_seconds.clone()))?;
+    registry.register(Box::new(metrics.computation_errors_total.clone()))?;
+    registry.register(Box::new(metrics.dlq_invalid_messages_total.clone()))?;
+    registry.register(Box::new(metrics.dlq_retries_total.clone()))?;
+    registry.register(Box::new(metrics.rate_feed_staleness_seconds.clone()))?;
     Ok(())
 }
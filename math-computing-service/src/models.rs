@@ -18,6 +18,19 @@ pub enum OptionType {
     Put,
 }
 
+/// Subscribe frame for `/subscribe/option-price`: an `OptionPriceRequest`
+/// with `spot_price` omitted, since the stream supplies it continuously
+/// from `RateFeed` instead of the caller providing one static value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptionPriceSubscribeRequest {
+    pub option_type: OptionType,
+    pub strike_price: f64,
+    pub time_to_expiry: f64,
+    pub risk_free_rate: f64,
+    pub volatility: f64,
+    pub dividend_yield: Option<f64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptionPriceResponse {
     pub price: f64,
@@ -35,7 +48,12 @@ pub struct ArbitrageRequest {
     pub token_pair: TokenPair,
     pub amount: f64,
     pub max_slippage: f64,
-    pub gas_costs: Vec<f64>,
+    /// Static fee samples, if the caller already has a fresh one. Omit (or
+    /// pass `null`) to have `MathComputingService` substitute a live
+    /// `PriorityFeeProvider` estimate instead, since a sample from the
+    /// caller is liable to already be stale by the time it's used.
+    #[serde(default)]
+    pub gas_costs: Option<Vec<f64>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +62,18 @@ pub struct ExchangeData {
     pub price: f64,
     pub liquidity: f64,
     pub fee: f64,
+    /// Real depth for this exchange, if the caller has it. When present,
+    /// `TradeSimulator` walks these levels instead of the quadratic
+    /// `amount / liquidity` slippage estimate.
+    pub order_book: Option<OrderBook>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBook {
+    /// Ask levels as `(price, size)`, ascending from the best (lowest) ask.
+    pub asks: Vec<(f64, f64)>,
+    /// Bid levels as `(price, size)`, descending from the best (highest) bid.
+    pub bids: Vec<(f64, f64)>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,9 +90,25 @@ pub struct ArbitrageResponse {
     pub optimal_path: Vec<ArbitrageStep>,
     pub estimated_gas_cost: f64,
     pub net_profit: f64,
+    /// Distributional view over `ArbitrageRequest.gas_costs`, alongside the
+    /// summed `estimated_gas_cost`. `None` when fewer than two samples were
+    /// supplied, since percentiles aren't meaningful over 0 or 1 points.
+    pub prio_fee_data: Option<PrioFeeData>,
     pub calculation_time_ms: u64,
 }
 
+/// Percentile summary over observed gas-cost/priority-fee samples, so a
+/// caller can see the shape of the distribution instead of just its sum.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrioFeeData {
+    pub min: f64,
+    pub max: f64,
+    pub median: f64,
+    pub p75: f64,
+    pub p90: f64,
+    pub p95: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArbitrageStep {
     pub exchange: String,
@@ -71,6 +117,14 @@ pub struct ArbitrageStep {
     pub price: f64,
 }
 
+/// One profitable multi-hop conversion loop found by
+/// `FinancialCalculations::find_arbitrage_cycles`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArbitragePath {
+    pub steps: Vec<ArbitrageStep>,
+    pub profit_percentage: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PortfolioOptimizationRequest {
     pub assets: Vec<AssetData>,
@@ -111,6 +165,26 @@ pub struct RiskMetricsRequest {
     pub portfolio_values: Vec<f64>,
     pub confidence_level: f64,
     pub time_horizon: u32, // in days
+    /// Which Value-at-Risk estimator to use; defaults to `Historical` when
+    /// absent.
+    pub var_method: Option<VarMethod>,
+    /// Benchmark value series, same cadence as `portfolio_values`. When
+    /// present, `calculate_risk_metrics` derives `beta`, `treynor_ratio`,
+    /// and `tracking_error` against it; when absent those stay `None`.
+    pub market_values: Option<Vec<f64>>,
+}
+
+/// Selects the Value-at-Risk estimator `calculate_risk_metrics` uses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum VarMethod {
+    /// Empirical quantile of the observed return sample.
+    Historical,
+    /// Closed-form normal approximation from a return sample's mean and
+    /// volatility; holds up better than `Historical` on short samples.
+    Parametric,
+    /// Empirical quantile of simulated terminal P&L under geometric
+    /// Brownian motion.
+    MonteCarlo,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -122,6 +196,13 @@ pub struct RiskMetricsResponse {
     pub skewness: f64,
     pub kurtosis: f64,
     pub beta: Option<f64>,
+    /// Mean return over beta, i.e. reward per unit of market risk, with the
+    /// risk-free rate taken as zero (no risk-free rate is subtracted).
+    /// `Some` only when `market_values` was supplied.
+    pub treynor_ratio: Option<f64>,
+    /// Std-dev of `r_asset - r_market`. `Some` only when `market_values`
+    /// was supplied.
+    pub tracking_error: Option<f64>,
     pub calculation_time_ms: u64,
 }
 
@@ -168,6 +249,42 @@ pub struct ImpermanentLossResponse {
     pub calculation_time_ms: u64,
 }
 
+/// Response for `FinancialCalculations::calculate_concentrated_impermanent_loss`,
+/// which models a bounded-range (Uniswap V3 / Raydium CLMM) position instead
+/// of `ImpermanentLossResponse`'s full-range constant-product assumption.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConcentratedImpermanentLossResponse {
+    pub impermanent_loss_percentage: f64,
+    pub impermanent_loss_amount: f64,
+    pub current_pool_value: f64,
+    pub hodl_value: f64,
+    /// True once `current_price` has crossed outside `[price_lower,
+    /// price_upper]`, meaning the position now holds only one of the two
+    /// tokens.
+    pub range_exit: bool,
+    pub calculation_time_ms: u64,
+}
+
+/// Request envelope for `POST /calculate/batch`: accepts either a single
+/// `MathComputationTask` or an array of them, so a caller submitting one
+/// task doesn't have to wrap it in a single-element array. Untagged, as in
+/// the unki refactor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OneOrVec<T> {
+    One(T),
+    Vec(Vec<T>),
+}
+
+impl<T> OneOrVec<T> {
+    pub fn into_vec(self) -> Vec<T> {
+        match self {
+            OneOrVec::One(item) => vec![item],
+            OneOrVec::Vec(items) => items,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MathComputationTask {
     pub task_id: String,
@@ -0,0 +1,128 @@
+//! Live spot-price cache backing `/subscribe/option-price`, so a streamed
+//! Greeks recalculation doesn't have to wait on a request/response round
+//! trip to an exchange for every tick. Modeled on ethers-rs's `eth_subscribe`
+//! pubsub and the Kraken WS ticker feed: a background task holds a single
+//! `tokio-tungstenite` connection to a configurable exchange endpoint,
+//! parses ticker messages as they arrive, and caches the latest price in an
+//! `RwLock` that `LatestRate::latest_rate` reads without blocking on the
+//! network.
+//!
+//! This crate has no `ErrorHandler` of its own (that lives in
+//! blockchain-sync-service, a separate crate/binary), so reconnection here
+//! is a small local equivalent: an unbounded loop with a capped exponential
+//! backoff between attempts, since — unlike a bounded retry — a market-data
+//! feed should keep trying to reconnect for as long as the service runs.
+
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use futures_util::StreamExt;
+use log::{debug, error, warn};
+use serde::Deserialize;
+use tokio_tungstenite::tungstenite::Message;
+
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// A cached spot-price observation.
+#[derive(Debug, Clone, Copy)]
+pub struct Rate {
+    pub spot_price: f64,
+    pub observed_at: Instant,
+}
+
+/// Anything that can report the most recently observed spot price, so
+/// `/subscribe/option-price` doesn't need to know whether it's backed by a
+/// live `RateFeed` or (in tests) a canned value.
+pub trait LatestRate: Send + Sync {
+    fn latest_rate(&self) -> Option<Rate>;
+}
+
+/// The subset of a ticker message this feed understands. Exchanges vary in
+/// field naming, but `price`/`last_price` covers the common cases (Kraken's
+/// `c[0]` last-trade price, Binance's `c`, Coinbase's `price`) once the raw
+/// frame has been normalized upstream into this shape.
+#[derive(Debug, Deserialize)]
+struct TickerMessage {
+    #[serde(alias = "last_price", alias = "c")]
+    price: f64,
+}
+
+/// Holds the latest spot price read off a WebSocket ticker feed.
+pub struct RateFeed {
+    endpoint: String,
+    latest: RwLock<Option<Rate>>,
+}
+
+impl RateFeed {
+    /// Spawns the background connection task and returns the shared handle
+    /// immediately; `latest_rate` returns `None` until the first ticker
+    /// message arrives.
+    pub fn spawn(endpoint: String) -> Arc<Self> {
+        let feed = Arc::new(Self {
+            endpoint,
+            latest: RwLock::new(None),
+        });
+
+        let task_feed = feed.clone();
+        tokio::spawn(async move {
+            task_feed.run().await;
+        });
+
+        feed
+    }
+
+    /// Staleness of the cached rate, if one has ever been observed. Feeds
+    /// `MathComputingMetrics::set_rate_feed_staleness`.
+    pub fn staleness(&self) -> Option<Duration> {
+        self.latest.read().unwrap().map(|rate| rate.observed_at.elapsed())
+    }
+
+    async fn run(self: Arc<Self>) {
+        let mut reconnect_delay = INITIAL_RECONNECT_DELAY;
+
+        loop {
+            match tokio_tungstenite::connect_async(&self.endpoint).await {
+                Ok((stream, _response)) => {
+                    debug!("Connected to rate feed at {}", self.endpoint);
+                    reconnect_delay = INITIAL_RECONNECT_DELAY;
+                    self.consume(stream).await;
+                    warn!("Rate feed connection to {} closed, reconnecting", self.endpoint);
+                }
+                Err(e) => {
+                    error!("Failed to connect to rate feed at {}: {}", self.endpoint, e);
+                }
+            }
+
+            tokio::time::sleep(reconnect_delay).await;
+            reconnect_delay = (reconnect_delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    }
+
+    async fn consume<S>(&self, mut stream: tokio_tungstenite::WebSocketStream<S>)
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+    {
+        while let Some(message) = stream.next().await {
+            let text = match message {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_)) | Err(_) => break,
+                Ok(_) => continue,
+            };
+
+            match serde_json::from_str::<TickerMessage>(&text) {
+                Ok(ticker) => {
+                    let mut latest = self.latest.write().unwrap();
+                    *latest = Some(Rate { spot_price: ticker.price, observed_at: Instant::now() });
+                }
+                Err(e) => debug!("Ignoring unparseable rate feed message: {}", e),
+            }
+        }
+    }
+}
+
+impl LatestRate for RateFeed {
+    fn latest_rate(&self) -> Option<Rate> {
+        *self.latest.read().unwrap()
+    }
+}
@@ -1,5 +1,8 @@
+use std::fs;
 use std::sync::Arc;
-use prometheus::{Registry, Counter, Gauge, Histogram, HistogramOpts};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use prometheus::{Registry, Counter, CounterVec, Gauge, Histogram, HistogramOpts, Opts};
 use axum::{
     extract::State,
     http::StatusCode,
@@ -17,8 +20,13 @@ pub struct AppState {
 
 pub struct ServiceMetrics {
     pub requests_total: Counter,
+    /// Same requests as `requests_total`, broken down by route and response
+    /// status so a caller can tell `/test` errors apart from `/test`
+    /// successes instead of only seeing one aggregate count.
+    pub requests_by_route: CounterVec,
     pub request_duration: Histogram,
     pub memory_usage: Gauge,
+    pub cpu_usage_percent: Gauge,
     pub active_connections: Gauge,
 }
 
@@ -29,6 +37,11 @@ impl ServiceMetrics {
             "Total number of requests"
         ).unwrap();
 
+        let requests_by_route = CounterVec::new(
+            Opts::new("service_requests_by_route_total", "Total number of requests, by route and status"),
+            &["route", "status"]
+        ).unwrap();
+
         let request_duration = Histogram::with_opts(
             HistogramOpts::new(
                 "service_request_duration_seconds",
@@ -41,6 +54,11 @@ impl ServiceMetrics {
             "Memory usage in bytes"
         ).unwrap();
 
+        let cpu_usage_percent = Gauge::new(
+            "service_cpu_usage_percent",
+            "CPU usage percentage"
+        ).unwrap();
+
         let active_connections = Gauge::new(
             "service_active_connections",
             "Number of active connections"
@@ -48,13 +66,77 @@ impl ServiceMetrics {
 
         Self {
             requests_total,
+            requests_by_route,
             request_duration,
             memory_usage,
+            cpu_usage_percent,
             active_connections,
         }
     }
 }
 
+struct PreviousCpuSample {
+    cpu_time_secs: f64,
+    wall_clock: Instant,
+}
+
+static PREVIOUS_CPU_SAMPLE: Mutex<Option<PreviousCpuSample>> = Mutex::new(None);
+
+/// Samples this process's own RSS and CPU utilization from procfs. Where
+/// `/proc/self` can't be read (e.g. a non-Linux host) the gauge is left
+/// unchanged, so it stays `0` if no read has ever succeeded.
+fn sample_resource_usage(metrics: &ServiceMetrics) {
+    if let Some(rss_bytes) = read_rss_bytes() {
+        metrics.memory_usage.set(rss_bytes as f64);
+    }
+
+    if let Some(cpu_time_secs) = read_cpu_time_secs() {
+        metrics.cpu_usage_percent.set(compute_cpu_percent(cpu_time_secs));
+    }
+}
+
+/// RSS in bytes, from `/proc/self/statm` (field 2, in pages).
+fn read_rss_bytes() -> Option<u64> {
+    let statm = fs::read_to_string("/proc/self/statm").ok()?;
+    let rss_pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    const PAGE_SIZE_BYTES: u64 = 4096;
+    Some(rss_pages * PAGE_SIZE_BYTES)
+}
+
+/// Total process CPU time (user + system), in seconds, from
+/// `/proc/self/stat` fields 14/15 (utime/stime, in clock ticks).
+fn read_cpu_time_secs() -> Option<f64> {
+    let stat = fs::read_to_string("/proc/self/stat").ok()?;
+    // The comm field is parenthesized and may itself contain spaces, so
+    // split on the closing paren and index the remaining fields from there.
+    let after_comm = stat.rsplit(')').next()?;
+    let fields: Vec<&str> = after_comm.split_whitespace().collect();
+    let utime: f64 = fields.get(11)?.parse().ok()?;
+    let stime: f64 = fields.get(12)?.parse().ok()?;
+    const USER_HZ: f64 = 100.0;
+    Some((utime + stime) / USER_HZ)
+}
+
+/// Computes CPU utilization (as a percentage of one core) over the time
+/// since the last call to this function. Returns `0.0` on the first call,
+/// since there's no prior window to measure against yet.
+fn compute_cpu_percent(cpu_time_secs: f64) -> f64 {
+    let now = Instant::now();
+    let mut previous = PREVIOUS_CPU_SAMPLE.lock().unwrap();
+
+    let percent = match previous.as_ref() {
+        Some(prev) => {
+            let elapsed = now.duration_since(prev.wall_clock).as_secs_f64();
+            let cpu_delta = cpu_time_secs - prev.cpu_time_secs;
+            if elapsed > 0.0 { (cpu_delta / elapsed * 100.0).max(0.0) } else { 0.0 }
+        }
+        None => 0.0,
+    };
+
+    *previous = Some(PreviousCpuSample { cpu_time_secs, wall_clock: now });
+    percent
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let metrics = Arc::new(ServiceMetrics::new());
@@ -63,6 +145,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         metrics: metrics.clone(),
     };
 
+    // Sample real process memory/CPU usage on an interval instead of
+    // reporting the hardcoded placeholder test_handler used to set.
+    let sampled_metrics = metrics.clone();
+    tokio::spawn(async move {
+        loop {
+            sample_resource_usage(&sampled_metrics);
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        }
+    });
+
     // Create HTTP API routes
     let app = Router::new()
         .route("/health", get(health_check))
@@ -93,12 +185,18 @@ async fn metrics_handler(State(state): State<AppState>) -> Result<String, Status
     if let Err(_) = registry.register(Box::new(state.metrics.requests_total.clone())) {
         return Err(StatusCode::INTERNAL_SERVER_ERROR);
     }
+    if let Err(_) = registry.register(Box::new(state.metrics.requests_by_route.clone())) {
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
     if let Err(_) = registry.register(Box::new(state.metrics.request_duration.clone())) {
         return Err(StatusCode::INTERNAL_SERVER_ERROR);
     }
     if let Err(_) = registry.register(Box::new(state.metrics.memory_usage.clone())) {
         return Err(StatusCode::INTERNAL_SERVER_ERROR);
     }
+    if let Err(_) = registry.register(Box::new(state.metrics.cpu_usage_percent.clone())) {
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
     if let Err(_) = registry.register(Box::new(state.metrics.active_connections.clone())) {
         return Err(StatusCode::INTERNAL_SERVER_ERROR);
     }
@@ -114,7 +212,7 @@ async fn metrics_handler(State(state): State<AppState>) -> Result<String, Status
 async fn test_handler(State(state): State<AppState>) -> Json<serde_json::Value> {
     // Simulate some work
     state.metrics.requests_total.inc();
-    state.metrics.memory_usage.set((1024 * 1024 * 10) as f64); // 10MB
+    state.metrics.requests_by_route.with_label_values(&["/test", "success"]).inc();
     state.metrics.active_connections.set(5.0);
     
     let timer = state.metrics.request_duration.start_timer();
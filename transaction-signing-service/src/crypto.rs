@@ -1,4 +1,5 @@
 use anyhow::Result;
+use thiserror::Error;
 use aes_gcm::{
     aead::{Aead, AeadCore, KeyInit, OsRng},
     Aes256Gcm, Nonce,
@@ -7,10 +8,34 @@ use argon2::{Argon2, PasswordHasher, password_hash::{rand_core::RngCore, SaltStr
 use ethereum_types::U256;
 // use rand::Rng;
 use secp256k1::{PublicKey, SecretKey, Secp256k1, Message, All};
+use serde_json::Value;
 use sha3::{Digest, Keccak256};
-use web3::types::{Address, TransactionParameters};
+use web3::types::{AccessList, Address, TransactionParameters};
 use zeroize::Zeroize;
 
+use crate::eip712::{self, Eip712Domain, Eip712Types};
+
+/// A transaction that `CryptoService` refuses to sign or broadcast outright,
+/// distinct from a malformed request — callers can match on this to tag the
+/// rejection with a stable `error_type` rather than parsing error text.
+#[derive(Debug, Error)]
+pub enum ValidationError {
+    /// EIP-3607: the `from` address has deployed code, so it can never have
+    /// produced a valid ECDSA signature itself. Signing anyway would most
+    /// likely be a caller mistake (address collision, stale wallet record).
+    #[error("sender {0:?} has code; refusing to sign (EIP-3607)")]
+    SenderHasCode(Address),
+}
+
+impl ValidationError {
+    /// Stable machine-readable tag for this rejection, for error tracking.
+    pub fn error_type(&self) -> &'static str {
+        match self {
+            ValidationError::SenderHasCode(_) => "sender_has_code",
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct CryptoService {
     secp: Secp256k1<All>,
@@ -29,6 +54,21 @@ impl CryptoService {
         Ok(secret_key)
     }
 
+    /// BIP39: validates `phrase` and derives its 64-byte seed, for HD wallet
+    /// restoration. Delegates to `hdwallet`, which `signing_service` and
+    /// `signer` already use for mnemonic-backed key generation.
+    pub fn mnemonic_to_seed(&self, phrase: &str, passphrase: &str) -> Result<[u8; 64]> {
+        crate::hdwallet::mnemonic_to_seed(phrase, passphrase)
+    }
+
+    /// BIP32/BIP44: walks `path` (e.g. `m/44'/60'/0'/0/0`) from the master
+    /// key derived from `seed`, returning a `SecretKey` usable by
+    /// `private_key_to_address`, `sign_transaction`, and `encrypt_private_key`
+    /// just like a randomly generated one.
+    pub fn derive_key(&self, seed: &[u8], path: &str) -> Result<SecretKey> {
+        crate::hdwallet::derive_private_key(seed, path)
+    }
+
     pub fn private_key_to_address(&self, private_key: &SecretKey) -> Result<Address> {
         let public_key = PublicKey::from_secret_key(&self.secp, private_key);
         let public_key_bytes = public_key.serialize_uncompressed();
@@ -64,6 +104,18 @@ impl CryptoService {
         Ok((signature_bytes.to_vec(), v))
     }
 
+    /// EIP-3607 guard: rejects signing for `address` if `code` (the result
+    /// of an `eth_getCode` call against it) is nonempty, i.e. `address`
+    /// belongs to a contract rather than an EOA. Optional — callers that
+    /// don't have `code` handy (no RPC access, a hardware-wallet address
+    /// lookup) simply skip the check.
+    pub fn validate_sender_is_eoa(&self, address: Address, code: &[u8]) -> Result<()> {
+        if !code.is_empty() {
+            return Err(ValidationError::SenderHasCode(address).into());
+        }
+        Ok(())
+    }
+
     pub fn encode_transaction(&self, transaction: &TransactionParameters, chain_id: u64) -> Result<Vec<u8>> {
         use rlp::RlpStream;
         
@@ -99,6 +151,195 @@ impl CryptoService {
         Ok(stream.out().to_vec())
     }
 
+    /// Signs an EIP-1559 (type-2) transaction. Returns the 64-byte compact
+    /// `(r, s)` signature alongside the raw `y_parity` (`0` or `1`) — unlike
+    /// legacy signing, type-2 envelopes carry `y_parity` directly rather
+    /// than the EIP-155 `v = recovery_id + chain_id*2 + 35` encoding.
+    pub fn sign_eip1559_transaction(
+        &self,
+        private_key: &SecretKey,
+        transaction: &TransactionParameters,
+        chain_id: u64,
+    ) -> Result<(Vec<u8>, u8)> {
+        let encoded = self.encode_eip1559_payload(transaction, chain_id)?;
+        let hash = self.keccak256(&encoded);
+
+        let message = Message::from_digest_slice(&hash)?;
+        let signature = self.secp.sign_ecdsa_recoverable(&message, private_key);
+        let (recovery_id, signature_bytes) = signature.serialize_compact();
+
+        Ok((signature_bytes.to_vec(), recovery_id.to_i32() as u8))
+    }
+
+    /// RLP-encodes the EIP-1559 signing payload: `0x02 || rlp([chain_id,
+    /// nonce, max_priority_fee_per_gas, max_fee_per_gas, gas_limit, to,
+    /// value, data, access_list])`. Per EIP-2718, typed transactions are
+    /// hashed over this full `type || payload` byte string, not just the
+    /// inner RLP list.
+    pub fn encode_eip1559_payload(&self, transaction: &TransactionParameters, chain_id: u64) -> Result<Vec<u8>> {
+        use rlp::RlpStream;
+
+        let mut stream = RlpStream::new();
+        stream.begin_list(9);
+        stream.append(&U256::from(chain_id));
+        stream.append(&U256::from(transaction.nonce.unwrap_or_default().as_u64()));
+        stream.append(&transaction.max_priority_fee_per_gas.unwrap_or_default());
+        stream.append(&transaction.max_fee_per_gas.unwrap_or_default());
+        stream.append(&transaction.gas);
+
+        match transaction.to {
+            Some(to) => stream.append(&to),
+            None => stream.append_empty_data(),
+        };
+
+        stream.append(&transaction.value);
+        stream.append(&transaction.data.0.as_slice());
+        Self::append_access_list(&mut stream, &transaction.access_list);
+
+        let mut payload = vec![0x02u8];
+        payload.extend_from_slice(&stream.out());
+        Ok(payload)
+    }
+
+    /// Signs an EIP-2930 (type-1) transaction. Like type-2, the envelope
+    /// carries `y_parity` directly rather than an EIP-155-adjusted `v`.
+    pub fn sign_eip2930_transaction(
+        &self,
+        private_key: &SecretKey,
+        transaction: &TransactionParameters,
+        chain_id: u64,
+    ) -> Result<(Vec<u8>, u8)> {
+        let encoded = self.encode_eip2930_payload(transaction, chain_id)?;
+        let hash = self.keccak256(&encoded);
+
+        let message = Message::from_digest_slice(&hash)?;
+        let signature = self.secp.sign_ecdsa_recoverable(&message, private_key);
+        let (recovery_id, signature_bytes) = signature.serialize_compact();
+
+        Ok((signature_bytes.to_vec(), recovery_id.to_i32() as u8))
+    }
+
+    /// RLP-encodes the EIP-2930 signing payload: `0x01 || rlp([chain_id,
+    /// nonce, gas_price, gas_limit, to, value, data, access_list])`.
+    pub fn encode_eip2930_payload(&self, transaction: &TransactionParameters, chain_id: u64) -> Result<Vec<u8>> {
+        use rlp::RlpStream;
+
+        let mut stream = RlpStream::new();
+        stream.begin_list(8);
+        stream.append(&U256::from(chain_id));
+        stream.append(&U256::from(transaction.nonce.unwrap_or_default().as_u64()));
+        stream.append(&transaction.gas_price.unwrap_or_default());
+        stream.append(&transaction.gas);
+
+        match transaction.to {
+            Some(to) => stream.append(&to),
+            None => stream.append_empty_data(),
+        };
+
+        stream.append(&transaction.value);
+        stream.append(&transaction.data.0.as_slice());
+        Self::append_access_list(&mut stream, &transaction.access_list);
+
+        let mut payload = vec![0x01u8];
+        payload.extend_from_slice(&stream.out());
+        Ok(payload)
+    }
+
+    /// Appends an optional EIP-2930 access list as `[[address, [storage_key,
+    /// ...]], ...]`, or an empty list when none is set.
+    fn append_access_list(stream: &mut rlp::RlpStream, access_list: &Option<AccessList>) {
+        let entries = access_list.as_deref().unwrap_or_default();
+        stream.begin_list(entries.len());
+        for entry in entries {
+            stream.begin_list(2);
+            stream.append(&entry.address);
+            stream.append_list(&entry.storage_keys);
+        }
+    }
+
+    /// Signs a pre-computed 32-byte digest directly, for message-signing
+    /// flows (EIP-712, `personal_sign`) that compute their own hash rather
+    /// than an RLP-encoded transaction. Returns the compact `(r, s)`
+    /// signature and the recovery byte in `27`/`28` form, matching the
+    /// `eth_sign`/`personal_sign` convention.
+    pub fn sign_digest(&self, private_key: &SecretKey, digest: &[u8; 32]) -> Result<(Vec<u8>, u8)> {
+        let message = Message::from_digest_slice(digest)?;
+        let signature = self.secp.sign_ecdsa_recoverable(&message, private_key);
+        let (recovery_id, signature_bytes) = signature.serialize_compact();
+
+        Ok((signature_bytes.to_vec(), recovery_id.to_i32() as u8 + 27))
+    }
+
+    /// Signs `message` per EIP-191 (`personal_sign`/`eth_sign`): hashes it
+    /// with the `"\x19Ethereum Signed Message:\n" || len || message` prefix
+    /// before signing, so the signature can't be replayed as a raw
+    /// transaction or typed-data digest.
+    pub fn sign_message(&self, private_key: &SecretKey, message: &[u8]) -> Result<(Vec<u8>, u8)> {
+        let hash = Self::eip191_hash(message);
+        self.sign_digest(private_key, &hash)
+    }
+
+    /// Signs an EIP-712 typed-data payload: hashes `message` of type
+    /// `primary_type` under `domain` via [`eip712::digest`], then signs the
+    /// resulting digest like any other pre-computed hash.
+    pub fn sign_typed_data(
+        &self,
+        private_key: &SecretKey,
+        domain: &Eip712Domain,
+        primary_type: &str,
+        types: &Eip712Types,
+        message: &Value,
+    ) -> Result<(Vec<u8>, u8)> {
+        let digest = eip712::digest(domain, primary_type, types, message)?;
+        self.sign_digest(private_key, &digest)
+    }
+
+    /// Recovers the address that produced an EIP-191 `personal_sign`
+    /// signature over `message`. `v` is the `27`/`28` recovery byte
+    /// returned by [`Self::sign_message`]/[`Self::sign_digest`].
+    pub fn recover_message_address(&self, message: &[u8], signature: &[u8], v: u8) -> Result<Address> {
+        let hash = Self::eip191_hash(message);
+        self.recover_address(&hash, signature, v)
+    }
+
+    /// Recovers the address that produced an EIP-712 typed-data signature,
+    /// re-deriving the same digest [`Self::sign_typed_data`] signed.
+    pub fn recover_typed_data_address(
+        &self,
+        domain: &Eip712Domain,
+        primary_type: &str,
+        types: &Eip712Types,
+        message: &Value,
+        signature: &[u8],
+        v: u8,
+    ) -> Result<Address> {
+        let digest = eip712::digest(domain, primary_type, types, message)?;
+        self.recover_address(&digest, signature, v)
+    }
+
+    /// Recovers the signer address from a 32-byte digest and its compact
+    /// `(r, s)` signature, given `v` in `27`/`28` form (the `sign_digest`
+    /// convention).
+    fn recover_address(&self, digest: &[u8; 32], signature: &[u8], v: u8) -> Result<Address> {
+        let recovery_id = secp256k1::ecdsa::RecoveryId::from_i32(v as i32 - 27)?;
+        let message = Message::from_digest_slice(digest)?;
+        let signature = secp256k1::ecdsa::RecoverableSignature::from_compact(signature, recovery_id)?;
+        let recovered_key = self.secp.recover_ecdsa(&message, &signature)?;
+        self.public_key_to_address(&recovered_key)
+    }
+
+    /// `keccak256("\x19Ethereum Signed Message:\n" || len(message).to_string()
+    /// || message)`, per EIP-191.
+    pub(crate) fn eip191_hash(message: &[u8]) -> [u8; 32] {
+        let mut hasher = Keccak256::new();
+        hasher.update(format!("\x19Ethereum Signed Message:\n{}", message.len()).as_bytes());
+        hasher.update(message);
+        let hash = hasher.finalize();
+        let mut out = [0u8; 32];
+        out.copy_from_slice(&hash);
+        out
+    }
+
     pub fn keccak256(&self, data: &[u8]) -> [u8; 32] {
         let mut hasher = Keccak256::new();
         hasher.update(data);
@@ -126,9 +367,10 @@ impl CryptoService {
         let cipher = Aes256Gcm::new_from_slice(key)?;
         let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
         
-        let private_key_bytes = private_key.secret_bytes();
+        let mut private_key_bytes = private_key.secret_bytes();
         let ciphertext = cipher.encrypt(&nonce, private_key_bytes.as_ref())
             .map_err(|e| anyhow::anyhow!("Encryption failed: {}", e))?;
+        private_key_bytes.zeroize();
         
         // Combine nonce and ciphertext
         let mut encrypted_data = nonce.to_vec();
@@ -0,0 +1,238 @@
+//! EIP-712 structured-data hashing, for signing off-chain DeFi intents
+//! (limit orders, permits, meta-transactions) rather than on-chain
+//! transactions. Implements `encodeType`/`encodeData`/`hashStruct` per the
+//! spec and the final `keccak256(0x19 0x01 || domainSeparator || hashStruct)`
+//! digest.
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha3::{Digest, Keccak256};
+use std::collections::{BTreeSet, HashMap};
+use web3::types::{H160, H256, U256};
+
+use crate::models::TransactionSignature;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Eip712Domain {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    #[serde(rename = "chainId")]
+    pub chain_id: Option<u64>,
+    #[serde(rename = "verifyingContract")]
+    pub verifying_contract: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Eip712FieldType {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
+}
+
+pub type Eip712Types = HashMap<String, Vec<Eip712FieldType>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignTypedDataRequest {
+    pub wallet_id: String,
+    pub domain: Eip712Domain,
+    pub primary_type: String,
+    pub types: Eip712Types,
+    pub message: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignTypedDataResponse {
+    pub digest: String,
+    pub signature: TransactionSignature,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoverTypedDataAddressRequest {
+    pub domain: Eip712Domain,
+    pub primary_type: String,
+    pub types: Eip712Types,
+    pub message: Value,
+    pub signature: TransactionSignature,
+}
+
+/// Computes the final signing digest for `message` of type `primary_type`
+/// under `domain`, per EIP-712.
+pub fn digest(domain: &Eip712Domain, primary_type: &str, types: &Eip712Types, message: &Value) -> Result<[u8; 32]> {
+    let domain_separator = domain_separator(domain)?;
+    let message_hash = hash_struct(primary_type, types, message)?;
+
+    let mut preimage = Vec::with_capacity(2 + 32 + 32);
+    preimage.push(0x19);
+    preimage.push(0x01);
+    preimage.extend_from_slice(domain_separator.as_bytes());
+    preimage.extend_from_slice(message_hash.as_bytes());
+
+    let hash = Keccak256::digest(&preimage);
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&hash);
+    Ok(out)
+}
+
+/// Builds the implicit `EIP712Domain` struct type out of whichever of
+/// `name`/`version`/`chainId`/`verifyingContract` are present, and hashes it.
+fn domain_separator(domain: &Eip712Domain) -> Result<H256> {
+    let mut fields = Vec::new();
+    let mut message = serde_json::Map::new();
+
+    if let Some(name) = &domain.name {
+        fields.push(Eip712FieldType { name: "name".to_string(), field_type: "string".to_string() });
+        message.insert("name".to_string(), Value::String(name.clone()));
+    }
+    if let Some(version) = &domain.version {
+        fields.push(Eip712FieldType { name: "version".to_string(), field_type: "string".to_string() });
+        message.insert("version".to_string(), Value::String(version.clone()));
+    }
+    if let Some(chain_id) = domain.chain_id {
+        fields.push(Eip712FieldType { name: "chainId".to_string(), field_type: "uint256".to_string() });
+        message.insert("chainId".to_string(), Value::String(chain_id.to_string()));
+    }
+    if let Some(verifying_contract) = &domain.verifying_contract {
+        fields.push(Eip712FieldType { name: "verifyingContract".to_string(), field_type: "address".to_string() });
+        message.insert("verifyingContract".to_string(), Value::String(verifying_contract.clone()));
+    }
+
+    let mut types = Eip712Types::new();
+    types.insert("EIP712Domain".to_string(), fields);
+
+    hash_struct("EIP712Domain", &types, &Value::Object(message))
+}
+
+/// `keccak256(typeHash || encodeData(data))` for `type_name`.
+fn hash_struct(type_name: &str, types: &Eip712Types, data: &Value) -> Result<H256> {
+    let encoded = encode_data(type_name, types, data)?;
+    let hash = Keccak256::digest(&encoded);
+    Ok(H256::from_slice(&hash))
+}
+
+fn type_hash(type_name: &str, types: &Eip712Types) -> Result<H256> {
+    let encoded_type = encode_type(type_name, types)?;
+    let hash = Keccak256::digest(encoded_type.as_bytes());
+    Ok(H256::from_slice(&hash))
+}
+
+/// `encodeType`: `name(type1 name1,type2 name2,...)` followed by the same
+/// for every struct type it (transitively) references, sorted alphabetically
+/// by type name.
+fn encode_type(primary_type: &str, types: &Eip712Types) -> Result<String> {
+    let mut deps = BTreeSet::new();
+    collect_dependencies(primary_type, types, &mut deps);
+    deps.remove(primary_type);
+
+    let mut ordered = vec![primary_type.to_string()];
+    ordered.extend(deps);
+
+    let mut encoded = String::new();
+    for type_name in ordered {
+        let fields = types.get(&type_name)
+            .ok_or_else(|| anyhow!("unknown EIP-712 type referenced: {}", type_name))?;
+        encoded.push_str(&type_name);
+        encoded.push('(');
+        let members: Vec<String> = fields.iter()
+            .map(|f| format!("{} {}", f.field_type, f.name))
+            .collect();
+        encoded.push_str(&members.join(","));
+        encoded.push(')');
+    }
+    Ok(encoded)
+}
+
+fn collect_dependencies(type_name: &str, types: &Eip712Types, seen: &mut BTreeSet<String>) {
+    if seen.contains(type_name) {
+        return;
+    }
+    let Some(fields) = types.get(type_name) else {
+        return; // Atomic type (uint256, address, bytes32, ...) — nothing to recurse into.
+    };
+    seen.insert(type_name.to_string());
+    for field in fields {
+        let base_type = field.field_type.trim_end_matches("[]");
+        collect_dependencies(base_type, types, seen);
+    }
+}
+
+/// `encodeData`: `typeHash || encode(field1) || encode(field2) || ...`,
+/// where each field encodes to exactly one 32-byte word (dynamic types are
+/// hashed down to a word, structs recurse via `hashStruct`).
+fn encode_data(type_name: &str, types: &Eip712Types, data: &Value) -> Result<Vec<u8>> {
+    let fields = types.get(type_name)
+        .ok_or_else(|| anyhow!("unknown EIP-712 type: {}", type_name))?;
+
+    let mut encoded = Vec::new();
+    encoded.extend_from_slice(type_hash(type_name, types)?.as_bytes());
+
+    for field in fields {
+        let value = data.get(&field.name)
+            .ok_or_else(|| anyhow!("missing EIP-712 field `{}`", field.name))?;
+        encoded.extend_from_slice(&encode_value(&field.field_type, types, value)?);
+    }
+
+    Ok(encoded)
+}
+
+fn encode_value(field_type: &str, types: &Eip712Types, value: &Value) -> Result<[u8; 32]> {
+    if let Some(element_type) = field_type.strip_suffix("[]") {
+        let items = value.as_array().ok_or_else(|| anyhow!("expected array for field type `{}`", field_type))?;
+        let mut concatenated = Vec::new();
+        for item in items {
+            concatenated.extend_from_slice(&encode_value(element_type, types, item)?);
+        }
+        return Ok(Keccak256::digest(&concatenated).into());
+    }
+
+    if types.contains_key(field_type) {
+        return Ok(hash_struct(field_type, types, value)?.0);
+    }
+
+    match field_type {
+        "string" => {
+            let s = value.as_str().ok_or_else(|| anyhow!("expected string for `string` field"))?;
+            Ok(Keccak256::digest(s.as_bytes()).into())
+        }
+        "bytes" => Ok(Keccak256::digest(&decode_bytes_value(value)?).into()),
+        "bool" => {
+            let b = value.as_bool().ok_or_else(|| anyhow!("expected bool for `bool` field"))?;
+            let mut word = [0u8; 32];
+            word[31] = b as u8;
+            Ok(word)
+        }
+        "address" => {
+            let s = value.as_str().ok_or_else(|| anyhow!("expected address string for `address` field"))?;
+            let addr: H160 = s.parse()?;
+            let mut word = [0u8; 32];
+            word[12..].copy_from_slice(addr.as_bytes());
+            Ok(word)
+        }
+        t if t.starts_with("uint") || t.starts_with("int") => {
+            let raw = value.as_str().map(|s| s.to_string())
+                .or_else(|| value.as_i64().map(|n| n.to_string()))
+                .or_else(|| value.as_u64().map(|n| n.to_string()))
+                .ok_or_else(|| anyhow!("expected numeric value for `{}` field", t))?;
+            let n = match raw.strip_prefix("0x") {
+                Some(hex) => U256::from_str_radix(hex, 16)?,
+                None => U256::from_dec_str(&raw)?,
+            };
+            let mut word = [0u8; 32];
+            n.to_big_endian(&mut word);
+            Ok(word)
+        }
+        t if t.starts_with("bytes") => {
+            let bytes = decode_bytes_value(value)?;
+            let mut word = [0u8; 32];
+            let len = bytes.len().min(32);
+            word[..len].copy_from_slice(&bytes[..len]);
+            Ok(word)
+        }
+        other => bail!("unsupported EIP-712 field type `{}`", other),
+    }
+}
+
+fn decode_bytes_value(value: &Value) -> Result<Vec<u8>> {
+    let s = value.as_str().ok_or_else(|| anyhow!("expected hex-encoded bytes string"))?;
+    Ok(hex::decode(s.trim_start_matches("0x"))?)
+}
@@ -0,0 +1,92 @@
+//! Suggests fee and gas-limit values for signing requests that omit them,
+//! so producers on the Kafka signing pipeline don't each have to
+//! re-implement fee estimation.
+
+use anyhow::{anyhow, Result};
+use web3::transports::Http;
+use web3::types::{BlockId, BlockNumber, CallRequest, U256};
+use web3::Web3;
+
+use crate::models::TaskPriority;
+
+#[derive(Clone)]
+pub struct GasOracle {
+    web3: Web3<Http>,
+}
+
+impl GasOracle {
+    pub fn new(web3: Web3<Http>) -> Self {
+        Self { web3 }
+    }
+
+    /// Suggests a legacy gas price via `eth_gasPrice`.
+    pub async fn legacy_gas_price(&self) -> Result<U256> {
+        Ok(self.web3.eth().gas_price().await?)
+    }
+
+    /// Suggests `(max_fee_per_gas, max_priority_fee_per_gas)` for an
+    /// EIP-1559 transaction at the given urgency: the priority fee comes
+    /// from `eth_maxPriorityFeePerGas` (falling back to a percentile of
+    /// recent `eth_feeHistory` rewards on nodes that don't support it), and
+    /// the max fee multiplies the latest block's `baseFeePerGas` by an
+    /// urgency-dependent factor so the transaction stays includable if the
+    /// base fee rises before it's mined.
+    pub async fn eip1559_fees(&self, urgency: &TaskPriority) -> Result<(U256, U256)> {
+        let priority_fee = match self.max_priority_fee_per_gas().await {
+            Ok(fee) => fee,
+            Err(_) => self.priority_fee_from_history(urgency).await?,
+        };
+
+        let block = self.web3.eth().block(BlockId::Number(BlockNumber::Latest)).await?
+            .ok_or_else(|| anyhow!("no latest block returned"))?;
+        let base_fee = block.base_fee_per_gas
+            .ok_or_else(|| anyhow!("chain does not report an EIP-1559 base fee"))?;
+
+        let max_fee = base_fee * Self::base_fee_multiplier(urgency) + priority_fee;
+        Ok((max_fee, priority_fee))
+    }
+
+    /// Estimates gas via `eth_estimateGas` for the given call.
+    pub async fn estimate_gas(&self, call: CallRequest) -> Result<U256> {
+        Ok(self.web3.eth().estimate_gas(call, None).await?)
+    }
+
+    async fn max_priority_fee_per_gas(&self) -> Result<U256> {
+        let value = self.web3.transport().execute("eth_maxPriorityFeePerGas", vec![]).await?;
+        serde_json::from_value(value).map_err(|e| anyhow!("malformed eth_maxPriorityFeePerGas response: {}", e))
+    }
+
+    async fn priority_fee_from_history(&self, urgency: &TaskPriority) -> Result<U256> {
+        let history = self.web3.eth()
+            .fee_history(U256::from(10), BlockNumber::Latest, Some(vec![Self::reward_percentile(urgency)]))
+            .await?;
+
+        history.reward
+            .and_then(|rewards| rewards.into_iter().rev().find_map(|block_rewards| block_rewards.first().copied()))
+            .ok_or_else(|| anyhow!("node returned no fee history reward data"))
+    }
+
+    /// Which `eth_feeHistory` reward percentile to request: higher urgency
+    /// asks for a higher percentile, so the suggested tip is competitive
+    /// with whatever recent blocks actually paid to get included quickly.
+    fn reward_percentile(urgency: &TaskPriority) -> f64 {
+        match urgency {
+            TaskPriority::Low => 10.0,
+            TaskPriority::Normal => 50.0,
+            TaskPriority::High => 75.0,
+            TaskPriority::Critical => 99.0,
+        }
+    }
+
+    /// Multiplier applied to the latest base fee: higher urgency buys more
+    /// headroom against the base fee rising before inclusion.
+    fn base_fee_multiplier(urgency: &TaskPriority) -> U256 {
+        let multiplier: u64 = match urgency {
+            TaskPriority::Low => 1,
+            TaskPriority::Normal => 2,
+            TaskPriority::High => 3,
+            TaskPriority::Critical => 4,
+        };
+        U256::from(multiplier)
+    }
+}
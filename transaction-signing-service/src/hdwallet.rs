@@ -0,0 +1,116 @@
+//! BIP39 mnemonic generation/recovery and BIP32/BIP44 hierarchical
+//! deterministic key derivation, so wallets created or imported here derive
+//! the same addresses MetaMask/Ledger would from the same mnemonic and
+//! derivation path.
+
+use anyhow::{anyhow, Result};
+use bip39::{Language, Mnemonic};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey};
+use sha2::Sha512;
+
+type HmacSha512 = Hmac<Sha512>;
+
+/// The default Ethereum account path clients derive from: `m/44'/60'/0'/0/{index}`.
+pub fn default_path(account_index: u32) -> String {
+    format!("m/44'/60'/0'/0/{}", account_index)
+}
+
+/// Generates a new mnemonic from `entropy_bits` bits of randomness (128-256,
+/// in 32-bit steps per BIP39), appending the SHA-256-derived checksum and
+/// mapping the result onto the standard English wordlist.
+pub fn generate_mnemonic(entropy_bits: u32) -> Result<String> {
+    if entropy_bits < 128 || entropy_bits > 256 || entropy_bits % 32 != 0 {
+        return Err(anyhow!("entropy_bits must be one of 128, 160, 192, 224, 256"));
+    }
+
+    let mut entropy = vec![0u8; (entropy_bits / 8) as usize];
+    rand::thread_rng().fill_bytes(&mut entropy);
+
+    let mnemonic = Mnemonic::from_entropy_in(Language::English, &entropy)
+        .map_err(|e| anyhow!("failed to build mnemonic from entropy: {}", e))?;
+    Ok(mnemonic.to_string())
+}
+
+/// Validates `phrase` against the BIP39 wordlist and checksum, then derives
+/// the 64-byte seed via `PBKDF2-HMAC-SHA512(mnemonic, "mnemonic"+passphrase,
+/// 2048 iterations)`.
+pub fn mnemonic_to_seed(phrase: &str, passphrase: &str) -> Result<[u8; 64]> {
+    let mnemonic = Mnemonic::parse_in_normalized(Language::English, phrase)
+        .map_err(|e| anyhow!("invalid mnemonic: {}", e))?;
+    Ok(mnemonic.to_seed(passphrase))
+}
+
+/// An extended private key: the 32-byte secret plus the chain code used to
+/// derive its children.
+struct ExtendedKey {
+    key: SecretKey,
+    chain_code: [u8; 32],
+}
+
+/// BIP32 master key: `HMAC-SHA512(key = "Bitcoin seed", data = seed)`, split
+/// into the master private key (`I_L`) and master chain code (`I_R`).
+fn derive_master(seed: &[u8]) -> Result<ExtendedKey> {
+    let mut mac = HmacSha512::new_from_slice(b"Bitcoin seed")
+        .map_err(|e| anyhow!("invalid HMAC key: {}", e))?;
+    mac.update(seed);
+    let out = mac.finalize().into_bytes();
+
+    let key = SecretKey::from_slice(&out[..32])?;
+    let mut chain_code = [0u8; 32];
+    chain_code.copy_from_slice(&out[32..]);
+    Ok(ExtendedKey { key, chain_code })
+}
+
+/// BIP32 child key derivation (CKD). Hardened indices (high bit set) HMAC
+/// the parent's private key; non-hardened indices HMAC the parent's
+/// compressed public key instead, per spec.
+fn derive_child(secp: &Secp256k1<secp256k1::All>, parent: &ExtendedKey, index: u32) -> Result<ExtendedKey> {
+    let mut mac = HmacSha512::new_from_slice(&parent.chain_code)
+        .map_err(|e| anyhow!("invalid HMAC key: {}", e))?;
+
+    if index & 0x8000_0000 != 0 {
+        mac.update(&[0u8]);
+        mac.update(&parent.key.secret_bytes());
+    } else {
+        let public_key = PublicKey::from_secret_key(secp, &parent.key);
+        mac.update(&public_key.serialize());
+    }
+    mac.update(&index.to_be_bytes());
+
+    let out = mac.finalize().into_bytes();
+    let tweak = Scalar::from_be_bytes(out[..32].try_into().unwrap())
+        .map_err(|_| anyhow!("derived key material is not a valid scalar"))?;
+    let key = parent.key.add_tweak(&tweak)?;
+
+    let mut chain_code = [0u8; 32];
+    chain_code.copy_from_slice(&out[32..]);
+    Ok(ExtendedKey { key, chain_code })
+}
+
+/// Parses a BIP32 path like `m/44'/60'/0'/0/0` into its raw index
+/// components, with hardened components (trailing `'`/`h`) having the high
+/// bit set.
+pub fn parse_derivation_path(path: &str) -> Result<Vec<u32>> {
+    path.trim_start_matches("m/")
+        .split('/')
+        .map(|component| {
+            let hardened = component.ends_with('\'') || component.ends_with('h');
+            let index: u32 = component.trim_end_matches(['\'', 'h']).parse()
+                .map_err(|_| anyhow!("invalid derivation path component `{}`", component))?;
+            Ok(if hardened { index | 0x8000_0000 } else { index })
+        })
+        .collect()
+}
+
+/// Walks `path` from the BIP32 master key derived from `seed`, returning the
+/// private key at that path.
+pub fn derive_private_key(seed: &[u8], path: &str) -> Result<SecretKey> {
+    let secp = Secp256k1::new();
+    let mut key = derive_master(seed)?;
+    for index in parse_derivation_path(path)? {
+        key = derive_child(&secp, &key, index)?;
+    }
+    Ok(key.key)
+}
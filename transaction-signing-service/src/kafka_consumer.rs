@@ -2,15 +2,34 @@ use anyhow::Result;
 use futures::StreamExt;
 use log::{error, info, warn};
 use rdkafka::config::ClientConfig;
-use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
 use rdkafka::message::Message;
+use rdkafka::producer::FutureProducer;
+use rdkafka::{Offset, TopicPartitionList};
 use serde_json;
-use std::time::Duration;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-use crate::models::{TransactionSigningTask, SigningResult};
+use crate::models::{BatchSigningRequest, BatchSigningResponse, TransactionSigningTask, SigningResult};
 
+/// Consumes signing requests with manual offset commits, batched on
+/// `commit_interval` (as in Arroyo's `strategies/commit_offsets.rs`) rather
+/// than committed per message: an offset is only staged for commit once its
+/// result has been durably produced, so a crash mid-handler redelivers the
+/// task instead of silently losing it.
 pub struct KafkaConsumerService {
     consumer: StreamConsumer,
+    /// Shared across every `send_*` method so result/metrics/health
+    /// publishing reuses one broker connection instead of re-establishing
+    /// one per message.
+    producer: FutureProducer,
+    /// How often staged offsets are flushed to the broker.
+    commit_interval: Duration,
+    /// Highest processed offset per `(topic, partition)`, staged by
+    /// `stage_commit` and flushed by `maybe_flush_commits`.
+    pending_offsets: Mutex<HashMap<(String, i32), i64>>,
+    last_commit: Mutex<Instant>,
 }
 
 impl KafkaConsumerService {
@@ -20,22 +39,43 @@ impl KafkaConsumerService {
             .set("bootstrap.servers", brokers)
             .set("enable.partition.eof", "false")
             .set("session.timeout.ms", "6000")
-            .set("enable.auto.commit", "true")
+            .set("enable.auto.commit", "false")
             .set("auto.offset.reset", "latest")
             .create()?;
 
-        Ok(Self { consumer })
+        let producer: FutureProducer = ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("message.timeout.ms", "5000")
+            .set("acks", "all")
+            .create()?;
+
+        Ok(Self {
+            consumer,
+            producer,
+            commit_interval: Duration::from_secs(5),
+            pending_offsets: Mutex::new(HashMap::new()),
+            last_commit: Mutex::new(Instant::now()),
+        })
     }
 
+    /// Overrides how often staged offsets are flushed to the broker.
+    pub fn with_commit_interval(mut self, interval: Duration) -> Self {
+        self.commit_interval = interval;
+        self
+    }
+
+    /// Subscribes to both the individual and batch signing request topics;
+    /// `start_consuming` tells them apart by message topic.
     pub async fn subscribe_to_signing_requests(&self) -> Result<()> {
-        self.consumer.subscribe(&["transaction-signing-requests"])?;
-        info!("Subscribed to transaction-signing-requests topic");
+        self.consumer.subscribe(&["transaction-signing-requests", "batch-signing-requests"])?;
+        info!("Subscribed to transaction-signing-requests and batch-signing-requests topics");
         Ok(())
     }
 
-    pub async fn start_consuming<F>(&self, mut handler: F) -> Result<()>
+    pub async fn start_consuming<F, G>(&self, mut task_handler: F, mut batch_handler: G) -> Result<()>
     where
         F: FnMut(TransactionSigningTask) -> Result<SigningResult> + Send,
+        G: FnMut(BatchSigningRequest) -> Result<BatchSigningResponse> + Send,
     {
         let mut message_stream = self.consumer.stream();
 
@@ -46,6 +86,10 @@ impl KafkaConsumerService {
                     continue;
                 }
                 Ok(m) => {
+                    let topic = m.topic().to_string();
+                    let partition = m.partition();
+                    let offset = m.offset();
+
                     let payload = match m.payload_view::<str>() {
                         None => {
                             warn!("Empty message payload");
@@ -58,16 +102,45 @@ impl KafkaConsumerService {
                         }
                     };
 
+                    if topic == "batch-signing-requests" {
+                        match serde_json::from_str::<BatchSigningRequest>(payload) {
+                            Ok(batch) => {
+                                info!("Received signing batch: {}", batch.batch_id);
+
+                                match batch_handler(batch) {
+                                    Ok(response) => {
+                                        info!("Successfully processed signing batch: {}", response.batch_id);
+                                        match self.send_batch_result(&response).await {
+                                            Ok(()) => self.stage_commit(&topic, partition, offset),
+                                            Err(e) => error!("Failed to send batch signing result: {}", e),
+                                        }
+                                    }
+                                    Err(e) => {
+                                        error!("Failed to process signing batch: {}", e);
+                                    }
+                                }
+                            }
+                            Err(e) => {
+                                error!("Failed to deserialize signing batch: {}", e);
+                            }
+                        }
+                        self.maybe_flush_commits();
+                        continue;
+                    }
+
                     match serde_json::from_str::<TransactionSigningTask>(payload) {
                         Ok(task) => {
                             info!("Received signing task: {}", task.task_id);
-                            
-                            match handler(task) {
+
+                            match task_handler(task) {
                                 Ok(result) => {
                                     info!("Successfully processed signing task: {}", result.task_id);
-                                    // Send result back to Kafka
-                                    if let Err(e) = self.send_signing_result(&result).await {
-                                        error!("Failed to send signing result: {}", e);
+                                    // Only stage the offset once the result has actually
+                                    // shipped — a crash before this point redelivers the
+                                    // task instead of silently losing it.
+                                    match self.send_signing_result(&result).await {
+                                        Ok(()) => self.stage_commit(&topic, partition, offset),
+                                        Err(e) => error!("Failed to send signing result: {}", e),
                                     }
                                 }
                                 Err(e) => {
@@ -79,6 +152,7 @@ impl KafkaConsumerService {
                             error!("Failed to deserialize signing task: {}", e);
                         }
                     }
+                    self.maybe_flush_commits();
                 }
             }
         }
@@ -86,15 +160,47 @@ impl KafkaConsumerService {
         Ok(())
     }
 
+    /// Records `offset` as the highest successfully-processed offset for
+    /// `(topic, partition)`; `maybe_flush_commits` is what actually commits
+    /// it to the broker.
+    fn stage_commit(&self, topic: &str, partition: i32, offset: i64) {
+        self.pending_offsets
+            .lock()
+            .unwrap()
+            .insert((topic.to_string(), partition), offset);
+    }
+
+    /// Flushes staged offsets to the broker once `commit_interval` has
+    /// elapsed since the last flush, batching commits instead of doing one
+    /// round trip per message.
+    fn maybe_flush_commits(&self) {
+        if self.last_commit.lock().unwrap().elapsed() < self.commit_interval {
+            return;
+        }
+
+        let staged: Vec<((String, i32), i64)> = self.pending_offsets.lock().unwrap().drain().collect();
+        *self.last_commit.lock().unwrap() = Instant::now();
+
+        if staged.is_empty() {
+            return;
+        }
+
+        let mut tpl = TopicPartitionList::new();
+        for ((topic, partition), offset) in staged {
+            // `rdkafka` commits the *next* offset to consume, not the last
+            // one processed.
+            if let Err(e) = tpl.add_partition_offset(&topic, partition, Offset::Offset(offset + 1)) {
+                error!("Failed to stage offset commit for {} partition {}: {}", topic, partition, e);
+            }
+        }
+
+        if let Err(e) = self.consumer.commit(&tpl, CommitMode::Async) {
+            error!("Failed to commit batched offsets: {}", e);
+        }
+    }
+
     async fn send_signing_result(&self, result: &SigningResult) -> Result<()> {
-        use rdkafka::producer::{FutureProducer, FutureRecord};
-        
-        // Create a producer for sending results
-        let producer: FutureProducer = ClientConfig::new()
-            .set("bootstrap.servers", std::env::var("KAFKA_BROKERS").unwrap_or_else(|_| "localhost:9092".to_string()))
-            .set("message.timeout.ms", "5000")
-            .set("acks", "all")
-            .create()?;
+        use rdkafka::producer::FutureRecord;
 
         let payload = serde_json::to_string(result)?;
         let key = result.task_id.clone();
@@ -103,7 +209,7 @@ impl KafkaConsumerService {
             .key(&key)
             .payload(&payload);
 
-        match producer.send(record, Duration::from_secs(0)).await {
+        match self.producer.send(record, Duration::from_secs(0)).await {
             Ok(_) => {
                 info!("Sent signing result for task: {}", result.task_id);
                 Ok(())
@@ -112,19 +218,27 @@ impl KafkaConsumerService {
         }
     }
 
-    pub async fn subscribe_to_batch_requests(&self) -> Result<()> {
-        self.consumer.subscribe(&["batch-signing-requests"])?;
-        info!("Subscribed to batch-signing-requests topic");
-        Ok(())
+    async fn send_batch_result(&self, response: &BatchSigningResponse) -> Result<()> {
+        use rdkafka::producer::FutureRecord;
+
+        let payload = serde_json::to_string(response)?;
+        let key = response.batch_id.clone();
+
+        let record = FutureRecord::to("transaction-signing-batch-results")
+            .key(&key)
+            .payload(&payload);
+
+        match self.producer.send(record, Duration::from_secs(0)).await {
+            Ok(_) => {
+                info!("Sent batch signing result for batch: {}", response.batch_id);
+                Ok(())
+            }
+            Err((e, _)) => Err(anyhow::anyhow!("Failed to send batch signing result: {}", e)),
+        }
     }
 
     pub async fn send_health_status(&self, status: &str) -> Result<()> {
-        use rdkafka::producer::{FutureProducer, FutureRecord};
-        
-        let producer: FutureProducer = ClientConfig::new()
-            .set("bootstrap.servers", std::env::var("KAFKA_BROKERS").unwrap_or_else(|_| "localhost:9092".to_string()))
-            .set("message.timeout.ms", "5000")
-            .create()?;
+        use rdkafka::producer::FutureRecord;
 
         let health_status = serde_json::json!({
             "service": "transaction-signing",
@@ -141,7 +255,7 @@ impl KafkaConsumerService {
             .key(&key)
             .payload(&payload);
 
-        match producer.send(record, Duration::from_secs(0)).await {
+        match self.producer.send(record, Duration::from_secs(0)).await {
             Ok(_) => Ok(()),
             Err((e, _)) => Err(anyhow::anyhow!("Failed to send health status: {}", e)),
         }
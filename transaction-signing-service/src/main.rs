@@ -2,29 +2,41 @@ use anyhow::Result;
 use axum::{
     extract::State,
     http::StatusCode,
-    response::Json,
-    routing::post,
+    response::{IntoResponse, Json},
+    routing::{get, post},
     Router,
 };
 use log::{error, info};
 use std::env;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::net::TcpListener;
 use tower_http::cors::CorsLayer;
 
 mod models;
 mod crypto;
+mod eip712;
+mod gas_oracle;
+mod hdwallet;
 mod kafka_consumer;
+mod metrics;
+mod nonce_manager;
+mod signer;
 mod signing_service;
 
+use eip712::{RecoverTypedDataAddressRequest, SignTypedDataRequest, SignTypedDataResponse};
 use models::*;
 use crypto::CryptoService;
 use kafka_consumer::KafkaConsumerService;
+use metrics::TransactionSigningMetrics;
 use signing_service::TransactionSigningService;
 
 #[derive(Clone)]
 pub struct AppState {
     signing_service: Arc<TransactionSigningService>,
+    metrics: Arc<TransactionSigningMetrics>,
+    registry: Arc<prometheus::Registry>,
+    start_time: Instant,
 }
 
 #[tokio::main]
@@ -37,9 +49,16 @@ async fn main() -> Result<()> {
         .unwrap_or_else(|_| "localhost:9092".to_string());
 
     let signing_service = Arc::new(TransactionSigningService::new(&kafka_brokers).await?);
-    
+
+    let registry = metrics::create_metrics_registry();
+    let signing_metrics = TransactionSigningMetrics::new();
+    metrics::register_signing_metrics(&registry, &signing_metrics)?;
+
     let app_state = AppState {
         signing_service: signing_service.clone(),
+        metrics: Arc::new(signing_metrics),
+        registry: Arc::new(registry),
+        start_time: Instant::now(),
     };
 
     // Start Kafka consumer in background
@@ -53,9 +72,18 @@ async fn main() -> Result<()> {
     // Create HTTP API routes
     let app = Router::new()
         .route("/health", axum::routing::get(health_check))
+        .route("/metrics", get(metrics_handler))
         .route("/sign", post(sign_transaction_handler))
+        .route("/sign-batch", post(sign_batch_handler))
+        .route("/sign-typed-data", post(sign_typed_data_handler))
+        .route("/sign-message", post(sign_message_handler))
+        .route("/recover-message-address", post(recover_message_address_handler))
+        .route("/recover-typed-data-address", post(recover_typed_data_address_handler))
         .route("/create-wallet", post(create_wallet_handler))
         .route("/import-wallet", post(import_wallet_handler))
+        .route("/reset-nonce", post(reset_nonce_handler))
+        .route("/unlock-wallet", post(unlock_wallet_handler))
+        .route("/lock-wallet", post(lock_wallet_handler))
         .layer(CorsLayer::permissive())
         .with_state(app_state);
 
@@ -75,6 +103,14 @@ async fn health_check() -> Json<serde_json::Value> {
     }))
 }
 
+async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
+    state.metrics.refresh_health_gauges(state.start_time);
+    (
+        [("Content-Type", "text/plain; version=0.0.4; charset=utf-8")],
+        metrics::encode(&state.registry),
+    )
+}
+
 async fn sign_transaction_handler(
     State(state): State<AppState>,
     Json(request): Json<SignTransactionRequest>,
@@ -88,6 +124,71 @@ async fn sign_transaction_handler(
     }
 }
 
+async fn sign_batch_handler(
+    State(state): State<AppState>,
+    Json(request): Json<BatchSigningRequest>,
+) -> Result<Json<BatchSigningResponse>, StatusCode> {
+    match state.signing_service.sign_batch(request).await {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => {
+            error!("Failed to sign batch: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn sign_typed_data_handler(
+    State(state): State<AppState>,
+    Json(request): Json<SignTypedDataRequest>,
+) -> Result<Json<SignTypedDataResponse>, StatusCode> {
+    match state.signing_service.sign_typed_data(request).await {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => {
+            error!("Failed to sign typed data: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn sign_message_handler(
+    State(state): State<AppState>,
+    Json(request): Json<SignMessageRequest>,
+) -> Result<Json<SignMessageResponse>, StatusCode> {
+    match state.signing_service.sign_message(request).await {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => {
+            error!("Failed to sign message: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn recover_message_address_handler(
+    State(state): State<AppState>,
+    Json(request): Json<RecoverMessageAddressRequest>,
+) -> Result<Json<RecoverAddressResponse>, StatusCode> {
+    match state.signing_service.recover_message_address(request).await {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => {
+            error!("Failed to recover message address: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn recover_typed_data_address_handler(
+    State(state): State<AppState>,
+    Json(request): Json<RecoverTypedDataAddressRequest>,
+) -> Result<Json<RecoverAddressResponse>, StatusCode> {
+    match state.signing_service.recover_typed_data_address(request).await {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => {
+            error!("Failed to recover typed-data address: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 async fn create_wallet_handler(
     State(state): State<AppState>,
     Json(request): Json<CreateWalletRequest>,
@@ -101,6 +202,62 @@ async fn create_wallet_handler(
     }
 }
 
+#[derive(serde::Deserialize)]
+struct ResetNonceRequest {
+    wallet_id: String,
+}
+
+async fn reset_nonce_handler(
+    State(state): State<AppState>,
+    Json(request): Json<ResetNonceRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    match state.signing_service.reset_nonce(&request.wallet_id).await {
+        Ok(()) => Ok(Json(serde_json::json!({ "status": "ok" }))),
+        Err(e) => {
+            error!("Failed to reset nonce: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct UnlockWalletRequest {
+    wallet_id: String,
+    password: String,
+    /// How long the wallet stays unlocked for, in seconds. Defaults to 300
+    /// (5 minutes) when omitted.
+    ttl_secs: Option<u64>,
+}
+
+async fn unlock_wallet_handler(
+    State(state): State<AppState>,
+    Json(request): Json<UnlockWalletRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    match state.signing_service
+        .unlock_wallet(&request.wallet_id, &request.password, request.ttl_secs.unwrap_or(300))
+        .await
+    {
+        Ok(()) => Ok(Json(serde_json::json!({ "status": "ok" }))),
+        Err(e) => {
+            error!("Failed to unlock wallet: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct LockWalletRequest {
+    wallet_id: String,
+}
+
+async fn lock_wallet_handler(
+    State(state): State<AppState>,
+    Json(request): Json<LockWalletRequest>,
+) -> Json<serde_json::Value> {
+    state.signing_service.lock_wallet(&request.wallet_id).await;
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
 async fn import_wallet_handler(
     State(state): State<AppState>,
     Json(request): Json<ImportWalletRequest>,
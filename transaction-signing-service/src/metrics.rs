@@ -1,15 +1,27 @@
 use std::sync::Arc;
-use prometheus::{Registry, Counter, Gauge, Histogram, Opts, HistogramOpts, CounterVec};
+use std::time::Instant;
+use prometheus::{Registry, Counter, Gauge, Histogram, Opts, HistogramOpts, CounterVec, TextEncoder};
 use std::collections::HashMap;
 
 pub struct TransactionSigningMetrics {
     pub transactions_signed_total: Counter,
     pub signing_duration: Histogram,
     pub signing_errors_total: CounterVec,
+    /// Scalar twin of `signing_errors_total` (summed across `error_type`
+    /// labels), so the health gauges below can report an overall error rate
+    /// without iterating a `CounterVec`'s label set.
+    pub signing_errors_total_scalar: Counter,
     pub wallets_created_total: Counter,
     pub wallets_imported_total: Counter,
     pub active_sessions: Gauge,
     pub memory_usage_bytes: Gauge,
+
+    /// Seconds since the `start_time` passed to `refresh_health_gauges`,
+    /// refreshed on each `/metrics` scrape.
+    pub uptime_seconds: Gauge,
+    /// Fraction of signing operations that have failed since startup,
+    /// refreshed on each `/metrics` scrape.
+    pub error_rate: Gauge,
 }
 
 impl TransactionSigningMetrics {
@@ -31,6 +43,11 @@ impl TransactionSigningMetrics {
             &["error_type"]
         ).unwrap();
 
+        let signing_errors_total_scalar = Counter::new(
+            "transaction_signing_errors_total_scalar",
+            "Total number of signing errors across all error types"
+        ).unwrap();
+
         let wallets_created_total = Counter::new(
             "transaction_signing_wallets_created_total",
             "Total number of wallets created"
@@ -51,14 +68,27 @@ impl TransactionSigningMetrics {
             "Memory usage in bytes"
         ).unwrap();
 
+        let uptime_seconds = Gauge::new(
+            "transaction_signing_uptime_seconds",
+            "Seconds since the signing service started"
+        ).unwrap();
+
+        let error_rate = Gauge::new(
+            "transaction_signing_error_rate",
+            "Fraction of signing operations that have failed since startup"
+        ).unwrap();
+
         Self {
             transactions_signed_total,
             signing_duration,
             signing_errors_total,
+            signing_errors_total_scalar,
             wallets_created_total,
             wallets_imported_total,
             active_sessions,
             memory_usage_bytes,
+            uptime_seconds,
+            error_rate,
         }
     }
 
@@ -72,6 +102,7 @@ impl TransactionSigningMetrics {
 
     pub fn record_signing_error(&self, error_type: &str) {
         self.signing_errors_total.with_label_values(&[error_type]).inc();
+        self.signing_errors_total_scalar.inc();
     }
 
     pub fn record_wallet_created(&self) {
@@ -89,6 +120,21 @@ impl TransactionSigningMetrics {
     pub fn update_memory_usage(&self, bytes: u64) {
         self.memory_usage_bytes.set(bytes as f64);
     }
+
+    /// Refreshes the health gauges (`uptime_seconds`, `error_rate`) from
+    /// `start_time` and the existing signing/error counters. Called just
+    /// before each `/metrics` scrape, mirroring how `HealthMonitor` in
+    /// blockchain-sync-service derives its stats from live counters rather
+    /// than tracking them independently.
+    pub fn refresh_health_gauges(&self, start_time: Instant) {
+        self.uptime_seconds.set(start_time.elapsed().as_secs_f64());
+
+        let successes = self.transactions_signed_total.get();
+        let errors = self.signing_errors_total_scalar.get();
+        let total = successes + errors;
+        let rate = if total > 0.0 { errors / total } else { 0.0 };
+        self.error_rate.set(rate);
+    }
 }
 
 pub fn create_metrics_registry() -> Registry {
@@ -100,9 +146,19 @@ pub fn register_signing_metrics(registry: &Registry, metrics: &TransactionSignin
     registry.register(Box::new(metrics.transactions_signed_total.clone()))?;
     registry.register(Box::new(metrics.signing_duration.clone()))?;
     registry.register(Box::new(metrics.signing_errors_total.clone()))?;
+    registry.register(Box::new(metrics.signing_errors_total_scalar.clone()))?;
     registry.register(Box::new(metrics.wallets_created_total.clone()))?;
     registry.register(Box::new(metrics.wallets_imported_total.clone()))?;
     registry.register(Box::new(metrics.active_sessions.clone()))?;
     registry.register(Box::new(metrics.memory_usage_bytes.clone()))?;
+    registry.register(Box::new(metrics.uptime_seconds.clone()))?;
+    registry.register(Box::new(metrics.error_rate.clone()))?;
     Ok(())
 }
+
+/// Renders every metric in `registry` as Prometheus text exposition format,
+/// for use by a `/metrics` handler.
+pub fn encode(registry: &Registry) -> String {
+    let metric_families = registry.gather();
+    TextEncoder::new().encode_to_string(&metric_families).unwrap_or_default()
+}
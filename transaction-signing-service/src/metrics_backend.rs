@@ -0,0 +1,321 @@
+//! Pluggable metrics sink sitting in front of `TransactionSigningMetrics`.
+//! `MetricsBackend` abstracts over *where* a metric ends up — the existing
+//! Prometheus registry, or a StatsD daemon reached over UDP — while
+//! `BufferedMetrics` coalesces emits in memory and flushes them to whichever
+//! backend is configured on a timer, so a burst of signing requests doesn't
+//! turn into a burst of UDP packets or lock contention on the registry.
+
+use std::collections::HashMap;
+use std::net::UdpSocket;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use log::{debug, warn};
+use prometheus::{CounterVec, GaugeVec, HistogramVec, HistogramOpts, Opts, Registry};
+
+/// A single destination for counters, gauges, and timings. Implementations
+/// need not be cheap to call from request-handling code, since
+/// `BufferedMetrics` calls them from its flush task rather than the hot path.
+pub trait MetricsBackend: Send + Sync {
+    fn increment(&self, name: &str, value: f64, tags: &[(String, String)]);
+    fn gauge(&self, name: &str, value: f64, tags: &[(String, String)]);
+    fn timing(&self, name: &str, duration: Duration, tags: &[(String, String)]);
+}
+
+/// Maximum number of entries in each tracking map: distinct metric names per
+/// kind in `PrometheusBackend`, and distinct name-plus-tags keys buffered
+/// between flushes in `BufferedMetrics`. New entries beyond it are dropped
+/// rather than registered, so a caller passing unbounded tag values (e.g. a
+/// raw wallet address) can't grow the buffer without limit.
+/// `PrometheusBackend` does not cap the label-value combinations inside one
+/// registered vector.
+const MAX_TAG_COMBINATIONS: usize = 200;
+
+fn tag_names(tags: &[(String, String)]) -> Vec<&str> {
+    tags.iter().map(|(k, _)| k.as_str()).collect()
+}
+
+fn tag_values(tags: &[(String, String)]) -> Vec<&str> {
+    tags.iter().map(|(_, v)| v.as_str()).collect()
+}
+
+/// Backs `MetricsBackend` with dynamically-registered Prometheus vectors,
+/// one `CounterVec`/`GaugeVec`/`HistogramVec` per metric name, keyed and
+/// cached the first time that name is seen. This is separate from
+/// `TransactionSigningMetrics`, whose metrics are fixed at compile time;
+/// this backend exists for call sites that want to emit ad hoc named
+/// metrics (e.g. per-backend StatsD parity) without a new struct field for
+/// each one.
+pub struct PrometheusBackend {
+    registry: Registry,
+    counters: Mutex<HashMap<String, CounterVec>>,
+    gauges: Mutex<HashMap<String, GaugeVec>>,
+    histograms: Mutex<HashMap<String, HistogramVec>>,
+}
+
+impl PrometheusBackend {
+    pub fn new(registry: Registry) -> Self {
+        Self {
+            registry,
+            counters: Mutex::new(HashMap::new()),
+            gauges: Mutex::new(HashMap::new()),
+            histograms: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn counter_vec(&self, name: &str, label_names: &[&str]) -> Option<CounterVec> {
+        let mut counters = self.counters.lock().unwrap();
+        if let Some(c) = counters.get(name) {
+            return Some(c.clone());
+        }
+        if counters.len() >= MAX_TAG_COMBINATIONS {
+            warn!("PrometheusBackend: dropping new counter '{}', metric cardinality limit reached", name);
+            return None;
+        }
+        let counter = CounterVec::new(Opts::new(name.to_string(), name.to_string()), label_names).ok()?;
+        if self.registry.register(Box::new(counter.clone())).is_err() {
+            return None;
+        }
+        counters.insert(name.to_string(), counter.clone());
+        Some(counter)
+    }
+
+    fn gauge_vec(&self, name: &str, label_names: &[&str]) -> Option<GaugeVec> {
+        let mut gauges = self.gauges.lock().unwrap();
+        if let Some(g) = gauges.get(name) {
+            return Some(g.clone());
+        }
+        if gauges.len() >= MAX_TAG_COMBINATIONS {
+            warn!("PrometheusBackend: dropping new gauge '{}', metric cardinality limit reached", name);
+            return None;
+        }
+        let gauge = GaugeVec::new(Opts::new(name.to_string(), name.to_string()), label_names).ok()?;
+        if self.registry.register(Box::new(gauge.clone())).is_err() {
+            return None;
+        }
+        gauges.insert(name.to_string(), gauge.clone());
+        Some(gauge)
+    }
+
+    fn histogram_vec(&self, name: &str, label_names: &[&str]) -> Option<HistogramVec> {
+        let mut histograms = self.histograms.lock().unwrap();
+        if let Some(h) = histograms.get(name) {
+            return Some(h.clone());
+        }
+        if histograms.len() >= MAX_TAG_COMBINATIONS {
+            warn!("PrometheusBackend: dropping new histogram '{}', metric cardinality limit reached", name);
+            return None;
+        }
+        let histogram = HistogramVec::new(HistogramOpts::new(name.to_string(), name.to_string()), label_names).ok()?;
+        if self.registry.register(Box::new(histogram.clone())).is_err() {
+            return None;
+        }
+        histograms.insert(name.to_string(), histogram.clone());
+        Some(histogram)
+    }
+}
+
+impl MetricsBackend for PrometheusBackend {
+    fn increment(&self, name: &str, value: f64, tags: &[(String, String)]) {
+        let names = tag_names(tags);
+        if let Some(counter) = self.counter_vec(name, &names) {
+            counter.with_label_values(&tag_values(tags)).inc_by(value);
+        }
+    }
+
+    fn gauge(&self, name: &str, value: f64, tags: &[(String, String)]) {
+        let names = tag_names(tags);
+        if let Some(gauge) = self.gauge_vec(name, &names) {
+            gauge.with_label_values(&tag_values(tags)).set(value);
+        }
+    }
+
+    fn timing(&self, name: &str, duration: Duration, tags: &[(String, String)]) {
+        let names = tag_names(tags);
+        if let Some(histogram) = self.histogram_vec(name, &names) {
+            histogram.with_label_values(&tag_values(tags)).observe(duration.as_secs_f64());
+        }
+    }
+}
+
+/// Sends metrics as StatsD text lines (`name:value|c`, `name:value|g`,
+/// `name:ms|ms`) over a non-blocking UDP socket. Tags are appended
+/// DogStatsD-style (`|#key:value,key:value`) since plain StatsD has no tag
+/// syntax of its own and this is the most widely supported extension.
+pub struct StatsdBackend {
+    socket: UdpSocket,
+}
+
+impl StatsdBackend {
+    pub fn new(target: &str) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.set_nonblocking(true)?;
+        socket.connect(target)?;
+        Ok(Self { socket })
+    }
+
+    fn send_line(&self, line: &str) {
+        if let Err(e) = self.socket.send(line.as_bytes()) {
+            debug!("StatsdBackend: failed to send '{}': {}", line, e);
+        }
+    }
+
+    fn format_tags(tags: &[(String, String)]) -> String {
+        if tags.is_empty() {
+            return String::new();
+        }
+        let joined = tags.iter().map(|(k, v)| format!("{}:{}", k, v)).collect::<Vec<_>>().join(",");
+        format!("|#{}", joined)
+    }
+}
+
+impl MetricsBackend for StatsdBackend {
+    fn increment(&self, name: &str, value: f64, tags: &[(String, String)]) {
+        self.send_line(&format!("{}:{}|c{}", name, value, Self::format_tags(tags)));
+    }
+
+    fn gauge(&self, name: &str, value: f64, tags: &[(String, String)]) {
+        self.send_line(&format!("{}:{}|g{}", name, value, Self::format_tags(tags)));
+    }
+
+    fn timing(&self, name: &str, duration: Duration, tags: &[(String, String)]) {
+        self.send_line(&format!("{}:{}|ms{}", name, duration.as_millis(), Self::format_tags(tags)));
+    }
+}
+
+fn tag_key(name: &str, tags: &[(String, String)]) -> String {
+    let mut key = name.to_string();
+    for (k, v) in tags {
+        key.push(';');
+        key.push_str(k);
+        key.push('=');
+        key.push_str(v);
+    }
+    key
+}
+
+enum Aggregate {
+    Counter { name: String, value: f64, tags: Vec<(String, String)> },
+    Gauge { name: String, value: f64, tags: Vec<(String, String)> },
+    Timings { name: String, samples: Vec<Duration>, tags: Vec<(String, String)> },
+}
+
+struct BufferState {
+    entries: HashMap<String, Aggregate>,
+}
+
+impl BufferState {
+    fn new() -> Self {
+        Self { entries: HashMap::new() }
+    }
+
+    fn record(&mut self, name: &str, tags: &[(String, String)], apply: impl FnOnce(Option<Aggregate>) -> Aggregate) {
+        let key = tag_key(name, tags);
+        if !self.entries.contains_key(&key) && self.entries.len() >= MAX_TAG_COMBINATIONS {
+            warn!("BufferedMetrics: dropping emit for '{}', tag cardinality limit reached", name);
+            return;
+        }
+        let existing = self.entries.remove(&key);
+        self.entries.insert(key, apply(existing));
+    }
+
+    fn drain(&mut self) -> HashMap<String, Aggregate> {
+        std::mem::take(&mut self.entries)
+    }
+}
+
+/// Buffers `increment`/`gauge`/`timing` calls in memory and flushes them to
+/// the wrapped `MetricsBackend` every `flush_interval` (default 1s, via
+/// [`Self::with_default_interval`]):
+/// counters are summed, gauges keep their last value, and timing samples
+/// are batched and replayed individually on flush. A background task drains
+/// the buffer on a timer; `Drop` performs one last synchronous flush so a
+/// shutdown doesn't lose whatever accumulated since the last tick.
+pub struct BufferedMetrics {
+    state: Arc<Mutex<BufferState>>,
+    backend: Arc<dyn MetricsBackend>,
+    shutdown: Arc<std::sync::atomic::AtomicBool>,
+}
+
+impl BufferedMetrics {
+    pub fn new(backend: Arc<dyn MetricsBackend>, flush_interval: Duration) -> Self {
+        let state = Arc::new(Mutex::new(BufferState::new()));
+        let shutdown = Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+        let task_state = state.clone();
+        let task_backend = backend.clone();
+        let task_shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(flush_interval);
+            loop {
+                ticker.tick().await;
+                if task_shutdown.load(std::sync::atomic::Ordering::Relaxed) {
+                    break;
+                }
+                Self::flush_once(&task_state, &task_backend);
+            }
+        });
+
+        Self { state, backend, shutdown }
+    }
+
+    /// [`Self::new`] with the default 1s flush interval.
+    pub fn with_default_interval(backend: Arc<dyn MetricsBackend>) -> Self {
+        Self::new(backend, Duration::from_secs(1))
+    }
+
+    fn flush_once(state: &Arc<Mutex<BufferState>>, backend: &Arc<dyn MetricsBackend>) {
+        let drained = state.lock().unwrap().drain();
+        for aggregate in drained.into_values() {
+            match aggregate {
+                Aggregate::Counter { name, value, tags } => backend.increment(&name, value, &tags),
+                Aggregate::Gauge { name, value, tags } => backend.gauge(&name, value, &tags),
+                Aggregate::Timings { name, samples, tags } => {
+                    for sample in samples {
+                        backend.timing(&name, sample, &tags);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn increment(&self, name: &str, value: f64, tags: &[(String, String)]) {
+        let owned_name = name.to_string();
+        let owned_tags = tags.to_vec();
+        self.state.lock().unwrap().record(name, tags, |existing| match existing {
+            Some(Aggregate::Counter { name, value: prev, tags }) => Aggregate::Counter { name, value: prev + value, tags },
+            _ => Aggregate::Counter { name: owned_name, value, tags: owned_tags },
+        });
+    }
+
+    pub fn gauge(&self, name: &str, value: f64, tags: &[(String, String)]) {
+        let owned_name = name.to_string();
+        let owned_tags = tags.to_vec();
+        self.state.lock().unwrap().record(name, tags, |_| Aggregate::Gauge { name: owned_name, value, tags: owned_tags });
+    }
+
+    pub fn timing(&self, name: &str, duration: Duration, tags: &[(String, String)]) {
+        let owned_name = name.to_string();
+        let owned_tags = tags.to_vec();
+        self.state.lock().unwrap().record(name, tags, |existing| match existing {
+            Some(Aggregate::Timings { name, mut samples, tags }) => {
+                samples.push(duration);
+                Aggregate::Timings { name, samples, tags }
+            }
+            _ => Aggregate::Timings { name: owned_name, samples: vec![duration], tags: owned_tags },
+        });
+    }
+
+    /// Drains the buffer and sends everything to the backend immediately,
+    /// independent of the flush-interval timer.
+    pub fn flush(&self) {
+        Self::flush_once(&self.state, &self.backend);
+    }
+}
+
+impl Drop for BufferedMetrics {
+    fn drop(&mut self) {
+        self.shutdown.store(true, std::sync::atomic::Ordering::Relaxed);
+        self.flush();
+    }
+}
@@ -6,11 +6,59 @@ pub struct SignTransactionRequest {
     pub wallet_id: String,
     pub to: String,
     pub value: String,
-    pub gas_limit: String,
-    pub gas_price: String,
-    pub nonce: u64,
+    /// When omitted, filled in via `eth_estimateGas`.
+    pub gas_limit: Option<String>,
+    /// Legacy gas price. Omit this (and supply `max_fee_per_gas` instead) to
+    /// get a Type-2 (EIP-1559) transaction. When the resolved envelope
+    /// needs a fee field and it's missing, the gas oracle fills it in.
+    pub gas_price: Option<String>,
+    /// When omitted, the service auto-fills the next sequential nonce for
+    /// this wallet's address via the nonce manager.
+    pub nonce: Option<u64>,
     pub data: Option<String>,
     pub chain_id: u64,
+    /// Transaction envelope to produce: `0` for legacy, `1` for EIP-2930 or
+    /// `2` for EIP-1559.
+    /// When omitted, it's inferred from whichever fee fields are present
+    /// (`max_fee_per_gas` set => `2`, else `0`), so callers that just fill
+    /// in the fee fields they have don't also need to set this explicitly.
+    pub tx_type: Option<u8>,
+    pub max_fee_per_gas: Option<String>,
+    pub max_priority_fee_per_gas: Option<String>,
+    pub access_list: Option<Vec<AccessListEntry>>,
+    /// Hex-encoded `eth_getCode` result for the sender address, when the
+    /// caller has it handy. If present and nonempty, signing is refused per
+    /// EIP-3607 (the sender can't be a contract). Omit to skip the check.
+    pub sender_code: Option<String>,
+    /// Urgency used to pick the gas oracle's fee-history percentile and
+    /// base-fee multiplier when fee fields are auto-filled. Defaults to
+    /// `Normal`; ignored when the caller supplies explicit fee fields.
+    pub priority: Option<TaskPriority>,
+    /// Which `Signer` backend should produce the signature. Defaults to the
+    /// in-memory keystore (`wallet_id` refers to a stored encrypted
+    /// wallet); `Ledger` signs through a hardware wallet instead, in which
+    /// case `wallet_id` is ignored in favor of `derivation_path`.
+    #[serde(default)]
+    pub backend: SignerBackend,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SignerBackend {
+    Keystore,
+    Ledger { derivation_path: String },
+}
+
+impl Default for SignerBackend {
+    fn default() -> Self {
+        SignerBackend::Keystore
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessListEntry {
+    pub address: String,
+    pub storage_keys: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,20 +72,65 @@ pub struct SignTransactionResponse {
 pub struct TransactionSignature {
     pub r: String,
     pub s: String,
+    /// For legacy transactions, the EIP-155 `v` (`recovery_id + chain_id*2
+    /// + 35`). For EIP-2930 and EIP-1559 transactions this instead holds the
+    /// raw `y_parity` (`0` or `1`), per the typed envelope format.
     pub v: u64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignMessageRequest {
+    pub wallet_id: String,
+    /// Hex-encoded (optionally `0x`-prefixed) message bytes to sign, e.g.
+    /// for wallet-connect/dApp auth flows rather than transactions.
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignMessageResponse {
+    /// The EIP-191-prefixed digest that was actually signed.
+    pub message_hash: String,
+    /// `v` here is always `27`/`28`, matching the `personal_sign`
+    /// convention (never an EIP-155-adjusted value).
+    pub signature: TransactionSignature,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoverMessageAddressRequest {
+    pub message: String,
+    pub signature: TransactionSignature,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoverAddressResponse {
+    pub address: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateWalletRequest {
     pub password: String,
     pub name: Option<String>,
+    /// BIP-44 path to derive the wallet's key from. Defaults to
+    /// `m/44'/60'/0'/0/{account_index}`.
+    pub derivation_path: Option<String>,
+    /// Account index used to build the default derivation path when
+    /// `derivation_path` isn't given. Defaults to `0`.
+    pub account_index: Option<u32>,
+    /// Which `Signer` backend should hold this wallet's key. Defaults to the
+    /// in-memory encrypted keystore; `Ledger` registers a hardware wallet
+    /// instead — only its derivation path and address are ever persisted,
+    /// and `password`/the returned `mnemonic` don't apply.
+    #[serde(default)]
+    pub backend: SignerBackend,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateWalletResponse {
     pub wallet_id: String,
     pub address: String,
-    pub mnemonic: String, // Should be returned securely and stored safely
+    /// `None` for a Ledger-backed wallet — there's no mnemonic to return
+    /// since the key was generated and stays on the device.
+    pub mnemonic: Option<String>, // Should be returned securely and stored safely
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,6 +139,20 @@ pub struct ImportWalletRequest {
     pub mnemonic: Option<String>,
     pub password: String,
     pub name: Option<String>,
+    /// BIP-44 path to derive the key from when importing via `mnemonic`, or
+    /// to register a Ledger wallet at (see `backend`). Defaults to
+    /// `m/44'/60'/0'/0/{account_index}`. Ignored when importing via
+    /// `private_key`.
+    pub derivation_path: Option<String>,
+    /// Account index used to build the default derivation path when
+    /// `derivation_path` isn't given. Defaults to `0`.
+    pub account_index: Option<u32>,
+    /// Which `Signer` backend owns this wallet's key. Defaults to the
+    /// in-memory encrypted keystore (via `private_key`/`mnemonic`);
+    /// `Ledger` registers an existing hardware wallet by derivation path
+    /// instead, ignoring `private_key`/`mnemonic`/`password`.
+    #[serde(default)]
+    pub backend: SignerBackend,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,6 +185,9 @@ pub struct SigningResult {
     pub signed_transaction: Option<String>,
     pub transaction_hash: Option<String>,
     pub error: Option<String>,
+    /// Stable machine-readable tag for `error`, e.g. `"sender_has_code"` for
+    /// an EIP-3607 rejection. `None` for untagged/unexpected failures.
+    pub error_type: Option<String>,
     pub processed_at: DateTime<Utc>,
 }
 
@@ -86,10 +196,14 @@ pub struct EncryptedWallet {
     pub wallet_id: String,
     pub name: Option<String>,
     pub address: String,
-    pub encrypted_private_key: String,
-    pub salt: String,
+    /// `None` for a Ledger-backed wallet (see `signer`) — no private key
+    /// material is ever persisted for hardware wallets.
+    pub encrypted_private_key: Option<String>,
+    pub salt: Option<String>,
     pub created_at: DateTime<Utc>,
     pub last_used: Option<DateTime<Utc>>,
+    /// Which `Signer` backend owns this wallet's key.
+    pub signer: SignerBackend,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,78 @@
+//! Per-address nonce tracking so concurrent signing requests for the same
+//! wallet — including the many tasks the Kafka-driven signing pipeline can
+//! dispatch for one address — receive sequential, collision-free nonces
+//! instead of all reading the same on-chain value.
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use web3::transports::Http;
+use web3::types::{Address, BlockNumber};
+use web3::Web3;
+
+#[derive(Clone)]
+pub struct NonceManager {
+    web3: Web3<Http>,
+    next_nonce: Arc<RwLock<HashMap<Address, u64>>>,
+}
+
+impl NonceManager {
+    pub fn new(web3: Web3<Http>) -> Self {
+        Self {
+            web3,
+            next_nonce: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Reserves and returns the next nonce for `address`, initializing it
+    /// from `eth_getTransactionCount(address, "pending")` the first time
+    /// this address is seen.
+    pub async fn next(&self, address: Address) -> Result<u64> {
+        {
+            let mut nonces = self.next_nonce.write().await;
+            if let Some(nonce) = nonces.get_mut(&address) {
+                let reserved = *nonce;
+                *nonce += 1;
+                return Ok(reserved);
+            }
+        }
+
+        let onchain = self.web3.eth()
+            .transaction_count(address, Some(BlockNumber::Pending))
+            .await?
+            .as_u64();
+
+        let mut nonces = self.next_nonce.write().await;
+        // Another task may have raced us and already initialized this
+        // address while we were awaiting the RPC call above.
+        let nonce = nonces.entry(address).or_insert(onchain);
+        let reserved = *nonce;
+        *nonce += 1;
+        Ok(reserved)
+    }
+
+    /// Resyncs `address`'s next nonce from the chain, for recovering after a
+    /// dropped or replaced transaction leaves the in-memory counter stale.
+    pub async fn resync(&self, address: Address) -> Result<()> {
+        let onchain = self.web3.eth()
+            .transaction_count(address, Some(BlockNumber::Pending))
+            .await?
+            .as_u64();
+        self.next_nonce.write().await.insert(address, onchain);
+        Ok(())
+    }
+
+    /// Releases a nonce reserved by [`Self::next`] after its signing attempt
+    /// failed, so it's handed out again instead of leaving a permanent gap.
+    /// Only rolls back if nothing has reserved a later nonce in the
+    /// meantime.
+    pub async fn release(&self, address: Address, nonce: u64) {
+        let mut nonces = self.next_nonce.write().await;
+        if let Some(next) = nonces.get_mut(&address) {
+            if *next == nonce + 1 {
+                *next = nonce;
+            }
+        }
+    }
+}
@@ -0,0 +1,341 @@
+//! Abstracts over where a wallet's private key actually lives, so
+//! `TransactionSigningService` can sign either through the in-memory
+//! encrypted keystore or through a Ledger hardware wallet without branching
+//! on the backend at every call site.
+
+use anyhow::{anyhow, bail, Result};
+use secp256k1::SecretKey;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use web3::types::{Address, TransactionParameters};
+use zeroize::Zeroizing;
+
+use crate::crypto::CryptoService;
+use crate::hdwallet;
+use crate::models::EncryptedWallet;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// `wallet_ref` is backend-specific: a wallet id for [`KeystoreSigner`], a
+/// BIP-44 derivation path (e.g. `m/44'/60'/0'/0/0`) for [`LedgerSigner`].
+pub trait Signer: Send + Sync {
+    fn address<'a>(&'a self, wallet_ref: &'a str) -> BoxFuture<'a, Address>;
+
+    /// Signs a legacy, EIP-2930 or EIP-1559 transaction (selected by
+    /// `transaction.transaction_type`) and returns the compact `(r, s)`
+    /// signature bytes plus the `v`/`y_parity` value.
+    fn sign_transaction<'a>(
+        &'a self,
+        wallet_ref: &'a str,
+        transaction: &'a TransactionParameters,
+        chain_id: u64,
+    ) -> BoxFuture<'a, (Vec<u8>, u64)>;
+}
+
+/// An unlock password, held only in memory and only until `expires_at`.
+struct Unlocked {
+    password: Zeroizing<String>,
+    expires_at: Instant,
+}
+
+/// Signs through the existing in-memory encrypted keystore.
+pub struct KeystoreSigner {
+    crypto_service: CryptoService,
+    wallets: Arc<RwLock<HashMap<String, EncryptedWallet>>>,
+    /// Passwords for wallets that have been explicitly unlocked via
+    /// [`Self::unlock`], keyed by wallet id. Nothing is signable until its
+    /// wallet is unlocked, and the password is dropped (zeroized) once its
+    /// TTL elapses or [`Self::lock`] is called.
+    unlocked: Arc<RwLock<HashMap<String, Unlocked>>>,
+}
+
+impl KeystoreSigner {
+    pub fn new(crypto_service: CryptoService, wallets: Arc<RwLock<HashMap<String, EncryptedWallet>>>) -> Self {
+        Self { crypto_service, wallets, unlocked: Arc::new(RwLock::new(HashMap::new())) }
+    }
+
+    /// Unlocks `wallet_id` for `ttl`, validating `password` up front against
+    /// the stored ciphertext so callers get a clear "wrong password" error
+    /// now rather than a confusing decryption failure at signing time.
+    pub async fn unlock(&self, wallet_id: &str, password: &str, ttl: Duration) -> Result<()> {
+        let (encrypted_private_key, salt) = {
+            let wallets = self.wallets.read().await;
+            let wallet = wallets.get(wallet_id)
+                .ok_or_else(|| anyhow!("Wallet not found: {}", wallet_id))?;
+            let encrypted_private_key = wallet.encrypted_private_key.clone()
+                .ok_or_else(|| anyhow!("wallet {} has no stored private key (hardware-backed?)", wallet_id))?;
+            let salt = wallet.salt.clone()
+                .ok_or_else(|| anyhow!("wallet {} has no stored private key (hardware-backed?)", wallet_id))?;
+            (encrypted_private_key, salt)
+        };
+
+        self.crypto_service.decrypt_private_key(&encrypted_private_key, password, &salt)
+            .map_err(|_| anyhow!("incorrect password for wallet {}", wallet_id))?;
+
+        self.unlocked.write().await.insert(wallet_id.to_string(), Unlocked {
+            password: Zeroizing::new(password.to_string()),
+            expires_at: Instant::now() + ttl,
+        });
+        Ok(())
+    }
+
+    /// Forgets `wallet_id`'s unlock password immediately instead of waiting
+    /// for its TTL to elapse.
+    pub async fn lock(&self, wallet_id: &str) {
+        self.unlocked.write().await.remove(wallet_id);
+    }
+
+    /// Returns the still-valid unlock password for `wallet_id`, evicting it
+    /// first if its TTL has elapsed.
+    async fn unlocked_password(&self, wallet_id: &str) -> Result<Zeroizing<String>> {
+        let mut unlocked = self.unlocked.write().await;
+        match unlocked.get(wallet_id) {
+            Some(entry) if entry.expires_at > Instant::now() => Ok(entry.password.clone()),
+            Some(_) => {
+                unlocked.remove(wallet_id);
+                Err(anyhow!("wallet {} is locked (unlock expired); call unlock first", wallet_id))
+            }
+            None => Err(anyhow!("wallet {} is locked; call unlock first", wallet_id)),
+        }
+    }
+
+    /// Decrypts `wallet_id`'s private key using its current unlock password,
+    /// for signing flows (like EIP-712) that don't go through the `Signer`
+    /// trait's transaction-signing path.
+    pub async fn decrypt_key(&self, wallet_id: &str) -> Result<SecretKey> {
+        let password = self.unlocked_password(wallet_id).await?;
+
+        let wallets = self.wallets.read().await;
+        let wallet = wallets.get(wallet_id)
+            .ok_or_else(|| anyhow!("Wallet not found: {}", wallet_id))?;
+        let encrypted_private_key = wallet.encrypted_private_key.as_deref()
+            .ok_or_else(|| anyhow!("wallet {} has no stored private key (hardware-backed?)", wallet_id))?;
+        let salt = wallet.salt.as_deref()
+            .ok_or_else(|| anyhow!("wallet {} has no stored private key (hardware-backed?)", wallet_id))?;
+
+        self.crypto_service.decrypt_private_key(encrypted_private_key, &password, salt)
+    }
+}
+
+impl Signer for KeystoreSigner {
+    fn address<'a>(&'a self, wallet_ref: &'a str) -> BoxFuture<'a, Address> {
+        Box::pin(async move {
+            let wallets = self.wallets.read().await;
+            let wallet = wallets.get(wallet_ref)
+                .ok_or_else(|| anyhow!("Wallet not found: {}", wallet_ref))?;
+            wallet.address.parse().map_err(|e| anyhow!("invalid stored wallet address: {}", e))
+        })
+    }
+
+    fn sign_transaction<'a>(
+        &'a self,
+        wallet_ref: &'a str,
+        transaction: &'a TransactionParameters,
+        chain_id: u64,
+    ) -> BoxFuture<'a, (Vec<u8>, u64)> {
+        Box::pin(async move {
+            let private_key = self.decrypt_key(wallet_ref).await?;
+
+            match transaction.transaction_type.map(|t| t.as_u64()) {
+                Some(2) => {
+                    let (signature, y_parity) = self.crypto_service
+                        .sign_eip1559_transaction(&private_key, transaction, chain_id)?;
+                    Ok((signature, y_parity as u64))
+                }
+                Some(1) => {
+                    let (signature, y_parity) = self.crypto_service
+                        .sign_eip2930_transaction(&private_key, transaction, chain_id)?;
+                    Ok((signature, y_parity as u64))
+                }
+                _ => self.crypto_service.sign_transaction(&private_key, transaction, chain_id),
+            }
+        })
+    }
+}
+
+const CLA_ETH: u8 = 0xe0;
+const INS_GET_PUBLIC_KEY: u8 = 0x02;
+const INS_SIGN_TX: u8 = 0x04;
+/// Ledger's transport layer caps each APDU at 255 bytes; leave headroom for
+/// the path prefix on the first chunk.
+const APDU_CHUNK_SIZE: usize = 150;
+
+/// Signs through a Ledger hardware wallet reached over USB HID, following
+/// the Ethereum app's `GET_PUBLIC_KEY`/`SIGN_TX` APDU protocol. Private keys
+/// never leave the device; this backend only ever sees derivation paths and
+/// unsigned RLP payloads.
+pub struct LedgerSigner;
+
+impl LedgerSigner {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn encode_path(components: &[u32]) -> Vec<u8> {
+        let mut data = vec![components.len() as u8];
+        for component in components {
+            data.extend_from_slice(&component.to_be_bytes());
+        }
+        data
+    }
+
+    fn open_transport() -> Result<ledger_transport_hid::TransportNativeHID> {
+        let hidapi = ledger_transport_hid::hidapi::HidApi::new()
+            .map_err(|e| anyhow!("failed to initialize HID backend: {}", e))?;
+        ledger_transport_hid::TransportNativeHID::new(&hidapi)
+            .map_err(|e| anyhow!("failed to open Ledger device: {}", e))
+    }
+}
+
+impl Signer for LedgerSigner {
+    fn address<'a>(&'a self, wallet_ref: &'a str) -> BoxFuture<'a, Address> {
+        Box::pin(async move {
+            let components = hdwallet::parse_derivation_path(wallet_ref)?;
+            let transport = Self::open_transport()?;
+
+            let command = ledger_transport::APDUCommand {
+                cla: CLA_ETH,
+                ins: INS_GET_PUBLIC_KEY,
+                p1: 0x00, // Don't require on-device confirmation for a plain address lookup.
+                p2: 0x00,
+                data: Self::encode_path(&components),
+            };
+            let response = ledger_transport::Exchange::exchange(&transport, &command)
+                .map_err(|e| anyhow!("Ledger GET_PUBLIC_KEY failed: {}", e))?;
+
+            // Response layout: [pubkey_len][pubkey][addr_len][addr as ASCII hex][chain_code...]
+            let data = response.apdu_data();
+            let pubkey_len = *data.first().ok_or_else(|| anyhow!("malformed Ledger response"))? as usize;
+            let addr_len_offset = 1 + pubkey_len;
+            let addr_len = *data.get(addr_len_offset).ok_or_else(|| anyhow!("malformed Ledger response"))? as usize;
+            let addr_ascii = data.get(addr_len_offset + 1..addr_len_offset + 1 + addr_len)
+                .ok_or_else(|| anyhow!("malformed Ledger response"))?;
+            let addr_hex = std::str::from_utf8(addr_ascii)?;
+
+            addr_hex.parse().map_err(|e| anyhow!("invalid address from Ledger: {}", e))
+        })
+    }
+
+    fn sign_transaction<'a>(
+        &'a self,
+        wallet_ref: &'a str,
+        transaction: &'a TransactionParameters,
+        chain_id: u64,
+    ) -> BoxFuture<'a, (Vec<u8>, u64)> {
+        Box::pin(async move {
+            let components = hdwallet::parse_derivation_path(wallet_ref)?;
+            let transport = Self::open_transport()?;
+
+            let crypto_service = CryptoService::new();
+            let unsigned_rlp = match transaction.transaction_type.map(|t| t.as_u64()) {
+                Some(2) => crypto_service.encode_eip1559_payload(transaction, chain_id)?,
+                Some(1) => crypto_service.encode_eip2930_payload(transaction, chain_id)?,
+                _ => crypto_service.encode_transaction(transaction, chain_id)?,
+            };
+
+            let mut payload = Self::encode_path(&components);
+            payload.extend_from_slice(&unsigned_rlp);
+            if payload.is_empty() {
+                bail!("nothing to sign");
+            }
+
+            let mut response_data = Vec::new();
+            for (i, chunk) in payload.chunks(APDU_CHUNK_SIZE).enumerate() {
+                let command = ledger_transport::APDUCommand {
+                    cla: CLA_ETH,
+                    ins: INS_SIGN_TX,
+                    p1: if i == 0 { 0x00 } else { 0x80 }, // First chunk vs. continuation, per the Ethereum app's chunking convention.
+                    p2: 0x00,
+                    data: chunk.to_vec(),
+                };
+                let response = ledger_transport::Exchange::exchange(&transport, &command)
+                    .map_err(|e| anyhow!("Ledger SIGN_TX failed: {}", e))?;
+                response_data = response.apdu_data().to_vec();
+            }
+
+            // Final chunk's response is [v][r (32 bytes)][s (32 bytes)].
+            if response_data.len() < 65 {
+                bail!("Ledger returned a short signature response");
+            }
+            let v = response_data[0] as u64;
+            let mut signature = Vec::with_capacity(64);
+            signature.extend_from_slice(&response_data[1..33]);
+            signature.extend_from_slice(&response_data[33..65]);
+
+            Ok((signature, v))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SignerBackend;
+    use chrono::Utc;
+
+    async fn wallet_with_password(password: &str) -> (KeystoreSigner, String) {
+        let crypto_service = CryptoService::new();
+        let private_key = crypto_service.generate_private_key().unwrap();
+        let address = crypto_service.private_key_to_address(&private_key).unwrap();
+        let (encrypted_private_key, salt) = crypto_service
+            .encrypt_private_key(&private_key, password)
+            .unwrap();
+
+        let wallet_id = "test-wallet".to_string();
+        let wallet = EncryptedWallet {
+            wallet_id: wallet_id.clone(),
+            name: None,
+            address: format!("{:?}", address),
+            encrypted_private_key: Some(encrypted_private_key),
+            salt: Some(salt),
+            created_at: Utc::now(),
+            last_used: None,
+            signer: SignerBackend::Keystore,
+        };
+        let wallets = Arc::new(RwLock::new(HashMap::from([(wallet_id.clone(), wallet)])));
+        (KeystoreSigner::new(crypto_service, wallets), wallet_id)
+    }
+
+    /// `lock` must make the unlock password immediately and permanently
+    /// unavailable — the whole point of zeroizing it is that nothing can
+    /// decrypt the wallet's key afterward, not just that the TTL is unset.
+    #[tokio::test]
+    async fn lock_evicts_the_unlock_password() {
+        let (signer, wallet_id) = wallet_with_password("correct horse battery staple").await;
+
+        signer.unlock(&wallet_id, "correct horse battery staple", Duration::from_secs(60)).await.unwrap();
+        assert!(signer.decrypt_key(&wallet_id).await.is_ok());
+
+        signer.lock(&wallet_id).await;
+        assert!(signer.decrypt_key(&wallet_id).await.is_err());
+    }
+
+    /// An unlock whose TTL has already elapsed must behave exactly like a
+    /// locked wallet: the password is evicted on the next access rather than
+    /// being usable past its stated lifetime.
+    #[tokio::test]
+    async fn expired_unlock_is_rejected_and_evicted() {
+        let (signer, wallet_id) = wallet_with_password("correct horse battery staple").await;
+
+        signer.unlock(&wallet_id, "correct horse battery staple", Duration::from_millis(0)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        assert!(signer.unlocked_password(&wallet_id).await.is_err());
+        assert!(signer.decrypt_key(&wallet_id).await.is_err());
+    }
+
+    /// `unlock` must validate the password against the stored ciphertext up
+    /// front, rather than caching a wrong password that would only surface a
+    /// confusing decryption failure later at signing time.
+    #[tokio::test]
+    async fn unlock_rejects_wrong_password_up_front() {
+        let (signer, wallet_id) = wallet_with_password("correct horse battery staple").await;
+
+        assert!(signer.unlock(&wallet_id, "wrong password", Duration::from_secs(60)).await.is_err());
+        assert!(signer.decrypt_key(&wallet_id).await.is_err());
+    }
+}
@@ -5,17 +5,28 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
-use web3::types::{TransactionParameters, Address, U256};
+use web3::transports::Http;
+use web3::types::{AccessListItem, Address, Bytes, CallRequest, TransactionParameters, H256, U256, U64};
+use web3::Web3;
 
-use crate::crypto::CryptoService;
+use crate::crypto::{CryptoService, ValidationError};
+use crate::eip712::{self, RecoverTypedDataAddressRequest, SignTypedDataRequest, SignTypedDataResponse};
+use crate::gas_oracle::GasOracle;
+use crate::hdwallet;
 use crate::kafka_consumer::KafkaConsumerService;
 use crate::models::*;
+use crate::nonce_manager::NonceManager;
+use crate::signer::{KeystoreSigner, LedgerSigner, Signer};
 
 #[derive(Clone)]
 pub struct TransactionSigningService {
     crypto_service: CryptoService,
     kafka_consumer: KafkaConsumerService,
     wallets: Arc<RwLock<HashMap<String, EncryptedWallet>>>,
+    keystore_signer: Arc<KeystoreSigner>,
+    ledger_signer: Arc<LedgerSigner>,
+    nonce_manager: NonceManager,
+    gas_oracle: GasOracle,
 }
 
 impl TransactionSigningService {
@@ -23,82 +34,153 @@ impl TransactionSigningService {
         let crypto_service = CryptoService::new();
         let kafka_consumer = KafkaConsumerService::new(kafka_brokers, "transaction-signing-group")?;
         let wallets = Arc::new(RwLock::new(HashMap::new()));
+        let keystore_signer = Arc::new(KeystoreSigner::new(crypto_service.clone(), wallets.clone()));
+        let ledger_signer = Arc::new(LedgerSigner::new());
+
+        let eth_rpc_url = std::env::var("ETH_RPC_URL")
+            .unwrap_or_else(|_| "http://localhost:8545".to_string());
+        let transport = Http::new(&eth_rpc_url)?;
+        let web3 = Web3::new(transport);
+        let nonce_manager = NonceManager::new(web3.clone());
+        let gas_oracle = GasOracle::new(web3);
 
         Ok(Self {
             crypto_service,
             kafka_consumer,
             wallets,
+            keystore_signer,
+            ledger_signer,
+            nonce_manager,
+            gas_oracle,
         })
     }
 
     pub async fn start_consumer(&self) -> Result<()> {
         self.kafka_consumer.subscribe_to_signing_requests().await?;
-        
+
         let service = self.clone();
-        self.kafka_consumer.start_consuming(move |task| {
-            tokio::runtime::Handle::current().block_on(async {
-                service.process_signing_task(task).await
-            })
-        }).await
+        let batch_service = self.clone();
+        self.kafka_consumer.start_consuming(
+            move |task| {
+                tokio::runtime::Handle::current().block_on(async {
+                    service.process_signing_task(task).await
+                })
+            },
+            move |batch| {
+                tokio::runtime::Handle::current().block_on(async {
+                    batch_service.sign_batch(batch).await
+                })
+            },
+        ).await
     }
 
     pub async fn create_wallet(&self, request: CreateWalletRequest) -> Result<CreateWalletResponse> {
-        let private_key = self.crypto_service.generate_private_key()?;
+        if let SignerBackend::Ledger { derivation_path } = &request.backend {
+            let address = self.ledger_signer.address(derivation_path).await?;
+            let wallet_id = Uuid::new_v4().to_string();
+            let wallet = EncryptedWallet {
+                wallet_id: wallet_id.clone(),
+                name: request.name,
+                address: format!("{:?}", address),
+                encrypted_private_key: None,
+                salt: None,
+                created_at: Utc::now(),
+                last_used: None,
+                signer: request.backend.clone(),
+            };
+            self.wallets.write().await.insert(wallet_id.clone(), wallet);
+
+            info!("Registered Ledger wallet: {} with address: {:?} (path {})", wallet_id, address, derivation_path);
+            return Ok(CreateWalletResponse {
+                wallet_id,
+                address: format!("{:?}", address),
+                mnemonic: None,
+            });
+        }
+
+        let mnemonic = hdwallet::generate_mnemonic(128)?;
+        let seed = hdwallet::mnemonic_to_seed(&mnemonic, "")?;
+        let path = request.derivation_path.clone()
+            .unwrap_or_else(|| hdwallet::default_path(request.account_index.unwrap_or(0)));
+        let private_key = hdwallet::derive_private_key(&seed, &path)?;
         let address = self.crypto_service.private_key_to_address(&private_key)?;
-        
+
         let (encrypted_private_key, salt) = self.crypto_service.encrypt_private_key(&private_key, &request.password)?;
-        
+
         let wallet_id = Uuid::new_v4().to_string();
         let encrypted_wallet = EncryptedWallet {
             wallet_id: wallet_id.clone(),
             name: request.name,
             address: format!("{:?}", address),
-            encrypted_private_key,
-            salt,
+            encrypted_private_key: Some(encrypted_private_key),
+            salt: Some(salt),
             created_at: Utc::now(),
             last_used: None,
+            signer: SignerBackend::Keystore,
         };
 
         // Store wallet in memory (in production, this should be persisted to a secure database)
         let mut wallets = self.wallets.write().await;
         wallets.insert(wallet_id.clone(), encrypted_wallet);
 
-        // Generate mnemonic (simplified - in production use proper BIP39 implementation)
-        let mnemonic = self.generate_mnemonic(&private_key)?;
-
-        info!("Created new wallet: {} with address: {:?}", wallet_id, address);
+        info!("Created new wallet: {} with address: {:?} (path {})", wallet_id, address, path);
 
         Ok(CreateWalletResponse {
             wallet_id,
             address: format!("{:?}", address),
-            mnemonic,
+            mnemonic: Some(mnemonic),
         })
     }
 
     pub async fn import_wallet(&self, request: ImportWalletRequest) -> Result<ImportWalletResponse> {
+        if let SignerBackend::Ledger { derivation_path } = &request.backend {
+            let address = self.ledger_signer.address(derivation_path).await?;
+            let wallet_id = Uuid::new_v4().to_string();
+            let wallet = EncryptedWallet {
+                wallet_id: wallet_id.clone(),
+                name: request.name,
+                address: format!("{:?}", address),
+                encrypted_private_key: None,
+                salt: None,
+                created_at: Utc::now(),
+                last_used: None,
+                signer: request.backend.clone(),
+            };
+            self.wallets.write().await.insert(wallet_id.clone(), wallet);
+
+            info!("Registered Ledger wallet via import: {} with address: {:?} (path {})", wallet_id, address, derivation_path);
+            return Ok(ImportWalletResponse {
+                wallet_id,
+                address: format!("{:?}", address),
+            });
+        }
+
         let private_key = if let Some(pk_hex) = request.private_key {
             // Import from private key
             let pk_bytes = hex::decode(pk_hex.trim_start_matches("0x"))?;
             secp256k1::SecretKey::from_slice(&pk_bytes)?
-        } else if let Some(_mnemonic) = request.mnemonic {
-            // Import from mnemonic (simplified implementation)
-            return Err(anyhow::anyhow!("Mnemonic import not implemented yet"));
+        } else if let Some(mnemonic) = request.mnemonic {
+            let seed = hdwallet::mnemonic_to_seed(&mnemonic, "")?;
+            let path = request.derivation_path.clone()
+                .unwrap_or_else(|| hdwallet::default_path(request.account_index.unwrap_or(0)));
+            hdwallet::derive_private_key(&seed, &path)?
         } else {
             return Err(anyhow::anyhow!("Either private_key or mnemonic must be provided"));
         };
 
         let address = self.crypto_service.private_key_to_address(&private_key)?;
         let (encrypted_private_key, salt) = self.crypto_service.encrypt_private_key(&private_key, &request.password)?;
-        
+
         let wallet_id = Uuid::new_v4().to_string();
         let encrypted_wallet = EncryptedWallet {
             wallet_id: wallet_id.clone(),
             name: request.name,
             address: format!("{:?}", address),
-            encrypted_private_key,
-            salt,
+            encrypted_private_key: Some(encrypted_private_key),
+            salt: Some(salt),
             created_at: Utc::now(),
             last_used: None,
+            signer: SignerBackend::Keystore,
         };
 
         let mut wallets = self.wallets.write().await;
@@ -113,50 +195,165 @@ impl TransactionSigningService {
     }
 
     pub async fn sign_transaction(&self, request: SignTransactionRequest) -> Result<SignTransactionResponse> {
-        // Get wallet
-        let wallets = self.wallets.read().await;
-        let wallet = wallets.get(&request.wallet_id)
-            .ok_or_else(|| anyhow::anyhow!("Wallet not found: {}", request.wallet_id))?;
+        let access_list = request.access_list.as_ref().map(|entries| {
+            entries.iter().map(|entry| {
+                Ok::<_, anyhow::Error>(AccessListItem {
+                    address: entry.address.parse()?,
+                    storage_keys: entry.storage_keys.iter()
+                        .map(|key| Ok::<H256, anyhow::Error>(key.parse()?))
+                        .collect::<Result<Vec<_>>>()?,
+                })
+            }).collect::<Result<Vec<_>>>()
+        }).transpose()?;
 
-        // For this example, we'll use a dummy password. In production, this should come from secure storage
-        let password = "dummy_password"; // This should be securely retrieved
-        
-        let private_key = self.crypto_service.decrypt_private_key(
-            &wallet.encrypted_private_key,
-            password,
-            &wallet.salt,
-        )?;
+        // When the caller doesn't say explicitly, infer the envelope from
+        // whichever fee fields they actually filled in.
+        let tx_type = request.tx_type.unwrap_or_else(|| if request.max_fee_per_gas.is_some() { 2 } else { 0 });
+        let urgency = request.priority.clone().unwrap_or(TaskPriority::Normal);
+
+        let gas_price = match (&request.gas_price, tx_type) {
+            (Some(gas_price), _) => Some(U256::from_dec_str(gas_price)?),
+            (None, 2) => None,
+            (None, _) => Some(self.gas_oracle.legacy_gas_price().await?),
+        };
+        let (max_fee_per_gas, max_priority_fee_per_gas) = if tx_type == 2 {
+            match (&request.max_fee_per_gas, &request.max_priority_fee_per_gas) {
+                (Some(max_fee), Some(priority_fee)) => {
+                    (Some(U256::from_dec_str(max_fee)?), Some(U256::from_dec_str(priority_fee)?))
+                }
+                _ => {
+                    let (max_fee, priority_fee) = self.gas_oracle.eip1559_fees(&urgency).await?;
+                    let max_fee = match &request.max_fee_per_gas {
+                        Some(max_fee) => U256::from_dec_str(max_fee)?,
+                        None => max_fee,
+                    };
+                    let priority_fee = match &request.max_priority_fee_per_gas {
+                        Some(priority_fee) => U256::from_dec_str(priority_fee)?,
+                        None => priority_fee,
+                    };
+                    (Some(max_fee), Some(priority_fee))
+                }
+            }
+        } else {
+            (None, None)
+        };
+
+        // Dispatch to whichever backend holds the key: the in-memory
+        // keystore (`wallet_id`) or a Ledger hardware wallet
+        // (`derivation_path`). Neither branch here ever sees a raw key for
+        // the hardware case — `LedgerSigner` only exchanges derivation
+        // paths and unsigned RLP with the device.
+        let (signer, wallet_ref): (&dyn Signer, &str) = match &request.backend {
+            SignerBackend::Keystore => (self.keystore_signer.as_ref(), &request.wallet_id),
+            SignerBackend::Ledger { derivation_path } => (self.ledger_signer.as_ref(), derivation_path.as_str()),
+        };
+
+        if let Some(code_hex) = &request.sender_code {
+            let code = hex::decode(code_hex.trim_start_matches("0x"))?;
+            let sender = signer.address(wallet_ref).await?;
+            self.crypto_service.validate_sender_is_eoa(sender, &code)?;
+        }
+
+        // Auto-fill the nonce from the nonce manager when the caller didn't
+        // pin one, tracking the reservation so we can roll it back if
+        // signing fails below and avoid leaving a gap in the sequence.
+        let mut reserved_nonce: Option<(Address, u64)> = None;
+        let nonce = match request.nonce {
+            Some(nonce) => nonce,
+            None => {
+                let address = signer.address(wallet_ref).await?;
+                let nonce = self.nonce_manager.next(address).await?;
+                reserved_nonce = Some((address, nonce));
+                nonce
+            }
+        };
+
+        let to: Address = request.to.parse()?;
+        let value = U256::from_dec_str(&request.value)?;
+        let data = request.data.as_ref()
+            .map(|d| hex::decode(d.trim_start_matches("0x")).unwrap_or_default())
+            .unwrap_or_default();
+
+        let gas_limit = match &request.gas_limit {
+            Some(gas_limit) => U256::from_dec_str(gas_limit)?,
+            None => {
+                let call = CallRequest {
+                    to: Some(to),
+                    value: Some(value),
+                    data: Some(Bytes(data.clone())),
+                    gas_price,
+                    max_fee_per_gas,
+                    max_priority_fee_per_gas,
+                    ..Default::default()
+                };
+                self.gas_oracle.estimate_gas(call).await?
+            }
+        };
 
         // Create transaction parameters
         let transaction = TransactionParameters {
-            to: Some(request.to.parse()?),
-            value: Some(U256::from_dec_str(&request.value)?),
-            gas: U256::from_dec_str(&request.gas_limit)?,
-            gas_price: Some(U256::from_dec_str(&request.gas_price)?),
-            nonce: Some(U256::from(request.nonce)),
-            data: request.data.map(|d| hex::decode(d.trim_start_matches("0x")).unwrap_or_default()).unwrap_or_default(),
-            transaction_type: None,
-            access_list: None,
-            max_fee_per_gas: None,
-            max_priority_fee_per_gas: None,
+            to: Some(to),
+            value: Some(value),
+            gas: gas_limit,
+            gas_price,
+            nonce: Some(U256::from(nonce)),
+            data,
+            transaction_type: Some(U64::from(tx_type)),
+            access_list,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
         };
 
-        // Sign transaction
-        let (signature_bytes, v) = self.crypto_service.sign_transaction(&private_key, &transaction, request.chain_id)?;
-        
-        // Extract r and s from signature
+        let signed = signer.sign_transaction(wallet_ref, &transaction, request.chain_id).await;
+        let (signature_bytes, v_or_parity) = match signed {
+            Ok(result) => result,
+            Err(e) => {
+                if let Some((address, nonce)) = reserved_nonce {
+                    self.nonce_manager.release(address, nonce).await;
+                }
+                return Err(e);
+            }
+        };
         let r = &signature_bytes[..32];
         let s = &signature_bytes[32..];
 
-        let signature = TransactionSignature {
-            r: hex::encode(r),
-            s: hex::encode(s),
-            v,
-        };
+        let (signed_transaction, transaction_hash, signature) = if tx_type == 2 {
+            let y_parity = v_or_parity as u8;
+            let signature = TransactionSignature {
+                r: hex::encode(r),
+                s: hex::encode(s),
+                v: y_parity as u64,
+            };
 
-        // Encode signed transaction
-        let signed_transaction = self.encode_signed_transaction(&transaction, &signature, request.chain_id)?;
-        let transaction_hash = self.calculate_transaction_hash(&signed_transaction)?;
+            let signed_transaction = self.encode_signed_eip1559_transaction(
+                &transaction, request.chain_id, y_parity, r, s,
+            )?;
+            let transaction_hash = self.calculate_transaction_hash(&signed_transaction)?;
+            (signed_transaction, transaction_hash, signature)
+        } else if tx_type == 1 {
+            let y_parity = v_or_parity as u8;
+            let signature = TransactionSignature {
+                r: hex::encode(r),
+                s: hex::encode(s),
+                v: y_parity as u64,
+            };
+
+            let signed_transaction = self.encode_signed_eip2930_transaction(
+                &transaction, request.chain_id, y_parity, r, s,
+            )?;
+            let transaction_hash = self.calculate_transaction_hash(&signed_transaction)?;
+            (signed_transaction, transaction_hash, signature)
+        } else {
+            let signature = TransactionSignature {
+                r: hex::encode(r),
+                s: hex::encode(s),
+                v: v_or_parity,
+            };
+
+            let signed_transaction = self.encode_signed_transaction(&transaction, &signature, request.chain_id)?;
+            let transaction_hash = self.calculate_transaction_hash(&signed_transaction)?;
+            (signed_transaction, transaction_hash, signature)
+        };
 
         info!("Signed transaction for wallet: {}", request.wallet_id);
 
@@ -167,6 +364,161 @@ impl TransactionSigningService {
         })
     }
 
+    /// Signs every transaction in `request` independently, collecting one
+    /// [`SigningResult`] per entry. A failure signing one transaction (a
+    /// locked wallet, an unknown wallet, a malformed field) only fails that
+    /// entry — it's recorded as `success: false` and the rest of the batch
+    /// still runs.
+    pub async fn sign_batch(&self, request: BatchSigningRequest) -> Result<BatchSigningResponse> {
+        let mut results = Vec::with_capacity(request.transactions.len());
+
+        for transaction_request in request.transactions {
+            let task_id = Uuid::new_v4().to_string();
+            let result = match self.sign_transaction(transaction_request).await {
+                Ok(response) => SigningResult {
+                    task_id,
+                    success: true,
+                    signed_transaction: Some(response.signed_transaction),
+                    transaction_hash: Some(response.transaction_hash),
+                    error: None,
+                    error_type: None,
+                    processed_at: Utc::now(),
+                },
+                Err(e) => {
+                    error!("Failed to sign transaction in batch {}: {}", request.batch_id, e);
+                    SigningResult {
+                        task_id,
+                        success: false,
+                        signed_transaction: None,
+                        transaction_hash: None,
+                        error_type: Self::classify_error(&e),
+                        error: Some(e.to_string()),
+                        processed_at: Utc::now(),
+                    }
+                }
+            };
+            results.push(result);
+        }
+
+        let successful_transactions = results.iter().filter(|r| r.success).count();
+        let failed_transactions = results.len() - successful_transactions;
+
+        info!(
+            "Processed signing batch {}: {} succeeded, {} failed",
+            request.batch_id, successful_transactions, failed_transactions
+        );
+
+        Ok(BatchSigningResponse {
+            batch_id: request.batch_id,
+            total_transactions: results.len(),
+            successful_transactions,
+            failed_transactions,
+            results,
+        })
+    }
+
+    /// Unlocks a keystore-backed wallet for `ttl_secs` seconds, so
+    /// `sign_transaction`/`sign_typed_data` can decrypt its key without a
+    /// password on every call. Returns an error distinguishing an unknown
+    /// wallet from an incorrect password.
+    pub async fn unlock_wallet(&self, wallet_id: &str, password: &str, ttl_secs: u64) -> Result<()> {
+        let wallet = self.wallets.read().await.get(wallet_id).cloned()
+            .ok_or_else(|| anyhow::anyhow!("Wallet not found: {}", wallet_id))?;
+        if wallet.signer != SignerBackend::Keystore {
+            return Err(anyhow::anyhow!("wallet {} is not keystore-backed and has no password to unlock", wallet_id));
+        }
+
+        self.keystore_signer.unlock(wallet_id, password, std::time::Duration::from_secs(ttl_secs)).await
+    }
+
+    /// Forgets `wallet_id`'s unlock password immediately instead of waiting
+    /// for its TTL to elapse.
+    pub async fn lock_wallet(&self, wallet_id: &str) {
+        self.keystore_signer.lock(wallet_id).await;
+    }
+
+    /// Resyncs the nonce manager's view of `wallet_id`'s next nonce from the
+    /// chain. Use after a transaction this service signed was dropped or
+    /// replaced out of band, so the in-memory counter doesn't drift from
+    /// what the chain actually has recorded.
+    pub async fn reset_nonce(&self, wallet_id: &str) -> Result<()> {
+        let address = self.keystore_signer.address(wallet_id).await?;
+        self.nonce_manager.resync(address).await
+    }
+
+    /// Signs an off-chain EIP-712 typed-data payload (limit orders,
+    /// permits, meta-transactions) rather than an on-chain transaction.
+    pub async fn sign_typed_data(&self, request: SignTypedDataRequest) -> Result<SignTypedDataResponse> {
+        let private_key = self.keystore_signer.decrypt_key(&request.wallet_id).await?;
+
+        let digest = eip712::digest(&request.domain, &request.primary_type, &request.types, &request.message)?;
+        let (signature_bytes, v) = self.crypto_service.sign_typed_data(
+            &private_key, &request.domain, &request.primary_type, &request.types, &request.message,
+        )?;
+        let r = &signature_bytes[..32];
+        let s = &signature_bytes[32..];
+
+        info!("Signed typed data for wallet: {}", request.wallet_id);
+
+        Ok(SignTypedDataResponse {
+            digest: hex::encode(digest),
+            signature: TransactionSignature {
+                r: hex::encode(r),
+                s: hex::encode(s),
+                v: v as u64,
+            },
+        })
+    }
+
+    /// Signs an EIP-191 `personal_sign` message — off-chain auth/intent
+    /// signing for dApp login flows rather than a transaction or typed-data
+    /// payload.
+    pub async fn sign_message(&self, request: SignMessageRequest) -> Result<SignMessageResponse> {
+        let private_key = self.keystore_signer.decrypt_key(&request.wallet_id).await?;
+        let message = hex::decode(request.message.trim_start_matches("0x"))?;
+
+        let (signature_bytes, v) = self.crypto_service.sign_message(&private_key, &message)?;
+        let r = &signature_bytes[..32];
+        let s = &signature_bytes[32..];
+
+        info!("Signed message for wallet: {}", request.wallet_id);
+
+        Ok(SignMessageResponse {
+            message_hash: hex::encode(CryptoService::eip191_hash(&message)),
+            signature: TransactionSignature {
+                r: hex::encode(r),
+                s: hex::encode(s),
+                v: v as u64,
+            },
+        })
+    }
+
+    /// Recovers the address that produced a `personal_sign` signature.
+    pub async fn recover_message_address(&self, request: RecoverMessageAddressRequest) -> Result<RecoverAddressResponse> {
+        let message = hex::decode(request.message.trim_start_matches("0x"))?;
+        let signature = Self::signature_bytes(&request.signature)?;
+
+        let address = self.crypto_service.recover_message_address(&message, &signature, request.signature.v as u8)?;
+        Ok(RecoverAddressResponse { address: format!("{:?}", address) })
+    }
+
+    /// Recovers the address that produced an EIP-712 typed-data signature.
+    pub async fn recover_typed_data_address(&self, request: RecoverTypedDataAddressRequest) -> Result<RecoverAddressResponse> {
+        let signature = Self::signature_bytes(&request.signature)?;
+
+        let address = self.crypto_service.recover_typed_data_address(
+            &request.domain, &request.primary_type, &request.types, &request.message,
+            &signature, request.signature.v as u8,
+        )?;
+        Ok(RecoverAddressResponse { address: format!("{:?}", address) })
+    }
+
+    fn signature_bytes(signature: &TransactionSignature) -> Result<Vec<u8>> {
+        let mut bytes = hex::decode(&signature.r)?;
+        bytes.extend_from_slice(&hex::decode(&signature.s)?);
+        Ok(bytes)
+    }
+
     async fn process_signing_task(&self, task: TransactionSigningTask) -> Result<SigningResult> {
         info!("Processing signing task: {}", task.task_id);
 
@@ -178,6 +530,7 @@ impl TransactionSigningService {
                     signed_transaction: Some(response.signed_transaction),
                     transaction_hash: Some(response.transaction_hash),
                     error: None,
+                    error_type: None,
                     processed_at: Utc::now(),
                 })
             }
@@ -188,6 +541,7 @@ impl TransactionSigningService {
                     success: false,
                     signed_transaction: None,
                     transaction_hash: None,
+                    error_type: Self::classify_error(&e),
                     error: Some(e.to_string()),
                     processed_at: Utc::now(),
                 })
@@ -195,22 +549,12 @@ impl TransactionSigningService {
         }
     }
 
-    fn generate_mnemonic(&self, _private_key: &secp256k1::SecretKey) -> Result<String> {
-        // Simplified mnemonic generation - in production use proper BIP39
-        let words = vec![
-            "abandon", "ability", "able", "about", "above", "absent", "absorb", "abstract",
-            "absurd", "abuse", "access", "accident", "account", "accuse", "achieve", "acid",
-        ];
-        
-        let mut mnemonic = String::new();
-        for i in 0..12 {
-            if i > 0 {
-                mnemonic.push(' ');
-            }
-            mnemonic.push_str(words[i % words.len()]);
-        }
-        
-        Ok(mnemonic)
+    /// Tags a signing failure with a stable machine-readable `error_type`
+    /// when it's a known [`crate::crypto::ValidationError`] rejection,
+    /// e.g. an EIP-3607 sender-has-code guard failure. `None` for anything
+    /// else (malformed input, RPC failures, ...).
+    fn classify_error(error: &anyhow::Error) -> Option<String> {
+        error.downcast_ref::<ValidationError>().map(|e| e.error_type().to_string())
     }
 
     fn encode_signed_transaction(&self, transaction: &TransactionParameters, signature: &TransactionSignature, chain_id: u64) -> Result<Vec<u8>> {
@@ -242,4 +586,94 @@ impl TransactionSigningService {
     fn calculate_transaction_hash(&self, signed_transaction: &[u8]) -> Result<[u8; 32]> {
         Ok(self.crypto_service.keccak256(signed_transaction))
     }
+
+    /// Serializes a signed EIP-1559 envelope: `0x02 || rlp([chain_id, nonce,
+    /// max_priority_fee_per_gas, max_fee_per_gas, gas_limit, to, value,
+    /// data, access_list, y_parity, r, s])`.
+    fn encode_signed_eip1559_transaction(
+        &self,
+        transaction: &TransactionParameters,
+        chain_id: u64,
+        y_parity: u8,
+        r: &[u8],
+        s: &[u8],
+    ) -> Result<Vec<u8>> {
+        use rlp::RlpStream;
+
+        let mut stream = RlpStream::new();
+        stream.begin_list(12);
+        stream.append(&U256::from(chain_id));
+        stream.append(&transaction.nonce.unwrap_or_default());
+        stream.append(&transaction.max_priority_fee_per_gas.unwrap_or_default());
+        stream.append(&transaction.max_fee_per_gas.unwrap_or_default());
+        stream.append(&transaction.gas);
+
+        match transaction.to {
+            Some(to) => stream.append(&to),
+            None => stream.append_empty_data(),
+        };
+
+        stream.append(&transaction.value.unwrap_or_default());
+        stream.append(&transaction.data.as_slice());
+
+        let access_list = transaction.access_list.as_deref().unwrap_or_default();
+        stream.begin_list(access_list.len());
+        for entry in access_list {
+            stream.begin_list(2);
+            stream.append(&entry.address);
+            stream.append_list(&entry.storage_keys);
+        }
+
+        stream.append(&U256::from(y_parity));
+        stream.append(&U256::from_big_endian(r));
+        stream.append(&U256::from_big_endian(s));
+
+        let mut out = vec![0x02u8];
+        out.extend_from_slice(&stream.out());
+        Ok(out)
+    }
+
+    /// Serializes a signed EIP-2930 envelope: `0x01 || rlp([chain_id, nonce,
+    /// gas_price, gas_limit, to, value, data, access_list, y_parity, r, s])`.
+    fn encode_signed_eip2930_transaction(
+        &self,
+        transaction: &TransactionParameters,
+        chain_id: u64,
+        y_parity: u8,
+        r: &[u8],
+        s: &[u8],
+    ) -> Result<Vec<u8>> {
+        use rlp::RlpStream;
+
+        let mut stream = RlpStream::new();
+        stream.begin_list(11);
+        stream.append(&U256::from(chain_id));
+        stream.append(&transaction.nonce.unwrap_or_default());
+        stream.append(&transaction.gas_price.unwrap_or_default());
+        stream.append(&transaction.gas);
+
+        match transaction.to {
+            Some(to) => stream.append(&to),
+            None => stream.append_empty_data(),
+        };
+
+        stream.append(&transaction.value.unwrap_or_default());
+        stream.append(&transaction.data.as_slice());
+
+        let access_list = transaction.access_list.as_deref().unwrap_or_default();
+        stream.begin_list(access_list.len());
+        for entry in access_list {
+            stream.begin_list(2);
+            stream.append(&entry.address);
+            stream.append_list(&entry.storage_keys);
+        }
+
+        stream.append(&U256::from(y_parity));
+        stream.append(&U256::from_big_endian(r));
+        stream.append(&U256::from_big_endian(s));
+
+        let mut out = vec![0x01u8];
+        out.extend_from_slice(&stream.out());
+        Ok(out)
+    }
 }